@@ -0,0 +1,48 @@
+//! Times parsing of a number-heavy document.
+//!
+//! Compare `cargo bench --bench numbers` with
+//! `cargo bench --bench numbers --features fast_float`.
+
+use std::{hint::black_box, time::Instant};
+
+const ROWS: usize = 20_000;
+const ITERATIONS: u32 = 20;
+
+fn main() {
+    let input = number_document();
+
+    // Warm up caches and the allocator before timing
+    json_parser::parse(input.clone()).unwrap();
+
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        black_box(json_parser::parse(black_box(input.clone())).unwrap());
+    }
+    let elapsed = start.elapsed() / ITERATIONS;
+
+    let megabytes = input.len() as f64 / (1024.0 * 1024.0);
+    println!(
+        "parsed {:.1} MiB of numbers in {:?} ({:.1} MiB/s)",
+        megabytes,
+        elapsed,
+        megabytes / elapsed.as_secs_f64()
+    );
+}
+
+/// Rows of coordinates, prices and counters, like a typical telemetry dump.
+fn number_document() -> String {
+    let rows: Vec<String> = (0..ROWS)
+        .map(|row| {
+            let n = row as f64;
+            format!(
+                "[{}, {:.6}, {:.6}, {:.2}, -{}]",
+                row,
+                n * 0.001 + 12.5,
+                -n * 0.0003 - 45.25,
+                n * 1.07,
+                row % 97
+            )
+        })
+        .collect();
+    format!("[{}]", rows.join(",\n"))
+}
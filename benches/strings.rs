@@ -0,0 +1,51 @@
+//! Times parsing of a string-heavy document.
+//!
+//! Run with `cargo bench --bench strings`.
+
+use std::{hint::black_box, time::Instant};
+
+const ROWS: usize = 20_000;
+const ITERATIONS: u32 = 20;
+
+fn main() {
+    let input = string_document();
+
+    // Warm up caches and the allocator before timing
+    json_parser::parse(input.clone()).unwrap();
+
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        black_box(json_parser::parse(black_box(input.clone())).unwrap());
+    }
+    let elapsed = start.elapsed() / ITERATIONS;
+
+    let megabytes = input.len() as f64 / (1024.0 * 1024.0);
+    println!(
+        "parsed {:.1} MiB of strings in {:?} ({:.1} MiB/s)",
+        megabytes,
+        elapsed,
+        megabytes / elapsed.as_secs_f64()
+    );
+}
+
+/// Rows of long prose with the odd escape and non-ASCII char.
+fn string_document() -> String {
+    let mut input = String::from("[");
+    for row in 0..ROWS {
+        if row > 0 {
+            input.push(',');
+        }
+        input.push_str(&format!(
+            concat!(
+                r#"{{"id":"row-{}","title":"Lorem ipsum dolor sit amet, consectetur adipiscing elit","#,
+                r#""body":"Sed do eiusmod tempor incididunt ut labore et dolore magna aliqua. "#,
+                r#"Ut enim ad minim veniam, quis nostrud exercitation ullamco laboris nisi ut "#,
+                r#"aliquip ex ea commodo consequat.\nDuis aute irure dolor in reprehenderit","#,
+                r#""author":"Zoë \"the editor\" Müller"}}"#
+            ),
+            row
+        ));
+    }
+    input.push(']');
+    input
+}
@@ -0,0 +1,272 @@
+use std::{cell::RefCell, collections::BTreeSet};
+
+use crate::{JsonPointer, Value, ValueKind};
+
+/// Wraps a document and records which parts of it were read through
+/// [`Tracked`] views, so unused payload fields can be found.
+///
+/// A value counts as read once a view reached it. Reading a container's
+/// contents through [`Tracked::value`] marks everything below it as read.
+#[derive(Debug)]
+pub struct AccessTracker {
+    value: Value,
+    reached: RefCell<BTreeSet<JsonPointer>>,
+    consumed: RefCell<BTreeSet<JsonPointer>>,
+}
+
+impl AccessTracker {
+    pub fn new(value: Value) -> Self {
+        Self {
+            value,
+            reached: RefCell::new(BTreeSet::new()),
+            consumed: RefCell::new(BTreeSet::new()),
+        }
+    }
+
+    /// View of the whole document.
+    pub fn root(&self) -> Tracked<'_> {
+        self.view(&self.value, JsonPointer::root())
+    }
+
+    /// Pointers to every value read so far, in sorted order.
+    pub fn accessed(&self) -> Vec<JsonPointer> {
+        self.reached.borrow().iter().cloned().collect()
+    }
+
+    /// Pointers to the values that were never read, in sorted order. Members
+    /// and elements of an unread container are not listed separately.
+    pub fn unused(&self) -> Vec<JsonPointer> {
+        let mut unused = Vec::new();
+        self.collect_unused(&self.value, &mut JsonPointer::root(), &mut unused);
+        unused.sort();
+        unused
+    }
+
+    pub fn into_inner(self) -> Value {
+        self.value
+    }
+
+    fn view<'a>(&'a self, value: &'a Value, pointer: JsonPointer) -> Tracked<'a> {
+        self.reached.borrow_mut().insert(pointer.clone());
+        Tracked {
+            tracker: self,
+            value,
+            pointer,
+        }
+    }
+
+    fn collect_unused(
+        &self,
+        value: &Value,
+        current: &mut JsonPointer,
+        unused: &mut Vec<JsonPointer>,
+    ) {
+        if !current.is_root() && !self.reached.borrow().contains(current) {
+            unused.push(current.clone());
+            return;
+        }
+        if self.consumed.borrow().contains(current) {
+            return;
+        }
+
+        match value {
+            Value::Array(values) => {
+                for (index, element) in values.iter().enumerate() {
+                    current.push(index.to_string());
+                    self.collect_unused(element, current, unused);
+                    current.pop();
+                }
+            }
+            Value::Object(map) => {
+                for (key, member) in map {
+                    current.push(key.as_str());
+                    self.collect_unused(member, current, unused);
+                    current.pop();
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Read-only view of a value inside an [`AccessTracker`], recording each
+/// member and element it hands out.
+#[derive(Debug, Clone)]
+pub struct Tracked<'a> {
+    tracker: &'a AccessTracker,
+    value: &'a Value,
+    pointer: JsonPointer,
+}
+
+impl<'a> Tracked<'a> {
+    /// Location of the viewed value in the document.
+    pub fn pointer(&self) -> &JsonPointer {
+        &self.pointer
+    }
+
+    pub fn kind(&self) -> ValueKind {
+        self.value.kind()
+    }
+
+    /// The viewed value itself, marking everything below it as read.
+    pub fn value(&self) -> &'a Value {
+        self.tracker
+            .consumed
+            .borrow_mut()
+            .insert(self.pointer.clone());
+        self.value
+    }
+
+    /// Object member with the given key.
+    pub fn get(&self, key: &str) -> Option<Tracked<'a>> {
+        let Value::Object(map) = self.value else {
+            return None;
+        };
+        let member = map.get(key)?;
+        Some(self.child(key, member))
+    }
+
+    /// Object member looked up like [`Value::get_ignore_case`], recorded
+    /// under the key it is stored with.
+    pub fn get_ignore_case(&self, key: &str) -> Option<Tracked<'a>> {
+        let Value::Object(map) = self.value else {
+            return None;
+        };
+        let member = self.value.get_ignore_case(key)?;
+        let (stored_key, _) = map
+            .iter()
+            .find(|(_, candidate)| std::ptr::eq(*candidate, member))?;
+        Some(self.child(stored_key, member))
+    }
+
+    /// Array element at the given index.
+    pub fn element(&self, index: usize) -> Option<Tracked<'a>> {
+        let Value::Array(values) = self.value else {
+            return None;
+        };
+        let element = values.get(index)?;
+        Some(self.child(index.to_string(), element))
+    }
+
+    /// Every object member, all of which are recorded as read. Empty for
+    /// anything but an object.
+    pub fn members(&self) -> Vec<(&'a str, Tracked<'a>)> {
+        let Value::Object(map) = self.value else {
+            return Vec::new();
+        };
+        map.iter()
+            .map(|(key, member)| (key.as_str(), self.child(key.as_str(), member)))
+            .collect()
+    }
+
+    /// Every array element, all of which are recorded as read. Empty for
+    /// anything but an array.
+    pub fn elements(&self) -> Vec<Tracked<'a>> {
+        let Value::Array(values) = self.value else {
+            return Vec::new();
+        };
+        values
+            .iter()
+            .enumerate()
+            .map(|(index, element)| self.child(index.to_string(), element))
+            .collect()
+    }
+
+    pub fn as_str(&self) -> Option<&'a str> {
+        match self.value {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self.value {
+            Value::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self.value {
+            Value::Boolean(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    fn child(&self, token: impl Into<String>, value: &'a Value) -> Tracked<'a> {
+        self.tracker.view(value, self.pointer.child(token))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{JsonPointer, Value};
+
+    use super::AccessTracker;
+
+    fn pointers(pointers: &[&str]) -> Vec<JsonPointer> {
+        pointers
+            .iter()
+            .map(|pointer| JsonPointer::parse(pointer).unwrap())
+            .collect()
+    }
+
+    fn order() -> Value {
+        Value::object([
+            ("id", Value::string("o-1")),
+            ("note", Value::string("leave at door")),
+            (
+                "items",
+                Value::array([
+                    Value::object([("sku", Value::string("a")), ("qty", Value::number(1.0))]),
+                    Value::object([("sku", Value::string("b")), ("qty", Value::number(2.0))]),
+                ]),
+            ),
+            ("customer", Value::object([("name", Value::string("Ana"))])),
+        ])
+    }
+
+    #[test]
+    fn reports_fields_never_read() {
+        let tracker = AccessTracker::new(order());
+        let root = tracker.root();
+
+        let id = root.get("id").and_then(|id| id.as_str());
+        let quantities: Vec<_> = root
+            .get("items")
+            .unwrap()
+            .elements()
+            .iter()
+            .filter_map(|item| item.get("qty")?.as_f64())
+            .collect();
+
+        assert_eq!(id, Some("o-1"));
+        assert_eq!(quantities, [1.0, 2.0]);
+        assert_eq!(
+            tracker.unused(),
+            pointers(&["/customer", "/items/0/sku", "/items/1/sku", "/note"])
+        );
+    }
+
+    #[test]
+    fn reading_whole_value_marks_its_contents() {
+        let tracker = AccessTracker::new(order());
+        let root = tracker.root();
+
+        root.get("items").unwrap().value();
+        root.get_ignore_case("CUSTOMER").unwrap().value();
+
+        assert_eq!(tracker.unused(), pointers(&["/id", "/note"]));
+        assert_eq!(tracker.accessed(), pointers(&["", "/customer", "/items"]));
+    }
+
+    #[test]
+    fn missing_members_are_not_recorded() {
+        let tracker = AccessTracker::new(order());
+
+        let missing = tracker.root().get("total");
+
+        assert!(missing.is_none());
+        assert_eq!(tracker.accessed(), pointers(&[""]));
+    }
+}
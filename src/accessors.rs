@@ -0,0 +1,211 @@
+use std::mem;
+
+use crate::{Map, Value};
+
+impl Value {
+    pub fn is_null(&self) -> bool {
+        matches!(self, Value::Null)
+    }
+
+    pub fn is_boolean(&self) -> bool {
+        matches!(self, Value::Boolean(_))
+    }
+
+    /// Whether the value is a string, stored as a `String` or a [`crate::Rope`].
+    pub fn is_string(&self) -> bool {
+        matches!(self, Value::String(_) | Value::Rope(_))
+    }
+
+    pub fn is_number(&self) -> bool {
+        matches!(self, Value::Number(_))
+    }
+
+    pub fn is_array(&self) -> bool {
+        matches!(self, Value::Array(_))
+    }
+
+    pub fn is_object(&self) -> bool {
+        matches!(self, Value::Object(_))
+    }
+
+    /// Whether the value is an array or object.
+    pub fn is_container(&self) -> bool {
+        self.kind().is_container()
+    }
+
+    /// `Some(())` for `null`, so it can be chained like the other accessors.
+    pub fn as_null(&self) -> Option<()> {
+        match self {
+            Value::Null => Some(()),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Boolean(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    /// `None` for a [`Value::Rope`], whose text is not contiguous, see
+    /// [`Value::as_text`].
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&Vec<Value>> {
+        match self {
+            Value::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    pub fn as_array_mut(&mut self) -> Option<&mut Vec<Value>> {
+        match self {
+            Value::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&Map<String, Value>> {
+        match self {
+            Value::Object(map) => Some(map),
+            _ => None,
+        }
+    }
+
+    pub fn as_object_mut(&mut self) -> Option<&mut Map<String, Value>> {
+        match self {
+            Value::Object(map) => Some(map),
+            _ => None,
+        }
+    }
+
+    /// Moves the text out of a string, including one stored as a rope.
+    /// `Value` implements `Drop`, so a `match` cannot move out of it.
+    pub fn into_string(mut self) -> Option<String> {
+        match &mut self {
+            Value::String(s) => Some(mem::take(s)),
+            Value::Rope(rope) => Some(rope.to_string()),
+            _ => None,
+        }
+    }
+
+    /// Moves the elements out of an array.
+    pub fn into_array(mut self) -> Option<Vec<Value>> {
+        match &mut self {
+            Value::Array(values) => Some(mem::take(values)),
+            _ => None,
+        }
+    }
+
+    /// Moves the members out of an object.
+    pub fn into_object(mut self) -> Option<Map<String, Value>> {
+        match &mut self {
+            Value::Object(map) => Some(mem::take(map)),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Map, Value};
+
+    #[test]
+    fn moves_contents_out() {
+        let array = Value::array([Value::Null]);
+        let object = Value::object([("a", Value::Boolean(true))]);
+
+        assert_eq!(Value::string("s").into_string(), Some(String::from("s")));
+        assert_eq!(
+            Value::Rope(crate::Rope::from("r")).into_string(),
+            Some(String::from("r"))
+        );
+        assert_eq!(array.clone().into_array(), Some(vec![Value::Null]));
+        assert_eq!(
+            object.clone().into_object(),
+            Some(Map::from([(String::from("a"), Value::Boolean(true))]))
+        );
+        assert_eq!(array.into_object(), None);
+        assert_eq!(object.into_string(), None);
+    }
+
+    #[test]
+    fn reads_matching_variant() {
+        let mut array = Value::array([Value::Null]);
+        let mut object = Value::object([("a", Value::Boolean(true))]);
+
+        assert_eq!(Value::Null.as_null(), Some(()));
+        assert_eq!(Value::Boolean(true).as_bool(), Some(true));
+        assert_eq!(Value::string("s").as_str(), Some("s"));
+        assert_eq!(Value::Number(1.5).as_f64(), Some(1.5));
+        assert_eq!(array.as_array(), Some(&vec![Value::Null]));
+        assert_eq!(
+            object.as_object(),
+            Some(&Map::from([(String::from("a"), Value::Boolean(true))]))
+        );
+
+        array.as_array_mut().unwrap().push(Value::Number(2.0));
+        object.as_object_mut().unwrap().remove("a");
+
+        assert_eq!(array, Value::array([Value::Null, Value::Number(2.0)]));
+        assert_eq!(object, Value::object::<&str, _>([]));
+    }
+
+    #[test]
+    fn misses_other_variants() {
+        let value = Value::string("1");
+
+        assert_eq!(value.as_null(), None);
+        assert_eq!(value.as_bool(), None);
+        assert_eq!(value.as_f64(), None);
+        assert_eq!(value.as_array(), None);
+        assert_eq!(value.as_object(), None);
+        assert_eq!(Value::Null.as_str(), None);
+    }
+
+    #[test]
+    fn checks_variant() {
+        let values = [
+            Value::Null,
+            Value::Boolean(false),
+            Value::string(""),
+            Value::Number(0.0),
+            Value::array([]),
+            Value::object::<&str, _>([]),
+        ];
+
+        let actual: Vec<[bool; 6]> = values
+            .iter()
+            .map(|value| {
+                [
+                    value.is_null(),
+                    value.is_boolean(),
+                    value.is_string(),
+                    value.is_number(),
+                    value.is_array(),
+                    value.is_object(),
+                ]
+            })
+            .collect();
+
+        for (index, flags) in actual.iter().enumerate() {
+            for (flag_index, flag) in flags.iter().enumerate() {
+                assert_eq!(*flag, index == flag_index);
+            }
+        }
+        assert!(values[4].is_container() && !values[3].is_container());
+    }
+}
@@ -0,0 +1,114 @@
+use crate::{Map, Value};
+
+/// Collects members for an object value, see [`Value::object_builder`].
+#[derive(Debug, Default)]
+pub struct ObjectBuilder {
+    map: Map<String, Value>,
+}
+
+/// Collects elements for an array value, see [`Value::array_builder`].
+#[derive(Debug, Default)]
+pub struct ArrayBuilder {
+    values: Vec<Value>,
+}
+
+impl Value {
+    /// Starts building an object member by member.
+    pub fn object_builder() -> ObjectBuilder {
+        ObjectBuilder::default()
+    }
+
+    /// Starts building an array element by element.
+    pub fn array_builder() -> ArrayBuilder {
+        ArrayBuilder::default()
+    }
+}
+
+impl ObjectBuilder {
+    /// Adds a member, replacing an earlier one with the same key.
+    pub fn insert(mut self, key: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.map.insert(key.into(), value.into());
+        self
+    }
+
+    /// Adds a member only when the value is present.
+    pub fn insert_some(self, key: impl Into<String>, value: Option<impl Into<Value>>) -> Self {
+        match value {
+            Some(value) => self.insert(key, value),
+            None => self,
+        }
+    }
+
+    pub fn build(self) -> Value {
+        Value::Object(self.map)
+    }
+}
+
+impl ArrayBuilder {
+    pub fn push(mut self, value: impl Into<Value>) -> Self {
+        self.values.push(value.into());
+        self
+    }
+
+    /// Adds every value of the iterator in order.
+    pub fn extend<I>(mut self, values: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<Value>,
+    {
+        self.values.extend(values.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn build(self) -> Value {
+        Value::Array(self.values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Value;
+
+    #[test]
+    fn builds_nested_document() {
+        let expected = Value::object([
+            ("id", Value::Number(1.0)),
+            (
+                "tags",
+                Value::Array(vec![
+                    Value::String(String::from("x")),
+                    Value::Boolean(true),
+                    Value::Number(2.0),
+                    Value::Number(3.0),
+                ]),
+            ),
+        ]);
+
+        let actual = Value::object_builder()
+            .insert("id", 1)
+            .insert(
+                "tags",
+                Value::array_builder()
+                    .push("x")
+                    .push(true)
+                    .extend([2, 3])
+                    .build(),
+            )
+            .insert_some("missing", None::<&str>)
+            .build();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn later_insert_replaces_member() {
+        let expected = Value::object([("a", Value::Null)]);
+
+        let actual = Value::object_builder()
+            .insert("a", 1)
+            .insert("a", Value::Null)
+            .build();
+
+        assert_eq!(actual, expected);
+    }
+}
@@ -0,0 +1,154 @@
+use std::{ops::Range, str};
+
+use crate::tokenizer::{tokenize, Token, TokenizeError};
+
+/// How byte sequences which are not valid UTF-8 are handled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InvalidUtf8Handling {
+    /// Fail, reporting every invalid range
+    Reject,
+
+    /// Replace each invalid range with U+FFFD and tokenize the rest
+    Replace,
+}
+
+/// Tokens read from byte input, with the byte ranges that were not valid UTF-8
+#[derive(Debug, PartialEq)]
+pub struct ByteTokens {
+    pub tokens: Vec<Token>,
+    pub invalid_utf8: Vec<Range<usize>>,
+}
+
+/// Possible errors that can occur when tokenizing byte input
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum BytesTokenizeError {
+    /// The input contains invalid UTF-8 at the given byte ranges
+    InvalidUtf8(Vec<Range<usize>>),
+
+    /// The decoded input could not be tokenized
+    TokenizeError(TokenizeError),
+}
+
+/// Returns the byte ranges of the input which are not valid UTF-8. Each range
+/// covers one maximal invalid sequence, as `String::from_utf8_lossy` replaces them.
+pub fn find_invalid_utf8(input: &[u8]) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut offset = 0;
+
+    while offset < input.len() {
+        match str::from_utf8(&input[offset..]) {
+            Ok(_) => break,
+            Err(err) => {
+                let start = offset + err.valid_up_to();
+                let len = err.error_len().unwrap_or(input.len() - start);
+                ranges.push(start..start + len);
+                offset = start + len;
+            }
+        }
+    }
+
+    ranges
+}
+
+/// Creates a vector of tokens from byte input, reporting invalid UTF-8 by byte range.
+pub fn tokenize_bytes(
+    input: &[u8],
+    handling: InvalidUtf8Handling,
+) -> Result<ByteTokens, BytesTokenizeError> {
+    let invalid_utf8 = find_invalid_utf8(input);
+
+    if !invalid_utf8.is_empty() && handling == InvalidUtf8Handling::Reject {
+        return Err(BytesTokenizeError::InvalidUtf8(invalid_utf8));
+    }
+
+    let decoded = String::from_utf8_lossy(input).into_owned();
+    let tokens = tokenize(decoded).map_err(BytesTokenizeError::TokenizeError)?;
+
+    Ok(ByteTokens {
+        tokens,
+        invalid_utf8,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Range;
+
+    use crate::tokenizer::{Token, TokenizeError};
+
+    use super::{find_invalid_utf8, tokenize_bytes, BytesTokenizeError, InvalidUtf8Handling};
+
+    fn bytes(start: usize, end: usize) -> Range<usize> {
+        Range { start, end }
+    }
+
+    #[test]
+    fn finds_nothing_in_valid_input() {
+        let input = "[\"ok\", \"é\"]".as_bytes();
+
+        let actual = find_invalid_utf8(input);
+
+        assert!(actual.is_empty());
+    }
+
+    #[test]
+    fn finds_invalid_ranges() {
+        let input = b"[\"a\xff\", \"b\xe2\x82\", \"\xc3\xa9\"]";
+        let expected = [bytes(3, 4), bytes(9, 11)];
+
+        let actual = find_invalid_utf8(input);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn finds_sequence_cut_off_at_end() {
+        let input = b"\"abc\xf0\x9f";
+        let expected = [bytes(4, 6)];
+
+        let actual = find_invalid_utf8(input);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn rejects_invalid_input() {
+        let input = b"[\"a\xff\"]";
+        let expected = Err(BytesTokenizeError::InvalidUtf8(vec![bytes(3, 4)]));
+
+        let actual = tokenize_bytes(input, InvalidUtf8Handling::Reject);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn replaces_invalid_input() {
+        let input = b"[\"a\xff\", 1]";
+
+        let actual = tokenize_bytes(input, InvalidUtf8Handling::Replace).unwrap();
+
+        assert_eq!(
+            actual.tokens,
+            [
+                Token::LeftBracket,
+                Token::string("a\u{fffd}"),
+                Token::Comma,
+                Token::Number(1.0),
+                Token::RightBracket
+            ]
+        );
+        assert_eq!(actual.invalid_utf8, [bytes(3, 4)]);
+    }
+
+    #[test]
+    fn reports_tokenize_errors_after_decoding() {
+        let input = b"[\xff]";
+        let expected = Err(BytesTokenizeError::TokenizeError(
+            TokenizeError::CharNotRecognized('\u{fffd}'),
+        ));
+
+        let actual = tokenize_bytes(input, InvalidUtf8Handling::Replace);
+
+        assert_eq!(actual, expected);
+    }
+}
@@ -0,0 +1,264 @@
+//! JSON Canonicalization Scheme (RFC 8785) output.
+
+use std::fmt::Write;
+
+use crate::{scan::find_escape_byte, JsonPointer, Value};
+
+/// Possible errors when writing canonical JSON
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CanonicalError {
+    /// The number at this location is NaN or infinite, which RFC 8785
+    /// cannot represent
+    NonFiniteNumber(JsonPointer),
+}
+
+impl Value {
+    /// Writes the value as canonical JSON text following RFC 8785: no
+    /// whitespace, object members sorted by the UTF-16 code units of their
+    /// keys, numbers formatted like ECMAScript and strings escaped minimally.
+    /// Equal values always produce the same bytes, so the output can be
+    /// signed or hashed.
+    ///
+    /// RFC 8785 requires non-finite numbers to fail. They are written as
+    /// `null` here, so the output is then a different document; use
+    /// [`Value::try_to_canonical_string`] before signing.
+    pub fn to_canonical_string(&self) -> String {
+        let mut output = String::new();
+        write_canonical(self, &mut output, false).expect("non-finite numbers become null");
+        output
+    }
+
+    /// Like [`Value::to_canonical_string`], but fails on NaN and infinite
+    /// numbers as RFC 8785 requires.
+    pub fn try_to_canonical_string(&self) -> Result<String, CanonicalError> {
+        let mut output = String::new();
+        write_canonical(self, &mut output, true).map_err(|mut tokens| {
+            let mut pointer = JsonPointer::root();
+            while let Some(token) = tokens.pop() {
+                pointer.push(token);
+            }
+            CanonicalError::NonFiniteNumber(pointer)
+        })?;
+        Ok(output)
+    }
+}
+
+/// Writes the value, failing on non-finite numbers when `strict`. The error
+/// holds the reference tokens of the number's location, innermost first.
+fn write_canonical(value: &Value, output: &mut String, strict: bool) -> Result<(), Vec<String>> {
+    match value {
+        Value::Null => output.push_str("null"),
+        Value::Boolean(b) => output.push_str(if *b { "true" } else { "false" }),
+        Value::Number(n) if strict && !n.is_finite() => return Err(Vec::new()),
+        Value::Number(n) => write_number(*n, output),
+        Value::String(s) => write_string(s, output),
+        #[cfg(feature = "datetime")]
+        Value::DateTime(date_time) => write_string(&date_time.to_string(), output),
+        Value::Rope(rope) => write_string(&rope.to_string(), output),
+        Value::Array(values) => {
+            output.push('[');
+            for (index, value) in values.iter().enumerate() {
+                if index > 0 {
+                    output.push(',');
+                }
+                write_canonical(value, output, strict).map_err(|mut tokens| {
+                    tokens.push(index.to_string());
+                    tokens
+                })?;
+            }
+            output.push(']');
+        }
+        Value::Object(map) => {
+            let mut members: Vec<_> = map.iter().collect();
+            members.sort_unstable_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+
+            output.push('{');
+            for (index, (key, value)) in members.into_iter().enumerate() {
+                if index > 0 {
+                    output.push(',');
+                }
+                write_string(key, output);
+                output.push(':');
+                write_canonical(value, output, strict).map_err(|mut tokens| {
+                    tokens.push(key.clone());
+                    tokens
+                })?;
+            }
+            output.push('}');
+        }
+    }
+    Ok(())
+}
+
+/// Writes the number as ECMAScript's `Number.prototype.toString` does:
+/// the shortest round-tripping digits, in plain notation for exponents from
+/// -7 to 20 and in exponent notation otherwise.
+fn write_number(n: f64, output: &mut String) {
+    if !n.is_finite() {
+        output.push_str("null");
+        return;
+    }
+    if n == 0.0 {
+        output.push('0');
+        return;
+    }
+    if n < 0.0 {
+        output.push('-');
+    }
+
+    // `{:e}` gives the shortest round-tripping digits, e.g. "1.2345e-7"
+    let scientific = format!("{:e}", n.abs());
+    let (mantissa, exponent) = scientific
+        .split_once('e')
+        .expect("exponent formatting has an exponent");
+    let digits: String = mantissa.chars().filter(|&c| c != '.').collect();
+    let exponent: i32 = exponent.parse().expect("exponent is an integer");
+
+    // Position of the decimal point relative to the start of the digits
+    let point = exponent + 1;
+    let len = digits.len() as i32;
+
+    if len <= point && point <= 21 {
+        output.push_str(&digits);
+        output.extend(std::iter::repeat_n('0', (point - len) as usize));
+    } else if 0 < point && point <= 21 {
+        let (integer, fraction) = digits.split_at(point as usize);
+        output.push_str(integer);
+        output.push('.');
+        output.push_str(fraction);
+    } else if -6 < point && point <= 0 {
+        output.push_str("0.");
+        output.extend(std::iter::repeat_n('0', -point as usize));
+        output.push_str(&digits);
+    } else {
+        let (first, rest) = digits.split_at(1);
+        output.push_str(first);
+        if !rest.is_empty() {
+            output.push('.');
+            output.push_str(rest);
+        }
+        let sign = if point > 0 { '+' } else { '-' };
+        write!(output, "e{sign}{}", (point - 1).abs()).expect("writing to a String cannot fail");
+    }
+}
+
+/// Writes the string between quotes, using the short escapes for quotes,
+/// backslashes and the control characters that have one and `\u00xx` for
+/// the other control characters.
+fn write_string(s: &str, output: &mut String) {
+    output.push('"');
+
+    let mut rest = s;
+    while let Some(index) = find_escape_byte(rest.as_bytes()) {
+        output.push_str(&rest[..index]);
+        match rest.as_bytes()[index] {
+            b'"' => output.push_str("\\\""),
+            b'\\' => output.push_str("\\\\"),
+            0x08 => output.push_str("\\b"),
+            0x0C => output.push_str("\\f"),
+            b'\n' => output.push_str("\\n"),
+            b'\r' => output.push_str("\\r"),
+            b'\t' => output.push_str("\\t"),
+            byte => write!(output, "\\u{byte:04x}").expect("writing to a String cannot fail"),
+        }
+        rest = &rest[index + 1..];
+    }
+    output.push_str(rest);
+
+    output.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse, JsonPointer, Value};
+
+    use super::CanonicalError;
+
+    #[test]
+    fn formats_numbers_like_ecmascript() {
+        let cases = [
+            (0.0, "0"),
+            (-0.0, "0"),
+            (1.0, "1"),
+            (-1.5, "-1.5"),
+            (0.1, "0.1"),
+            (123456789.0, "123456789"),
+            (1e20, "100000000000000000000"),
+            (1e21, "1e+21"),
+            (1.5e300, "1.5e+300"),
+            (0.000001, "0.000001"),
+            (0.0000001, "1e-7"),
+            (-1.2345e-10, "-1.2345e-10"),
+            (9007199254740992.0, "9007199254740992"),
+            (333333333.3333333, "333333333.3333333"),
+            (f64::MIN_POSITIVE, "2.2250738585072014e-308"),
+            (5e-324, "5e-324"),
+        ];
+
+        for (input, expected) in cases {
+            let actual = Value::Number(input).to_canonical_string();
+
+            assert_eq!(actual, expected, "input: {input:e}");
+        }
+    }
+
+    #[test]
+    fn sorts_keys_by_utf16_code_units() {
+        // U+1F600 is encoded as the surrogates D83D DE00, which sort before U+FB01
+        let input = Value::object([
+            ("\u{fb01}", Value::Number(1.0)),
+            ("\u{1f600}", Value::Number(2.0)),
+            ("b", Value::Number(3.0)),
+            ("a", Value::object([("y", Value::Null), ("x", Value::Null)])),
+        ]);
+        let expected = "{\"a\":{\"x\":null,\"y\":null},\"b\":3,\"\u{1f600}\":2,\"\u{fb01}\":1}";
+
+        let actual = input.to_canonical_string();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn escapes_strings_minimally() {
+        let input = Value::string("\u{8}\u{c}\n\u{1f}\"\\/é\u{7f}");
+        let expected = "\"\\b\\f\\n\\u001f\\\"\\\\/é\u{7f}\"";
+
+        let actual = input.to_canonical_string();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn round_trips_through_parse() {
+        let input = Value::array([
+            Value::Number(1e21),
+            Value::Number(-1.5e300),
+            Value::Number(1e-7),
+            Value::Number(5e-324),
+            Value::Number(0.1),
+            Value::object([("a", Value::Number(-2.5e-10))]),
+        ]);
+
+        let actual = parse(input.to_canonical_string()).unwrap();
+
+        assert_eq!(actual, input);
+    }
+
+    #[test]
+    fn fails_on_non_finite_numbers() {
+        let input = Value::object([(
+            "a",
+            Value::array([Value::Number(1.0), Value::Number(f64::NAN)]),
+        )]);
+        let expected = Err(CanonicalError::NonFiniteNumber(
+            JsonPointer::parse("/a/1").unwrap(),
+        ));
+
+        assert_eq!(input.try_to_canonical_string(), expected);
+        assert_eq!(input.to_canonical_string(), r#"{"a":[1,null]}"#);
+        assert_eq!(
+            Value::Number(1.0).try_to_canonical_string(),
+            Ok(String::from("1"))
+        );
+    }
+}
@@ -0,0 +1,355 @@
+use std::ops::{Bound, RangeBounds};
+
+use crate::{convert::mismatch, JsonPointer, KindMismatch, Value, ValueKind};
+
+/// Lightweight description of the values a document may hold, built
+/// fluently and evaluated with [`Check::validate`].
+///
+/// Modifiers such as [`Check::range`] only apply to checks of their kind and
+/// are ignored by the others.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Check {
+    rule: Rule,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Rule {
+    Any,
+    Null,
+    Boolean,
+    Number {
+        integer: bool,
+        min: Bound<f64>,
+        max: Bound<f64>,
+    },
+    String {
+        non_empty: bool,
+        max_len: Option<usize>,
+    },
+    Array {
+        items: Option<Box<Check>>,
+        non_empty: bool,
+    },
+    Object {
+        fields: Vec<Field>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Field {
+    key: String,
+    check: Check,
+    required: bool,
+}
+
+/// A value that failed a check
+#[derive(Debug, Clone, PartialEq)]
+pub struct Violation {
+    /// Location of the value, or of the missing member
+    pub path: JsonPointer,
+    pub problem: Problem,
+}
+
+/// Reasons a value can fail a check
+#[derive(Debug, Clone, PartialEq)]
+pub enum Problem {
+    /// The value is not of the kind the check expects
+    WrongKind(KindMismatch),
+
+    /// The number has a fractional part
+    NotAnInteger(f64),
+
+    /// The number is outside the allowed range
+    OutOfRange(f64),
+
+    /// The string or array is empty
+    Empty,
+
+    /// The string has more characters than allowed
+    TooLong { len: usize, max: usize },
+
+    /// A required object member is absent
+    Missing,
+}
+
+impl Check {
+    /// Accepts every value.
+    pub fn any() -> Self {
+        Self::new(Rule::Any)
+    }
+
+    pub fn null() -> Self {
+        Self::new(Rule::Null)
+    }
+
+    pub fn boolean() -> Self {
+        Self::new(Rule::Boolean)
+    }
+
+    pub fn number() -> Self {
+        Self::new(Rule::Number {
+            integer: false,
+            min: Bound::Unbounded,
+            max: Bound::Unbounded,
+        })
+    }
+
+    /// A number without a fractional part.
+    pub fn integer() -> Self {
+        Self::new(Rule::Number {
+            integer: true,
+            min: Bound::Unbounded,
+            max: Bound::Unbounded,
+        })
+    }
+
+    pub fn string() -> Self {
+        Self::new(Rule::String {
+            non_empty: false,
+            max_len: None,
+        })
+    }
+
+    /// An array whose elements are not checked unless [`Check::items`] is used.
+    pub fn array() -> Self {
+        Self::new(Rule::Array {
+            items: None,
+            non_empty: false,
+        })
+    }
+
+    /// An object which may hold members besides the ones given with
+    /// [`Check::field`] and [`Check::optional_field`].
+    pub fn object() -> Self {
+        Self::new(Rule::Object { fields: Vec::new() })
+    }
+
+    /// Restricts numbers to the range, such as `1..65536`.
+    pub fn range<T: Into<f64> + Copy>(mut self, range: impl RangeBounds<T>) -> Self {
+        if let Rule::Number { min, max, .. } = &mut self.rule {
+            *min = range.start_bound().map(|bound| (*bound).into());
+            *max = range.end_bound().map(|bound| (*bound).into());
+        }
+        self
+    }
+
+    /// Rejects empty strings and arrays.
+    pub fn non_empty(mut self) -> Self {
+        match &mut self.rule {
+            Rule::String { non_empty, .. } | Rule::Array { non_empty, .. } => *non_empty = true,
+            _ => {}
+        }
+        self
+    }
+
+    /// Limits strings to `max` characters.
+    pub fn max_len(mut self, max: usize) -> Self {
+        if let Rule::String { max_len, .. } = &mut self.rule {
+            *max_len = Some(max);
+        }
+        self
+    }
+
+    /// Checks every element of an array.
+    pub fn items(mut self, check: Check) -> Self {
+        if let Rule::Array { items, .. } = &mut self.rule {
+            *items = Some(Box::new(check));
+        }
+        self
+    }
+
+    /// Requires an object member and checks its value.
+    pub fn field(self, key: impl Into<String>, check: Check) -> Self {
+        self.add_field(key.into(), check, true)
+    }
+
+    /// Checks the value of an object member when it is present.
+    pub fn optional_field(self, key: impl Into<String>, check: Check) -> Self {
+        self.add_field(key.into(), check, false)
+    }
+
+    /// Every violation found in the value, empty when it passes.
+    pub fn validate(&self, value: &Value) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        self.collect(value, &mut JsonPointer::root(), &mut violations);
+        violations
+    }
+
+    fn new(rule: Rule) -> Self {
+        Self { rule }
+    }
+
+    fn add_field(mut self, key: String, check: Check, required: bool) -> Self {
+        if let Rule::Object { fields } = &mut self.rule {
+            fields.push(Field {
+                key,
+                check,
+                required,
+            });
+        }
+        self
+    }
+
+    fn collect(&self, value: &Value, path: &mut JsonPointer, violations: &mut Vec<Violation>) {
+        let mut report = |problem| {
+            violations.push(Violation {
+                path: path.clone(),
+                problem,
+            })
+        };
+
+        match (&self.rule, value) {
+            (Rule::Any, _) | (Rule::Null, Value::Null) | (Rule::Boolean, Value::Boolean(_)) => {}
+            (Rule::Number { integer, min, max }, Value::Number(n)) => {
+                if *integer && n.fract() != 0.0 {
+                    report(Problem::NotAnInteger(*n));
+                } else if !(*min, *max).contains(n) {
+                    report(Problem::OutOfRange(*n));
+                }
+            }
+            (Rule::String { non_empty, max_len }, Value::String(s)) => {
+                let len = s.chars().count();
+                if *non_empty && len == 0 {
+                    report(Problem::Empty);
+                }
+                if let Some(max) = *max_len {
+                    if len > max {
+                        report(Problem::TooLong { len, max });
+                    }
+                }
+            }
+            (Rule::Array { items, non_empty }, Value::Array(values)) => {
+                if *non_empty && values.is_empty() {
+                    report(Problem::Empty);
+                }
+                if let Some(check) = items {
+                    for (index, element) in values.iter().enumerate() {
+                        path.push(index.to_string());
+                        check.collect(element, path, violations);
+                        path.pop();
+                    }
+                }
+            }
+            (Rule::Object { fields }, Value::Object(map)) => {
+                for field in fields {
+                    path.push(field.key.clone());
+                    match map.get(&field.key) {
+                        Some(member) => field.check.collect(member, path, violations),
+                        None if field.required => violations.push(Violation {
+                            path: path.clone(),
+                            problem: Problem::Missing,
+                        }),
+                        None => {}
+                    }
+                    path.pop();
+                }
+            }
+            (rule, value) => report(Problem::WrongKind(mismatch(rule.kind(), value))),
+        }
+    }
+}
+
+impl Rule {
+    /// Kind of value the rule accepts, for reporting mismatches.
+    fn kind(&self) -> ValueKind {
+        match self {
+            Rule::Any | Rule::Null => ValueKind::Null,
+            Rule::Boolean => ValueKind::Boolean,
+            Rule::Number { .. } => ValueKind::Number,
+            Rule::String { .. } => ValueKind::String,
+            Rule::Array { .. } => ValueKind::Array,
+            Rule::Object { .. } => ValueKind::Object,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{JsonPointer, KindMismatch, Value, ValueKind};
+
+    use super::{Check, Problem, Violation};
+
+    fn server() -> Check {
+        Check::object()
+            .field("port", Check::integer().range(1..65536))
+            .field("host", Check::string().non_empty())
+            .optional_field("tags", Check::array().items(Check::string().max_len(3)))
+    }
+
+    fn violation(path: &str, problem: Problem) -> Violation {
+        Violation {
+            path: JsonPointer::parse(path).unwrap(),
+            problem,
+        }
+    }
+
+    #[test]
+    fn accepts_matching_document() {
+        let input = Value::object([
+            ("port", Value::Number(8080.0)),
+            ("host", Value::string("localhost")),
+            ("extra", Value::Null),
+        ]);
+
+        assert_eq!(server().validate(&input), vec![]);
+    }
+
+    #[test]
+    fn reports_every_violation_with_its_path() {
+        let input = Value::object([
+            ("port", Value::Number(65536.0)),
+            (
+                "tags",
+                Value::array([Value::string("ok"), Value::string("long"), Value::Null]),
+            ),
+        ]);
+        let expected = vec![
+            violation("/port", Problem::OutOfRange(65536.0)),
+            violation("/host", Problem::Missing),
+            violation("/tags/1", Problem::TooLong { len: 4, max: 3 }),
+            violation(
+                "/tags/2",
+                Problem::WrongKind(KindMismatch {
+                    expected: ValueKind::String,
+                    found: ValueKind::Null,
+                }),
+            ),
+        ];
+
+        let actual = server().validate(&input);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn checks_numbers_and_emptiness() {
+        let cases = [
+            (
+                Check::integer(),
+                Value::Number(1.5),
+                vec![violation("", Problem::NotAnInteger(1.5))],
+            ),
+            (Check::number().range(0.0..=1.0), Value::Number(1.0), vec![]),
+            (
+                Check::number().range(..0),
+                Value::Number(0.0),
+                vec![violation("", Problem::OutOfRange(0.0))],
+            ),
+            (
+                Check::string().non_empty(),
+                Value::string(""),
+                vec![violation("", Problem::Empty)],
+            ),
+            (
+                Check::array().non_empty(),
+                Value::array([]),
+                vec![violation("", Problem::Empty)],
+            ),
+            (Check::any(), Value::array([]), vec![]),
+        ];
+
+        for (check, input, expected) in cases {
+            assert_eq!(check.validate(&input), expected, "{input}");
+        }
+    }
+}
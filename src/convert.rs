@@ -0,0 +1,562 @@
+use std::{collections::HashMap, fmt, mem};
+
+use crate::{Map, Value, ValueKind};
+
+/// A value was not of the kind a conversion required
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct KindMismatch {
+    pub expected: ValueKind,
+    pub found: ValueKind,
+}
+
+impl fmt::Display for KindMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "expected {}, found {}",
+            kind_name(self.expected),
+            kind_name(self.found)
+        )
+    }
+}
+
+/// Possible errors when converting a value into an `i64`
+#[derive(Debug, PartialEq, Clone)]
+pub enum IntegerError {
+    /// The value is not a number
+    NotANumber(KindMismatch),
+
+    /// The number has a fractional part or does not fit in an `i64`
+    NotAnInteger(f64),
+}
+
+impl fmt::Display for IntegerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IntegerError::NotANumber(mismatch) => mismatch.fmt(f),
+            IntegerError::NotAnInteger(n) => write!(f, "expected integer, found {n}"),
+        }
+    }
+}
+
+/// Possible errors that can occur when converting the contents of a container
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ConvertError<E> {
+    /// The value is not the container the conversion expects
+    NotAContainer(KindMismatch),
+
+    /// The object member with this key failed to convert
+    Key(String, E),
+
+    /// The array element at this index failed to convert
+    Index(usize, E),
+}
+
+impl Value {
+    /// Converts every member of an object, reporting the key of a member
+    /// that fails to convert.
+    pub fn into_map_of<T>(mut self) -> Result<Map<String, T>, ConvertError<T::Error>>
+    where
+        T: TryFrom<Value>,
+    {
+        match &mut self {
+            Value::Object(map) => mem::take(map)
+                .into_iter()
+                .map(|(key, value)| match T::try_from(value) {
+                    Ok(converted) => Ok((key, converted)),
+                    Err(err) => Err(ConvertError::Key(key, err)),
+                })
+                .collect(),
+            other => Err(ConvertError::NotAContainer(KindMismatch {
+                expected: ValueKind::Object,
+                found: other.kind(),
+            })),
+        }
+    }
+
+    /// Converts every element of an array, reporting the index of the first
+    /// element that fails to convert.
+    pub fn into_vec_of<T>(mut self) -> Result<Vec<T>, ConvertError<T::Error>>
+    where
+        T: TryFrom<Value>,
+    {
+        match &mut self {
+            Value::Array(values) => mem::take(values)
+                .into_iter()
+                .enumerate()
+                .map(|(index, value)| {
+                    T::try_from(value).map_err(|err| ConvertError::Index(index, err))
+                })
+                .collect(),
+            other => Err(ConvertError::NotAContainer(KindMismatch {
+                expected: ValueKind::Array,
+                found: other.kind(),
+            })),
+        }
+    }
+}
+
+impl TryFrom<Value> for String {
+    type Error = KindMismatch;
+
+    fn try_from(mut value: Value) -> Result<Self, Self::Error> {
+        match &mut value {
+            Value::String(s) => Ok(mem::take(s)),
+            Value::Rope(rope) => Ok(rope.to_string()),
+            other => Err(mismatch(ValueKind::String, other)),
+        }
+    }
+}
+
+impl TryFrom<Value> for f64 {
+    type Error = KindMismatch;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Number(n) => Ok(n),
+            other => Err(mismatch(ValueKind::Number, &other)),
+        }
+    }
+}
+
+impl TryFrom<Value> for bool {
+    type Error = KindMismatch;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Boolean(b) => Ok(b),
+            other => Err(mismatch(ValueKind::Boolean, &other)),
+        }
+    }
+}
+
+impl TryFrom<Value> for i64 {
+    type Error = IntegerError;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        i64::try_from(&value)
+    }
+}
+
+impl TryFrom<Value> for Vec<Value> {
+    type Error = KindMismatch;
+
+    fn try_from(mut value: Value) -> Result<Self, Self::Error> {
+        match &mut value {
+            Value::Array(values) => Ok(mem::take(values)),
+            other => Err(mismatch(ValueKind::Array, other)),
+        }
+    }
+}
+
+impl TryFrom<Value> for HashMap<String, Value> {
+    type Error = KindMismatch;
+
+    fn try_from(mut value: Value) -> Result<Self, Self::Error> {
+        match &mut value {
+            Value::Object(map) => Ok(mem::take(map).into_iter().collect()),
+            other => Err(mismatch(ValueKind::Object, other)),
+        }
+    }
+}
+
+impl TryFrom<&Value> for String {
+    type Error = KindMismatch;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::String(s) => Ok(s.clone()),
+            Value::Rope(rope) => Ok(rope.to_string()),
+            other => Err(mismatch(ValueKind::String, other)),
+        }
+    }
+}
+
+impl TryFrom<&Value> for f64 {
+    type Error = KindMismatch;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Number(n) => Ok(*n),
+            other => Err(mismatch(ValueKind::Number, other)),
+        }
+    }
+}
+
+/// Only numbers without a fractional part that fit in an `i64` convert.
+impl TryFrom<&Value> for i64 {
+    type Error = IntegerError;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        let n = f64::try_from(value).map_err(IntegerError::NotANumber)?;
+        // 2^63 is exactly representable, so the upper bound is exclusive.
+        if n.fract() == 0.0 && n >= i64::MIN as f64 && n < i64::MAX as f64 {
+            Ok(n as i64)
+        } else {
+            Err(IntegerError::NotAnInteger(n))
+        }
+    }
+}
+
+impl TryFrom<&Value> for bool {
+    type Error = KindMismatch;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Boolean(b) => Ok(*b),
+            other => Err(mismatch(ValueKind::Boolean, other)),
+        }
+    }
+}
+
+impl TryFrom<&Value> for Vec<Value> {
+    type Error = KindMismatch;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Array(values) => Ok(values.clone()),
+            other => Err(mismatch(ValueKind::Array, other)),
+        }
+    }
+}
+
+impl TryFrom<&Value> for HashMap<String, Value> {
+    type Error = KindMismatch;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Object(map) => Ok(map
+                .iter()
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect()),
+            other => Err(mismatch(ValueKind::Object, other)),
+        }
+    }
+}
+
+impl From<bool> for Value {
+    fn from(b: bool) -> Self {
+        Value::Boolean(b)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(n: f64) -> Self {
+        Value::Number(n)
+    }
+}
+
+/// Integers are stored as `f64`, so those beyond 2^53 lose precision.
+macro_rules! from_integer {
+    ($($int:ty),*) => {
+        $(
+            impl From<$int> for Value {
+                fn from(n: $int) -> Self {
+                    Value::Number(n as f64)
+                }
+            }
+        )*
+    };
+}
+
+from_integer!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize, f32);
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Self {
+        Value::String(String::from(s))
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Self {
+        Value::String(s)
+    }
+}
+
+impl<V: Into<Value>> From<Vec<V>> for Value {
+    fn from(values: Vec<V>) -> Self {
+        Value::Array(values.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<V: Into<Value>> From<HashMap<String, V>> for Value {
+    fn from(map: HashMap<String, V>) -> Self {
+        Value::Object(
+            map.into_iter()
+                .map(|(key, value)| (key, value.into()))
+                .collect(),
+        )
+    }
+}
+
+#[cfg(feature = "preserve_order")]
+impl<V: Into<Value>> From<crate::OrderedMap<String, V>> for Value {
+    fn from(map: crate::OrderedMap<String, V>) -> Self {
+        Value::Object(
+            map.into_iter()
+                .map(|(key, value)| (key, value.into()))
+                .collect(),
+        )
+    }
+}
+
+impl FromIterator<Value> for Value {
+    fn from_iter<I: IntoIterator<Item = Value>>(values: I) -> Self {
+        Value::Array(values.into_iter().collect())
+    }
+}
+
+/// Later pairs replace earlier ones with the same key.
+impl FromIterator<(String, Value)> for Value {
+    fn from_iter<I: IntoIterator<Item = (String, Value)>>(pairs: I) -> Self {
+        Value::Object(pairs.into_iter().collect())
+    }
+}
+
+/// Appends to an array.
+///
+/// # Panics
+///
+/// Panics if the value is not an array.
+impl Extend<Value> for Value {
+    fn extend<I: IntoIterator<Item = Value>>(&mut self, values: I) {
+        match self {
+            Value::Array(array) => array.extend(values),
+            other => panic!("cannot extend {}", kind_name(other.kind())),
+        }
+    }
+}
+
+/// `None` becomes `null`.
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(option: Option<T>) -> Self {
+        match option {
+            Some(value) => value.into(),
+            None => Value::Null,
+        }
+    }
+}
+
+pub(crate) fn mismatch(expected: ValueKind, found: &Value) -> KindMismatch {
+    KindMismatch {
+        expected,
+        found: found.kind(),
+    }
+}
+
+pub(crate) fn kind_name(kind: ValueKind) -> &'static str {
+    match kind {
+        ValueKind::Null => "null",
+        ValueKind::Boolean => "boolean",
+        ValueKind::String => "string",
+        ValueKind::Number => "number",
+        ValueKind::Array => "array",
+        ValueKind::Object => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{Map, Value, ValueKind};
+
+    use super::{ConvertError, IntegerError, KindMismatch};
+
+    /// A caller-defined type converted from `{"x": <number>}` objects.
+    #[derive(Debug, PartialEq)]
+    struct Point {
+        x: f64,
+    }
+
+    impl TryFrom<Value> for Point {
+        type Error = String;
+
+        fn try_from(mut value: Value) -> Result<Self, Self::Error> {
+            match &mut value {
+                Value::Object(map) => match map.remove("x") {
+                    Some(Value::Number(x)) => Ok(Point { x }),
+                    _ => Err(String::from("missing x")),
+                },
+                _ => Err(String::from("not an object")),
+            }
+        }
+    }
+
+    #[test]
+    fn converts_homogeneous_object() {
+        let input = Value::object([
+            ("a", Value::object([("x", Value::Number(1.0))])),
+            ("b", Value::object([("x", Value::Number(2.5))])),
+        ]);
+        let expected = Map::from([
+            (String::from("a"), Point { x: 1.0 }),
+            (String::from("b"), Point { x: 2.5 }),
+        ]);
+
+        let actual = input.into_map_of::<Point>();
+
+        assert_eq!(actual, Ok(expected));
+    }
+
+    #[test]
+    fn reports_key_of_failing_member() {
+        let input = Value::object([
+            ("a", Value::object([("x", Value::Number(1.0))])),
+            ("b", Value::Null),
+        ]);
+        let expected = Err(ConvertError::Key(
+            String::from("b"),
+            String::from("not an object"),
+        ));
+
+        let actual = input.into_map_of::<Point>();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn converts_array_of_custom_type() {
+        let input = Value::Array(vec![
+            Value::object([("x", Value::Number(1.0))]),
+            Value::object([("x", Value::Number(2.0))]),
+        ]);
+        let expected = vec![Point { x: 1.0 }, Point { x: 2.0 }];
+
+        let actual = input.into_vec_of::<Point>();
+
+        assert_eq!(actual, Ok(expected));
+    }
+
+    #[test]
+    fn reports_index_of_failing_element() {
+        let input = Value::Array(vec![
+            Value::object([("x", Value::Number(1.0))]),
+            Value::Array(vec![]),
+        ]);
+        let expected = Err(ConvertError::Index(1, String::from("not an object")));
+
+        let actual = input.into_vec_of::<Point>();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn converts_into_value() {
+        let cases = [
+            (Value::from(true), Value::Boolean(true)),
+            (Value::from(-2i64), Value::Number(-2.0)),
+            (Value::from(0.5), Value::Number(0.5)),
+            (Value::from("a"), Value::String(String::from("a"))),
+            (
+                Value::from(String::from("b")),
+                Value::String(String::from("b")),
+            ),
+            (Value::from(None::<bool>), Value::Null),
+            (Value::from(Some("c")), Value::String(String::from("c"))),
+            (
+                Value::from(vec![1, 2]),
+                Value::Array(vec![Value::Number(1.0), Value::Number(2.0)]),
+            ),
+            (
+                Value::from(HashMap::from([(String::from("k"), vec![Some(1), None])])),
+                Value::object([("k", Value::Array(vec![Value::Number(1.0), Value::Null]))]),
+            ),
+        ];
+
+        for (actual, expected) in cases {
+            assert_eq!(actual, expected);
+        }
+    }
+
+    #[test]
+    fn rejects_wrong_container() {
+        let input = Value::Array(vec![]);
+        let expected = Err(ConvertError::NotAContainer(KindMismatch {
+            expected: ValueKind::Object,
+            found: ValueKind::Array,
+        }));
+
+        let actual = input.into_map_of::<Point>();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn converts_scalars_and_containers() {
+        let array = Value::Array(vec![Value::Null]);
+        let object = Value::object([("a", Value::Boolean(true))]);
+
+        assert_eq!(i64::try_from(Value::Number(-3.0)), Ok(-3));
+        assert_eq!(String::try_from(&Value::from("s")), Ok(String::from("s")));
+        assert_eq!(f64::try_from(&Value::Number(0.5)), Ok(0.5));
+        assert_eq!(bool::try_from(&Value::Boolean(false)), Ok(false));
+        assert_eq!(Vec::<Value>::try_from(&array), Ok(vec![Value::Null]));
+        assert_eq!(Vec::<Value>::try_from(array), Ok(vec![Value::Null]));
+        assert_eq!(
+            HashMap::<String, Value>::try_from(object),
+            Ok(HashMap::from([(String::from("a"), Value::Boolean(true))]))
+        );
+    }
+
+    #[test]
+    fn rejects_non_integer_numbers() {
+        let cases = [
+            (Value::Number(1.5), IntegerError::NotAnInteger(1.5)),
+            (Value::Number(1e19), IntegerError::NotAnInteger(1e19)),
+            (
+                Value::Null,
+                IntegerError::NotANumber(KindMismatch {
+                    expected: ValueKind::Number,
+                    found: ValueKind::Null,
+                }),
+            ),
+        ];
+
+        for (input, expected) in cases {
+            assert_eq!(i64::try_from(&input), Err(expected));
+        }
+    }
+
+    #[test]
+    fn describes_mismatch() {
+        let error = HashMap::<String, Value>::try_from(&Value::Array(vec![])).unwrap_err();
+
+        assert_eq!(error.to_string(), "expected object, found array");
+        assert_eq!(
+            IntegerError::NotAnInteger(2.5).to_string(),
+            "expected integer, found 2.5"
+        );
+    }
+
+    #[test]
+    fn collects_iterators() {
+        let array: Value = (1..=2).map(Value::from).collect();
+        let object: Value = [("a", 1)]
+            .into_iter()
+            .map(|(key, n)| (String::from(key), Value::from(n)))
+            .collect();
+
+        assert_eq!(
+            array,
+            Value::array([Value::Number(1.0), Value::Number(2.0)])
+        );
+        assert_eq!(object, Value::object([("a", Value::Number(1.0))]));
+    }
+
+    #[test]
+    fn extends_array() {
+        let mut input = Value::array([Value::Null]);
+        let expected = Value::array([Value::Null, Value::Boolean(true), Value::Boolean(false)]);
+
+        input.extend([Value::Boolean(true), Value::Boolean(false)]);
+
+        assert_eq!(input, expected);
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot extend object")]
+    fn refuses_to_extend_object() {
+        Value::object([("a", Value::Null)]).extend([Value::Null]);
+    }
+}
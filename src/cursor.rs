@@ -0,0 +1,358 @@
+use std::io::Read;
+
+use crate::{
+    convert::KindMismatch,
+    stream::{opens_container, Step, StreamError, Walker},
+    tokenizer::{Token, TokenizeError},
+    JsonPointer, PointerError, Value, ValueKind,
+};
+
+/// Steps through the elements of an array read from a stream one at a time,
+/// building only the elements asked for.
+///
+/// Nothing after the end of the array is read, so the rest of the input is
+/// not checked for errors.
+pub struct ArrayCursor<R> {
+    walker: Walker<R>,
+    finished: bool,
+}
+
+impl<R: Read> ArrayCursor<R> {
+    /// Cursor over the top-level array.
+    pub fn new(reader: R) -> Result<Self, StreamError> {
+        Self::at(reader, "")
+    }
+
+    /// Cursor over the array at `pointer`. Values before it are skipped without being built.
+    pub fn at(reader: R, pointer: &str) -> Result<Self, StreamError> {
+        let walker = seek_container(reader, pointer, ValueKind::Array)?;
+        Ok(Self {
+            walker,
+            finished: false,
+        })
+    }
+
+    /// Builds the next element, `None` once the array has ended. Elements
+    /// nested too deep fail with
+    /// [`crate::TokenParseError::NestingTooDeep`].
+    pub fn next_element(&mut self) -> Result<Option<Value>, StreamError> {
+        match next_child(&mut self.walker, &mut self.finished)? {
+            Some(token) => self.walker.read_value(token).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Skips the next element without building it. Returns `false` once the array has ended.
+    pub fn skip_element(&mut self) -> Result<bool, StreamError> {
+        match next_child(&mut self.walker, &mut self.finished)? {
+            Some(token) => {
+                skip_rest(&mut self.walker, &token)?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+}
+
+/// Steps through the members of an object read from a stream one at a time,
+/// building only the members asked for.
+///
+/// Nothing after the end of the object is read, so the rest of the input is
+/// not checked for errors.
+pub struct ObjectCursor<R> {
+    walker: Walker<R>,
+    finished: bool,
+}
+
+impl<R: Read> ObjectCursor<R> {
+    /// Cursor over the top-level object.
+    pub fn new(reader: R) -> Result<Self, StreamError> {
+        Self::at(reader, "")
+    }
+
+    /// Cursor over the object at `pointer`. Values before it are skipped without being built.
+    pub fn at(reader: R, pointer: &str) -> Result<Self, StreamError> {
+        let walker = seek_container(reader, pointer, ValueKind::Object)?;
+        Ok(Self {
+            walker,
+            finished: false,
+        })
+    }
+
+    /// Builds the next member, `None` once the object has ended. Values
+    /// nested too deep fail with
+    /// [`crate::TokenParseError::NestingTooDeep`].
+    pub fn next_member(&mut self) -> Result<Option<(String, Value)>, StreamError> {
+        match next_child(&mut self.walker, &mut self.finished)? {
+            Some(token) => {
+                let key = self.current_key();
+                let value = self.walker.read_value(token)?;
+                Ok(Some((key, value)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Skips the value of the next member without building it and returns
+    /// its key, `None` once the object has ended.
+    pub fn skip_member(&mut self) -> Result<Option<String>, StreamError> {
+        match next_child(&mut self.walker, &mut self.finished)? {
+            Some(token) => {
+                let key = self.current_key();
+                skip_rest(&mut self.walker, &token)?;
+                Ok(Some(key))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn current_key(&self) -> String {
+        self.walker.path().last().cloned().unwrap_or_default()
+    }
+}
+
+/// Iterates the members of an object as they are read, see [`object_members`].
+///
+/// Iteration stops after the first error.
+pub struct ObjectMembers<R> {
+    cursor: Option<Result<ObjectCursor<R>, StreamError>>,
+}
+
+/// Iterates the members of the top-level object one at a time, so only the
+/// current member is held in memory. Useful for HAR files and large bundles
+/// keyed by name.
+pub fn object_members<R: Read>(reader: R) -> ObjectMembers<R> {
+    ObjectMembers {
+        cursor: Some(ObjectCursor::new(reader)),
+    }
+}
+
+impl<R: Read> IntoIterator for ObjectCursor<R> {
+    type Item = Result<(String, Value), StreamError>;
+    type IntoIter = ObjectMembers<R>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        ObjectMembers {
+            cursor: Some(Ok(self)),
+        }
+    }
+}
+
+impl<R: Read> Iterator for ObjectMembers<R> {
+    type Item = Result<(String, Value), StreamError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut cursor = match self.cursor.take()? {
+            Ok(cursor) => cursor,
+            Err(err) => return Some(Err(err)),
+        };
+        let member = cursor.next_member().transpose()?;
+        if member.is_ok() {
+            self.cursor = Some(Ok(cursor));
+        }
+        Some(member)
+    }
+}
+
+fn seek_container<R: Read>(
+    reader: R,
+    pointer: &str,
+    expected: ValueKind,
+) -> Result<Walker<R>, StreamError> {
+    let target = JsonPointer::parse(pointer)?;
+    let mut walker = Walker::new(reader);
+
+    let token = walker
+        .seek(target.tokens())?
+        .ok_or_else(|| PointerError::NotFound(pointer.to_string()))?;
+    let found = token_kind(&token);
+    if found != expected {
+        return Err(StreamError::KindMismatch(KindMismatch { expected, found }));
+    }
+
+    Ok(walker)
+}
+
+/// First token of the next child of the container, `None` once it has ended.
+fn next_child<R: Read>(
+    walker: &mut Walker<R>,
+    finished: &mut bool,
+) -> Result<Option<Token>, StreamError> {
+    if *finished {
+        return Ok(None);
+    }
+
+    match walker.next()? {
+        Some(Step::Value { token, .. }) => Ok(Some(token)),
+        Some(Step::Close(_)) => {
+            *finished = true;
+            Ok(None)
+        }
+        None => Err(TokenizeError::UnexpectedEof.into()),
+    }
+}
+
+fn skip_rest<R: Read>(walker: &mut Walker<R>, first: &Token) -> Result<(), StreamError> {
+    if opens_container(first) {
+        walker.skip_container()?;
+    }
+    Ok(())
+}
+
+fn token_kind(token: &Token) -> ValueKind {
+    match token {
+        Token::LeftBrace => ValueKind::Object,
+        Token::LeftBracket => ValueKind::Array,
+        Token::String(_) => ValueKind::String,
+        Token::Number(_) => ValueKind::Number,
+        Token::True | Token::False => ValueKind::Boolean,
+        _ => ValueKind::Null,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        convert::KindMismatch, parser::TokenParseError, stream::StreamError, PointerError, Value,
+        ValueKind,
+    };
+
+    use super::{object_members, ArrayCursor, ObjectCursor};
+
+    const DOCUMENT: &str = r#"{
+        "meta": {"count": 3, "tags": ["a"]},
+        "items": [{"id": 1}, [2, [3]], "three", null]
+    }"#;
+
+    #[test]
+    fn steps_through_array_elements() {
+        let mut cursor = ArrayCursor::at(DOCUMENT.as_bytes(), "/items").unwrap();
+
+        let first = cursor.next_element().unwrap();
+        let skipped = cursor.skip_element().unwrap();
+        let third = cursor.next_element().unwrap();
+        let fourth = cursor.next_element().unwrap();
+        let end = cursor.next_element().unwrap();
+
+        assert_eq!(first, Some(Value::object([("id", Value::Number(1.0))])));
+        assert!(skipped);
+        assert_eq!(third, Some(Value::string("three")));
+        assert_eq!(fourth, Some(Value::Null));
+        assert_eq!(end, None);
+        assert!(!cursor.skip_element().unwrap());
+    }
+
+    #[test]
+    fn steps_through_object_members() {
+        let mut cursor = ObjectCursor::new(DOCUMENT.as_bytes()).unwrap();
+
+        let meta = cursor.next_member().unwrap();
+        let items = cursor.skip_member().unwrap();
+        let end = cursor.next_member().unwrap();
+
+        assert_eq!(
+            meta,
+            Some((
+                String::from("meta"),
+                Value::object([
+                    ("count", Value::Number(3.0)),
+                    ("tags", Value::array([Value::string("a")])),
+                ])
+            ))
+        );
+        assert_eq!(items, Some(String::from("items")));
+        assert_eq!(end, None);
+    }
+
+    #[test]
+    fn fails_when_target_is_missing_or_of_other_kind() {
+        let missing = ArrayCursor::at(DOCUMENT.as_bytes(), "/missing");
+        let object = ArrayCursor::at(DOCUMENT.as_bytes(), "/meta");
+
+        assert!(matches!(
+            missing,
+            Err(StreamError::PointerError(PointerError::NotFound(_)))
+        ));
+        assert!(matches!(
+            object,
+            Err(StreamError::KindMismatch(KindMismatch {
+                expected: ValueKind::Array,
+                found: ValueKind::Object
+            }))
+        ));
+    }
+
+    #[test]
+    fn reports_errors_in_elements() {
+        let input = "[1, [2,], 3]";
+        let mut cursor = ArrayCursor::new(input.as_bytes()).unwrap();
+
+        cursor.next_element().unwrap();
+        let actual = cursor.skip_element();
+
+        assert!(matches!(
+            actual,
+            Err(StreamError::ParseError(TokenParseError::TrailingComma))
+        ));
+    }
+
+    #[test]
+    fn iterates_members_lazily() {
+        let input = r#"{"a": 1, "b": {"c": null}} trailing"#;
+        let expected = vec![
+            (String::from("a"), Value::Number(1.0)),
+            (String::from("b"), Value::object([("c", Value::Null)])),
+        ];
+
+        let actual: Result<Vec<_>, _> = object_members(input.as_bytes()).collect();
+
+        assert_eq!(actual.unwrap(), expected);
+    }
+
+    #[test]
+    fn iterates_nested_object_through_cursor() {
+        let cursor = ObjectCursor::at(DOCUMENT.as_bytes(), "/meta").unwrap();
+
+        let keys: Vec<String> = cursor.into_iter().map(|member| member.unwrap().0).collect();
+
+        assert_eq!(keys, ["count", "tags"]);
+    }
+
+    #[test]
+    fn stops_iterating_after_error() {
+        let mut members = object_members(r#"{"a": 1, "b": [,]}"#.as_bytes());
+
+        assert!(matches!(members.next(), Some(Ok(_))));
+        assert!(matches!(
+            members.next(),
+            Some(Err(StreamError::ParseError(TokenParseError::ExpectedValue)))
+        ));
+        assert!(members.next().is_none());
+        assert!(matches!(
+            object_members("[]".as_bytes()).collect::<Vec<_>>()[..],
+            [Err(StreamError::KindMismatch(_))]
+        ));
+    }
+
+    #[test]
+    fn rejects_deeply_nested_elements() {
+        let nested = format!("{}{}", "[".repeat(5000), "]".repeat(5000));
+        let too_deep = |result| {
+            matches!(
+                result,
+                Err(StreamError::ParseError(TokenParseError::NestingTooDeep {
+                    limit: 128
+                }))
+            )
+        };
+
+        let input = format!("[1, {nested}]");
+        let mut cursor = ArrayCursor::new(input.as_bytes()).unwrap();
+        assert_eq!(cursor.next_element().unwrap(), Some(Value::Number(1.0)));
+        assert!(too_deep(cursor.next_element().map(|_| ())));
+
+        let input = format!(r#"{{"a": {nested}}}"#);
+        let mut cursor = ObjectCursor::new(input.as_bytes()).unwrap();
+        assert!(too_deep(cursor.next_member().map(|_| ())));
+    }
+}
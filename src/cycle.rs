@@ -0,0 +1,89 @@
+use crate::{pointer::escape_token, Value};
+
+/// A container was reached again while it was still being traversed
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct CycleDetected {
+    /// JSON pointer of the reference that closes the cycle
+    pub pointer: String,
+}
+
+impl Value {
+    /// Checks that no container is reachable from itself.
+    ///
+    /// Values own their children and [`crate::SharedValue`] subtrees are
+    /// immutable once built, so every value is acyclic by construction and
+    /// this currently always succeeds. Serialization and traversals rely on
+    /// this invariant; the check pins it down for any future kind of sharing
+    /// and lets callers assert it up front instead of risking a loop forever.
+    pub fn validate_acyclic(&self) -> Result<(), CycleDetected> {
+        let mut ancestors = Vec::new();
+        check(self, &mut ancestors, &mut String::new())
+    }
+}
+
+fn check(
+    value: &Value,
+    ancestors: &mut Vec<*const Value>,
+    pointer: &mut String,
+) -> Result<(), CycleDetected> {
+    let children: Vec<(String, &Value)> = match value {
+        Value::Array(values) => values
+            .iter()
+            .enumerate()
+            .map(|(index, value)| (index.to_string(), value))
+            .collect(),
+        Value::Object(map) => map
+            .iter()
+            .map(|(key, value)| (escape_token(key), value))
+            .collect(),
+        _ => return Ok(()),
+    };
+
+    let address = value as *const Value;
+    if ancestors.contains(&address) {
+        return Err(CycleDetected {
+            pointer: pointer.clone(),
+        });
+    }
+
+    ancestors.push(address);
+    for (token, child) in children {
+        let length = pointer.len();
+        pointer.push('/');
+        pointer.push_str(&token);
+
+        check(child, ancestors, pointer)?;
+
+        pointer.truncate(length);
+    }
+    ancestors.pop();
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Value;
+
+    #[test]
+    fn accepts_nested_tree() {
+        let input = Value::object([(
+            "a",
+            Value::Array(vec![Value::object([("b", Value::Null)]), Value::Null]),
+        )]);
+
+        let actual = input.validate_acyclic();
+
+        assert_eq!(actual, Ok(()));
+    }
+
+    #[test]
+    fn accepts_repeated_equal_subtrees() {
+        let shared = Value::object([("x", Value::Boolean(true))]);
+        let input = Value::Array(vec![shared.clone(), shared.clone(), shared]);
+
+        let actual = input.validate_acyclic();
+
+        assert_eq!(actual, Ok(()));
+    }
+}
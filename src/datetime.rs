@@ -0,0 +1,286 @@
+use std::{fmt, str::FromStr};
+
+use crate::{
+    calendar::{days_from_civil, days_in_month, unsigned},
+    Value,
+};
+
+/// RFC 3339 timestamp such as `2024-03-01T12:30:00.25+01:00`, keeping the
+/// UTC offset it was written with. Two timestamps for the same instant with
+/// different offsets are not equal; compare [`DateTime::unix_timestamp`] for that.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DateTime {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+    pub nanosecond: u32,
+    /// Offset from UTC in minutes, `0` for `Z`
+    pub offset_minutes: i16,
+}
+
+impl DateTime {
+    /// Parses an RFC 3339 `date-time`. `None` when the text is not one or
+    /// names a day that does not exist.
+    pub fn parse(text: &str) -> Option<Self> {
+        let bytes = text.as_bytes();
+        let separators = [(4, b'-'), (7, b'-'), (13, b':'), (16, b':')];
+        if bytes.len() < 20
+            || !text.is_ascii()
+            || separators.iter().any(|&(at, b)| bytes[at] != b)
+            || !matches!(bytes[10], b'T' | b't' | b' ')
+        {
+            return None;
+        }
+
+        let mut rest = &text[19..];
+        let mut nanosecond = 0;
+        if let Some(fraction) = rest.strip_prefix('.') {
+            let end = fraction
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(fraction.len());
+            if end == 0 {
+                return None;
+            }
+            // Digits past nanoseconds are truncated
+            let mut digits = String::from(&fraction[..end.min(9)]);
+            while digits.len() < 9 {
+                digits.push('0');
+            }
+            nanosecond = unsigned(&digits)?;
+            rest = &fraction[end..];
+        }
+
+        let offset_minutes = match rest.as_bytes() {
+            [b'Z' | b'z'] => 0,
+            [sign @ (b'+' | b'-'), _, _, b':', _, _] => {
+                let hours: i16 = unsigned(&rest[1..3])?;
+                let minutes: i16 = unsigned(&rest[4..6])?;
+                if hours > 23 || minutes > 59 {
+                    return None;
+                }
+                let offset = hours * 60 + minutes;
+                if *sign == b'-' {
+                    -offset
+                } else {
+                    offset
+                }
+            }
+            _ => return None,
+        };
+
+        let date_time = DateTime {
+            year: unsigned(&text[0..4])?,
+            month: unsigned(&text[5..7])?,
+            day: unsigned(&text[8..10])?,
+            hour: unsigned(&text[11..13])?,
+            minute: unsigned(&text[14..16])?,
+            second: unsigned(&text[17..19])?,
+            nanosecond,
+            offset_minutes,
+        };
+        date_time.is_valid().then_some(date_time)
+    }
+
+    fn is_valid(&self) -> bool {
+        (1..=12).contains(&self.month)
+            && (1..=days_in_month(self.year.into(), self.month.into())).contains(&self.day.into())
+            && self.hour < 24
+            && self.minute < 60
+            // 60 allows for leap seconds
+            && self.second <= 60
+    }
+
+    /// Seconds since the Unix epoch, ignoring the nanoseconds.
+    pub fn unix_timestamp(&self) -> i64 {
+        let days = days_from_civil(self.year.into(), self.month.into(), self.day.into());
+        days * 86_400
+            + i64::from(self.hour) * 3_600
+            + i64::from(self.minute) * 60
+            + i64::from(self.second)
+            - i64::from(self.offset_minutes) * 60
+    }
+}
+
+/// Possible errors when reading a [`DateTime`] with [`str::parse`]
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum DateTimeError {
+    /// The text is not an RFC 3339 `date-time` or names a day that does not exist
+    InvalidSyntax(String),
+}
+
+impl FromStr for DateTime {
+    type Err = DateTimeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        DateTime::parse(s).ok_or_else(|| DateTimeError::InvalidSyntax(s.to_string()))
+    }
+}
+
+/// Writes the RFC 3339 form, with as many fraction digits as needed.
+///
+/// Only the fields are kept, not the text they were parsed from, so this is
+/// not always the original spelling: the separator is written as `T`, a zero
+/// offset as `Z` (including `+00:00` and `-00:00`) and trailing zeros of the
+/// fraction are dropped. The instant and offset stay the same.
+impl fmt::Display for DateTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        )?;
+        if self.nanosecond > 0 {
+            let fraction = format!("{:09}", self.nanosecond);
+            write!(f, ".{}", fraction.trim_end_matches('0'))?;
+        }
+        match self.offset_minutes {
+            0 => f.write_str("Z"),
+            offset => {
+                let sign = if offset < 0 { '-' } else { '+' };
+                let offset = offset.unsigned_abs();
+                write!(f, "{sign}{:02}:{:02}", offset / 60, offset % 60)
+            }
+        }
+    }
+}
+
+impl From<DateTime> for Value {
+    fn from(date_time: DateTime) -> Self {
+        Value::DateTime(date_time)
+    }
+}
+
+impl Value {
+    pub fn is_datetime(&self) -> bool {
+        matches!(self, Value::DateTime(_))
+    }
+
+    pub fn as_datetime(&self) -> Option<&DateTime> {
+        match self {
+            Value::DateTime(date_time) => Some(date_time),
+            _ => None,
+        }
+    }
+
+    /// Replaces every string holding an RFC 3339 timestamp with a
+    /// [`Value::DateTime`], including strings nested in arrays and objects.
+    pub fn parse_datetimes(&mut self) {
+        match self {
+            Value::String(s) => {
+                if let Some(date_time) = DateTime::parse(s) {
+                    *self = Value::DateTime(date_time);
+                }
+            }
+            Value::Array(values) => values.iter_mut().for_each(Value::parse_datetimes),
+            Value::Object(map) => map.values_mut().for_each(Value::parse_datetimes),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse, parse_with_options, ParseOptions, Value};
+
+    use super::{DateTime, DateTimeError};
+
+    #[test]
+    fn parses_and_writes_rfc_3339() {
+        let cases = [
+            ("2024-02-29T23:59:60Z", "2024-02-29T23:59:60Z"),
+            (
+                "2024-03-01t12:30:00.250+01:00",
+                "2024-03-01T12:30:00.25+01:00",
+            ),
+            (
+                "1999-12-31 00:00:00.000000001-05:30",
+                "1999-12-31T00:00:00.000000001-05:30",
+            ),
+        ];
+
+        for (input, expected) in cases {
+            let date_time = DateTime::parse(input).unwrap();
+            assert_eq!(date_time.to_string(), expected, "{input}");
+        }
+    }
+
+    #[test]
+    fn reads_with_from_str() {
+        let date_time: DateTime = "2024-01-01T00:00:00+00:00".parse().unwrap();
+
+        assert_eq!(date_time.to_string(), "2024-01-01T00:00:00Z");
+        assert_eq!(
+            "2024-01-01".parse::<DateTime>(),
+            Err(DateTimeError::InvalidSyntax(String::from("2024-01-01")))
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_timestamps() {
+        let inputs = [
+            "2023-02-29T00:00:00Z",
+            "2024-13-01T00:00:00Z",
+            "2024-01-01T24:00:00Z",
+            "2024-01-01T00:00:00",
+            "2024-01-01T00:00:00.Z",
+            "2024-01-01T00:00:00+1:00",
+            "2024-01-01",
+            "2024-01-01T00:00:0éZ",
+            "2024-01-01T00:00:00é",
+            "2024-01-01T00:00:00+é:00",
+            "2024-01-01T00:00:00.1é",
+        ];
+
+        for input in inputs {
+            assert_eq!(DateTime::parse(input), None, "{input}");
+        }
+    }
+
+    #[test]
+    fn converts_to_unix_timestamp() {
+        let utc = DateTime::parse("2009-02-13T23:31:30Z").unwrap();
+        let shifted = DateTime::parse("2009-02-14T00:31:30+01:00").unwrap();
+
+        assert_eq!(utc.unix_timestamp(), 1_234_567_890);
+        assert_eq!(shifted.unix_timestamp(), 1_234_567_890);
+        assert_ne!(utc, shifted);
+    }
+
+    #[test]
+    fn reads_timestamps_when_enabled() {
+        let input = r#"{"at": "2024-01-01T00:00:00Z", "name": "2024"}"#;
+        let options = ParseOptions {
+            datetimes: true,
+            ..ParseOptions::default()
+        };
+
+        let value = parse_with_options(String::from(input), &options).unwrap();
+
+        assert_eq!(
+            value["at"].as_datetime(),
+            DateTime::parse("2024-01-01T00:00:00Z").as_ref()
+        );
+        assert_eq!(value["name"], Value::string("2024"));
+        assert!(parse(String::from(input)).unwrap()["at"].is_string());
+    }
+
+    #[test]
+    fn writes_timestamps_as_strings() {
+        let mut value = Value::array([
+            Value::string("2024-01-01T10:00:00-02:00"),
+            Value::string("not a date"),
+        ]);
+
+        value.parse_datetimes();
+
+        assert!(value[0].is_datetime());
+        assert_eq!(
+            value.to_string(),
+            r#"["2024-01-01T10:00:00-02:00","not a date"]"#
+        );
+        assert_eq!(value.estimate_serialized_size(), value.to_string().len());
+    }
+}
@@ -0,0 +1,261 @@
+//! Clone, drop and comparison for [`Value`] without recursion.
+//!
+//! The derived implementations recurse once per nesting level, so a deep
+//! enough document overflows the stack when it is cloned, dropped or
+//! compared. These keep the pending containers in a heap-allocated stack
+//! instead.
+//!
+//! Implementing `Drop` means patterns cannot move out of a `Value`, which
+//! is why the `into_` accessors exist.
+//!
+//! `Debug`, serializing and [`Value::freeze`] still recurse. Parsing stops
+//! at [`crate::ParseOptions::max_depth`], which keeps parsed documents well
+//! within their reach; only values built deeper by hand need care.
+
+use std::{mem, slice};
+
+use crate::{Map, Value};
+
+impl Value {
+    /// Clones the value without recursing into nested containers.
+    pub fn deep_clone(&self) -> Value {
+        let mut stack: Vec<CloneFrame<'_>> = Vec::new();
+        let mut next = Some(self);
+
+        loop {
+            let mut finished = match next.take() {
+                Some(Value::Array(values)) => {
+                    stack.push(CloneFrame::Array(
+                        values.iter(),
+                        Vec::with_capacity(values.len()),
+                    ));
+                    None
+                }
+                Some(Value::Object(map)) => {
+                    stack.push(CloneFrame::Object(
+                        map.iter(),
+                        Map::with_capacity(map.len()),
+                        None,
+                    ));
+                    None
+                }
+                Some(scalar) => Some(clone_scalar(scalar)),
+                None => None,
+            };
+
+            // Hand finished values to their container until one has another child to clone
+            loop {
+                let Some(frame) = stack.last_mut() else {
+                    return finished.expect("the root value is finished last");
+                };
+                if let Some(value) = finished.take() {
+                    frame.push(value);
+                }
+                match frame.next_child() {
+                    Some(child) => {
+                        next = Some(child);
+                        break;
+                    }
+                    None => finished = stack.pop().map(CloneFrame::finish),
+                }
+            }
+        }
+    }
+}
+
+impl Clone for Value {
+    fn clone(&self) -> Self {
+        self.deep_clone()
+    }
+}
+
+impl Drop for Value {
+    fn drop(&mut self) {
+        let mut stack = match self {
+            Value::Array(values) if values.iter().any(is_nonempty_container) => mem::take(values),
+            Value::Object(map) if map.values().any(is_nonempty_container) => {
+                mem::take(map).into_values().collect()
+            }
+            _ => return,
+        };
+
+        // Each value popped here has its children moved onto the stack
+        // first, so dropping it does not recurse any further
+        while let Some(mut value) = stack.pop() {
+            match &mut value {
+                Value::Array(values) => stack.append(values),
+                Value::Object(map) => stack.extend(mem::take(map).into_values()),
+                _ => {}
+            }
+        }
+    }
+}
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Value) -> bool {
+        let mut pending = vec![(self, other)];
+
+        while let Some(pair) = pending.pop() {
+            match pair {
+                (Value::Array(a), Value::Array(b)) if a.len() == b.len() => {
+                    pending.extend(a.iter().zip(b));
+                }
+                (Value::Object(a), Value::Object(b)) if a.len() == b.len() => {
+                    for (key, a) in a {
+                        let Some(b) = b.get(key) else {
+                            return false;
+                        };
+                        pending.push((a, b));
+                    }
+                }
+                (a, b) if !scalar_eq(a, b) => return false,
+                _ => {}
+            }
+        }
+
+        true
+    }
+}
+
+/// Compares values of which at least one is a scalar.
+fn scalar_eq(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Null, Value::Null) => true,
+        (Value::Boolean(a), Value::Boolean(b)) => a == b,
+        (Value::String(a), Value::String(b)) => a == b,
+        (Value::Number(a), Value::Number(b)) => a == b,
+        #[cfg(feature = "datetime")]
+        (Value::DateTime(a), Value::DateTime(b)) => a == b,
+        (Value::Rope(a), Value::Rope(b)) => a == b,
+        (Value::String(s), Value::Rope(rope)) | (Value::Rope(rope), Value::String(s)) => {
+            rope == s.as_str()
+        }
+        _ => false,
+    }
+}
+
+fn is_nonempty_container(value: &Value) -> bool {
+    match value {
+        Value::Array(values) => !values.is_empty(),
+        Value::Object(map) => !map.is_empty(),
+        _ => false,
+    }
+}
+
+fn clone_scalar(value: &Value) -> Value {
+    match value {
+        Value::Null => Value::Null,
+        Value::Boolean(b) => Value::Boolean(*b),
+        Value::String(s) => Value::String(s.clone()),
+        Value::Number(n) => Value::Number(*n),
+        #[cfg(feature = "datetime")]
+        Value::DateTime(date_time) => Value::DateTime(*date_time),
+        Value::Rope(rope) => Value::Rope(rope.clone()),
+        Value::Array(_) | Value::Object(_) => unreachable!("containers are cloned by deep_clone"),
+    }
+}
+
+type MapIter<'a> = <&'a Map<String, Value> as IntoIterator>::IntoIter;
+
+/// A container being cloned: the children left to clone and the copy built so far
+enum CloneFrame<'a> {
+    Array(slice::Iter<'a, Value>, Vec<Value>),
+    Object(MapIter<'a>, Map<String, Value>, Option<&'a String>),
+}
+
+impl<'a> CloneFrame<'a> {
+    fn next_child(&mut self) -> Option<&'a Value> {
+        match self {
+            CloneFrame::Array(source, _) => source.next(),
+            CloneFrame::Object(source, _, pending_key) => {
+                let (key, value) = source.next()?;
+                *pending_key = Some(key);
+                Some(value)
+            }
+        }
+    }
+
+    fn push(&mut self, value: Value) {
+        match self {
+            CloneFrame::Array(_, built) => built.push(value),
+            CloneFrame::Object(_, built, pending_key) => {
+                let key = pending_key.take().expect("a child was handed out");
+                built.insert(key.clone(), value);
+            }
+        }
+    }
+
+    fn finish(self) -> Value {
+        match self {
+            CloneFrame::Array(_, built) => Value::Array(built),
+            CloneFrame::Object(_, built, _) => Value::Object(built),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse, Value};
+
+    /// Deeper than the default test thread stack survives with recursive drop
+    const DEPTH: usize = 1_000_000;
+
+    fn nested_arrays(depth: usize) -> Value {
+        let mut value = Value::Null;
+        for _ in 0..depth {
+            value = Value::Array(vec![value]);
+        }
+        value
+    }
+
+    fn depth_of(mut value: &Value) -> usize {
+        let mut depth = 0;
+        while let Value::Array(values) = value {
+            value = &values[0];
+            depth += 1;
+        }
+        depth
+    }
+
+    #[test]
+    fn clones_and_drops_deep_trees() {
+        let value = nested_arrays(DEPTH);
+
+        let cloned = value.deep_clone();
+        drop(value);
+
+        assert_eq!(depth_of(&cloned), DEPTH);
+    }
+
+    #[test]
+    fn compares_deep_trees() {
+        let value = nested_arrays(DEPTH);
+        let cloned = value.deep_clone();
+
+        assert_eq!(value, cloned);
+        assert_ne!(value, nested_arrays(DEPTH - 1));
+    }
+
+    #[test]
+    fn compares_mixed_documents() {
+        let a = parse(String::from(r#"{"a": [1, {"b": null}], "c": "x"}"#)).unwrap();
+        let b = parse(String::from(r#"{"c": "x", "a": [1, {"b": null}]}"#)).unwrap();
+        let c = parse(String::from(r#"{"c": "x", "a": [1, {"b": false}]}"#)).unwrap();
+
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert_ne!(Value::Array(Vec::new()), Value::Null);
+    }
+
+    #[test]
+    fn clones_mixed_documents() {
+        let input = parse(String::from(
+            r#"{"a": [1, {"b": null, "c": [true, "x"]}, []], "d": {}}"#,
+        ))
+        .unwrap();
+
+        let actual = input.clone();
+
+        assert_eq!(actual, input);
+    }
+}
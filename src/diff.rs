@@ -0,0 +1,216 @@
+use std::fmt::Write;
+
+use crate::{JsonPointer, Value};
+
+/// A difference between two documents at a single location
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change<'a> {
+    /// The location only exists in the new document
+    Added { path: JsonPointer, value: &'a Value },
+
+    /// The location only exists in the old document
+    Removed { path: JsonPointer, value: &'a Value },
+
+    /// The location holds different scalars, or values of different kinds
+    Changed {
+        path: JsonPointer,
+        old: &'a Value,
+        new: &'a Value,
+    },
+}
+
+impl Value {
+    /// Lists the locations where `other` differs from `self`. Objects are
+    /// compared member by member in key order, arrays element by element.
+    pub fn diff<'a>(&'a self, other: &'a Value) -> Vec<Change<'a>> {
+        let mut changes = Vec::new();
+        collect_changes(self, other, &mut JsonPointer::root(), &mut changes);
+        changes
+    }
+}
+
+/// Renders the differences between two documents one location per line, a
+/// `-` line for the old value and a `+` line for the new one. Empty when the
+/// documents are equal.
+pub fn diff_to_string(old: &Value, new: &Value) -> String {
+    render(&old.diff(new), false)
+}
+
+/// Like [`diff_to_string`], with removed lines in red and added lines in
+/// green for terminals that understand ANSI escape codes.
+#[cfg(feature = "color")]
+pub fn diff_to_colored_string(old: &Value, new: &Value) -> String {
+    render(&old.diff(new), true)
+}
+
+fn collect_changes<'a>(
+    old: &'a Value,
+    new: &'a Value,
+    path: &mut JsonPointer,
+    changes: &mut Vec<Change<'a>>,
+) {
+    match (old, new) {
+        (Value::Object(old_map), Value::Object(new_map)) => {
+            let mut keys: Vec<&String> = old_map.keys().chain(new_map.keys()).collect();
+            keys.sort();
+            keys.dedup();
+
+            for key in keys {
+                path.push(key.clone());
+                match (old_map.get(key), new_map.get(key)) {
+                    (Some(old), Some(new)) => collect_changes(old, new, path, changes),
+                    (Some(value), None) => changes.push(Change::Removed {
+                        path: path.clone(),
+                        value,
+                    }),
+                    (None, Some(value)) => changes.push(Change::Added {
+                        path: path.clone(),
+                        value,
+                    }),
+                    (None, None) => {}
+                }
+                path.pop();
+            }
+        }
+        (Value::Array(old_values), Value::Array(new_values)) => {
+            for index in 0..old_values.len().max(new_values.len()) {
+                path.push(index.to_string());
+                match (old_values.get(index), new_values.get(index)) {
+                    (Some(old), Some(new)) => collect_changes(old, new, path, changes),
+                    (Some(value), None) => changes.push(Change::Removed {
+                        path: path.clone(),
+                        value,
+                    }),
+                    (None, Some(value)) => changes.push(Change::Added {
+                        path: path.clone(),
+                        value,
+                    }),
+                    (None, None) => {}
+                }
+                path.pop();
+            }
+        }
+        _ if old == new => {}
+        _ => changes.push(Change::Changed {
+            path: path.clone(),
+            old,
+            new,
+        }),
+    }
+}
+
+fn render(changes: &[Change<'_>], colored: bool) -> String {
+    let mut output = String::new();
+    for change in changes {
+        match change {
+            Change::Added { path, value } => write_line(&mut output, '+', path, value, colored),
+            Change::Removed { path, value } => write_line(&mut output, '-', path, value, colored),
+            Change::Changed { path, old, new } => {
+                write_line(&mut output, '-', path, old, colored);
+                write_line(&mut output, '+', path, new, colored);
+            }
+        }
+    }
+    output
+}
+
+fn write_line(output: &mut String, sign: char, path: &JsonPointer, value: &Value, colored: bool) {
+    let (start, end) = match (colored, sign) {
+        (false, _) => ("", ""),
+        (true, '-') => ("\x1b[31m", "\x1b[0m"),
+        (true, _) => ("\x1b[32m", "\x1b[0m"),
+    };
+    let path = if path.is_root() {
+        String::from("(root)")
+    } else {
+        path.to_string()
+    };
+    writeln!(output, "{start}{sign} {path}: {value}{end}")
+        .expect("writing to a String cannot fail");
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{JsonPointer, Value};
+
+    use super::{diff_to_string, render, Change};
+
+    fn pointer(text: &str) -> JsonPointer {
+        JsonPointer::parse(text).unwrap()
+    }
+
+    #[test]
+    fn finds_changed_added_and_removed_locations() {
+        let old = Value::object([
+            ("port", Value::Number(80.0)),
+            (
+                "tags",
+                Value::array([Value::string("a"), Value::string("b")]),
+            ),
+            ("debug", Value::Boolean(true)),
+        ]);
+        let new = Value::object([
+            ("port", Value::Number(8080.0)),
+            ("tags", Value::array([Value::string("a")])),
+            ("name", Value::Null),
+        ]);
+        let (debug, null, port, new_port, b) = (
+            Value::Boolean(true),
+            Value::Null,
+            Value::Number(80.0),
+            Value::Number(8080.0),
+            Value::string("b"),
+        );
+        let expected = vec![
+            Change::Removed {
+                path: pointer("/debug"),
+                value: &debug,
+            },
+            Change::Added {
+                path: pointer("/name"),
+                value: &null,
+            },
+            Change::Changed {
+                path: pointer("/port"),
+                old: &port,
+                new: &new_port,
+            },
+            Change::Removed {
+                path: pointer("/tags/1"),
+                value: &b,
+            },
+        ];
+
+        let actual = old.diff(&new);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn renders_one_line_per_side() {
+        let old = Value::object([("a", Value::object([("b", Value::Number(1.0))]))]);
+        let new = Value::object([("a", Value::array([Value::Boolean(true)]))]);
+        let expected = "- /a: {\"b\":1}\n+ /a: [true]\n";
+
+        let actual = diff_to_string(&old, &new);
+
+        assert_eq!(actual, expected);
+        assert_eq!(diff_to_string(&new, &new), "");
+        assert_eq!(
+            diff_to_string(&Value::Null, &Value::Number(1.0)),
+            "- (root): null\n+ (root): 1\n"
+        );
+    }
+
+    #[test]
+    fn colors_removed_and_added_lines() {
+        let (old, new) = (Value::Null, Value::Boolean(false));
+
+        let actual = render(&old.diff(&new), true);
+
+        assert_eq!(
+            actual,
+            "\x1b[31m- (root): null\x1b[0m\n\x1b[32m+ (root): false\x1b[0m\n"
+        );
+    }
+}
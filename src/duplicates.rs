@@ -0,0 +1,263 @@
+use std::collections::HashMap;
+
+use crate::{
+    hash::visit_subtree_hashes,
+    parse,
+    parser::{unescape_string, TokenParseError},
+    tokenizer::{tokenize, Token},
+    JsonPointer, ParseError, Value,
+};
+
+/// Findings of [`duplicate_report`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct DuplicateReport {
+    /// Keys occurring more than once within the same object, in document order
+    pub duplicate_keys: Vec<DuplicateKey>,
+
+    /// Equal subtrees occurring more than once, largest first
+    pub repeated_subtrees: Vec<RepeatedSubtree>,
+}
+
+/// A key which occurs several times in one object. Only the last occurrence
+/// is kept when parsing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateKey {
+    /// Location of the object containing the key
+    pub object: JsonPointer,
+    pub key: String,
+    pub count: usize,
+}
+
+/// A subtree which occurs several times in the document.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RepeatedSubtree {
+    /// Locations of every occurrence, sorted
+    pub paths: Vec<JsonPointer>,
+
+    /// Number of values in one occurrence of the subtree
+    pub size: usize,
+}
+
+/// Parses the input and reports duplicated keys and repeated subtrees made of
+/// at least `min_subtree_size` values. Subtrees are only reported when some of
+/// their occurrences are not already part of a larger repeated subtree.
+pub fn duplicate_report(
+    input: &str,
+    min_subtree_size: usize,
+) -> Result<DuplicateReport, ParseError> {
+    let value = parse(input.to_string())?;
+    let tokens = tokenize(input.to_string())?;
+
+    let mut duplicate_keys = Vec::new();
+    scan_keys(
+        &tokens,
+        &mut 0,
+        &mut JsonPointer::root(),
+        &mut duplicate_keys,
+    )?;
+
+    Ok(DuplicateReport {
+        duplicate_keys,
+        repeated_subtrees: repeated_subtrees(&value, min_subtree_size.max(1)),
+    })
+}
+
+/// Walks tokens the parser already accepted, counting keys per object.
+fn scan_keys(
+    tokens: &[Token],
+    index: &mut usize,
+    path: &mut JsonPointer,
+    duplicates: &mut Vec<DuplicateKey>,
+) -> Result<(), TokenParseError> {
+    match tokens.get(*index) {
+        Some(Token::LeftBrace) => {
+            *index += 1;
+            let mut keys: Vec<(String, usize)> = Vec::new();
+
+            while let Some(Token::String(raw)) = tokens.get(*index) {
+                let key = unescape_string(raw)?;
+                *index += 2;
+
+                path.push(key.clone());
+                scan_keys(tokens, index, path, duplicates)?;
+                path.pop();
+
+                match keys.iter_mut().find(|(k, _)| *k == key) {
+                    Some((_, count)) => *count += 1,
+                    None => keys.push((key, 1)),
+                }
+
+                if tokens.get(*index) == Some(&Token::Comma) {
+                    *index += 1;
+                }
+            }
+            *index += 1;
+
+            duplicates.extend(keys.into_iter().filter(|(_, count)| *count > 1).map(
+                |(key, count)| DuplicateKey {
+                    object: path.clone(),
+                    key,
+                    count,
+                },
+            ));
+        }
+        Some(Token::LeftBracket) => {
+            *index += 1;
+            let mut position = 0;
+
+            while !matches!(tokens.get(*index), Some(Token::RightBracket) | None) {
+                path.push(position.to_string());
+                scan_keys(tokens, index, path, duplicates)?;
+                path.pop();
+                position += 1;
+
+                if tokens.get(*index) == Some(&Token::Comma) {
+                    *index += 1;
+                }
+            }
+            *index += 1;
+        }
+        _ => *index += 1,
+    }
+
+    Ok(())
+}
+
+fn repeated_subtrees(value: &Value, min_size: usize) -> Vec<RepeatedSubtree> {
+    let mut groups: HashMap<u64, Vec<(JsonPointer, &Value, usize)>> = HashMap::new();
+    visit_subtree_hashes(value, &mut |path, value, hash, size| {
+        if size >= min_size && value.kind().is_container() {
+            groups
+                .entry(hash)
+                .or_default()
+                .push((path.clone(), value, size));
+        }
+    });
+
+    let mut candidates = Vec::new();
+    for (_, mut group) in groups {
+        // Split hash collisions into groups of values which really are equal
+        while let Some((path, value, size)) = group.pop() {
+            let mut paths = vec![path];
+            group.retain(|(other_path, other, _)| {
+                let equal = *other == value;
+                if equal {
+                    paths.push(other_path.clone());
+                }
+                !equal
+            });
+
+            if paths.len() > 1 {
+                paths.sort();
+                candidates.push(RepeatedSubtree { paths, size });
+            }
+        }
+    }
+    candidates.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.paths.cmp(&b.paths)));
+
+    let mut reported: Vec<RepeatedSubtree> = Vec::new();
+    for candidate in candidates {
+        let is_covered = |path: &JsonPointer| {
+            reported
+                .iter()
+                .flat_map(|r| &r.paths)
+                .any(|outer| path.tokens().starts_with(outer.tokens()))
+        };
+        if !candidate.paths.iter().all(is_covered) {
+            reported.push(candidate);
+        }
+    }
+    reported
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::JsonPointer;
+
+    use super::{duplicate_report, DuplicateKey};
+
+    fn pointers(paths: &[&str]) -> Vec<JsonPointer> {
+        paths
+            .iter()
+            .map(|p| JsonPointer::parse(p).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn reports_duplicate_keys_per_object() {
+        let input =
+            r#"{"a": 1, "b": {"x": 1, "x": 2, "x": 3}, "a": 2, "list": [{"k": 0, "k": 1}]}"#;
+        let expected = vec![
+            DuplicateKey {
+                object: JsonPointer::parse("/b").unwrap(),
+                key: String::from("x"),
+                count: 3,
+            },
+            DuplicateKey {
+                object: JsonPointer::parse("/list/0").unwrap(),
+                key: String::from("k"),
+                count: 2,
+            },
+            DuplicateKey {
+                object: JsonPointer::root(),
+                key: String::from("a"),
+                count: 2,
+            },
+        ];
+
+        let actual = duplicate_report(input, 2).unwrap();
+
+        assert_eq!(actual.duplicate_keys, expected);
+    }
+
+    #[test]
+    fn compares_keys_after_unescaping() {
+        let input = r#"{"a": 1, "a": 2}"#;
+
+        let actual = duplicate_report(input, 2).unwrap();
+
+        assert_eq!(actual.duplicate_keys.len(), 1);
+        assert_eq!(actual.duplicate_keys[0].key, "a");
+    }
+
+    #[test]
+    fn reports_largest_repeated_subtrees_only() {
+        let input = r#"[
+            {"id": 1, "meta": {"source": "export", "tags": ["a", "b"]}},
+            {"id": 2, "meta": {"source": "export", "tags": ["a", "b"]}},
+            {"id": 3, "meta": {"source": "export", "tags": ["a", "b"]}}
+        ]"#;
+
+        let actual = duplicate_report(input, 2).unwrap();
+
+        assert_eq!(actual.repeated_subtrees.len(), 1);
+        assert_eq!(actual.repeated_subtrees[0].size, 5);
+        assert_eq!(
+            actual.repeated_subtrees[0].paths,
+            pointers(&["/0/meta", "/1/meta", "/2/meta"])
+        );
+    }
+
+    #[test]
+    fn reports_nested_repeats_that_also_occur_elsewhere() {
+        let input = r#"{"a": {"x": [1, 2]}, "b": {"x": [1, 2]}, "c": [1, 2]}"#;
+
+        let actual = duplicate_report(input, 3).unwrap();
+
+        assert_eq!(actual.repeated_subtrees.len(), 2);
+        assert_eq!(actual.repeated_subtrees[0].paths, pointers(&["/a", "/b"]));
+        assert_eq!(
+            actual.repeated_subtrees[1].paths,
+            pointers(&["/a/x", "/b/x", "/c"])
+        );
+    }
+
+    #[test]
+    fn ignores_small_subtrees() {
+        let input = r#"[[1], [1], {}, {}]"#;
+
+        let actual = duplicate_report(input, 3).unwrap();
+
+        assert!(actual.repeated_subtrees.is_empty());
+    }
+}
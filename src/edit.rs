@@ -0,0 +1,392 @@
+use std::mem;
+
+use crate::{
+    convert::mismatch,
+    pointer::{parse_index, parse_pointer, resolve_tokens_mut},
+    KindMismatch, PointerError, Value, ValueKind,
+};
+
+/// A single change to a document, addressed by a JSON pointer.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Edit {
+    /// Replaces the value at the pointer, creating missing members along the way
+    Set { pointer: String, value: Value },
+
+    /// Inserts before the addressed array element (`-` appends), or adds an
+    /// object member that must not exist yet
+    Insert { pointer: String, value: Value },
+
+    /// Removes the addressed array element or object member
+    Remove { pointer: String },
+}
+
+/// The edit at `index` could not be applied, so none of the edits were
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct EditError {
+    pub index: usize,
+    pub error: PointerError,
+}
+
+impl Value {
+    /// Applies all edits in order. Either every edit succeeds, or the value is
+    /// left unchanged and the first failing edit is reported.
+    pub fn apply_edits(&mut self, edits: Vec<Edit>) -> Result<(), EditError> {
+        let original = self.clone();
+
+        for (index, edit) in edits.into_iter().enumerate() {
+            if let Err(error) = self.apply_edit(edit) {
+                *self = original;
+                return Err(EditError { index, error });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Replaces the value at the pointer and returns the old one, `null` when
+    /// the location was created. With `create` set, missing object members
+    /// along the path are created, as are array elements addressed by `-` or
+    /// the index right after the last element; see [`Value::update_pointer`].
+    pub fn set_by_pointer(
+        &mut self,
+        pointer: &str,
+        value: Value,
+        create: bool,
+    ) -> Result<Value, PointerError> {
+        let mut old = Value::Null;
+        self.update_pointer(pointer, create, |previous| {
+            old = previous;
+            value
+        })?;
+        Ok(old)
+    }
+
+    /// Removes the addressed array element or object member and returns it.
+    /// The root cannot be removed.
+    pub fn remove_by_pointer(&mut self, pointer: &str) -> Result<Value, PointerError> {
+        let (parent, last) = split_parent(self, pointer)?;
+        let removed = match parent {
+            Value::Array(values) => match parse_index(&last) {
+                Some(index) if index < values.len() => Some(values.remove(index)),
+                _ => None,
+            },
+            Value::Object(map) => map.remove(&last),
+            _ => None,
+        };
+        removed.ok_or_else(|| PointerError::NotFound(pointer.to_string()))
+    }
+
+    /// Appends to an array. Fails for anything but an array.
+    pub fn push(&mut self, value: impl Into<Value>) -> Result<(), KindMismatch> {
+        match self {
+            Value::Array(values) => {
+                values.push(value.into());
+                Ok(())
+            }
+            value => Err(mismatch(ValueKind::Array, value)),
+        }
+    }
+
+    /// Sets an object member and returns the value it replaced. Fails for
+    /// anything but an object.
+    pub fn insert(
+        &mut self,
+        key: impl Into<String>,
+        value: impl Into<Value>,
+    ) -> Result<Option<Value>, KindMismatch> {
+        match self {
+            Value::Object(map) => Ok(map.insert(key.into(), value.into())),
+            value => Err(mismatch(ValueKind::Object, value)),
+        }
+    }
+
+    /// Removes an object member and returns it. Fails for anything but an
+    /// object.
+    pub fn remove(&mut self, key: &str) -> Result<Option<Value>, KindMismatch> {
+        match self {
+            Value::Object(map) => Ok(map.remove(key)),
+            value => Err(mismatch(ValueKind::Object, value)),
+        }
+    }
+
+    /// Leaves `null` in place of the value and returns the value.
+    pub fn take(&mut self) -> Value {
+        mem::replace(self, Value::Null)
+    }
+
+    /// Puts `value` in place of the value and returns the old one.
+    pub fn replace(&mut self, value: impl Into<Value>) -> Value {
+        mem::replace(self, value.into())
+    }
+
+    fn apply_edit(&mut self, edit: Edit) -> Result<(), PointerError> {
+        match edit {
+            Edit::Set { pointer, value } => self.set_by_pointer(&pointer, value, true).map(|_| ()),
+            Edit::Insert { pointer, value } => {
+                let (parent, last) = split_parent(self, &pointer)?;
+                let not_found = || PointerError::NotFound(pointer.clone());
+
+                match parent {
+                    Value::Array(values) => {
+                        let index = if last == "-" {
+                            values.len()
+                        } else {
+                            parse_index(&last)
+                                .filter(|i| *i <= values.len())
+                                .ok_or_else(not_found)?
+                        };
+                        values.insert(index, value);
+                    }
+                    Value::Object(map) => {
+                        if map.contains_key(&last) {
+                            return Err(PointerError::AlreadyExists(pointer));
+                        }
+                        map.insert(last, value);
+                    }
+                    _ => return Err(not_found()),
+                }
+                Ok(())
+            }
+            Edit::Remove { pointer } => self.remove_by_pointer(&pointer).map(|_| ()),
+        }
+    }
+}
+
+/// Resolves the parent of the pointed-to location and returns it with the last token.
+pub(crate) fn split_parent<'a>(
+    value: &'a mut Value,
+    pointer: &str,
+) -> Result<(&'a mut Value, String), PointerError> {
+    let mut tokens =
+        parse_pointer(pointer).ok_or_else(|| PointerError::InvalidSyntax(pointer.to_string()))?;
+    let last = tokens
+        .pop()
+        .ok_or_else(|| PointerError::NotFound(pointer.to_string()))?;
+    let parent = resolve_tokens_mut(value, &tokens)
+        .ok_or_else(|| PointerError::NotFound(pointer.to_string()))?;
+
+    Ok((parent, last))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{KindMismatch, PointerError, Value, ValueKind};
+
+    use super::{Edit, EditError};
+
+    fn document() -> Value {
+        Value::object([
+            ("name", Value::string("app")),
+            (
+                "tags",
+                Value::Array(vec![Value::string("a"), Value::string("c")]),
+            ),
+        ])
+    }
+
+    fn set(pointer: &str, value: Value) -> Edit {
+        Edit::Set {
+            pointer: String::from(pointer),
+            value,
+        }
+    }
+
+    fn insert(pointer: &str, value: Value) -> Edit {
+        Edit::Insert {
+            pointer: String::from(pointer),
+            value,
+        }
+    }
+
+    fn remove(pointer: &str) -> Edit {
+        Edit::Remove {
+            pointer: String::from(pointer),
+        }
+    }
+
+    #[test]
+    fn applies_all_edits() {
+        let mut value = document();
+        let edits = vec![
+            set("/name", Value::string("service")),
+            set("/owner/team", Value::string("core")),
+            insert("/tags/1", Value::string("b")),
+            insert("/tags/-", Value::string("d")),
+            remove("/tags/0"),
+        ];
+        let expected = Value::object([
+            ("name", Value::string("service")),
+            ("owner", Value::object([("team", Value::string("core"))])),
+            (
+                "tags",
+                Value::Array(vec![
+                    Value::string("b"),
+                    Value::string("c"),
+                    Value::string("d"),
+                ]),
+            ),
+        ]);
+
+        value.apply_edits(edits).unwrap();
+
+        assert_eq!(value, expected);
+    }
+
+    #[test]
+    fn rolls_back_on_failure() {
+        let mut value = document();
+        let edits = vec![
+            set("/name", Value::string("service")),
+            remove("/tags/0"),
+            remove("/missing"),
+        ];
+        let expected = Err(EditError {
+            index: 2,
+            error: PointerError::NotFound(String::from("/missing")),
+        });
+
+        let actual = value.apply_edits(edits);
+
+        assert_eq!(actual, expected);
+        assert_eq!(value, document());
+    }
+
+    #[test]
+    fn insert_rejects_existing_member() {
+        let mut value = document();
+        let expected = Err(EditError {
+            index: 0,
+            error: PointerError::AlreadyExists(String::from("/name")),
+        });
+
+        let actual = value.apply_edits(vec![insert("/name", Value::Null)]);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn insert_rejects_index_past_end() {
+        let mut value = document();
+        let expected = Err(EditError {
+            index: 0,
+            error: PointerError::NotFound(String::from("/tags/5")),
+        });
+
+        let actual = value.apply_edits(vec![insert("/tags/5", Value::Null)]);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn insert_rejects_non_canonical_index() {
+        for pointer in ["/tags/01", "/tags/+1"] {
+            let mut value = document();
+            let expected = Err(EditError {
+                index: 0,
+                error: PointerError::NotFound(String::from(pointer)),
+            });
+
+            let actual = value.apply_edits(vec![insert(pointer, Value::Null)]);
+
+            assert_eq!(actual, expected, "pointer: {pointer}");
+            assert_eq!(value, document());
+        }
+    }
+
+    #[test]
+    fn set_replaces_root() {
+        let mut value = document();
+
+        value
+            .apply_edits(vec![set("", Value::Boolean(true))])
+            .unwrap();
+
+        assert_eq!(value, Value::Boolean(true));
+    }
+
+    #[test]
+    fn sets_and_removes_by_pointer() {
+        let mut value = document();
+
+        let old = value.set_by_pointer("/name", Value::string("service"), false);
+        let created = value.set_by_pointer("/owner/ids/-", Value::Number(7.0), true);
+        let missing = value.set_by_pointer("/other/id", Value::Null, false);
+        let removed = value.remove_by_pointer("/tags/0");
+
+        assert_eq!(old, Ok(Value::string("app")));
+        assert_eq!(created, Ok(Value::Null));
+        assert_eq!(
+            missing,
+            Err(PointerError::NotFound(String::from("/other/id")))
+        );
+        assert_eq!(removed, Ok(Value::string("a")));
+        assert_eq!(
+            value,
+            Value::object([
+                ("name", Value::string("service")),
+                (
+                    "owner",
+                    Value::object([("ids", Value::array([Value::Number(7.0)]))])
+                ),
+                ("tags", Value::array([Value::string("c")])),
+            ])
+        );
+        assert_eq!(
+            value.remove_by_pointer(""),
+            Err(PointerError::NotFound(String::new()))
+        );
+    }
+
+    #[test]
+    fn mutates_in_place() {
+        let mut value = document();
+
+        value.get_mut("tags").unwrap().push("d").unwrap();
+        let old = value.insert("name", "service").unwrap();
+        let removed = value.remove("missing").unwrap();
+        let tags = value.get_mut("tags").unwrap().take();
+        let name = value.get_mut("name").unwrap().replace(1.0);
+
+        assert_eq!(old, Some(Value::string("app")));
+        assert_eq!(removed, None);
+        assert_eq!(
+            tags,
+            Value::Array(vec![
+                Value::string("a"),
+                Value::string("c"),
+                Value::string("d"),
+            ])
+        );
+        assert_eq!(name, Value::string("service"));
+        assert_eq!(
+            value,
+            Value::object([("name", Value::Number(1.0)), ("tags", Value::Null)])
+        );
+    }
+
+    #[test]
+    fn rejects_mutation_of_wrong_kind() {
+        let mut value = document();
+        let expected = KindMismatch {
+            expected: ValueKind::Array,
+            found: ValueKind::Object,
+        };
+
+        assert_eq!(value.push(Value::Null), Err(expected));
+        assert_eq!(
+            value.get_mut("name").unwrap().insert("a", true),
+            Err(KindMismatch {
+                expected: ValueKind::Object,
+                found: ValueKind::String,
+            })
+        );
+        assert_eq!(
+            value.get_mut("tags").unwrap().remove("a"),
+            Err(KindMismatch {
+                expected: ValueKind::Object,
+                found: ValueKind::Array,
+            })
+        );
+        assert_eq!(value, document());
+    }
+}
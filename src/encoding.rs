@@ -0,0 +1,197 @@
+use std::str;
+
+use crate::{parse, ParseError, Value};
+
+/// Unicode encodings JSON text can be detected in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Utf8,
+    Utf16Be,
+    Utf16Le,
+    Utf32Be,
+    Utf32Le,
+}
+
+/// Possible errors that can occur when parsing input of unknown encoding
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum EncodingError {
+    /// The input is not valid in the detected encoding from this byte offset on
+    InvalidSequence { encoding: Encoding, offset: usize },
+
+    /// The decoded input is not valid JSON
+    ParseError(ParseError),
+}
+
+impl From<ParseError> for EncodingError {
+    fn from(err: ParseError) -> Self {
+        EncodingError::ParseError(err)
+    }
+}
+
+/// Detects the encoding of JSON text from its leading bytes, together with
+/// the length of the byte order mark found, if any.
+///
+/// Without a byte order mark the rules of RFC 4627 apply: the first two
+/// characters of JSON text are ASCII, so the pattern of zero bytes among the
+/// first four bytes tells the encodings apart.
+pub fn detect_encoding(input: &[u8]) -> (Encoding, usize) {
+    match input {
+        [0xEF, 0xBB, 0xBF, ..] => (Encoding::Utf8, 3),
+        [0x00, 0x00, 0xFE, 0xFF, ..] => (Encoding::Utf32Be, 4),
+        [0xFF, 0xFE, 0x00, 0x00, ..] => (Encoding::Utf32Le, 4),
+        [0xFE, 0xFF, ..] => (Encoding::Utf16Be, 2),
+        [0xFF, 0xFE, ..] => (Encoding::Utf16Le, 2),
+        [0x00, 0x00, 0x00, _, ..] => (Encoding::Utf32Be, 0),
+        [_, 0x00, 0x00, 0x00, ..] => (Encoding::Utf32Le, 0),
+        [0x00, _, ..] => (Encoding::Utf16Be, 0),
+        [_, 0x00, ..] => (Encoding::Utf16Le, 0),
+        _ => (Encoding::Utf8, 0),
+    }
+}
+
+/// Parses JSON text in UTF-8, UTF-16 or UTF-32 of either byte order, detected
+/// with [`detect_encoding`]. A byte order mark is skipped.
+pub fn parse_bytes_any_encoding(input: &[u8]) -> Result<Value, EncodingError> {
+    let (encoding, bom_len) = detect_encoding(input);
+    let decoded =
+        decode(&input[bom_len..], encoding).map_err(|offset| EncodingError::InvalidSequence {
+            encoding,
+            offset: bom_len + offset,
+        })?;
+    Ok(parse(decoded)?)
+}
+
+/// Decodes the input, returning the byte offset of the first invalid sequence on failure.
+fn decode(input: &[u8], encoding: Encoding) -> Result<String, usize> {
+    match encoding {
+        Encoding::Utf8 => str::from_utf8(input)
+            .map(str::to_string)
+            .map_err(|err| err.valid_up_to()),
+        Encoding::Utf16Be => decode_utf16(input, u16::from_be_bytes),
+        Encoding::Utf16Le => decode_utf16(input, u16::from_le_bytes),
+        Encoding::Utf32Be => decode_utf32(input, u32::from_be_bytes),
+        Encoding::Utf32Le => decode_utf32(input, u32::from_le_bytes),
+    }
+}
+
+fn decode_utf16(input: &[u8], unit: fn([u8; 2]) -> u16) -> Result<String, usize> {
+    let chunks = input.chunks_exact(2);
+    if !chunks.remainder().is_empty() {
+        return Err(input.len() - 1);
+    }
+
+    let units = chunks.map(|chunk| unit([chunk[0], chunk[1]]));
+    let mut output = String::with_capacity(input.len() / 2);
+    let mut offset = 0;
+    for decoded in char::decode_utf16(units) {
+        let ch = decoded.map_err(|_| offset)?;
+        output.push(ch);
+        offset += ch.len_utf16() * 2;
+    }
+    Ok(output)
+}
+
+fn decode_utf32(input: &[u8], unit: fn([u8; 4]) -> u32) -> Result<String, usize> {
+    let chunks = input.chunks_exact(4);
+    if !chunks.remainder().is_empty() {
+        return Err(input.len() - chunks.remainder().len());
+    }
+
+    chunks
+        .enumerate()
+        .map(|(index, chunk)| {
+            char::from_u32(unit([chunk[0], chunk[1], chunk[2], chunk[3]])).ok_or(index * 4)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse, ParseError, TokenParseError, Value};
+
+    use super::{detect_encoding, parse_bytes_any_encoding, Encoding, EncodingError};
+
+    const TEXT: &str = r#"{"name": "Zoë 🎉", "n": [1, true]}"#;
+
+    fn expected() -> Value {
+        parse(String::from(TEXT)).unwrap()
+    }
+
+    fn utf16(text: &str, to_bytes: fn(u16) -> [u8; 2]) -> Vec<u8> {
+        text.encode_utf16().flat_map(to_bytes).collect()
+    }
+
+    fn utf32(text: &str, to_bytes: fn(u32) -> [u8; 4]) -> Vec<u8> {
+        text.chars().flat_map(|ch| to_bytes(ch as u32)).collect()
+    }
+
+    #[test]
+    fn parses_every_encoding_without_bom() {
+        let cases = [
+            (TEXT.as_bytes().to_vec(), Encoding::Utf8),
+            (utf16(TEXT, u16::to_be_bytes), Encoding::Utf16Be),
+            (utf16(TEXT, u16::to_le_bytes), Encoding::Utf16Le),
+            (utf32(TEXT, u32::to_be_bytes), Encoding::Utf32Be),
+            (utf32(TEXT, u32::to_le_bytes), Encoding::Utf32Le),
+        ];
+
+        for (input, encoding) in cases {
+            assert_eq!(detect_encoding(&input), (encoding, 0), "{encoding:?}");
+
+            let actual = parse_bytes_any_encoding(&input);
+
+            assert_eq!(actual, Ok(expected()), "{encoding:?}");
+        }
+    }
+
+    #[test]
+    fn skips_byte_order_mark() {
+        let mut input = vec![0xFF, 0xFE];
+        input.extend(utf16(TEXT, u16::to_le_bytes));
+
+        let actual = parse_bytes_any_encoding(&input);
+
+        assert_eq!(actual, Ok(expected()));
+    }
+
+    #[test]
+    fn detects_short_documents() {
+        let cases: [(&[u8], Encoding); 4] = [
+            (b"1", Encoding::Utf8),
+            (b"\x001", Encoding::Utf16Be),
+            (b"1\x00", Encoding::Utf16Le),
+            (b"", Encoding::Utf8),
+        ];
+
+        for (input, expected) in cases {
+            let (actual, _) = detect_encoding(input);
+
+            assert_eq!(actual, expected, "input: {input:?}");
+        }
+    }
+
+    #[test]
+    fn reports_offset_of_invalid_sequence() {
+        // "[" followed by an unpaired high surrogate
+        let input = [0x5B, 0x00, 0x00, 0xD8, 0x5D, 0x00];
+        let expected = Err(EncodingError::InvalidSequence {
+            encoding: Encoding::Utf16Le,
+            offset: 2,
+        });
+
+        let actual = parse_bytes_any_encoding(&input);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn reports_truncated_document() {
+        let expected = Err(EncodingError::ParseError(ParseError::ParseError(
+            TokenParseError::UnexpectedEof,
+        )));
+
+        let actual = parse_bytes_any_encoding(b"[1");
+
+        assert_eq!(actual, expected);
+    }
+}
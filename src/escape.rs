@@ -0,0 +1,148 @@
+use std::ops::Range;
+
+use crate::parser::{decode_unicode_escape, TokenParseError};
+
+/// Computes, for every character of the unescaped string, the byte range of the
+/// raw string token (without quotes) it was decoded from.
+pub fn raw_char_ranges(raw: &str) -> Result<Vec<Range<usize>>, TokenParseError> {
+    let mut ranges = Vec::with_capacity(raw.len());
+    let mut chars = raw.char_indices();
+
+    while let Some((start, ch)) = chars.next() {
+        if ch != '\\' {
+            ranges.push(start..start + ch.len_utf8());
+            continue;
+        }
+
+        let (escape_start, escaped) = chars
+            .next()
+            .ok_or_else(|| TokenParseError::UnfinishedEscape(String::from("\\")))?;
+        let mut end = escape_start + escaped.len_utf8();
+
+        if escaped == 'u' {
+            let (_, len) = decode_unicode_escape(chars.as_str())?;
+            chars.nth(len - 1);
+            // The escape is ASCII, one byte per char
+            end += len;
+        }
+
+        ranges.push(start..end);
+    }
+
+    Ok(ranges)
+}
+
+/// Number of characters the raw string token contains once unescaped.
+pub fn unescaped_len(raw: &str) -> Result<usize, TokenParseError> {
+    raw_char_ranges(raw).map(|ranges| ranges.len())
+}
+
+/// Maps a character offset in the unescaped string to the byte offset in the raw
+/// token where that character's (possibly escaped) representation starts.
+/// The offset right after the last character maps to the end of the raw token.
+pub fn raw_offset(raw: &str, unescaped_offset: usize) -> Result<Option<usize>, TokenParseError> {
+    let ranges = raw_char_ranges(raw)?;
+    let offset = match ranges.get(unescaped_offset) {
+        Some(range) => Some(range.start),
+        None if unescaped_offset == ranges.len() => Some(raw.len()),
+        None => None,
+    };
+    Ok(offset)
+}
+
+/// Maps a byte offset in the raw token to the character offset in the unescaped
+/// string. Offsets inside an escape sequence map to the character it encodes.
+pub fn unescaped_offset(raw: &str, raw_offset: usize) -> Result<Option<usize>, TokenParseError> {
+    let ranges = raw_char_ranges(raw)?;
+    if raw_offset == raw.len() {
+        return Ok(Some(ranges.len()));
+    }
+    let offset = ranges.iter().position(|range| range.contains(&raw_offset));
+    Ok(offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::TokenParseError;
+
+    use super::{raw_char_ranges, raw_offset, unescaped_len, unescaped_offset};
+
+    #[test]
+    fn ranges_without_escapes() {
+        let expected = vec![0..1, 1..3, 3..4];
+
+        let actual = raw_char_ranges("aéb").unwrap();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn ranges_with_escapes() {
+        let input = r#"a\n\u00e9b"#;
+        let expected = vec![0..1, 1..3, 3..9, 9..10];
+
+        let actual = raw_char_ranges(input).unwrap();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn surrogate_pair_is_one_range() {
+        let input = r"a\ud83d\ude00b";
+        let expected = vec![0..1, 1..13, 13..14];
+
+        let actual = raw_char_ranges(input).unwrap();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn length_counts_escapes_once() {
+        let input = r#"\"quoted\" 名"#;
+
+        let actual = unescaped_len(input).unwrap();
+
+        assert_eq!(actual, 10);
+    }
+
+    #[test]
+    fn maps_unescaped_offset_to_raw() {
+        let input = r#"a\tb"#;
+
+        assert_eq!(raw_offset(input, 0), Ok(Some(0)));
+        assert_eq!(raw_offset(input, 1), Ok(Some(1)));
+        assert_eq!(raw_offset(input, 2), Ok(Some(3)));
+        assert_eq!(raw_offset(input, 3), Ok(Some(4)));
+        assert_eq!(raw_offset(input, 4), Ok(None));
+    }
+
+    #[test]
+    fn maps_raw_offset_inside_escape() {
+        let input = r#"x\u0041y"#;
+
+        assert_eq!(unescaped_offset(input, 0), Ok(Some(0)));
+        assert_eq!(unescaped_offset(input, 4), Ok(Some(1)));
+        assert_eq!(unescaped_offset(input, 7), Ok(Some(2)));
+        assert_eq!(unescaped_offset(input, 8), Ok(Some(3)));
+    }
+
+    #[test]
+    fn fails_on_unfinished_escape() {
+        let input = r#"ab\u12"#;
+        let expected = Err(TokenParseError::UnfinishedEscape(String::from(r"\u12")));
+
+        let actual = unescaped_len(input);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn fails_on_invalid_hex() {
+        let input = r#"\u12G4"#;
+        let expected = Err(TokenParseError::InvalidHexValue(String::from(r"\u12G4")));
+
+        let actual = unescaped_len(input);
+
+        assert_eq!(actual, expected);
+    }
+}
@@ -0,0 +1,287 @@
+use std::mem;
+
+use crate::{
+    calendar::{days_from_civil, days_in_month, unsigned},
+    JsonPointer, Map, Value,
+};
+
+/// Possible errors when normalizing MongoDB Extended JSON
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ExtendedJsonError {
+    /// The wrapper object at this pointer holds a malformed payload
+    InvalidWrapper {
+        pointer: JsonPointer,
+        wrapper: &'static str,
+    },
+}
+
+/// Wrapper keys recognized when they are the only member of an object.
+const WRAPPERS: [&str; 6] = [
+    "$oid",
+    "$date",
+    "$numberInt",
+    "$numberLong",
+    "$numberDouble",
+    "$binary",
+];
+
+impl Value {
+    /// Replaces MongoDB Extended JSON (v2) wrappers with plain values:
+    /// `$oid` becomes its hex string, `$numberInt`, `$numberLong` and
+    /// `$numberDouble` a number, `$date` the milliseconds since the Unix
+    /// epoch and `$binary` its base64 payload.
+    ///
+    /// Objects with members besides the wrapper key are left as they are.
+    pub fn normalize_extended_json(self) -> Result<Value, ExtendedJsonError> {
+        normalize(self, &mut JsonPointer::root())
+    }
+}
+
+fn normalize(mut value: Value, pointer: &mut JsonPointer) -> Result<Value, ExtendedJsonError> {
+    match &mut value {
+        Value::Array(values) => {
+            let mut normalized = Vec::with_capacity(values.len());
+            for (index, element) in mem::take(values).into_iter().enumerate() {
+                pointer.push(index.to_string());
+                normalized.push(normalize(element, pointer)?);
+                pointer.pop();
+            }
+            Ok(Value::Array(normalized))
+        }
+        Value::Object(map) => {
+            if let Some(wrapper) = wrapper_of(map) {
+                return match map
+                    .get(wrapper)
+                    .and_then(|payload| unwrap(wrapper, payload))
+                {
+                    Some(unwrapped) => Ok(unwrapped),
+                    None => Err(ExtendedJsonError::InvalidWrapper {
+                        pointer: pointer.clone(),
+                        wrapper,
+                    }),
+                };
+            }
+
+            let mut normalized = Map::with_capacity(map.len());
+            for (key, member) in mem::take(map) {
+                pointer.push(key.as_str());
+                let member = normalize(member, pointer)?;
+                pointer.pop();
+                normalized.insert(key, member);
+            }
+            Ok(Value::Object(normalized))
+        }
+        _ => Ok(value),
+    }
+}
+
+fn wrapper_of(map: &Map<String, Value>) -> Option<&'static str> {
+    if map.len() != 1 {
+        return None;
+    }
+    let key = map.keys().next()?;
+    WRAPPERS.into_iter().find(|wrapper| wrapper == key)
+}
+
+/// Returns the plain value for a wrapper payload, `None` if it is malformed.
+fn unwrap(wrapper: &str, payload: &Value) -> Option<Value> {
+    let number = match (wrapper, payload) {
+        ("$oid", Value::String(hex)) => {
+            let valid = hex.len() == 24 && hex.bytes().all(|b| b.is_ascii_hexdigit());
+            return valid.then(|| Value::String(hex.clone()));
+        }
+        ("$numberInt", Value::String(text)) => f64::from(integer::<i32>(text)?),
+        ("$numberLong", Value::String(text)) => integer::<i64>(text)? as f64,
+        ("$numberDouble", Value::String(text)) => match text.as_str() {
+            "Infinity" => f64::INFINITY,
+            "-Infinity" => f64::NEG_INFINITY,
+            "NaN" => f64::NAN,
+            _ => text.parse().ok()?,
+        },
+        ("$date", Value::String(text)) => parse_date(text)?,
+        ("$date", Value::Object(map)) => match wrapper_of(map)? {
+            "$numberLong" => return unwrap("$numberLong", map.get("$numberLong")?),
+            _ => return None,
+        },
+        ("$binary", Value::Object(map)) => {
+            let base64 = match (map.len(), map.get("base64"), map.get("subType")) {
+                (2, Some(Value::String(base64)), Some(Value::String(sub_type))) => {
+                    let sub_type_valid = (1..=2).contains(&sub_type.len())
+                        && sub_type.bytes().all(|b| b.is_ascii_hexdigit());
+                    (sub_type_valid && is_base64(base64)).then_some(base64)?
+                }
+                _ => return None,
+            };
+            return Some(Value::String(base64.clone()));
+        }
+        _ => return None,
+    };
+    Some(Value::Number(number))
+}
+
+/// Parses an optionally signed run of ASCII digits.
+fn integer<T: std::str::FromStr>(text: &str) -> Option<T> {
+    let digits = text.strip_prefix('-').unwrap_or(text);
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    text.parse().ok()
+}
+
+fn is_base64(text: &str) -> bool {
+    let unpadded = text.trim_end_matches('=');
+    text.len().is_multiple_of(4)
+        && text.len() - unpadded.len() <= 2
+        && unpadded
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'+' || b == b'/')
+}
+
+/// Converts an ISO-8601 timestamp like `2009-02-13T23:31:30.123Z` or
+/// `2009-02-14T00:31:30+01:00` into milliseconds since the Unix epoch.
+fn parse_date(text: &str) -> Option<f64> {
+    let bytes = text.as_bytes();
+    let separators = [(4, b'-'), (7, b'-'), (10, b'T'), (13, b':'), (16, b':')];
+    // Non-ASCII text could put a char boundary inside the fields sliced below
+    if bytes.len() < 20 || !text.is_ascii() || separators.iter().any(|&(at, b)| bytes[at] != b) {
+        return None;
+    }
+
+    let year: i64 = unsigned(&text[0..4])?;
+    let month: i64 = unsigned(&text[5..7])?;
+    let day: i64 = unsigned(&text[8..10])?;
+    let hour: i64 = unsigned(&text[11..13])?;
+    let minute: i64 = unsigned(&text[14..16])?;
+    let second: i64 = unsigned(&text[17..19])?;
+
+    let mut rest = &text[19..];
+    let mut millis = 0;
+    if let Some(fraction) = rest.strip_prefix('.') {
+        let end = fraction
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(fraction.len());
+        if end == 0 {
+            return None;
+        }
+        let mut digits = String::from(&fraction[..end.min(3)]);
+        while digits.len() < 3 {
+            digits.push('0');
+        }
+        millis = unsigned(&digits)?;
+        rest = &fraction[end..];
+    }
+
+    let offset_minutes = match rest.as_bytes() {
+        [b'Z'] => 0,
+        [sign @ (b'+' | b'-'), _, _, b':', _, _] => {
+            let offset: i64 = unsigned::<i64>(&rest[1..3])? * 60 + unsigned::<i64>(&rest[4..6])?;
+            if *sign == b'-' {
+                -offset
+            } else {
+                offset
+            }
+        }
+        _ => return None,
+    };
+
+    let valid = (1..=12).contains(&month)
+        && (1..=days_in_month(year, month)).contains(&day)
+        && hour < 24
+        && minute < 60
+        && second < 60;
+    if !valid {
+        return None;
+    }
+
+    let seconds = days_from_civil(year, month, day) * 86_400 + hour * 3_600 + minute * 60 + second
+        - offset_minutes * 60;
+    Some((seconds * 1_000 + millis) as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse, JsonPointer, Value};
+
+    use super::{parse_date, ExtendedJsonError};
+
+    #[test]
+    fn unwraps_known_wrappers() {
+        let input = parse(String::from(
+            r#"{
+                "_id": {"$oid": "5f1b2c3d4e5f6a7b8c9d0e1f"},
+                "created": {"$date": "2009-02-13T23:31:30.123Z"},
+                "updated": {"$date": {"$numberLong": "1234567890123"}},
+                "count": {"$numberLong": "42"},
+                "ratio": {"$numberDouble": "-Infinity"},
+                "blob": {"$binary": {"base64": "AQID", "subType": "00"}},
+                "tags": [{"$numberInt": "7"}, {"$oid": "x", "note": "kept"}]
+            }"#,
+        ))
+        .unwrap();
+        let expected = Value::object([
+            (
+                "_id",
+                Value::String(String::from("5f1b2c3d4e5f6a7b8c9d0e1f")),
+            ),
+            ("created", Value::Number(1234567890123.0)),
+            ("updated", Value::Number(1234567890123.0)),
+            ("count", Value::Number(42.0)),
+            ("ratio", Value::Number(f64::NEG_INFINITY)),
+            ("blob", Value::String(String::from("AQID"))),
+            (
+                "tags",
+                Value::Array(vec![
+                    Value::Number(7.0),
+                    Value::object([
+                        ("$oid", Value::String(String::from("x"))),
+                        ("note", Value::String(String::from("kept"))),
+                    ]),
+                ]),
+            ),
+        ]);
+
+        let actual = input.normalize_extended_json();
+
+        assert_eq!(actual, Ok(expected));
+    }
+
+    #[test]
+    fn reports_pointer_of_malformed_wrapper() {
+        let input = parse(String::from(r#"{"a": [{"$numberLong": "12x"}]}"#)).unwrap();
+        let expected = Err(ExtendedJsonError::InvalidWrapper {
+            pointer: JsonPointer::parse("/a/0").unwrap(),
+            wrapper: "$numberLong",
+        });
+
+        let actual = input.normalize_extended_json();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn rejects_non_ascii_date() {
+        let input = Value::object([("$date", Value::string("2024-01-01T00:00:0éZ"))]);
+
+        let actual = input.normalize_extended_json();
+
+        assert!(actual.is_err());
+    }
+
+    #[test]
+    fn parses_dates() {
+        let cases = [
+            ("1970-01-01T00:00:00Z", Some(0.0)),
+            ("2000-03-01T01:00:00+01:00", Some(951868800000.0)),
+            ("1969-12-31T23:59:59.5Z", Some(-500.0)),
+            ("2001-02-29T00:00:00Z", None),
+            ("2000-01-01 00:00:00Z", None),
+            ("2000-01-01T00:00:00", None),
+            ("2024-01-01T00:00:0éZ", None),
+            ("2024-01-01T00:00:00+0é:00", None),
+        ];
+
+        for (input, expected) in cases {
+            assert_eq!(parse_date(input), expected, "{input}");
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use crate::JsonPointer;
+
+/// Selects which parts of a document are kept while transcoding
+#[derive(Debug, Clone, Default)]
+pub struct Filter {
+    /// Members whose key matches one of these patterns are dropped at any
+    /// depth. `*` in a pattern matches any run of characters.
+    pub drop_keys: Vec<String>,
+
+    /// When not empty, only values at or below one of these pointers are
+    /// kept, together with the containers leading to them
+    pub allow: Vec<JsonPointer>,
+}
+
+impl Filter {
+    /// Whether the filter keeps every value.
+    pub fn is_empty(&self) -> bool {
+        self.drop_keys.is_empty() && self.allow.is_empty()
+    }
+
+    /// Whether the value at `path` is written. `key` is the unescaped key
+    /// when the value is an object member.
+    pub(crate) fn keeps(&self, path: &[String], key: Option<&str>, is_container: bool) -> bool {
+        if let Some(key) = key {
+            if self
+                .drop_keys
+                .iter()
+                .any(|pattern| matches_pattern(pattern, key))
+            {
+                return false;
+            }
+        }
+
+        self.allow.is_empty()
+            || self.allow.iter().any(|pointer| {
+                let allowed = pointer.tokens();
+                path.starts_with(allowed) || (is_container && allowed.starts_with(path))
+            })
+    }
+}
+
+/// Matches `text` against a glob pattern where `*` stands for any run of characters.
+fn matches_pattern(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack = None;
+
+    while t < text.len() {
+        if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if p < pattern.len() && pattern[p] == text[t] {
+            p += 1;
+            t += 1;
+        } else if let Some((star, matched)) = backtrack {
+            backtrack = Some((star, matched + 1));
+            p = star + 1;
+            t = matched + 1;
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::matches_pattern;
+
+    #[test]
+    fn matches_glob_patterns() {
+        let cases = [
+            ("email", "email", true),
+            ("email", "emails", false),
+            ("*_token", "access_token", true),
+            ("*_token", "token", false),
+            ("pass*", "password", true),
+            ("*ss*", "password", true),
+            ("a*b*c", "aXbYbc", true),
+            ("a*b*c", "aXcY", false),
+            ("*", "", true),
+        ];
+
+        for (pattern, text, expected) in cases {
+            let actual = matches_pattern(pattern, text);
+
+            assert_eq!(actual, expected, "pattern: {pattern}, text: {text}");
+        }
+    }
+}
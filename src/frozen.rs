@@ -0,0 +1,212 @@
+use crate::{
+    pointer::{parse_index, parse_pointer},
+    Value, ValueKind,
+};
+
+/// Immutable JSON value for long-lived documents that are read far more often
+/// than changed, such as loaded configuration. Containers are boxed slices
+/// without spare capacity, and object members are sorted by key so lookups
+/// are a binary search. Created with [`Value::freeze`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum FrozenValue {
+    Null,
+    Boolean(bool),
+    Number(f64),
+    String(Box<str>),
+    Array(Box<[FrozenValue]>),
+    /// Members sorted by key
+    Object(Box<[(Box<str>, FrozenValue)]>),
+}
+
+impl Value {
+    /// Copies the value into a [`FrozenValue`]. Timestamps and chunked
+    /// strings become the strings they are written as.
+    pub fn freeze(&self) -> FrozenValue {
+        match self {
+            Value::Null => FrozenValue::Null,
+            Value::Boolean(b) => FrozenValue::Boolean(*b),
+            Value::Number(n) => FrozenValue::Number(*n),
+            Value::String(s) => FrozenValue::String(s.as_str().into()),
+            #[cfg(feature = "datetime")]
+            Value::DateTime(date_time) => FrozenValue::String(date_time.to_string().into()),
+            Value::Rope(rope) => FrozenValue::String(rope.to_string().into()),
+            Value::Array(values) => FrozenValue::Array(values.iter().map(Value::freeze).collect()),
+            Value::Object(map) => {
+                let mut members: Vec<(Box<str>, FrozenValue)> = map
+                    .iter()
+                    .map(|(key, value)| (key.as_str().into(), value.freeze()))
+                    .collect();
+                members.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+                FrozenValue::Object(members.into_boxed_slice())
+            }
+        }
+    }
+}
+
+impl FrozenValue {
+    /// Converts back into an owned [`Value`].
+    pub fn thaw(&self) -> Value {
+        match self {
+            FrozenValue::Null => Value::Null,
+            FrozenValue::Boolean(b) => Value::Boolean(*b),
+            FrozenValue::Number(n) => Value::Number(*n),
+            FrozenValue::String(s) => Value::String(s.to_string()),
+            FrozenValue::Array(values) => Value::Array(values.iter().map(Self::thaw).collect()),
+            FrozenValue::Object(members) => Value::Object(
+                members
+                    .iter()
+                    .map(|(key, value)| (key.to_string(), value.thaw()))
+                    .collect(),
+            ),
+        }
+    }
+
+    pub fn kind(&self) -> ValueKind {
+        match self {
+            FrozenValue::Null => ValueKind::Null,
+            FrozenValue::Boolean(_) => ValueKind::Boolean,
+            FrozenValue::Number(_) => ValueKind::Number,
+            FrozenValue::String(_) => ValueKind::String,
+            FrozenValue::Array(_) => ValueKind::Array,
+            FrozenValue::Object(_) => ValueKind::Object,
+        }
+    }
+
+    /// Looks up an object member, `None` when the key is absent or the value
+    /// is not an object.
+    pub fn get(&self, key: &str) -> Option<&FrozenValue> {
+        let FrozenValue::Object(members) = self else {
+            return None;
+        };
+        members
+            .binary_search_by(|(member, _)| (**member).cmp(key))
+            .ok()
+            .map(|index| &members[index].1)
+    }
+
+    /// Looks up an array element, `None` when the index is out of range or
+    /// the value is not an array.
+    pub fn get_index(&self, index: usize) -> Option<&FrozenValue> {
+        match self {
+            FrozenValue::Array(values) => values.get(index),
+            _ => None,
+        }
+    }
+
+    /// Looks up the value at a JSON pointer, see [`Value::pointer`].
+    pub fn pointer(&self, pointer: &str) -> Option<&FrozenValue> {
+        let mut current = self;
+        for token in parse_pointer(pointer)? {
+            current = match current {
+                FrozenValue::Object(_) => current.get(&token)?,
+                FrozenValue::Array(_) => current.get_index(parse_index(&token)?)?,
+                _ => return None,
+            };
+        }
+        Some(current)
+    }
+
+    /// Object members in key order, empty for anything but an object.
+    pub fn members(&self) -> impl Iterator<Item = (&str, &FrozenValue)> {
+        let members: &[(Box<str>, FrozenValue)] = match self {
+            FrozenValue::Object(members) => members,
+            _ => &[],
+        };
+        members.iter().map(|(key, value)| (&**key, value))
+    }
+
+    /// Array elements, empty for anything but an array.
+    pub fn elements(&self) -> &[FrozenValue] {
+        match self {
+            FrozenValue::Array(values) => values,
+            _ => &[],
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, FrozenValue::Null)
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            FrozenValue::Boolean(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            FrozenValue::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            FrozenValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+}
+
+impl From<&Value> for FrozenValue {
+    fn from(value: &Value) -> Self {
+        value.freeze()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse, Value, ValueKind};
+
+    use super::FrozenValue;
+
+    fn config() -> Value {
+        parse(String::from(
+            r#"{"name": "app", "servers": [{"host": "a", "port": 80}, {"host": "b"}],
+                "debug": false, "ratio": 0.5, "owner": null}"#,
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn looks_up_frozen_members() {
+        let frozen = config().freeze();
+
+        assert_eq!(
+            frozen.get("name").and_then(FrozenValue::as_str),
+            Some("app")
+        );
+        assert_eq!(
+            frozen.get("debug").and_then(FrozenValue::as_bool),
+            Some(false)
+        );
+        assert_eq!(frozen.get("ratio").and_then(FrozenValue::as_f64), Some(0.5));
+        assert!(frozen.get("owner").unwrap().is_null());
+        assert_eq!(frozen.get("missing"), None);
+        assert_eq!(
+            frozen.pointer("/servers/0/port"),
+            Some(&FrozenValue::Number(80.0))
+        );
+        assert_eq!(frozen.pointer("/servers/1/port"), None);
+        assert_eq!(frozen.get("servers").unwrap().elements().len(), 2);
+        assert_eq!(frozen.get("servers").unwrap().kind(), ValueKind::Array);
+    }
+
+    #[test]
+    fn sorts_members_by_key() {
+        let frozen = config().freeze();
+
+        let keys: Vec<&str> = frozen.members().map(|(key, _)| key).collect();
+
+        assert_eq!(keys, ["debug", "name", "owner", "ratio", "servers"]);
+        assert_eq!(FrozenValue::Null.members().count(), 0);
+    }
+
+    #[test]
+    fn thaws_to_equal_value() {
+        let value = config();
+
+        assert_eq!(value.freeze().thaw(), value);
+    }
+}
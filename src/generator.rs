@@ -0,0 +1,273 @@
+//! Random [`Value`] trees for load tests and fuzzing corpora.
+
+use crate::{Map, Value, ValueKind};
+
+/// Relative likelihood of generating each kind of value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KindWeights {
+    pub null: u32,
+    pub boolean: u32,
+    pub number: u32,
+    pub string: u32,
+    pub array: u32,
+    pub object: u32,
+}
+
+impl Default for KindWeights {
+    fn default() -> Self {
+        Self {
+            null: 1,
+            boolean: 1,
+            number: 2,
+            string: 2,
+            array: 1,
+            object: 1,
+        }
+    }
+}
+
+/// Shape of the generated trees.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeneratorConfig {
+    /// Nesting depth beyond which only scalars are generated, 0 means scalars only
+    pub max_depth: usize,
+
+    /// Maximum number of elements in a generated array
+    pub max_array_len: usize,
+
+    /// Maximum number of members in a generated object
+    pub max_object_len: usize,
+
+    /// Maximum number of characters in generated strings and keys
+    pub max_string_len: usize,
+
+    /// Characters strings and keys are made of
+    pub alphabet: Vec<char>,
+
+    pub weights: KindWeights,
+}
+
+impl Default for GeneratorConfig {
+    fn default() -> Self {
+        Self {
+            max_depth: 4,
+            max_array_len: 5,
+            max_object_len: 5,
+            max_string_len: 12,
+            alphabet: ('a'..='z').chain('0'..='9').collect(),
+            weights: KindWeights::default(),
+        }
+    }
+}
+
+impl Value {
+    /// Generates a random value. The same configuration and seed always
+    /// produce the same value.
+    pub fn random(config: &GeneratorConfig, seed: u64) -> Value {
+        let mut rng = Rng(seed);
+        generate(config, &mut rng, 0)
+    }
+}
+
+fn generate(config: &GeneratorConfig, rng: &mut Rng, depth: usize) -> Value {
+    let weights = &config.weights;
+    let allow_containers = depth < config.max_depth;
+
+    let mut choices = vec![
+        (weights.null, ValueKind::Null),
+        (weights.boolean, ValueKind::Boolean),
+        (weights.number, ValueKind::Number),
+        (weights.string, ValueKind::String),
+    ];
+    if allow_containers {
+        choices.push((weights.array, ValueKind::Array));
+        choices.push((weights.object, ValueKind::Object));
+    }
+
+    let total: u64 = choices.iter().map(|(weight, _)| u64::from(*weight)).sum();
+    if total == 0 {
+        return Value::Null;
+    }
+
+    let mut pick = rng.below(total);
+    let kind = choices
+        .iter()
+        .find(|(weight, _)| {
+            let weight = u64::from(*weight);
+            if pick < weight {
+                true
+            } else {
+                pick -= weight;
+                false
+            }
+        })
+        .map(|(_, kind)| *kind)
+        .unwrap_or(ValueKind::Null);
+
+    match kind {
+        ValueKind::Null => Value::Null,
+        ValueKind::Boolean => Value::Boolean(rng.below(2) == 1),
+        ValueKind::Number => Value::Number(random_number(rng)),
+        ValueKind::String => Value::String(random_string(config, rng, 0)),
+        ValueKind::Array => {
+            let len = rng.below(config.max_array_len as u64 + 1) as usize;
+            Value::Array((0..len).map(|_| generate(config, rng, depth + 1)).collect())
+        }
+        ValueKind::Object => {
+            let len = rng.below(config.max_object_len as u64 + 1) as usize;
+            let mut map = Map::with_capacity(len);
+            for _ in 0..len {
+                let key = random_string(config, rng, 1);
+                map.insert(key, generate(config, rng, depth + 1));
+            }
+            Value::Object(map)
+        }
+    }
+}
+
+/// Mostly small integers, which are most common in real documents, and some fractions.
+fn random_number(rng: &mut Rng) -> f64 {
+    let integer = rng.below(2001) as f64 - 1000.0;
+    if rng.below(4) == 0 {
+        integer + rng.below(100) as f64 / 100.0
+    } else {
+        integer
+    }
+}
+
+fn random_string(config: &GeneratorConfig, rng: &mut Rng, min_len: usize) -> String {
+    if config.alphabet.is_empty() {
+        return String::new();
+    }
+
+    let max_len = config.max_string_len.max(min_len);
+    let len = min_len + rng.below((max_len - min_len) as u64 + 1) as usize;
+    (0..len)
+        .map(|_| config.alphabet[rng.below(config.alphabet.len() as u64) as usize])
+        .collect()
+}
+
+/// SplitMix64, small and good enough for test data.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Number in `0..bound`, `bound` must not be zero.
+    fn below(&mut self, bound: u64) -> u64 {
+        self.next() % bound
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Value;
+
+    use super::{GeneratorConfig, KindWeights};
+
+    fn depth(value: &Value) -> usize {
+        match value {
+            Value::Array(values) => 1 + values.iter().map(depth).max().unwrap_or(0),
+            Value::Object(map) => 1 + map.values().map(depth).max().unwrap_or(0),
+            _ => 0,
+        }
+    }
+
+    fn strings(value: &Value, output: &mut Vec<String>) {
+        match value {
+            Value::String(s) => output.push(s.clone()),
+            Value::Array(values) => values.iter().for_each(|v| strings(v, output)),
+            Value::Object(map) => {
+                for (key, value) in map {
+                    output.push(key.clone());
+                    strings(value, output);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    #[test]
+    fn same_seed_same_value() {
+        let config = GeneratorConfig::default();
+
+        let first = Value::random(&config, 42);
+        let second = Value::random(&config, 42);
+
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn different_seeds_differ() {
+        let config = GeneratorConfig::default();
+
+        let values: Vec<Value> = (0..8).map(|seed| Value::random(&config, seed)).collect();
+
+        assert!(values.iter().any(|value| value != &values[0]));
+    }
+
+    #[test]
+    fn respects_max_depth() {
+        let config = GeneratorConfig {
+            max_depth: 2,
+            weights: KindWeights {
+                null: 0,
+                boolean: 0,
+                number: 1,
+                string: 0,
+                array: 5,
+                object: 5,
+            },
+            ..GeneratorConfig::default()
+        };
+
+        for seed in 0..50 {
+            let value = Value::random(&config, seed);
+
+            assert!(depth(&value) <= 2, "seed {seed}: {value:?}");
+        }
+    }
+
+    #[test]
+    fn uses_only_alphabet() {
+        let config = GeneratorConfig {
+            alphabet: vec!['x', 'y'],
+            ..GeneratorConfig::default()
+        };
+
+        for seed in 0..20 {
+            let mut output = Vec::new();
+            strings(&Value::random(&config, seed), &mut output);
+
+            assert!(output
+                .iter()
+                .flat_map(|s| s.chars())
+                .all(|c| c == 'x' || c == 'y'));
+        }
+    }
+
+    #[test]
+    fn zero_weights_generate_null() {
+        let config = GeneratorConfig {
+            weights: KindWeights {
+                null: 0,
+                boolean: 0,
+                number: 0,
+                string: 0,
+                array: 0,
+                object: 0,
+            },
+            ..GeneratorConfig::default()
+        };
+
+        let actual = Value::random(&config, 1);
+
+        assert_eq!(actual, Value::Null);
+    }
+}
@@ -0,0 +1,552 @@
+//! GeoJSON ([RFC 7946](https://www.rfc-editor.org/rfc/rfc7946)) objects read
+//! from a [`Value`].
+//!
+//! Reading validates the structure the RFC requires: known `type` names,
+//! coordinate nesting, line strings of at least two positions, closed linear
+//! rings of at least four positions and bounding boxes that contain every
+//! position of the object they belong to.
+
+use crate::{JsonPointer, Map, Value};
+
+/// A position; `altitude` is kept when the input has a third element.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Coordinate {
+    pub longitude: f64,
+    pub latitude: f64,
+    pub altitude: Option<f64>,
+}
+
+/// Area covered by an object, `west` may be greater than `east` for boxes
+/// crossing the antimeridian.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingBox {
+    pub west: f64,
+    pub south: f64,
+    pub east: f64,
+    pub north: f64,
+    pub altitudes: Option<(f64, f64)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Geometry {
+    Point(Coordinate),
+    MultiPoint(Vec<Coordinate>),
+    LineString(Vec<Coordinate>),
+    MultiLineString(Vec<Vec<Coordinate>>),
+    /// Exterior ring first, then holes
+    Polygon(Vec<Vec<Coordinate>>),
+    MultiPolygon(Vec<Vec<Vec<Coordinate>>>),
+    GeometryCollection(Vec<Geometry>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Feature {
+    /// String or number identifier
+    pub id: Option<Value>,
+    /// `None` for an unlocated feature
+    pub geometry: Option<Geometry>,
+    pub properties: Option<Map<String, Value>>,
+    pub bbox: Option<BoundingBox>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeatureCollection {
+    pub features: Vec<Feature>,
+    pub bbox: Option<BoundingBox>,
+}
+
+/// Any GeoJSON object, chosen by its `type` member.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GeoJson {
+    Geometry(Geometry),
+    Feature(Feature),
+    FeatureCollection(FeatureCollection),
+}
+
+/// Possible errors when reading GeoJSON
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GeoJsonError {
+    /// The object at this pointer lacks a required member
+    MissingMember {
+        pointer: JsonPointer,
+        member: &'static str,
+    },
+
+    /// The `type` member at this pointer names no object expected there
+    UnknownType { pointer: JsonPointer, found: String },
+
+    /// The value at this pointer breaks the rule described
+    Invalid {
+        pointer: JsonPointer,
+        reason: &'static str,
+    },
+}
+
+impl BoundingBox {
+    pub fn contains(&self, coordinate: &Coordinate) -> bool {
+        let longitude = if self.west <= self.east {
+            (self.west..=self.east).contains(&coordinate.longitude)
+        } else {
+            coordinate.longitude >= self.west || coordinate.longitude <= self.east
+        };
+        let altitude = match (self.altitudes, coordinate.altitude) {
+            (Some((low, high)), Some(altitude)) => (low..=high).contains(&altitude),
+            _ => true,
+        };
+        longitude && (self.south..=self.north).contains(&coordinate.latitude) && altitude
+    }
+}
+
+impl Geometry {
+    /// Every position of the geometry, in document order.
+    pub fn coordinates(&self) -> Vec<&Coordinate> {
+        let mut coordinates = Vec::new();
+        self.collect(&mut coordinates);
+        coordinates
+    }
+
+    fn collect<'a>(&'a self, out: &mut Vec<&'a Coordinate>) {
+        match self {
+            Geometry::Point(point) => out.push(point),
+            Geometry::MultiPoint(points) | Geometry::LineString(points) => out.extend(points),
+            Geometry::MultiLineString(lines) | Geometry::Polygon(lines) => {
+                out.extend(lines.iter().flatten())
+            }
+            Geometry::MultiPolygon(polygons) => out.extend(polygons.iter().flatten().flatten()),
+            Geometry::GeometryCollection(geometries) => {
+                for geometry in geometries {
+                    geometry.collect(out);
+                }
+            }
+        }
+    }
+}
+
+impl Feature {
+    /// Positions of the geometry, empty for an unlocated feature.
+    pub fn coordinates(&self) -> Vec<&Coordinate> {
+        self.geometry
+            .as_ref()
+            .map(Geometry::coordinates)
+            .unwrap_or_default()
+    }
+}
+
+impl FeatureCollection {
+    /// Positions of every feature, in document order.
+    pub fn coordinates(&self) -> Vec<&Coordinate> {
+        self.features
+            .iter()
+            .flat_map(Feature::coordinates)
+            .collect()
+    }
+}
+
+impl GeoJson {
+    pub fn coordinates(&self) -> Vec<&Coordinate> {
+        match self {
+            GeoJson::Geometry(geometry) => geometry.coordinates(),
+            GeoJson::Feature(feature) => feature.coordinates(),
+            GeoJson::FeatureCollection(collection) => collection.coordinates(),
+        }
+    }
+}
+
+impl TryFrom<&Value> for GeoJson {
+    type Error = GeoJsonError;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        let root = JsonPointer::root();
+        match type_of(object(value, &root)?, &root)? {
+            "Feature" => feature(value, &root).map(GeoJson::Feature),
+            "FeatureCollection" => feature_collection(value, &root).map(GeoJson::FeatureCollection),
+            _ => geometry(value, &root).map(GeoJson::Geometry),
+        }
+    }
+}
+
+impl TryFrom<&Value> for Geometry {
+    type Error = GeoJsonError;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        geometry(value, &JsonPointer::root())
+    }
+}
+
+impl TryFrom<&Value> for Feature {
+    type Error = GeoJsonError;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        feature(value, &JsonPointer::root())
+    }
+}
+
+impl TryFrom<&Value> for FeatureCollection {
+    type Error = GeoJsonError;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        feature_collection(value, &JsonPointer::root())
+    }
+}
+
+fn feature_collection(
+    value: &Value,
+    pointer: &JsonPointer,
+) -> Result<FeatureCollection, GeoJsonError> {
+    let map = object(value, pointer)?;
+    expect_type(map, pointer, "FeatureCollection")?;
+
+    let features_pointer = pointer.child("features");
+    let features = array(member(map, pointer, "features")?, &features_pointer)?
+        .iter()
+        .enumerate()
+        .map(|(index, value)| feature(value, &features_pointer.child(index.to_string())))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let coordinates = features.iter().flat_map(Feature::coordinates).collect();
+    let bbox = bbox(map, pointer, coordinates)?;
+    Ok(FeatureCollection { features, bbox })
+}
+
+fn feature(value: &Value, pointer: &JsonPointer) -> Result<Feature, GeoJsonError> {
+    let map = object(value, pointer)?;
+    expect_type(map, pointer, "Feature")?;
+
+    let id = match map.get("id") {
+        None => None,
+        Some(id @ (Value::String(_) | Value::Number(_))) => Some(id.clone()),
+        Some(_) => {
+            return Err(invalid(
+                pointer.child("id"),
+                "id must be a string or a number",
+            ))
+        }
+    };
+    let geometry = match member(map, pointer, "geometry")? {
+        Value::Null => None,
+        value => Some(geometry(value, &pointer.child("geometry"))?),
+    };
+    let properties = match map.get("properties") {
+        None | Some(Value::Null) => None,
+        Some(Value::Object(properties)) => Some(properties.clone()),
+        Some(_) => {
+            return Err(invalid(
+                pointer.child("properties"),
+                "properties must be an object or null",
+            ))
+        }
+    };
+
+    let coordinates = geometry.iter().flat_map(Geometry::coordinates).collect();
+    let bbox = bbox(map, pointer, coordinates)?;
+    Ok(Feature {
+        id,
+        geometry,
+        properties,
+        bbox,
+    })
+}
+
+fn geometry(value: &Value, pointer: &JsonPointer) -> Result<Geometry, GeoJsonError> {
+    let map = object(value, pointer)?;
+    let kind = type_of(map, pointer)?;
+
+    let geometry = if kind == "GeometryCollection" {
+        let geometries_pointer = pointer.child("geometries");
+        let geometries = array(member(map, pointer, "geometries")?, &geometries_pointer)?
+            .iter()
+            .enumerate()
+            .map(|(index, value)| geometry(value, &geometries_pointer.child(index.to_string())))
+            .collect::<Result<_, _>>()?;
+        Geometry::GeometryCollection(geometries)
+    } else {
+        let coordinates = member(map, pointer, "coordinates")?;
+        let pointer = &pointer.child("coordinates");
+        match kind {
+            "Point" => Geometry::Point(position(coordinates, pointer)?),
+            "MultiPoint" => Geometry::MultiPoint(positions(coordinates, pointer)?),
+            "LineString" => Geometry::LineString(line(coordinates, pointer)?),
+            "MultiLineString" => Geometry::MultiLineString(each(coordinates, pointer, line)?),
+            "Polygon" => Geometry::Polygon(polygon(coordinates, pointer)?),
+            "MultiPolygon" => Geometry::MultiPolygon(each(coordinates, pointer, polygon)?),
+            other => {
+                return Err(GeoJsonError::UnknownType {
+                    pointer: pointer.parent().unwrap_or_default(),
+                    found: String::from(other),
+                })
+            }
+        }
+    };
+
+    bbox(map, pointer, geometry.coordinates())?;
+    Ok(geometry)
+}
+
+fn polygon(value: &Value, pointer: &JsonPointer) -> Result<Vec<Vec<Coordinate>>, GeoJsonError> {
+    each(value, pointer, |value, pointer| {
+        let ring = positions(value, pointer)?;
+        if ring.len() < 4 || ring.first() != ring.last() {
+            return Err(invalid(
+                pointer.clone(),
+                "linear ring must be closed and have at least four positions",
+            ));
+        }
+        Ok(ring)
+    })
+}
+
+fn line(value: &Value, pointer: &JsonPointer) -> Result<Vec<Coordinate>, GeoJsonError> {
+    let line = positions(value, pointer)?;
+    if line.len() < 2 {
+        return Err(invalid(
+            pointer.clone(),
+            "line string must have at least two positions",
+        ));
+    }
+    Ok(line)
+}
+
+fn positions(value: &Value, pointer: &JsonPointer) -> Result<Vec<Coordinate>, GeoJsonError> {
+    each(value, pointer, position)
+}
+
+fn position(value: &Value, pointer: &JsonPointer) -> Result<Coordinate, GeoJsonError> {
+    match numbers(value, pointer)?.as_slice() {
+        [longitude, latitude, rest @ ..] => Ok(Coordinate {
+            longitude: *longitude,
+            latitude: *latitude,
+            altitude: rest.first().copied(),
+        }),
+        _ => Err(invalid(
+            pointer.clone(),
+            "position must have at least two numbers",
+        )),
+    }
+}
+
+/// Reads every element of an array, passing the element's pointer along.
+fn each<T>(
+    value: &Value,
+    pointer: &JsonPointer,
+    read: impl Fn(&Value, &JsonPointer) -> Result<T, GeoJsonError>,
+) -> Result<Vec<T>, GeoJsonError> {
+    array(value, pointer)?
+        .iter()
+        .enumerate()
+        .map(|(index, element)| read(element, &pointer.child(index.to_string())))
+        .collect()
+}
+
+/// Reads the optional `bbox` member, checking that it covers `coordinates`.
+fn bbox(
+    map: &Map<String, Value>,
+    pointer: &JsonPointer,
+    coordinates: Vec<&Coordinate>,
+) -> Result<Option<BoundingBox>, GeoJsonError> {
+    let Some(value) = map.get("bbox") else {
+        return Ok(None);
+    };
+    let pointer = pointer.child("bbox");
+
+    let bbox = match *numbers(value, &pointer)?.as_slice() {
+        [west, south, east, north] => BoundingBox {
+            west,
+            south,
+            east,
+            north,
+            altitudes: None,
+        },
+        [west, south, low, east, north, high] => BoundingBox {
+            west,
+            south,
+            east,
+            north,
+            altitudes: Some((low, high)),
+        },
+        _ => return Err(invalid(pointer, "bbox must have four or six numbers")),
+    };
+
+    let ordered = bbox.south <= bbox.north && bbox.altitudes.is_none_or(|(low, high)| low <= high);
+    if !ordered {
+        return Err(invalid(pointer, "bbox minimum must not exceed its maximum"));
+    }
+    if !coordinates
+        .into_iter()
+        .all(|coordinate| bbox.contains(coordinate))
+    {
+        return Err(invalid(pointer, "bbox must contain every position"));
+    }
+    Ok(Some(bbox))
+}
+
+fn numbers(value: &Value, pointer: &JsonPointer) -> Result<Vec<f64>, GeoJsonError> {
+    each(value, pointer, |value, pointer| match value {
+        Value::Number(n) => Ok(*n),
+        _ => Err(invalid(pointer.clone(), "expected a number")),
+    })
+}
+
+fn expect_type(
+    map: &Map<String, Value>,
+    pointer: &JsonPointer,
+    expected: &'static str,
+) -> Result<(), GeoJsonError> {
+    match type_of(map, pointer)? {
+        kind if kind == expected => Ok(()),
+        other => Err(GeoJsonError::UnknownType {
+            pointer: pointer.clone(),
+            found: String::from(other),
+        }),
+    }
+}
+
+fn type_of<'a>(
+    map: &'a Map<String, Value>,
+    pointer: &JsonPointer,
+) -> Result<&'a str, GeoJsonError> {
+    match member(map, pointer, "type")? {
+        Value::String(kind) => Ok(kind),
+        _ => Err(invalid(pointer.child("type"), "type must be a string")),
+    }
+}
+
+fn member<'a>(
+    map: &'a Map<String, Value>,
+    pointer: &JsonPointer,
+    member: &'static str,
+) -> Result<&'a Value, GeoJsonError> {
+    map.get(member).ok_or_else(|| GeoJsonError::MissingMember {
+        pointer: pointer.clone(),
+        member,
+    })
+}
+
+fn object<'a>(
+    value: &'a Value,
+    pointer: &JsonPointer,
+) -> Result<&'a Map<String, Value>, GeoJsonError> {
+    match value {
+        Value::Object(map) => Ok(map),
+        _ => Err(invalid(pointer.clone(), "expected an object")),
+    }
+}
+
+fn array<'a>(value: &'a Value, pointer: &JsonPointer) -> Result<&'a [Value], GeoJsonError> {
+    match value {
+        Value::Array(values) => Ok(values),
+        _ => Err(invalid(pointer.clone(), "expected an array")),
+    }
+}
+
+fn invalid(pointer: JsonPointer, reason: &'static str) -> GeoJsonError {
+    GeoJsonError::Invalid { pointer, reason }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse, JsonPointer, Value};
+
+    use super::{BoundingBox, Coordinate, GeoJson, GeoJsonError, Geometry};
+
+    fn read(input: &str) -> Result<GeoJson, GeoJsonError> {
+        GeoJson::try_from(&parse(String::from(input)).unwrap())
+    }
+
+    #[test]
+    fn reads_feature_collection() {
+        let input = r#"{
+            "type": "FeatureCollection",
+            "bbox": [100, 0, 105, 1],
+            "features": [
+                {"type": "Feature", "id": 1, "properties": {"name": "a"},
+                 "geometry": {"type": "Point", "coordinates": [102, 0.5, 12]}},
+                {"type": "Feature", "properties": null, "geometry": {
+                    "type": "Polygon",
+                    "coordinates": [[[100, 0], [101, 0], [101, 1], [100, 0]]]
+                }},
+                {"type": "Feature", "geometry": null}
+            ]
+        }"#;
+
+        let GeoJson::FeatureCollection(collection) = read(input).unwrap() else {
+            panic!("expected a feature collection");
+        };
+
+        assert_eq!(collection.features.len(), 3);
+        assert_eq!(collection.features[0].id, Some(Value::Number(1.0)));
+        assert_eq!(
+            collection.features[0].geometry,
+            Some(Geometry::Point(Coordinate {
+                longitude: 102.0,
+                latitude: 0.5,
+                altitude: Some(12.0),
+            }))
+        );
+        assert_eq!(collection.coordinates().len(), 5);
+        assert_eq!(
+            collection.bbox,
+            Some(BoundingBox {
+                west: 100.0,
+                south: 0.0,
+                east: 105.0,
+                north: 1.0,
+                altitudes: None,
+            })
+        );
+    }
+
+    #[test]
+    fn accepts_bbox_crossing_antimeridian() {
+        let input = r#"{"type": "LineString", "bbox": [170, -5, -170, 5],
+            "coordinates": [[175, 0], [-175, 1]]}"#;
+
+        let actual = read(input).map(|geojson| geojson.coordinates().len());
+
+        assert_eq!(actual, Ok(2));
+    }
+
+    #[test]
+    fn reports_invalid_objects() {
+        let cases = [
+            (
+                r#"{"type": "Polygon", "coordinates": [[[0, 0], [1, 0], [1, 1], [0, 1]]]}"#,
+                GeoJsonError::Invalid {
+                    pointer: JsonPointer::parse("/coordinates/0").unwrap(),
+                    reason: "linear ring must be closed and have at least four positions",
+                },
+            ),
+            (
+                r#"{"type": "Point", "bbox": [0, 0, 1, 1], "coordinates": [2, 0]}"#,
+                GeoJsonError::Invalid {
+                    pointer: JsonPointer::parse("/bbox").unwrap(),
+                    reason: "bbox must contain every position",
+                },
+            ),
+            (
+                r#"{"type": "GeometryCollection", "geometries": [{"type": "Circle", "coordinates": []}]}"#,
+                GeoJsonError::UnknownType {
+                    pointer: JsonPointer::parse("/geometries/0").unwrap(),
+                    found: String::from("Circle"),
+                },
+            ),
+            (
+                r#"{"type": "Feature", "properties": {}}"#,
+                GeoJsonError::MissingMember {
+                    pointer: JsonPointer::root(),
+                    member: "geometry",
+                },
+            ),
+            (
+                r#"{"type": "MultiPoint", "coordinates": [[0, "1"]]}"#,
+                GeoJsonError::Invalid {
+                    pointer: JsonPointer::parse("/coordinates/0/1").unwrap(),
+                    reason: "expected a number",
+                },
+            ),
+        ];
+
+        for (input, expected) in cases {
+            assert_eq!(read(input), Err(expected), "{input}");
+        }
+    }
+}
@@ -0,0 +1,468 @@
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{Hash, Hasher},
+};
+
+use crate::{
+    parser::unescape_string,
+    stream::{Step, StreamError, Walker},
+    tokenizer::Token,
+    JsonPointer, ParseError, Value, ValueKind,
+};
+
+/// Hashes the tokens of a JSON text without building a [`Value`], so texts
+/// differing only in insignificant whitespace, string escapes or number
+/// formatting hash equal. Member order is significant.
+///
+/// Hashes are only stable within a build of the crate, so they are suited
+/// to in-memory caches rather than storage.
+pub fn semantic_hash(input: &str) -> Result<u64, ParseError> {
+    hash_tokens(input, false)
+}
+
+/// Like [`semantic_hash`], but objects with the same members in a different
+/// order hash equal too.
+pub fn semantic_hash_unordered(input: &str) -> Result<u64, ParseError> {
+    hash_tokens(input, true)
+}
+
+/// A container whose children are being hashed
+struct Frame {
+    is_object: bool,
+    /// Unescaped key of the container when it is an object member
+    key: Option<String>,
+    children: Vec<u64>,
+}
+
+fn hash_tokens(input: &str, ignore_key_order: bool) -> Result<u64, ParseError> {
+    walk_hashes(input, ignore_key_order).map_err(StreamError::into_parse_error)
+}
+
+fn walk_hashes(input: &str, ignore_key_order: bool) -> Result<u64, StreamError> {
+    let mut walker = Walker::new(input.as_bytes());
+    let mut frames: Vec<Frame> = Vec::new();
+    let mut root = None;
+
+    while let Some(step) = walker.next()? {
+        let (hash, key) = match step {
+            Step::Value { token, key } => {
+                let key = key.and_then(|_| walker.path().last().cloned());
+                let mut hasher = DefaultHasher::new();
+                match token {
+                    Token::LeftBrace | Token::LeftBracket => {
+                        frames.push(Frame {
+                            is_object: token == Token::LeftBrace,
+                            key,
+                            children: Vec::new(),
+                        });
+                        continue;
+                    }
+                    Token::Null => 0u8.hash(&mut hasher),
+                    Token::True => (1u8, true).hash(&mut hasher),
+                    Token::False => (1u8, false).hash(&mut hasher),
+                    Token::Number(n) => (2u8, number_bits(n)).hash(&mut hasher),
+                    Token::String(raw) => (3u8, unescape_string(&raw)?).hash(&mut hasher),
+                    _ => unreachable!("the walker only starts values with value tokens"),
+                }
+                (hasher.finish(), key)
+            }
+            Step::Close(_) => {
+                let mut frame = frames
+                    .pop()
+                    .expect("the walker only closes open containers");
+                if frame.is_object && ignore_key_order {
+                    frame.children.sort_unstable();
+                }
+                let mut hasher = DefaultHasher::new();
+                (if frame.is_object { 5u8 } else { 4u8 }, frame.children).hash(&mut hasher);
+                (hasher.finish(), frame.key)
+            }
+        };
+
+        let hash = match key {
+            Some(key) => {
+                let mut hasher = DefaultHasher::new();
+                (key, hash).hash(&mut hasher);
+                hasher.finish()
+            }
+            None => hash,
+        };
+        match frames.last_mut() {
+            Some(parent) => parent.children.push(hash),
+            None => root = Some(hash),
+        }
+    }
+
+    Ok(root.expect("the walker fails on input without a value"))
+}
+
+/// Hashes every subtree bottom-up, calling `visit` with the location, value,
+/// structural hash and number of values in the subtree. Returns the hash and
+/// size of the whole value. Equal values always have equal hashes, regardless
+/// of the iteration order of their objects.
+pub(crate) fn visit_subtree_hashes<'a, F>(value: &'a Value, visit: &mut F) -> (u64, usize)
+where
+    F: FnMut(&JsonPointer, &'a Value, u64, usize),
+{
+    visit_at(value, &mut JsonPointer::root(), visit)
+}
+
+/// Hash of the value as computed by [`visit_subtree_hashes`].
+pub(crate) fn structural_hash(value: &Value) -> u64 {
+    visit_subtree_hashes(value, &mut |_, _, _, _| {}).0
+}
+
+fn visit_at<'a, F>(value: &'a Value, path: &mut JsonPointer, visit: &mut F) -> (u64, usize)
+where
+    F: FnMut(&JsonPointer, &'a Value, u64, usize),
+{
+    let mut size = 1;
+
+    let hash = match value {
+        Value::Array(values) => {
+            let mut children = Vec::with_capacity(values.len());
+            for (index, value) in values.iter().enumerate() {
+                path.push(index.to_string());
+                let (hash, child_size) = visit_at(value, path, visit);
+                path.pop();
+
+                children.push(hash);
+                size += child_size;
+            }
+            array_hash(children)
+        }
+        Value::Object(map) => {
+            let mut members = Vec::with_capacity(map.len());
+            for (key, value) in map {
+                path.push(key.clone());
+                let (hash, child_size) = visit_at(value, path, visit);
+                path.pop();
+
+                members.push((key.as_str(), hash));
+                size += child_size;
+            }
+            object_hash(members)
+        }
+        scalar => scalar_hash(scalar).expect("containers are hashed above"),
+    };
+
+    visit(path, value, hash, size);
+    (hash, size)
+}
+
+/// Bits of a number for hashing, with `-0.0` as `0.0` since the two are equal.
+fn number_bits(n: f64) -> u64 {
+    if n == 0.0 {
+        0.0f64.to_bits()
+    } else {
+        n.to_bits()
+    }
+}
+
+/// Structural hash of a scalar, `None` for arrays and objects. Together with
+/// [`array_hash`] and [`object_hash`] this defines the hash of every value, so
+/// all bottom-up traversals agree on it.
+pub(crate) fn scalar_hash(value: &Value) -> Option<u64> {
+    let mut hasher = DefaultHasher::new();
+    match value {
+        Value::Null => 0u8.hash(&mut hasher),
+        Value::Boolean(b) => (1u8, b).hash(&mut hasher),
+        Value::Number(n) => (2u8, number_bits(*n)).hash(&mut hasher),
+        Value::String(s) => (3u8, s).hash(&mut hasher),
+        #[cfg(feature = "datetime")]
+        Value::DateTime(date_time) => (6u8, date_time).hash(&mut hasher),
+        // Ropes hash like the strings they are equal to
+        Value::Rope(rope) => (3u8, rope).hash(&mut hasher),
+        Value::Array(_) | Value::Object(_) => return None,
+    }
+    Some(hasher.finish())
+}
+
+/// Structural hash of an array from the hashes of its elements, in order.
+pub(crate) fn array_hash(elements: impl IntoIterator<Item = u64>) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    4u8.hash(&mut hasher);
+    for hash in elements {
+        hash.hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// Structural hash of an object from the hashes of its members, in any order.
+pub(crate) fn object_hash(mut members: Vec<(&str, u64)>) -> u64 {
+    // Iteration order of the map is unspecified, so hash in key order
+    members.sort_unstable();
+    let mut hasher = DefaultHasher::new();
+    (5u8, members).hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Structural hash and kind of every subtree of a value, recorded with
+/// [`Value::subtree_hashes`] to find what changed in a later version.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SubtreeHashes {
+    entries: HashMap<JsonPointer, (u64, ValueKind)>,
+}
+
+impl SubtreeHashes {
+    /// Hash of the subtree at `path`, `None` when there was no value there.
+    pub fn get(&self, path: &JsonPointer) -> Option<u64> {
+        self.entries.get(path).map(|&(hash, _)| hash)
+    }
+
+    /// Number of subtrees, one per value.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl Value {
+    /// Hashes every subtree, for a later [`Value::changed_paths_since`].
+    pub fn subtree_hashes(&self) -> SubtreeHashes {
+        let mut entries = HashMap::new();
+        visit_subtree_hashes(self, &mut |path, value, hash, _| {
+            entries.insert(path.clone(), (hash, value.kind()));
+        });
+        SubtreeHashes { entries }
+    }
+
+    /// Locations whose value was added, removed or changed since `previous`
+    /// was recorded, sorted. Only the outermost changed location is reported:
+    /// containers of the same kind are compared member by member and array
+    /// elements by index, anything else is reported as a whole. Unchanged
+    /// subtrees are skipped by comparing their hashes.
+    pub fn changed_paths_since(&self, previous: &SubtreeHashes) -> Vec<JsonPointer> {
+        let current = self.subtree_hashes();
+        let mut children: HashMap<JsonPointer, Vec<&JsonPointer>> = HashMap::new();
+        for path in previous.entries.keys() {
+            if let Some(parent) = path.parent() {
+                children.entry(parent).or_default().push(path);
+            }
+        }
+
+        let mut changed = Vec::new();
+        let mut path = JsonPointer::root();
+        collect_changes(self, &mut path, &current, previous, &children, &mut changed);
+        changed.sort();
+        changed
+    }
+}
+
+fn collect_changes(
+    value: &Value,
+    path: &mut JsonPointer,
+    current: &SubtreeHashes,
+    previous: &SubtreeHashes,
+    children: &HashMap<JsonPointer, Vec<&JsonPointer>>,
+    changed: &mut Vec<JsonPointer>,
+) {
+    let (hash, kind) = current.entries[path];
+    match previous.entries.get(path) {
+        Some(&(old, _)) if old == hash => {}
+        Some(&(_, old_kind))
+            if old_kind == kind && matches!(kind, ValueKind::Array | ValueKind::Object) =>
+        {
+            match value {
+                Value::Array(values) => {
+                    for (index, value) in values.iter().enumerate() {
+                        path.push(index.to_string());
+                        collect_changes(value, path, current, previous, children, changed);
+                        path.pop();
+                    }
+                }
+                Value::Object(map) => {
+                    for (key, value) in map {
+                        path.push(key.clone());
+                        collect_changes(value, path, current, previous, children, changed);
+                        path.pop();
+                    }
+                }
+                _ => unreachable!("only containers are compared by member"),
+            }
+
+            let removed = children.get(path).into_iter().flatten();
+            changed.extend(
+                removed
+                    .filter(|child| !current.entries.contains_key(child))
+                    .map(|&child| child.clone()),
+            );
+        }
+        _ => changed.push(path.clone()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{JsonPointer, Map, Value};
+
+    use crate::{ParseError, TokenParseError};
+
+    use super::{semantic_hash, semantic_hash_unordered, structural_hash, visit_subtree_hashes};
+
+    #[test]
+    fn equal_values_hash_equal() {
+        let first = Value::object([
+            ("a", Value::Number(1.0)),
+            ("b", Value::Array(vec![Value::Null])),
+        ]);
+        let second = Value::object([
+            ("b", Value::Array(vec![Value::Null])),
+            ("a", Value::Number(1.0)),
+        ]);
+
+        assert_eq!(structural_hash(&first), structural_hash(&second));
+    }
+
+    #[test]
+    fn different_values_hash_differently() {
+        let values = [
+            Value::Null,
+            Value::Boolean(false),
+            Value::Number(0.0),
+            Value::string(""),
+            Value::Array(vec![]),
+            Value::Object(Map::new()),
+            Value::Array(vec![Value::Null]),
+        ];
+
+        for (i, a) in values.iter().enumerate() {
+            for b in &values[i + 1..] {
+                assert_ne!(structural_hash(a), structural_hash(b), "{a:?} vs {b:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn hashes_negative_zero_like_zero() {
+        assert_eq!(Value::Number(0.0), Value::Number(-0.0));
+        assert_eq!(
+            structural_hash(&Value::Number(0.0)),
+            structural_hash(&Value::Number(-0.0))
+        );
+        assert_eq!(semantic_hash("[0]"), semantic_hash("[-0.0]"));
+    }
+
+    #[test]
+    fn visits_every_subtree_with_size() {
+        let value = Value::Array(vec![Value::Null, Value::Array(vec![Value::Null])]);
+        let mut visited = Vec::new();
+
+        let (_, size) = visit_subtree_hashes(&value, &mut |path, _, _, size| {
+            visited.push((path.to_string(), size));
+        });
+
+        assert_eq!(size, 4);
+        assert_eq!(
+            visited,
+            [
+                (String::from("/0"), 1),
+                (String::from("/1/0"), 1),
+                (String::from("/1"), 2),
+                (String::new(), 4),
+            ]
+        );
+    }
+
+    #[test]
+    fn ignores_insignificant_differences() {
+        let compact = r#"{"a":[1,"A"],"b":null}"#;
+        let spaced = "{ \"a\" : [ 1.0 , \"\\u0041\" ] ,\n \"b\": null }";
+
+        assert_eq!(semantic_hash(compact), semantic_hash(spaced));
+        assert_ne!(
+            semantic_hash(compact),
+            semantic_hash(r#"{"a":[1,"A"],"b":false}"#)
+        );
+        assert_ne!(semantic_hash("[1,[2]]"), semantic_hash("[[1],2]"));
+        assert_ne!(semantic_hash(r#"{"a":1}"#), semantic_hash(r#"{"b":1}"#));
+    }
+
+    #[test]
+    fn ignores_key_order_when_asked() {
+        let first = r#"{"a": 1, "b": {"c": 2, "d": 3}}"#;
+        let second = r#"{"b": {"d": 3, "c": 2}, "a": 1}"#;
+
+        assert_ne!(semantic_hash(first), semantic_hash(second));
+        assert_eq!(
+            semantic_hash_unordered(first),
+            semantic_hash_unordered(second)
+        );
+        assert_ne!(
+            semantic_hash_unordered("[1, 2]"),
+            semantic_hash_unordered("[2, 1]")
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_input() {
+        assert_eq!(
+            semantic_hash("[1,]"),
+            Err(ParseError::ParseError(TokenParseError::TrailingComma))
+        );
+        assert_eq!(
+            semantic_hash("1 2"),
+            Err(ParseError::ParseError(TokenParseError::TrailingContent))
+        );
+    }
+
+    fn pointers(paths: &[&str]) -> Vec<JsonPointer> {
+        paths
+            .iter()
+            .map(|path| JsonPointer::parse(path).unwrap())
+            .collect()
+    }
+
+    fn config() -> Value {
+        Value::object([
+            (
+                "server",
+                Value::object([("host", Value::string("a")), ("port", Value::Number(80.0))]),
+            ),
+            (
+                "tags",
+                Value::Array(vec![Value::string("x"), Value::string("y")]),
+            ),
+            ("debug", Value::Boolean(false)),
+        ])
+    }
+
+    #[test]
+    fn reports_changed_settings() {
+        let previous = config().subtree_hashes();
+        let mut value = config();
+        value["server"]["port"] = Value::Number(8080.0);
+        value["server"].remove("host").unwrap();
+        value["server"].insert("tls", true).unwrap();
+        value["tags"].get_index_mut(1).unwrap().replace("z");
+        value["debug"] = Value::object([("level", Value::Number(2.0))]);
+
+        let actual = value.changed_paths_since(&previous);
+
+        assert_eq!(
+            actual,
+            pointers(&[
+                "/debug",
+                "/server/host",
+                "/server/port",
+                "/server/tls",
+                "/tags/1"
+            ])
+        );
+    }
+
+    #[test]
+    fn reports_nothing_for_equal_values() {
+        let previous = config().subtree_hashes();
+
+        assert_eq!(previous.len(), 8);
+        assert_eq!(config().changed_paths_since(&previous), []);
+        assert_eq!(
+            Value::Null.changed_paths_since(&previous),
+            [JsonPointer::root()]
+        );
+    }
+}
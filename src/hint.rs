@@ -0,0 +1,228 @@
+use crate::{ParseError, Position, TokenParseError, TokenizeError};
+
+/// Suggests how to fix a parse failure caused by a common mistake, such as
+/// single-quoted strings, comments or `True` instead of `true`. `position`
+/// is where the failure was detected, as in [`crate::ParseFailure`]. `None`
+/// when the failure does not look like a mistake it knows about.
+pub fn suggest(input: &str, error: &ParseError, position: Position) -> Option<String> {
+    let rest = input.get(position.offset..).unwrap_or("");
+    let ends_early = matches!(
+        error,
+        ParseError::TokenizeError(TokenizeError::UnexpectedEof)
+            | ParseError::ParseError(TokenParseError::UnexpectedEof)
+    );
+    if ends_early && input.trim().is_empty() {
+        return Some(String::from(
+            "the input is empty, a JSON document needs a value",
+        ));
+    }
+    match error {
+        ParseError::TokenizeError(error) => suggest_for_token(rest, error),
+        ParseError::ParseError(error) => suggest_for_structure(rest, error),
+    }
+}
+
+const UNEXPECTED_END: &str =
+    "the input ends before the value is complete, check for a missing `}` or `]`";
+
+fn suggest_for_token(rest: &str, error: &TokenizeError) -> Option<String> {
+    let hint = match error {
+        TokenizeError::CharNotRecognized('\'') => {
+            String::from("found `'`: JSON strings must use double quotes")
+        }
+        TokenizeError::CharNotRecognized('/' | '#')
+            if rest.starts_with("//") || rest.starts_with("/*") || rest.starts_with('#') =>
+        {
+            String::from("comments are not allowed in JSON")
+        }
+        TokenizeError::CharNotRecognized('+') => String::from("numbers cannot start with `+`"),
+        TokenizeError::CharNotRecognized('.') => {
+            String::from("numbers need a digit before the decimal point, as in `0.5`")
+        }
+        TokenizeError::CharNotRecognized('=') => {
+            String::from("found `=`: object keys are followed by `:`")
+        }
+        TokenizeError::CharNotRecognized(ch) if is_word_char(*ch) => {
+            let word = leading_word(rest);
+            match literal_hint(word) {
+                Some(hint) => hint,
+                None if rest[word.len()..].trim_start().starts_with(':') => {
+                    format!("object keys must be strings in double quotes, as in `\"{word}\"`")
+                }
+                None => format!("found `{word}`: strings must be enclosed in double quotes"),
+            }
+        }
+        TokenizeError::UnfinishedLiteralValue => literal_hint(leading_word(rest))?,
+        TokenizeError::InvalidNumberLiteral(run) => {
+            let digits = run.strip_prefix('-').unwrap_or(run);
+            if digits.starts_with("0x") || digits.starts_with("0X") {
+                String::from("hexadecimal numbers are not allowed in JSON, write the decimal value")
+            } else {
+                literal_hint(digits)?
+            }
+        }
+        TokenizeError::UnclosedQuotes => String::from("the string is never closed with `\"`"),
+        TokenizeError::UnexpectedEof => String::from(UNEXPECTED_END),
+        _ => return None,
+    };
+    Some(hint)
+}
+
+fn suggest_for_structure(rest: &str, error: &TokenParseError) -> Option<String> {
+    let next = rest.chars().next();
+    let hint = match error {
+        TokenParseError::TrailingComma => match next {
+            Some(close @ ('}' | ']')) => format!("trailing comma before `{close}`"),
+            _ => String::from("trailing comma after the last element"),
+        },
+        TokenParseError::UnexpectedEof => String::from(UNEXPECTED_END),
+        TokenParseError::ExpectedColon => String::from("missing `:` after the object key"),
+        TokenParseError::ExpectedComma => {
+            String::from("missing `,` between values, or `:` after an object key")
+        }
+        TokenParseError::ExpectedProperty | TokenParseError::ExpectedValue if next == Some(',') => {
+            String::from("found an extra `,` with nothing before it")
+        }
+        TokenParseError::ExpectedProperty => {
+            String::from("object keys must be strings in double quotes")
+        }
+        TokenParseError::ExpectedValueForKey(key) => {
+            format!("the member `{key}` has no value after `:`")
+        }
+        TokenParseError::MismatchedBracket { expected, found } => {
+            format!(
+                "found `{found}` where the innermost open container is closed with `{expected}`"
+            )
+        }
+        TokenParseError::TrailingContent => {
+            match next {
+                Some(close @ ('}' | ']')) => {
+                    format!("found an unmatched closing `{close}`, remove it or add its opening bracket")
+                }
+                _ => String::from(
+                    "only one top-level value is allowed, wrap several values in an array",
+                ),
+            }
+        }
+        _ => return None,
+    };
+    Some(hint)
+}
+
+/// Hints for literals written the way other languages spell them.
+fn literal_hint(word: &str) -> Option<String> {
+    let hint = match word {
+        "True" | "TRUE" | "tru" => "literals are lowercase: use `true`",
+        "False" | "FALSE" | "fals" => "literals are lowercase: use `false`",
+        "Null" | "NULL" | "nul" => "literals are lowercase: use `null`",
+        "None" | "none" | "nil" | "undefined" => "JSON has no `{word}`, use `null`",
+        "NaN" | "Infinity" => "`{word}` is not a valid JSON number, use `null` or a string",
+        _ => return None,
+    };
+    Some(hint.replace("{word}", word))
+}
+
+fn is_word_char(ch: char) -> bool {
+    ch.is_alphanumeric() || ch == '_' || ch == '$'
+}
+
+fn leading_word(text: &str) -> &str {
+    let end = text
+        .char_indices()
+        .find(|&(_, ch)| !is_word_char(ch))
+        .map_or(text.len(), |(index, _)| index);
+    &text[..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse_with_failure, ParseError, Position, TokenParseError};
+
+    use super::suggest;
+
+    fn hint(input: &str) -> Option<String> {
+        let failure = parse_with_failure(String::from(input)).unwrap_err();
+        suggest(input, &failure.error, failure.position)
+    }
+
+    #[test]
+    fn suggests_fixes_for_common_mistakes() {
+        let cases = [
+            ("{'a': 1}", "found `'`: JSON strings must use double quotes"),
+            ("[1] // note", "comments are not allowed in JSON"),
+            (
+                "{port: 80}",
+                "object keys must be strings in double quotes, as in `\"port\"`",
+            ),
+            (
+                "[yes]",
+                "found `yes`: strings must be enclosed in double quotes",
+            ),
+            ("[True]", "literals are lowercase: use `true`"),
+            ("[nil]", "JSON has no `nil`, use `null`"),
+            (
+                "[-Infinity]",
+                "`Infinity` is not a valid JSON number, use `null` or a string",
+            ),
+            (
+                "[0xFF]",
+                "hexadecimal numbers are not allowed in JSON, write the decimal value",
+            ),
+            ("[+1]", "numbers cannot start with `+`"),
+            ("{\"a\" = 1}", "found `=`: object keys are followed by `:`"),
+            (
+                "[1 2]",
+                "missing `,` between values, or `:` after an object key",
+            ),
+            ("[1,,2]", "found an extra `,` with nothing before it"),
+            (
+                "[1}",
+                "found `}` where the innermost open container is closed with `]`",
+            ),
+            ("[\"abc", "the string is never closed with `\"`"),
+            (
+                "{\"a\": [1, 2",
+                "the input ends before the value is complete, check for a missing `}` or `]`",
+            ),
+            ("[1, 2,]", "trailing comma before `]`"),
+            ("{\"a\": 1,\n}", "trailing comma before `}`"),
+            ("{\"a\" 1}", "missing `:` after the object key"),
+            (
+                "{\"a\": 1} {\"b\": 2}",
+                "only one top-level value is allowed, wrap several values in an array",
+            ),
+            (
+                "{\"a\":1}}",
+                "found an unmatched closing `}`, remove it or add its opening bracket",
+            ),
+            (
+                "[1]]",
+                "found an unmatched closing `]`, remove it or add its opening bracket",
+            ),
+            ("", "the input is empty, a JSON document needs a value"),
+            (" \n\t", "the input is empty, a JSON document needs a value"),
+        ];
+
+        for (input, expected) in cases {
+            assert_eq!(hint(input).as_deref(), Some(expected), "input: {input}");
+        }
+    }
+
+    #[test]
+    fn suggests_removing_trailing_comma_anywhere() {
+        let error = ParseError::ParseError(TokenParseError::TrailingComma);
+
+        let actual = suggest("[1,", &error, Position::START.advance_str("[1,"));
+
+        assert_eq!(
+            actual.as_deref(),
+            Some("trailing comma after the last element")
+        );
+    }
+
+    #[test]
+    fn gives_no_hint_for_unknown_mistakes() {
+        assert_eq!(hint("[@]"), None);
+        assert_eq!(hint("[\"\\u00zz\"]"), None);
+    }
+}
@@ -0,0 +1,27 @@
+use crate::{intern::Unchanged, Value};
+
+/// Transformation applied to every value while the parser builds the tree,
+/// so conventions such as MongoDB extended JSON can be decoded without a
+/// second pass over the document.
+///
+/// Values are passed bottom-up: the members and elements of a container
+/// have already been transformed when the container itself is passed.
+/// Closures taking the key and the value implement it.
+pub trait ValueHook {
+    /// Returns the value to store in place of `value`. `key` is the
+    /// unescaped key when the value is an object member, `None` for array
+    /// elements and the top-level value.
+    fn transform(&mut self, key: Option<&str>, value: Value) -> Value;
+}
+
+impl<F: FnMut(Option<&str>, Value) -> Value> ValueHook for F {
+    fn transform(&mut self, key: Option<&str>, value: Value) -> Value {
+        self(key, value)
+    }
+}
+
+impl ValueHook for Unchanged {
+    fn transform(&mut self, _: Option<&str>, value: Value) -> Value {
+        value
+    }
+}
@@ -0,0 +1,24 @@
+/// Hook choosing the `String` stored for every object key, and for every
+/// string value when [`crate::ParseOptions::intern_values`] is set.
+///
+/// Applications with their own symbol table can use it to look up or
+/// register each key as it is parsed; closures taking and returning a
+/// `String` implement it.
+pub trait Interner {
+    fn intern(&mut self, s: String) -> String;
+}
+
+impl<F: FnMut(String) -> String> Interner for F {
+    fn intern(&mut self, s: String) -> String {
+        self(s)
+    }
+}
+
+/// Stores every string as parsed.
+pub(crate) struct Unchanged;
+
+impl Interner for Unchanged {
+    fn intern(&mut self, s: String) -> String {
+        s
+    }
+}
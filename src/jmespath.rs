@@ -0,0 +1,1116 @@
+//! JMESPath expressions such as ``people[?age > `20`].name | sort(@)``.
+//!
+//! Covers the grammar of the specification: sub-expressions, indexes,
+//! slices, list, object, flatten and filter projections, multi-selects,
+//! pipes, literals, comparisons, logical operators and the built-in
+//! functions, with `&expr` references for `sort_by`, `map` and friends.
+
+use std::{cmp::Ordering, mem};
+
+use crate::{
+    convert::kind_name, parse, parser::unescape_string, rope::compare_text, Map, QueryLimits, Value,
+};
+
+/// Compiled JMESPath expression, reusable across documents
+#[derive(Debug, Clone, PartialEq)]
+pub struct JmesPath {
+    ast: Ast,
+}
+
+/// Possible errors when compiling or evaluating a JMESPath expression
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JmesPathError {
+    /// The expression ended in the middle of a construct
+    UnexpectedEnd,
+
+    /// The character at this byte offset does not fit the grammar
+    UnexpectedChar(usize, char),
+
+    /// The literal starting at this byte offset is not valid JSON
+    InvalidLiteral(usize),
+
+    /// No built-in function has this name
+    UnknownFunction(String),
+
+    /// The function was called with the wrong number of arguments
+    InvalidArity(String),
+
+    /// An argument of the function is of the wrong type
+    InvalidType(String),
+
+    /// Sub-expressions are nested deeper than allowed
+    TooDeep { limit: usize },
+
+    /// The expression is longer than allowed
+    TooLong { length: usize, limit: usize },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Ast {
+    /// `@`, or the empty right-hand side of a projection
+    Identity,
+    Field(String),
+    Subexpression(Box<Ast>, Box<Ast>),
+    Index(i64),
+    Slice(Option<i64>, Option<i64>, Option<i64>),
+    /// Applies the right side to every element of the array on the left
+    Projection(Box<Ast>, Box<Ast>),
+    /// Applies the right side to every member value of the object on the left
+    ValueProjection(Box<Ast>, Box<Ast>),
+    FilterProjection {
+        left: Box<Ast>,
+        right: Box<Ast>,
+        condition: Box<Ast>,
+    },
+    Flatten(Box<Ast>),
+    Or(Box<Ast>, Box<Ast>),
+    And(Box<Ast>, Box<Ast>),
+    Not(Box<Ast>),
+    Compare(Comparator, Box<Ast>, Box<Ast>),
+    Literal(Value),
+    MultiSelectList(Vec<Ast>),
+    MultiSelectHash(Vec<(String, Ast)>),
+    Pipe(Box<Ast>, Box<Ast>),
+    Function(String, Vec<Ast>),
+    ExpressionRef(Box<Ast>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Comparator {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl JmesPath {
+    /// Compiles `expression`, checking function names and arities up front.
+    pub fn parse(expression: &str) -> Result<Self, JmesPathError> {
+        Self::parse_with_limits(expression, &QueryLimits::nesting_only())
+    }
+
+    /// Compiles an expression from untrusted input, checking its length up
+    /// front and its nesting while parsing.
+    pub fn parse_with_limits(
+        expression: &str,
+        limits: &QueryLimits,
+    ) -> Result<Self, JmesPathError> {
+        if expression.len() > limits.max_length {
+            return Err(JmesPathError::TooLong {
+                length: expression.len(),
+                limit: limits.max_length,
+            });
+        }
+
+        let tokens = lex(expression)?;
+        let mut parser = Parser {
+            input: expression,
+            tokens,
+            index: 0,
+            previous: 0,
+            depth: 0,
+            max_depth: limits.max_depth,
+        };
+        let ast = parser.expression(0)?;
+        match parser.peek() {
+            Token::End => Ok(Self { ast }),
+            _ => Err(parser.error()),
+        }
+    }
+
+    /// Evaluates the expression against `value`.
+    pub fn search(&self, value: &Value) -> Result<Value, JmesPathError> {
+        evaluate(&self.ast, value)
+    }
+}
+
+impl Value {
+    /// Compiles `expression` as JMESPath and evaluates it against the value.
+    pub fn search(&self, expression: &str) -> Result<Value, JmesPathError> {
+        JmesPath::parse(expression)?.search(self)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Dot,
+    Star,
+    At,
+    Comma,
+    Colon,
+    LeftParen,
+    RightParen,
+    LeftBrace,
+    RightBrace,
+    LeftBracket,
+    RightBracket,
+    /// `[]`
+    Flatten,
+    /// `[?`
+    Filter,
+    Pipe,
+    Or,
+    Ampersand,
+    And,
+    Not,
+    Compare(Comparator),
+    Identifier(String),
+    QuotedIdentifier(String),
+    Literal(Value),
+    Number(i64),
+    End,
+}
+
+impl Token {
+    /// How strongly the token binds to the expression on its left.
+    fn binding_power(&self) -> u8 {
+        match self {
+            Token::Pipe => 1,
+            Token::Or => 2,
+            Token::And => 3,
+            Token::Compare(_) => 5,
+            Token::Flatten => 9,
+            Token::Star => 20,
+            Token::Filter => 21,
+            Token::Dot => 40,
+            Token::Not => 45,
+            Token::LeftBrace => 50,
+            Token::LeftBracket => 55,
+            Token::LeftParen => 60,
+            _ => 0,
+        }
+    }
+}
+
+/// Right-hand sides of projections stop at tokens binding weaker than this.
+const PROJECTION_STOP: u8 = 10;
+
+fn lex(input: &str) -> Result<Vec<(usize, Token)>, JmesPathError> {
+    let mut tokens = Vec::new();
+    let mut chars = input.char_indices().peekable();
+
+    while let Some((start, c)) = chars.next() {
+        let mut next_is = |expected: char| chars.next_if(|&(_, c)| c == expected).is_some();
+        let token = match c {
+            ' ' | '\t' | '\n' | '\r' => continue,
+            '.' => Token::Dot,
+            '*' => Token::Star,
+            '@' => Token::At,
+            ',' => Token::Comma,
+            ':' => Token::Colon,
+            '(' => Token::LeftParen,
+            ')' => Token::RightParen,
+            '{' => Token::LeftBrace,
+            '}' => Token::RightBrace,
+            ']' => Token::RightBracket,
+            '[' if next_is(']') => Token::Flatten,
+            '[' if next_is('?') => Token::Filter,
+            '[' => Token::LeftBracket,
+            '|' if next_is('|') => Token::Or,
+            '|' => Token::Pipe,
+            '&' if next_is('&') => Token::And,
+            '&' => Token::Ampersand,
+            '!' if next_is('=') => Token::Compare(Comparator::Ne),
+            '!' => Token::Not,
+            '=' if next_is('=') => Token::Compare(Comparator::Eq),
+            '<' if next_is('=') => Token::Compare(Comparator::Le),
+            '<' => Token::Compare(Comparator::Lt),
+            '>' if next_is('=') => Token::Compare(Comparator::Ge),
+            '>' => Token::Compare(Comparator::Gt),
+            '"' => {
+                let raw = delimited(input, start, '"')?;
+                chars.nth(raw.chars().count());
+                let name =
+                    unescape_string(raw).map_err(|_| JmesPathError::UnexpectedChar(start, '"'))?;
+                Token::QuotedIdentifier(name)
+            }
+            '\'' => {
+                let raw = delimited(input, start, '\'')?;
+                chars.nth(raw.chars().count());
+                Token::Literal(Value::String(raw.replace("\\'", "'")))
+            }
+            '`' => {
+                let raw = delimited(input, start, '`')?;
+                chars.nth(raw.chars().count());
+                let value = parse(raw.replace("\\`", "`"))
+                    .map_err(|_| JmesPathError::InvalidLiteral(start))?;
+                Token::Literal(value)
+            }
+            c if c == '-' || c.is_ascii_digit() => {
+                let mut end = start + 1;
+                while let Some((index, _)) = chars.next_if(|(_, c)| c.is_ascii_digit()) {
+                    end = index + 1;
+                }
+                let number = input[start..end]
+                    .parse()
+                    .map_err(|_| JmesPathError::UnexpectedChar(start, c))?;
+                Token::Number(number)
+            }
+            c if c.is_ascii_alphabetic() || c == '_' => {
+                let mut end = start + 1;
+                while let Some((index, _)) =
+                    chars.next_if(|(_, c)| c.is_ascii_alphanumeric() || *c == '_')
+                {
+                    end = index + 1;
+                }
+                Token::Identifier(input[start..end].to_string())
+            }
+            c => return Err(JmesPathError::UnexpectedChar(start, c)),
+        };
+        tokens.push((start, token));
+    }
+
+    tokens.push((input.len(), Token::End));
+    Ok(tokens)
+}
+
+/// Text between the delimiter at `start` and the next one not preceded by a
+/// backslash, left escaped.
+fn delimited(input: &str, start: usize, delimiter: char) -> Result<&str, JmesPathError> {
+    let content = &input[start + 1..];
+    let mut escaped = false;
+    for (index, c) in content.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            c if c == delimiter => return Ok(&content[..index]),
+            _ => {}
+        }
+    }
+    Err(JmesPathError::UnexpectedEnd)
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    tokens: Vec<(usize, Token)>,
+    index: usize,
+    /// Byte offset of the last consumed token
+    previous: usize,
+    /// Number of expressions being parsed around the current one
+    depth: usize,
+    max_depth: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.index].1
+    }
+
+    fn advance(&mut self) -> Token {
+        let (position, token) = self.tokens[self.index].clone();
+        self.previous = position;
+        if token != Token::End {
+            self.index += 1;
+        }
+        token
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), JmesPathError> {
+        if *self.peek() == expected {
+            self.advance();
+            Ok(())
+        } else {
+            Err(self.error())
+        }
+    }
+
+    fn error(&self) -> JmesPathError {
+        self.error_at(self.tokens[self.index].0)
+    }
+
+    /// Error for the token that was just consumed.
+    fn error_before(&self) -> JmesPathError {
+        self.error_at(self.previous)
+    }
+
+    fn error_at(&self, position: usize) -> JmesPathError {
+        match self.input[position..].chars().next() {
+            Some(c) => JmesPathError::UnexpectedChar(position, c),
+            None => JmesPathError::UnexpectedEnd,
+        }
+    }
+
+    fn expression(&mut self, binding_power: u8) -> Result<Ast, JmesPathError> {
+        if self.depth == self.max_depth {
+            return Err(JmesPathError::TooDeep {
+                limit: self.max_depth,
+            });
+        }
+        self.depth += 1;
+        let expression = self.operators(binding_power);
+        self.depth -= 1;
+        expression
+    }
+
+    /// Parses operators binding tighter than `binding_power`, Pratt style.
+    fn operators(&mut self, binding_power: u8) -> Result<Ast, JmesPathError> {
+        let token = self.advance();
+        let mut left = self.prefix(token)?;
+        while binding_power < self.peek().binding_power() {
+            let token = self.advance();
+            left = self.infix(token, left)?;
+        }
+        Ok(left)
+    }
+
+    fn prefix(&mut self, token: Token) -> Result<Ast, JmesPathError> {
+        match token {
+            Token::Literal(value) => Ok(Ast::Literal(value)),
+            Token::Identifier(name) if *self.peek() == Token::LeftParen => self.function(name),
+            Token::Identifier(name) | Token::QuotedIdentifier(name) => Ok(Ast::Field(name)),
+            Token::Star => {
+                let right = self.projection_rhs(Token::Star.binding_power())?;
+                Ok(Ast::ValueProjection(
+                    Box::new(Ast::Identity),
+                    Box::new(right),
+                ))
+            }
+            Token::Filter => self.filter_projection(Ast::Identity),
+            Token::Flatten => {
+                let right = self.projection_rhs(Token::Flatten.binding_power())?;
+                let left = Ast::Flatten(Box::new(Ast::Identity));
+                Ok(Ast::Projection(Box::new(left), Box::new(right)))
+            }
+            Token::LeftBrace => self.multi_select_hash(),
+            Token::LeftBracket => match self.peek() {
+                Token::Number(_) | Token::Colon => self.index_or_slice(Ast::Identity),
+                Token::Star if self.tokens[self.index + 1].1 == Token::RightBracket => {
+                    self.advance();
+                    self.advance();
+                    let right = self.projection_rhs(Token::Star.binding_power())?;
+                    Ok(Ast::Projection(Box::new(Ast::Identity), Box::new(right)))
+                }
+                _ => self.multi_select_list(),
+            },
+            Token::Ampersand => Ok(Ast::ExpressionRef(Box::new(self.expression(0)?))),
+            Token::Not => Ok(Ast::Not(Box::new(
+                self.expression(Token::Not.binding_power())?,
+            ))),
+            Token::LeftParen => {
+                let inner = self.expression(0)?;
+                self.expect(Token::RightParen)?;
+                Ok(inner)
+            }
+            Token::At => Ok(Ast::Identity),
+            _ => Err(self.error_before()),
+        }
+    }
+
+    fn infix(&mut self, token: Token, left: Ast) -> Result<Ast, JmesPathError> {
+        let left = Box::new(left);
+        match token {
+            Token::Dot if *self.peek() == Token::Star => {
+                self.advance();
+                let right = self.projection_rhs(Token::Dot.binding_power())?;
+                Ok(Ast::ValueProjection(left, Box::new(right)))
+            }
+            Token::Dot => {
+                let right = self.dot_rhs(Token::Dot.binding_power())?;
+                Ok(Ast::Subexpression(left, Box::new(right)))
+            }
+            Token::Pipe => Ok(Ast::Pipe(left, Box::new(self.expression(1)?))),
+            Token::Or => Ok(Ast::Or(left, Box::new(self.expression(2)?))),
+            Token::And => Ok(Ast::And(left, Box::new(self.expression(3)?))),
+            Token::Compare(comparator) => {
+                let right = self.expression(Token::Compare(comparator).binding_power())?;
+                Ok(Ast::Compare(comparator, left, Box::new(right)))
+            }
+            Token::Flatten => {
+                let right = self.projection_rhs(Token::Flatten.binding_power())?;
+                Ok(Ast::Projection(
+                    Box::new(Ast::Flatten(left)),
+                    Box::new(right),
+                ))
+            }
+            Token::Filter => self.filter_projection(*left),
+            Token::LeftBracket => match self.peek() {
+                Token::Number(_) | Token::Colon => self.index_or_slice(*left),
+                _ => {
+                    self.expect(Token::Star)?;
+                    self.expect(Token::RightBracket)?;
+                    let right = self.projection_rhs(Token::Star.binding_power())?;
+                    Ok(Ast::Projection(left, Box::new(right)))
+                }
+            },
+            _ => Err(self.error_before()),
+        }
+    }
+
+    /// Parses the rest of `[n]` or `[start:end:step]` applied to `left`.
+    fn index_or_slice(&mut self, left: Ast) -> Result<Ast, JmesPathError> {
+        let mut parts = [None; 3];
+        let mut part = 0;
+        loop {
+            match self.advance() {
+                Token::RightBracket => break,
+                Token::Colon if part < 2 => part += 1,
+                Token::Number(n) if parts[part].is_none() => {
+                    if part == 2 && n == 0 {
+                        return Err(self.error_before());
+                    }
+                    parts[part] = Some(n);
+                }
+                _ => return Err(self.error_before()),
+            }
+        }
+
+        let [start, end, step] = parts;
+        if part == 0 {
+            let index = Ast::Index(start.ok_or_else(|| self.error_before())?);
+            return Ok(subexpression(left, index));
+        }
+        let slice = subexpression(left, Ast::Slice(start, end, step));
+        let right = self.projection_rhs(Token::Star.binding_power())?;
+        Ok(Ast::Projection(Box::new(slice), Box::new(right)))
+    }
+
+    fn filter_projection(&mut self, left: Ast) -> Result<Ast, JmesPathError> {
+        let condition = self.expression(0)?;
+        self.expect(Token::RightBracket)?;
+        let right = self.projection_rhs(Token::Filter.binding_power())?;
+        Ok(Ast::FilterProjection {
+            left: Box::new(left),
+            right: Box::new(right),
+            condition: Box::new(condition),
+        })
+    }
+
+    fn projection_rhs(&mut self, binding_power: u8) -> Result<Ast, JmesPathError> {
+        match self.peek() {
+            token if token.binding_power() < PROJECTION_STOP => Ok(Ast::Identity),
+            Token::LeftBracket | Token::Filter => self.expression(binding_power),
+            Token::Dot => {
+                self.advance();
+                self.dot_rhs(binding_power)
+            }
+            _ => Err(self.error()),
+        }
+    }
+
+    fn dot_rhs(&mut self, binding_power: u8) -> Result<Ast, JmesPathError> {
+        match self.peek() {
+            Token::Identifier(_) | Token::QuotedIdentifier(_) | Token::Star => {
+                self.expression(binding_power)
+            }
+            Token::LeftBracket => {
+                self.advance();
+                self.multi_select_list()
+            }
+            Token::LeftBrace => {
+                self.advance();
+                self.multi_select_hash()
+            }
+            _ => Err(self.error()),
+        }
+    }
+
+    fn multi_select_list(&mut self) -> Result<Ast, JmesPathError> {
+        let mut items = Vec::new();
+        loop {
+            items.push(self.expression(0)?);
+            match self.advance() {
+                Token::Comma => {}
+                Token::RightBracket => return Ok(Ast::MultiSelectList(items)),
+                _ => return Err(self.error_before()),
+            }
+        }
+    }
+
+    fn multi_select_hash(&mut self) -> Result<Ast, JmesPathError> {
+        let mut members = Vec::new();
+        loop {
+            let key = match self.advance() {
+                Token::Identifier(key) | Token::QuotedIdentifier(key) => key,
+                _ => return Err(self.error_before()),
+            };
+            self.expect(Token::Colon)?;
+            members.push((key, self.expression(0)?));
+            match self.advance() {
+                Token::Comma => {}
+                Token::RightBrace => return Ok(Ast::MultiSelectHash(members)),
+                _ => return Err(self.error_before()),
+            }
+        }
+    }
+
+    fn function(&mut self, name: String) -> Result<Ast, JmesPathError> {
+        self.expect(Token::LeftParen)?;
+        let mut args = Vec::new();
+        if *self.peek() == Token::RightParen {
+            self.advance();
+        } else {
+            loop {
+                args.push(self.expression(0)?);
+                match self.advance() {
+                    Token::Comma => {}
+                    Token::RightParen => break,
+                    _ => return Err(self.error_before()),
+                }
+            }
+        }
+
+        let (min, variadic) =
+            arity(&name).ok_or_else(|| JmesPathError::UnknownFunction(name.clone()))?;
+        if args.len() < min || (!variadic && args.len() > min) {
+            return Err(JmesPathError::InvalidArity(name));
+        }
+        Ok(Ast::Function(name, args))
+    }
+}
+
+fn subexpression(left: Ast, right: Ast) -> Ast {
+    match left {
+        Ast::Identity => right,
+        left => Ast::Subexpression(Box::new(left), Box::new(right)),
+    }
+}
+
+/// Number of arguments a built-in function takes, and whether it takes more.
+fn arity(name: &str) -> Option<(usize, bool)> {
+    let arity = match name {
+        "abs" | "avg" | "ceil" | "floor" | "keys" | "length" | "max" | "min" | "reverse"
+        | "sort" | "sum" | "to_array" | "to_string" | "to_number" | "type" | "values" => (1, false),
+        "contains" | "ends_with" | "join" | "map" | "max_by" | "min_by" | "sort_by"
+        | "starts_with" => (2, false),
+        "merge" | "not_null" => (1, true),
+        _ => return None,
+    };
+    Some(arity)
+}
+
+fn evaluate(ast: &Ast, value: &Value) -> Result<Value, JmesPathError> {
+    match ast {
+        Ast::Identity => Ok(value.clone()),
+        Ast::Field(name) => Ok(value.get(name).cloned().unwrap_or(Value::Null)),
+        Ast::Subexpression(left, right) => evaluate(right, &evaluate(left, value)?),
+        Ast::Index(index) => Ok(match value {
+            Value::Array(values) => normalize_index(*index, values.len())
+                .map(|index| values[index].clone())
+                .unwrap_or(Value::Null),
+            _ => Value::Null,
+        }),
+        Ast::Slice(start, end, step) => Ok(match value {
+            Value::Array(values) => Value::Array(
+                slice_indexes(*start, *end, *step, values.len())
+                    .into_iter()
+                    .map(|index| values[index].clone())
+                    .collect(),
+            ),
+            _ => Value::Null,
+        }),
+        Ast::Projection(left, right) => match &evaluate(left, value)? {
+            Value::Array(values) => project(values.iter(), right),
+            _ => Ok(Value::Null),
+        },
+        Ast::ValueProjection(left, right) => match &evaluate(left, value)? {
+            Value::Object(map) => project(map.values(), right),
+            _ => Ok(Value::Null),
+        },
+        Ast::FilterProjection {
+            left,
+            right,
+            condition,
+        } => match &evaluate(left, value)? {
+            Value::Array(values) => {
+                let mut kept = Vec::new();
+                for element in values.iter() {
+                    if is_truthy(&evaluate(condition, element)?) {
+                        kept.push(element);
+                    }
+                }
+                project(kept.into_iter(), right)
+            }
+            _ => Ok(Value::Null),
+        },
+        Ast::Flatten(inner) => Ok(match &evaluate(inner, value)? {
+            Value::Array(values) => {
+                let mut flat = Vec::new();
+                for element in values.iter() {
+                    match element {
+                        Value::Array(nested) => flat.extend(nested.iter().cloned()),
+                        element => flat.push(element.clone()),
+                    }
+                }
+                Value::Array(flat)
+            }
+            _ => Value::Null,
+        }),
+        Ast::Or(left, right) => {
+            let left = evaluate(left, value)?;
+            if is_truthy(&left) {
+                Ok(left)
+            } else {
+                evaluate(right, value)
+            }
+        }
+        Ast::And(left, right) => {
+            let left = evaluate(left, value)?;
+            if is_truthy(&left) {
+                evaluate(right, value)
+            } else {
+                Ok(left)
+            }
+        }
+        Ast::Not(inner) => Ok(Value::Boolean(!is_truthy(&evaluate(inner, value)?))),
+        Ast::Compare(comparator, left, right) => {
+            let left = evaluate(left, value)?;
+            let right = evaluate(right, value)?;
+            Ok(compare(*comparator, &left, &right))
+        }
+        Ast::Literal(literal) => Ok(literal.clone()),
+        Ast::MultiSelectList(items) => match value {
+            Value::Null => Ok(Value::Null),
+            _ => items
+                .iter()
+                .map(|item| evaluate(item, value))
+                .collect::<Result<_, _>>()
+                .map(Value::Array),
+        },
+        Ast::MultiSelectHash(members) => match value {
+            Value::Null => Ok(Value::Null),
+            _ => members
+                .iter()
+                .map(|(key, item)| Ok((key.clone(), evaluate(item, value)?)))
+                .collect::<Result<_, _>>()
+                .map(Value::Object),
+        },
+        Ast::Pipe(left, right) => evaluate(right, &evaluate(left, value)?),
+        Ast::Function(name, args) => call(name, args, value),
+        // Only meaningful as a function argument
+        Ast::ExpressionRef(_) => Ok(Value::Null),
+    }
+}
+
+/// Applies `right` to every element, dropping `null` results.
+fn project<'a>(
+    elements: impl Iterator<Item = &'a Value>,
+    right: &Ast,
+) -> Result<Value, JmesPathError> {
+    let mut results = Vec::new();
+    for element in elements {
+        let result = evaluate(right, element)?;
+        if !result.is_null() {
+            results.push(result);
+        }
+    }
+    Ok(Value::Array(results))
+}
+
+fn normalize_index(index: i64, len: usize) -> Option<usize> {
+    let len = i64::try_from(len).ok()?;
+    let index = if index < 0 { len + index } else { index };
+    (0..len).contains(&index).then_some(index as usize)
+}
+
+fn slice_indexes(
+    start: Option<i64>,
+    end: Option<i64>,
+    step: Option<i64>,
+    len: usize,
+) -> Vec<usize> {
+    let len = len as i64;
+    let step = step.unwrap_or(1);
+    let normalize = |index: i64| if index < 0 { len + index } else { index };
+    let mut indexes = Vec::new();
+
+    if step > 0 {
+        let lower = normalize(start.unwrap_or(0)).clamp(0, len);
+        let upper = normalize(end.unwrap_or(len)).clamp(0, len);
+        let mut index = lower;
+        while index < upper {
+            indexes.push(index as usize);
+            index = index.saturating_add(step);
+        }
+    } else {
+        let upper = normalize(start.unwrap_or(len - 1)).clamp(-1, len - 1);
+        let lower = normalize(end.unwrap_or(-len - 1)).clamp(-1, len - 1);
+        let mut index = upper;
+        while lower < index {
+            indexes.push(index as usize);
+            index = index.saturating_add(step);
+        }
+    }
+
+    indexes
+}
+
+/// Empty strings, arrays and objects, `false` and `null` are false.
+fn is_truthy(value: &Value) -> bool {
+    match value {
+        Value::Null | Value::Boolean(false) => false,
+        Value::String(s) => !s.is_empty(),
+        Value::Rope(rope) => !rope.is_empty(),
+        Value::Array(values) => !values.is_empty(),
+        Value::Object(map) => !map.is_empty(),
+        _ => true,
+    }
+}
+
+/// Any values can be tested for equality, only numbers are ordered.
+fn compare(comparator: Comparator, left: &Value, right: &Value) -> Value {
+    let ordering = match (left, right) {
+        (Value::Number(a), Value::Number(b)) => a.partial_cmp(b),
+        _ => None,
+    };
+
+    match (comparator, ordering) {
+        (Comparator::Eq, _) => Value::Boolean(left == right),
+        (Comparator::Ne, _) => Value::Boolean(left != right),
+        (_, None) => Value::Null,
+        (Comparator::Lt, Some(ordering)) => Value::Boolean(ordering.is_lt()),
+        (Comparator::Le, Some(ordering)) => Value::Boolean(ordering.is_le()),
+        (Comparator::Gt, Some(ordering)) => Value::Boolean(ordering.is_gt()),
+        (Comparator::Ge, Some(ordering)) => Value::Boolean(ordering.is_ge()),
+    }
+}
+
+fn call(name: &str, args: &[Ast], current: &Value) -> Result<Value, JmesPathError> {
+    let invalid = || JmesPathError::InvalidType(name.to_string());
+    let arg = |index: usize| match &args[index] {
+        Ast::ExpressionRef(_) => Err(invalid()),
+        arg => evaluate(arg, current),
+    };
+    let expression = |index: usize| match &args[index] {
+        Ast::ExpressionRef(inner) => Ok(&**inner),
+        _ => Err(invalid()),
+    };
+    let number = |value: Value| match value {
+        Value::Number(n) => Ok(n),
+        _ => Err(invalid()),
+    };
+    let string = |value: Value| value.into_string().ok_or_else(invalid);
+    let array = |mut value: Value| match &mut value {
+        Value::Array(values) => Ok(mem::take(values)),
+        _ => Err(invalid()),
+    };
+    let object = |mut value: Value| match &mut value {
+        Value::Object(map) => Ok(mem::take(map)),
+        _ => Err(invalid()),
+    };
+
+    let result = match name {
+        "abs" => Value::Number(number(arg(0)?)?.abs()),
+        "ceil" => Value::Number(number(arg(0)?)?.ceil()),
+        "floor" => Value::Number(number(arg(0)?)?.floor()),
+        "sum" | "avg" => {
+            let values = array(arg(0)?)?;
+            let count = values.len();
+            let sum = values.into_iter().map(number).sum::<Result<f64, _>>()?;
+            match (name, count) {
+                ("sum", _) => Value::Number(sum),
+                (_, 0) => Value::Null,
+                _ => Value::Number(sum / count as f64),
+            }
+        }
+        "contains" => {
+            let search = arg(1)?;
+            match &arg(0)? {
+                Value::Array(values) => Value::Boolean(values.contains(&search)),
+                value => match value.as_text() {
+                    Some(s) => Value::Boolean(s.contains(string(search)?.as_str())),
+                    None => return Err(invalid()),
+                },
+            }
+        }
+        "starts_with" => Value::Boolean(string(arg(0)?)?.starts_with(&string(arg(1)?)?)),
+        "ends_with" => Value::Boolean(string(arg(0)?)?.ends_with(&string(arg(1)?)?)),
+        "join" => {
+            let glue = string(arg(0)?)?;
+            let parts = array(arg(1)?)?
+                .into_iter()
+                .map(string)
+                .collect::<Result<Vec<_>, _>>()?;
+            Value::String(parts.join(&glue))
+        }
+        "keys" | "values" => {
+            let mut members: Vec<_> = object(arg(0)?)?.into_iter().collect();
+            members.sort_by(|(a, _), (b, _)| a.cmp(b));
+            members
+                .into_iter()
+                .map(|(key, value)| {
+                    if name == "keys" {
+                        Value::String(key)
+                    } else {
+                        value
+                    }
+                })
+                .collect()
+        }
+        "length" => match &arg(0)? {
+            Value::String(s) => Value::from(s.chars().count()),
+            Value::Rope(rope) => Value::from(rope.chars().count()),
+            Value::Array(values) => Value::from(values.len()),
+            Value::Object(map) => Value::from(map.len()),
+            _ => return Err(invalid()),
+        },
+        "map" => {
+            let inner = expression(0)?;
+            array(arg(1)?)?
+                .iter()
+                .map(|element| evaluate(inner, element))
+                .collect::<Result<_, _>>()?
+        }
+        "max" | "min" => {
+            let values = array(arg(0)?)?;
+            let keyed: Vec<_> = values.iter().map(|value| (value.clone(), value)).collect();
+            extreme(name == "max", keyed, invalid)?
+                .cloned()
+                .unwrap_or(Value::Null)
+        }
+        "max_by" | "min_by" => {
+            let values = array(arg(0)?)?;
+            let inner = expression(1)?;
+            let keyed = values
+                .iter()
+                .map(|value| Ok((evaluate(inner, value)?, value)))
+                .collect::<Result<Vec<_>, _>>()?;
+            extreme(name == "max_by", keyed, invalid)?
+                .cloned()
+                .unwrap_or(Value::Null)
+        }
+        "sort" => {
+            let values = array(arg(0)?)?;
+            let keyed: Vec<_> = values.iter().map(|value| (value.clone(), value)).collect();
+            Value::Array(sorted(keyed, invalid)?)
+        }
+        "sort_by" => {
+            let values = array(arg(0)?)?;
+            let inner = expression(1)?;
+            let keyed = values
+                .iter()
+                .map(|value| Ok((evaluate(inner, value)?, value)))
+                .collect::<Result<Vec<_>, _>>()?;
+            Value::Array(sorted(keyed, invalid)?)
+        }
+        "merge" => {
+            let mut merged = Map::new();
+            for index in 0..args.len() {
+                for (key, value) in object(arg(index)?)? {
+                    merged.insert(key, value);
+                }
+            }
+            Value::Object(merged)
+        }
+        "not_null" => {
+            for index in 0..args.len() {
+                let value = arg(index)?;
+                if !value.is_null() {
+                    return Ok(value);
+                }
+            }
+            Value::Null
+        }
+        "reverse" => match &arg(0)? {
+            Value::Array(values) => values.iter().rev().cloned().collect(),
+            value => match value.as_text() {
+                Some(s) => Value::String(s.chars().rev().collect()),
+                None => return Err(invalid()),
+            },
+        },
+        "to_array" => match arg(0)? {
+            array @ Value::Array(_) => array,
+            value => Value::Array(vec![value]),
+        },
+        "to_string" => match arg(0)? {
+            string @ (Value::String(_) | Value::Rope(_)) => string,
+            value => Value::String(value.to_string()),
+        },
+        "to_number" => match arg(0)? {
+            number @ Value::Number(_) => number,
+            value => match value.into_string().map(parse) {
+                Some(Ok(number @ Value::Number(_))) => number,
+                _ => Value::Null,
+            },
+        },
+        "type" => Value::from(kind_name(arg(0)?.kind())),
+        _ => return Err(JmesPathError::UnknownFunction(name.to_string())),
+    };
+    Ok(result)
+}
+
+/// Orders values by their keys, which must be all numbers or all strings.
+fn sorted(
+    mut keyed: Vec<(Value, &Value)>,
+    invalid: impl Fn() -> JmesPathError,
+) -> Result<Vec<Value>, JmesPathError> {
+    check_sort_keys(&keyed, &invalid)?;
+    keyed.sort_by(|(a, _), (b, _)| order(a, b));
+    Ok(keyed.into_iter().map(|(_, value)| value.clone()).collect())
+}
+
+/// Value with the largest or smallest key, `None` for no values.
+fn extreme(
+    largest: bool,
+    keyed: Vec<(Value, &Value)>,
+    invalid: impl Fn() -> JmesPathError,
+) -> Result<Option<&Value>, JmesPathError> {
+    check_sort_keys(&keyed, &invalid)?;
+    let pick = |(a_key, a), (b_key, b)| match (order(&a_key, &b_key), largest) {
+        (Ordering::Less, true) | (Ordering::Greater, false) => (b_key, b),
+        _ => (a_key, a),
+    };
+    Ok(keyed.into_iter().reduce(pick).map(|(_, value)| value))
+}
+
+fn check_sort_keys(
+    keyed: &[(Value, &Value)],
+    invalid: &impl Fn() -> JmesPathError,
+) -> Result<(), JmesPathError> {
+    let all_numbers = keyed.iter().all(|(key, _)| key.is_number());
+    let all_strings = keyed.iter().all(|(key, _)| key.is_string());
+    if all_numbers || all_strings {
+        Ok(())
+    } else {
+        Err(invalid())
+    }
+}
+
+fn order(a: &Value, b: &Value) -> Ordering {
+    match (a, b) {
+        (Value::Number(a), Value::Number(b)) => a.total_cmp(b),
+        (a, b) => compare_text(a, b).unwrap_or(Ordering::Equal),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse, QueryLimits, Value};
+
+    use super::{JmesPath, JmesPathError};
+
+    fn people() -> Value {
+        parse(String::from(
+            r#"{
+                "people": [
+                    {"name": "a", "age": 30, "tags": ["x", "y"]},
+                    {"name": "b", "age": 20, "tags": ["z"]},
+                    {"name": "c", "age": 40, "tags": []}
+                ],
+                "owner": {"name": "o", "age": null}
+            }"#,
+        ))
+        .unwrap()
+    }
+
+    fn search(expression: &str) -> String {
+        people().search(expression).unwrap().to_string()
+    }
+
+    #[test]
+    fn selects_fields_indexes_and_slices() {
+        assert_eq!(search("owner.name"), r#""o""#);
+        assert_eq!(search("people[-1].name"), r#""c""#);
+        assert_eq!(search("people[1:].name"), r#"["b","c"]"#);
+        assert_eq!(search("people[::-1].age"), "[40,20,30]");
+        assert_eq!(search("missing.name"), "null");
+        assert_eq!(search(r#""owner"."name""#), r#""o""#);
+    }
+
+    #[test]
+    fn projects_and_filters() {
+        assert_eq!(search("people[*].name"), r#"["a","b","c"]"#);
+        assert_eq!(search("people[?age > `25`].name"), r#"["a","c"]"#);
+        assert_eq!(
+            search("people[?age > `25` && !contains(tags, 'y')].name"),
+            r#"["c"]"#
+        );
+        assert_eq!(search("people[].tags[]"), r#"["x","y","z"]"#);
+        assert_eq!(search("people[*].tags[0]"), r#"["x","z"]"#);
+        assert_eq!(search("*.name"), r#"["o"]"#);
+    }
+
+    #[test]
+    fn pipes_stop_projections() {
+        assert_eq!(search("people[*].name | [0]"), r#""a""#);
+        assert_eq!(search("people[*].name[0]"), "[]");
+    }
+
+    #[test]
+    fn builds_multi_selects() {
+        assert_eq!(search("people[0].[name, age]"), r#"["a",30]"#);
+        assert_eq!(
+            people().search("owner.{n: name, age: age || `1`}").unwrap(),
+            Value::object([("n", Value::string("o")), ("age", Value::from(1))])
+        );
+        assert_eq!(search("missing.[a, b]"), "null");
+    }
+
+    #[test]
+    fn calls_functions() {
+        assert_eq!(search("length(people)"), "3");
+        assert_eq!(search("sort_by(people, &age)[*].name"), r#"["b","a","c"]"#);
+        assert_eq!(search("max_by(people, &age).name"), r#""c""#);
+        assert_eq!(search("min_by(people, &age).name"), r#""b""#);
+        assert_eq!(
+            search("sort(people[*].name) | reverse(@)"),
+            r#"["c","b","a"]"#
+        );
+        assert_eq!(search("sum(people[*].age)"), "90");
+        assert_eq!(search("avg(people[*].age)"), "30");
+        assert_eq!(search("map(&length(tags), people)"), "[2,1,0]");
+        assert_eq!(search("join(', ', people[*].name)"), r#""a, b, c""#);
+        assert_eq!(search("keys(owner)"), r#"["age","name"]"#);
+        assert_eq!(search("not_null(owner.age, owner.name)"), r#""o""#);
+        assert_eq!(search("to_number('12')"), "12");
+        assert_eq!(search("type(people[0].tags)"), r#""array""#);
+        assert_eq!(search("merge(owner, `{\"age\": 1}`).age"), "1");
+    }
+
+    #[test]
+    fn reports_errors() {
+        let cases = [
+            ("people[", JmesPathError::UnexpectedEnd),
+            ("people.[", JmesPathError::UnexpectedEnd),
+            ("a ! b", JmesPathError::UnexpectedChar(2, '!')),
+            ("people[::0]", JmesPathError::UnexpectedChar(9, '0')),
+            ("`{bad`", JmesPathError::InvalidLiteral(0)),
+            (
+                "nope(@)",
+                JmesPathError::UnknownFunction(String::from("nope")),
+            ),
+            (
+                "length(@, @)",
+                JmesPathError::InvalidArity(String::from("length")),
+            ),
+        ];
+
+        for (input, expected) in cases {
+            assert_eq!(JmesPath::parse(input), Err(expected), "{input}");
+        }
+        assert_eq!(
+            people().search("abs(people)"),
+            Err(JmesPathError::InvalidType(String::from("abs")))
+        );
+        assert_eq!(
+            people().search("sort_by(people, &tags)"),
+            Err(JmesPathError::InvalidType(String::from("sort_by")))
+        );
+    }
+
+    #[test]
+    fn rejects_queries_beyond_limits() {
+        let nested = format!("{}a{}", "(".repeat(5000), ")".repeat(5000));
+        assert_eq!(
+            JmesPath::parse(&nested),
+            Err(JmesPathError::TooDeep { limit: 128 })
+        );
+
+        let limits = QueryLimits {
+            max_depth: 2,
+            max_length: 8,
+        };
+        assert_eq!(
+            JmesPath::parse_with_limits("people[0].name", &limits),
+            Err(JmesPathError::TooLong {
+                length: 14,
+                limit: 8
+            })
+        );
+        assert_eq!(
+            JmesPath::parse_with_limits("((a))", &limits),
+            Err(JmesPathError::TooDeep { limit: 2 })
+        );
+    }
+}
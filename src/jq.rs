@@ -0,0 +1,1410 @@
+//! jq-style filters such as `.items | map(select(.price > 10) | "\(.name): \(.price)")`.
+//!
+//! Covers a subset of the jq language: paths, iteration, slices, `?`, pipes
+//! and commas, array and object construction, string interpolation,
+//! arithmetic, comparisons, `and`, `or`, `//`, `if` and common builtins.
+//! A filter produces a stream of values, collected into a `Vec`.
+
+use std::{borrow::Cow, cmp::Ordering};
+
+use crate::{
+    convert::kind_name, number::parse_number, parser::unescape_string, rope::compare_text, Map,
+    QueryLimits, Value,
+};
+
+/// Runs the jq filter `expression` with `value` as input, returning every output.
+pub fn eval(expression: &str, value: &Value) -> Result<Vec<Value>, JqError> {
+    JqFilter::parse(expression)?.run(value)
+}
+
+/// Compiled jq filter, reusable across inputs
+#[derive(Debug, Clone, PartialEq)]
+pub struct JqFilter {
+    ast: Ast,
+}
+
+/// Possible errors when compiling or running a jq filter
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JqError {
+    /// The filter ended in the middle of a construct
+    UnexpectedEnd,
+
+    /// The character at this byte offset does not fit the grammar
+    UnexpectedChar(usize, char),
+
+    /// No builtin has this name and number of arguments, given as `name/arity`
+    UnknownFunction(String),
+
+    /// Running the filter failed, such as when indexing a number with a key
+    Runtime(String),
+
+    /// Parentheses, pipes and other sub-filters are nested deeper than allowed
+    TooDeep { limit: usize },
+
+    /// The filter is longer than allowed
+    TooLong { length: usize, limit: usize },
+}
+
+impl JqFilter {
+    pub fn parse(expression: &str) -> Result<Self, JqError> {
+        Self::parse_with_limits(expression, &QueryLimits::nesting_only())
+    }
+
+    /// Parses a filter from untrusted input, checking its length up front
+    /// and its nesting while parsing.
+    pub fn parse_with_limits(expression: &str, limits: &QueryLimits) -> Result<Self, JqError> {
+        if expression.len() > limits.max_length {
+            return Err(JqError::TooLong {
+                length: expression.len(),
+                limit: limits.max_length,
+            });
+        }
+
+        let mut parser = Parser {
+            input: expression,
+            position: 0,
+            depth: 0,
+            max_depth: limits.max_depth,
+        };
+        let ast = parser.pipe()?;
+        parser.skip_whitespace();
+        match parser.peek() {
+            None => Ok(Self { ast }),
+            Some(_) => Err(parser.error()),
+        }
+    }
+
+    /// Runs the filter with `value` as input, returning every output.
+    pub fn run(&self, value: &Value) -> Result<Vec<Value>, JqError> {
+        run(&self.ast, value)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Ast {
+    /// `.`
+    Identity,
+    /// `..`, the input and every value below it
+    Recurse,
+    Field(Box<Ast>, String),
+    /// `target[index]`, the index being evaluated against the input
+    Index(Box<Ast>, Box<Ast>),
+    Slice(Box<Ast>, Option<Box<Ast>>, Option<Box<Ast>>),
+    /// `target[]`
+    Iterate(Box<Ast>),
+    /// `target?`, dropping errors
+    Try(Box<Ast>),
+    Literal(Value),
+    Interpolation(Vec<Part>),
+    Array(Option<Box<Ast>>),
+    Object(Vec<(Ast, Ast)>),
+    Pipe(Box<Ast>, Box<Ast>),
+    Comma(Box<Ast>, Box<Ast>),
+    Negate(Box<Ast>),
+    Binary(Operator, Box<Ast>, Box<Ast>),
+    And(Box<Ast>, Box<Ast>),
+    Or(Box<Ast>, Box<Ast>),
+    /// `left // right`
+    Alternative(Box<Ast>, Box<Ast>),
+    If {
+        branches: Vec<(Ast, Ast)>,
+        otherwise: Box<Ast>,
+    },
+    Call(String, Vec<Ast>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Part {
+    Text(String),
+    Filter(Ast),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Remainder,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+/// Words that cannot start a term
+const KEYWORDS: [&str; 6] = ["then", "elif", "else", "end", "and", "or"];
+
+struct Parser<'a> {
+    input: &'a str,
+    /// Byte offset of the next char
+    position: usize,
+    /// Number of sub-filters being parsed around the current one
+    depth: usize,
+    max_depth: usize,
+}
+
+impl Parser<'_> {
+    fn rest(&self) -> &str {
+        &self.input[self.position..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.position += c.len_utf8();
+        Some(c)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(|c| c.is_whitespace()) {
+            self.bump();
+        }
+    }
+
+    fn error(&self) -> JqError {
+        match self.peek() {
+            Some(c) => JqError::UnexpectedChar(self.position, c),
+            None => JqError::UnexpectedEnd,
+        }
+    }
+
+    /// Consumes `token` if the input continues with it, after whitespace.
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_whitespace();
+        if self.rest().starts_with(token) {
+            self.position += token.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Consumes `token` unless it is followed by one of `unless`.
+    fn eat_unless(&mut self, token: &str, unless: &[char]) -> bool {
+        self.skip_whitespace();
+        let next = self
+            .rest()
+            .strip_prefix(token)
+            .and_then(|r| r.chars().next());
+        if next.is_some_and(|c| unless.contains(&c)) {
+            return false;
+        }
+        self.eat(token)
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        self.skip_whitespace();
+        let rest = self.rest();
+        let matches = rest.starts_with(keyword)
+            && !rest[keyword.len()..]
+                .chars()
+                .next()
+                .is_some_and(is_identifier_char);
+        if matches {
+            self.position += keyword.len();
+        }
+        matches
+    }
+
+    fn expect(&mut self, token: &str) -> Result<(), JqError> {
+        if self.eat(token) {
+            Ok(())
+        } else {
+            Err(self.error())
+        }
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<(), JqError> {
+        if self.eat_keyword(keyword) {
+            Ok(())
+        } else {
+            Err(self.error())
+        }
+    }
+
+    /// Runs `parse` one level of nesting deeper, failing beyond the limit.
+    fn nested(
+        &mut self,
+        parse: impl FnOnce(&mut Self) -> Result<Ast, JqError>,
+    ) -> Result<Ast, JqError> {
+        if self.depth == self.max_depth {
+            return Err(JqError::TooDeep {
+                limit: self.max_depth,
+            });
+        }
+        self.depth += 1;
+        let ast = parse(self);
+        self.depth -= 1;
+        ast
+    }
+
+    fn pipe(&mut self) -> Result<Ast, JqError> {
+        self.nested(|parser| {
+            let left = parser.comma()?;
+            if parser.eat("|") {
+                Ok(Ast::Pipe(Box::new(left), Box::new(parser.pipe()?)))
+            } else {
+                Ok(left)
+            }
+        })
+    }
+
+    fn comma(&mut self) -> Result<Ast, JqError> {
+        let mut left = self.alternative()?;
+        while self.eat(",") {
+            left = Ast::Comma(Box::new(left), Box::new(self.alternative()?));
+        }
+        Ok(left)
+    }
+
+    fn alternative(&mut self) -> Result<Ast, JqError> {
+        self.nested(|parser| {
+            let left = parser.or()?;
+            if parser.eat("//") {
+                Ok(Ast::Alternative(
+                    Box::new(left),
+                    Box::new(parser.alternative()?),
+                ))
+            } else {
+                Ok(left)
+            }
+        })
+    }
+
+    fn or(&mut self) -> Result<Ast, JqError> {
+        let mut left = self.and()?;
+        while self.eat_keyword("or") {
+            left = Ast::Or(Box::new(left), Box::new(self.and()?));
+        }
+        Ok(left)
+    }
+
+    fn and(&mut self) -> Result<Ast, JqError> {
+        let mut left = self.comparison()?;
+        while self.eat_keyword("and") {
+            left = Ast::And(Box::new(left), Box::new(self.comparison()?));
+        }
+        Ok(left)
+    }
+
+    fn comparison(&mut self) -> Result<Ast, JqError> {
+        let left = self.additive()?;
+        let operators = [
+            ("==", Operator::Eq),
+            ("!=", Operator::Ne),
+            ("<=", Operator::Le),
+            (">=", Operator::Ge),
+            ("<", Operator::Lt),
+            (">", Operator::Gt),
+        ];
+        for (token, operator) in operators {
+            if self.eat(token) {
+                let right = self.additive()?;
+                return Ok(Ast::Binary(operator, Box::new(left), Box::new(right)));
+            }
+        }
+        Ok(left)
+    }
+
+    fn additive(&mut self) -> Result<Ast, JqError> {
+        let mut left = self.multiplicative()?;
+        loop {
+            let operator = if self.eat("+") {
+                Operator::Add
+            } else if self.eat("-") {
+                Operator::Subtract
+            } else {
+                return Ok(left);
+            };
+            let right = self.multiplicative()?;
+            left = Ast::Binary(operator, Box::new(left), Box::new(right));
+        }
+    }
+
+    fn multiplicative(&mut self) -> Result<Ast, JqError> {
+        let mut left = self.unary()?;
+        loop {
+            let operator = if self.eat("*") {
+                Operator::Multiply
+            } else if self.eat_unless("/", &['/']) {
+                Operator::Divide
+            } else if self.eat("%") {
+                Operator::Remainder
+            } else {
+                return Ok(left);
+            };
+            let right = self.unary()?;
+            left = Ast::Binary(operator, Box::new(left), Box::new(right));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Ast, JqError> {
+        if self.eat("-") {
+            Ok(Ast::Negate(Box::new(self.postfix()?)))
+        } else {
+            self.postfix()
+        }
+    }
+
+    fn postfix(&mut self) -> Result<Ast, JqError> {
+        let mut target = self.term()?;
+        loop {
+            self.skip_whitespace();
+            target = if self.eat("?") {
+                Ast::Try(Box::new(target))
+            } else if self.eat(".[") || self.eat("[") {
+                self.bracket_suffix(target)?
+            } else if self.rest().starts_with('.') && self.starts_field_name(1) {
+                self.bump();
+                Ast::Field(Box::new(target), self.field_name()?)
+            } else {
+                return Ok(target);
+            };
+        }
+    }
+
+    /// Whether a field name starts `offset` bytes ahead.
+    fn starts_field_name(&self, offset: usize) -> bool {
+        self.rest()[offset..]
+            .chars()
+            .next()
+            .is_some_and(|c| c == '"' || c.is_ascii_alphabetic() || c == '_')
+    }
+
+    fn field_name(&mut self) -> Result<String, JqError> {
+        if self.peek() == Some('"') {
+            match self.string()? {
+                Ast::Literal(Value::String(ref name)) => Ok(name.clone()),
+                _ => Err(self.error()),
+            }
+        } else {
+            Ok(self.identifier())
+        }
+    }
+
+    fn identifier(&mut self) -> String {
+        let start = self.position;
+        while self.peek().is_some_and(is_identifier_char) {
+            self.bump();
+        }
+        self.input[start..self.position].to_string()
+    }
+
+    /// Parses the rest of `[]`, `[index]` or `[from:to]` after the `[`.
+    fn bracket_suffix(&mut self, target: Ast) -> Result<Ast, JqError> {
+        let target = Box::new(target);
+        if self.eat("]") {
+            return Ok(Ast::Iterate(target));
+        }
+
+        let from = if self.eat(":") {
+            None
+        } else {
+            let index = self.pipe()?;
+            if self.eat("]") {
+                return Ok(Ast::Index(target, Box::new(index)));
+            }
+            self.expect(":")?;
+            Some(Box::new(index))
+        };
+        let to = if self.eat("]") {
+            None
+        } else {
+            let to = self.pipe()?;
+            self.expect("]")?;
+            Some(Box::new(to))
+        };
+        if from.is_none() && to.is_none() {
+            return Err(JqError::UnexpectedChar(self.position - 1, ']'));
+        }
+        Ok(Ast::Slice(target, from, to))
+    }
+
+    fn term(&mut self) -> Result<Ast, JqError> {
+        self.skip_whitespace();
+        let start = self.position;
+        match self.peek() {
+            Some('.') if self.rest().starts_with("..") => {
+                self.position += 2;
+                Ok(Ast::Recurse)
+            }
+            Some('.') => {
+                self.bump();
+                if self.starts_field_name(0) {
+                    Ok(Ast::Field(Box::new(Ast::Identity), self.field_name()?))
+                } else {
+                    Ok(Ast::Identity)
+                }
+            }
+            Some(c) if c.is_ascii_digit() => self.number(),
+            Some('"') => self.string(),
+            Some('(') => {
+                self.bump();
+                let inner = self.pipe()?;
+                self.expect(")")?;
+                Ok(inner)
+            }
+            Some('[') => {
+                self.bump();
+                if self.eat("]") {
+                    return Ok(Ast::Array(None));
+                }
+                let inner = self.pipe()?;
+                self.expect("]")?;
+                Ok(Ast::Array(Some(Box::new(inner))))
+            }
+            Some('{') => {
+                self.bump();
+                self.object()
+            }
+            Some(c) if c.is_ascii_alphabetic() || c == '_' => {
+                let name = self.identifier();
+                match name.as_str() {
+                    "if" => self.conditional(),
+                    "true" => Ok(Ast::Literal(Value::Boolean(true))),
+                    "false" => Ok(Ast::Literal(Value::Boolean(false))),
+                    "null" => Ok(Ast::Literal(Value::Null)),
+                    keyword if KEYWORDS.contains(&keyword) => {
+                        self.position = start;
+                        Err(self.error())
+                    }
+                    _ => self.call(name),
+                }
+            }
+            _ => Err(self.error()),
+        }
+    }
+
+    fn number(&mut self) -> Result<Ast, JqError> {
+        let start = self.position;
+        while self.peek().is_some_and(|c| c.is_ascii_digit() || c == '.') {
+            self.bump();
+        }
+        if self.peek().is_some_and(|c| c == 'e' || c == 'E') {
+            self.bump();
+            if self.peek().is_some_and(|c| c == '+' || c == '-') {
+                self.bump();
+            }
+            while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+                self.bump();
+            }
+        }
+        parse_number(&self.input[start..self.position])
+            .map(|n| Ast::Literal(Value::Number(n)))
+            .map_err(|_| {
+                JqError::UnexpectedChar(start, self.input[start..].chars().next().unwrap())
+            })
+    }
+
+    /// Parses a string literal, with `\(filter)` interpolations.
+    fn string(&mut self) -> Result<Ast, JqError> {
+        let start = self.position;
+        self.bump();
+        let mut parts = Vec::new();
+        let mut raw = String::new();
+        let text = |raw: &mut String| {
+            unescape_string(&std::mem::take(raw)).map_err(|_| JqError::UnexpectedChar(start, '"'))
+        };
+
+        loop {
+            match self.bump() {
+                None => return Err(JqError::UnexpectedEnd),
+                Some('"') => break,
+                Some('\\') if self.peek() == Some('(') => {
+                    self.bump();
+                    parts.push(Part::Text(text(&mut raw)?));
+                    parts.push(Part::Filter(self.pipe()?));
+                    self.expect(")")?;
+                }
+                Some('\\') => {
+                    raw.push('\\');
+                    raw.extend(self.bump());
+                }
+                Some(c) => raw.push(c),
+            }
+        }
+        parts.push(Part::Text(text(&mut raw)?));
+
+        match parts.as_slice() {
+            [Part::Text(text)] => Ok(Ast::Literal(Value::String(text.clone()))),
+            _ => Ok(Ast::Interpolation(parts)),
+        }
+    }
+
+    /// Parses object construction after the `{`.
+    fn object(&mut self) -> Result<Ast, JqError> {
+        let mut entries = Vec::new();
+        if self.eat("}") {
+            return Ok(Ast::Object(entries));
+        }
+
+        loop {
+            self.skip_whitespace();
+            let (key, shorthand) = match self.peek() {
+                Some(c) if c.is_ascii_alphabetic() || c == '_' => {
+                    let name = self.identifier();
+                    let value = Ast::Field(Box::new(Ast::Identity), name.clone());
+                    (Ast::Literal(Value::String(name)), Some(value))
+                }
+                Some('"') => (self.string()?, None),
+                Some('(') => {
+                    self.bump();
+                    let key = self.pipe()?;
+                    self.expect(")")?;
+                    (key, None)
+                }
+                _ => return Err(self.error()),
+            };
+
+            let value = match shorthand {
+                Some(value) if !self.eat(":") => value,
+                _ => {
+                    if shorthand.is_none() {
+                        self.expect(":")?;
+                    }
+                    let mut value = self.alternative()?;
+                    while self.eat("|") {
+                        value = Ast::Pipe(Box::new(value), Box::new(self.alternative()?));
+                    }
+                    value
+                }
+            };
+            entries.push((key, value));
+
+            if self.eat("}") {
+                return Ok(Ast::Object(entries));
+            }
+            self.expect(",")?;
+        }
+    }
+
+    /// Parses the rest of `if c then a elif d then b else e end` after `if`.
+    fn conditional(&mut self) -> Result<Ast, JqError> {
+        let mut branches = Vec::new();
+        loop {
+            let condition = self.pipe()?;
+            self.expect_keyword("then")?;
+            branches.push((condition, self.pipe()?));
+            if !self.eat_keyword("elif") {
+                break;
+            }
+        }
+
+        let otherwise = if self.eat_keyword("else") {
+            self.pipe()?
+        } else {
+            Ast::Identity
+        };
+        self.expect_keyword("end")?;
+        Ok(Ast::If {
+            branches,
+            otherwise: Box::new(otherwise),
+        })
+    }
+
+    fn call(&mut self, name: String) -> Result<Ast, JqError> {
+        let mut args = Vec::new();
+        if self.eat("(") {
+            loop {
+                args.push(self.pipe()?);
+                if self.eat(")") {
+                    break;
+                }
+                self.expect(";")?;
+            }
+        }
+
+        if !is_builtin(&name, args.len()) {
+            return Err(JqError::UnknownFunction(format!("{name}/{}", args.len())));
+        }
+        Ok(Ast::Call(name, args))
+    }
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+fn is_builtin(name: &str, arity: usize) -> bool {
+    match arity {
+        0 => matches!(
+            name,
+            "empty"
+                | "not"
+                | "length"
+                | "keys"
+                | "type"
+                | "add"
+                | "tostring"
+                | "tonumber"
+                | "sort"
+                | "reverse"
+                | "unique"
+                | "min"
+                | "max"
+                | "floor"
+                | "sqrt"
+                | "to_entries"
+                | "from_entries"
+                | "ascii_downcase"
+                | "ascii_upcase"
+        ),
+        1 => matches!(
+            name,
+            "map" | "select" | "has" | "sort_by" | "join" | "split" | "startswith" | "endswith"
+        ),
+        _ => false,
+    }
+}
+
+fn runtime<T>(message: String) -> Result<T, JqError> {
+    Err(JqError::Runtime(message))
+}
+
+fn kind(value: &Value) -> &'static str {
+    kind_name(value.kind())
+}
+
+/// `false` and `null` are false, every other value is true.
+fn is_truthy(value: &Value) -> bool {
+    !matches!(value, Value::Null | Value::Boolean(false))
+}
+
+fn run(ast: &Ast, input: &Value) -> Result<Vec<Value>, JqError> {
+    let outputs = match ast {
+        Ast::Identity => vec![input.clone()],
+        Ast::Recurse => {
+            let mut outputs = Vec::new();
+            recurse(input, &mut outputs);
+            outputs
+        }
+        Ast::Field(target, name) => run(target, input)?
+            .iter()
+            .map(|value| index(value, &Value::String(name.clone())))
+            .collect::<Result<_, _>>()?,
+        Ast::Index(target, key) => {
+            let mut outputs = Vec::new();
+            for value in run(target, input)? {
+                for key in run(key, input)? {
+                    outputs.push(index(&value, &key)?);
+                }
+            }
+            outputs
+        }
+        Ast::Slice(target, from, to) => {
+            let bound = |bound: &Option<Box<Ast>>| match bound {
+                Some(bound) => run(bound, input),
+                None => Ok(vec![Value::Null]),
+            };
+            let mut outputs = Vec::new();
+            for value in run(target, input)? {
+                for to in bound(to)? {
+                    for from in bound(from)? {
+                        outputs.push(slice(&value, &from, &to)?);
+                    }
+                }
+            }
+            outputs
+        }
+        Ast::Iterate(target) => {
+            let mut outputs = Vec::new();
+            for value in run(target, input)? {
+                outputs.extend(iterate(&value)?);
+            }
+            outputs
+        }
+        Ast::Try(inner) => run(inner, input).unwrap_or_default(),
+        Ast::Literal(value) => vec![value.clone()],
+        Ast::Interpolation(parts) => {
+            let mut strings = vec![String::new()];
+            for part in parts {
+                strings = match part {
+                    Part::Text(text) => strings.into_iter().map(|s| s + text).collect(),
+                    Part::Filter(filter) => {
+                        let values = run(filter, input)?;
+                        let mut next = Vec::new();
+                        for s in &strings {
+                            for value in &values {
+                                next.push(format!("{s}{}", to_text(value)));
+                            }
+                        }
+                        next
+                    }
+                };
+            }
+            strings.into_iter().map(Value::String).collect()
+        }
+        Ast::Array(None) => vec![Value::Array(Vec::new())],
+        Ast::Array(Some(inner)) => vec![Value::Array(run(inner, input)?)],
+        Ast::Object(entries) => {
+            let mut objects = vec![Map::new()];
+            for (key, value) in entries {
+                let keys = run(key, input)?;
+                let values = run(value, input)?;
+                let mut next = Vec::new();
+                for object in &objects {
+                    for key in &keys {
+                        let Some(key) = key.as_text() else {
+                            return runtime(format!(
+                                "object keys must be strings, not {}",
+                                kind(key)
+                            ));
+                        };
+                        for value in &values {
+                            let mut object = object.clone();
+                            object.insert(key.to_string(), value.clone());
+                            next.push(object);
+                        }
+                    }
+                }
+                objects = next;
+            }
+            objects.into_iter().map(Value::Object).collect()
+        }
+        Ast::Pipe(left, right) => {
+            let mut outputs = Vec::new();
+            for value in run(left, input)? {
+                outputs.extend(run(right, &value)?);
+            }
+            outputs
+        }
+        Ast::Comma(left, right) => {
+            let mut outputs = run(left, input)?;
+            outputs.extend(run(right, input)?);
+            outputs
+        }
+        Ast::Negate(inner) => run(inner, input)?
+            .iter()
+            .map(|value| match value {
+                Value::Number(n) => Ok(Value::Number(-n)),
+                value => runtime(format!("cannot negate {}", kind(value))),
+            })
+            .collect::<Result<_, _>>()?,
+        Ast::Binary(operator, left, right) => {
+            let mut outputs = Vec::new();
+            let lefts = run(left, input)?;
+            for right in run(right, input)? {
+                for left in &lefts {
+                    outputs.push(apply(*operator, left, &right)?);
+                }
+            }
+            outputs
+        }
+        Ast::And(left, right) | Ast::Or(left, right) => {
+            let is_and = matches!(ast, Ast::And(..));
+            let mut outputs = Vec::new();
+            for left in run(left, input)? {
+                if is_truthy(&left) != is_and {
+                    outputs.push(Value::Boolean(!is_and));
+                    continue;
+                }
+                for right in run(right, input)? {
+                    outputs.push(Value::Boolean(is_truthy(&right)));
+                }
+            }
+            outputs
+        }
+        Ast::Alternative(left, right) => {
+            let outputs: Vec<_> = run(left, input)
+                .unwrap_or_default()
+                .into_iter()
+                .filter(is_truthy)
+                .collect();
+            if outputs.is_empty() {
+                run(right, input)?
+            } else {
+                outputs
+            }
+        }
+        Ast::If {
+            branches,
+            otherwise,
+        } => conditional(branches, otherwise, input)?,
+        Ast::Call(name, args) => call(name, args, input)?,
+    };
+    Ok(outputs)
+}
+
+fn recurse(value: &Value, outputs: &mut Vec<Value>) {
+    outputs.push(value.clone());
+    match value {
+        Value::Array(values) => values.iter().for_each(|value| recurse(value, outputs)),
+        Value::Object(map) => map.values().for_each(|value| recurse(value, outputs)),
+        _ => {}
+    }
+}
+
+fn conditional(
+    branches: &[(Ast, Ast)],
+    otherwise: &Ast,
+    input: &Value,
+) -> Result<Vec<Value>, JqError> {
+    let Some(((condition, then), rest)) = branches.split_first() else {
+        return run(otherwise, input);
+    };
+
+    let mut outputs = Vec::new();
+    for value in run(condition, input)? {
+        if is_truthy(&value) {
+            outputs.extend(run(then, input)?);
+        } else {
+            outputs.extend(conditional(rest, otherwise, input)?);
+        }
+    }
+    Ok(outputs)
+}
+
+fn index(value: &Value, key: &Value) -> Result<Value, JqError> {
+    match (value, key) {
+        (value, Value::Rope(key)) => index(value, &Value::String(key.to_string())),
+        (Value::Object(map), Value::String(key)) => {
+            Ok(map.get(key).cloned().unwrap_or(Value::Null))
+        }
+        (Value::Array(values), Value::Number(n)) => {
+            let index = n.floor() as i64;
+            let index = if index < 0 {
+                index + values.len() as i64
+            } else {
+                index
+            };
+            Ok(usize::try_from(index)
+                .ok()
+                .and_then(|index| values.get(index))
+                .cloned()
+                .unwrap_or(Value::Null))
+        }
+        (Value::Null, Value::String(_) | Value::Number(_)) => Ok(Value::Null),
+        (value, Value::String(key)) => {
+            runtime(format!("cannot index {} with \"{key}\"", kind(value)))
+        }
+        (value, key) => runtime(format!("cannot index {} with {}", kind(value), kind(key))),
+    }
+}
+
+fn slice(value: &Value, from: &Value, to: &Value) -> Result<Value, JqError> {
+    let bound = |bound: &Value, len: usize, default: usize| match bound {
+        Value::Null => Ok(default),
+        Value::Number(n) => {
+            let index = n.floor() as i64;
+            let index = if index < 0 { index + len as i64 } else { index };
+            Ok(index.clamp(0, len as i64) as usize)
+        }
+        bound => runtime(format!("cannot slice with {}", kind(bound))),
+    };
+    let range = |len: usize| -> Result<_, JqError> {
+        let from = bound(from, len, 0)?;
+        Ok(from..bound(to, len, len)?.max(from))
+    };
+
+    match value {
+        Value::Null => Ok(Value::Null),
+        Value::Array(values) => Ok(Value::Array(values[range(values.len())?].to_vec())),
+        Value::String(s) => {
+            let range = range(s.chars().count())?;
+            Ok(Value::String(
+                s.chars().skip(range.start).take(range.len()).collect(),
+            ))
+        }
+        Value::Rope(rope) => {
+            let range = range(rope.chars().count())?;
+            Ok(Value::String(
+                rope.chars().skip(range.start).take(range.len()).collect(),
+            ))
+        }
+        value => runtime(format!("cannot slice {}", kind(value))),
+    }
+}
+
+fn iterate(value: &Value) -> Result<Vec<Value>, JqError> {
+    match value {
+        Value::Array(values) => Ok(values.clone()),
+        Value::Object(map) => Ok(map.values().cloned().collect()),
+        value => runtime(format!("cannot iterate over {}", kind(value))),
+    }
+}
+
+/// Strings as they are, other values as JSON.
+fn to_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Rope(rope) => rope.to_string(),
+        value => value.to_string(),
+    }
+}
+
+/// A rope joined into a `Value::String`, for the builtins that need its text
+/// in one piece; other values as they are.
+fn joined(value: &Value) -> Cow<'_, Value> {
+    match value {
+        Value::Rope(rope) => Cow::Owned(Value::String(rope.to_string())),
+        value => Cow::Borrowed(value),
+    }
+}
+
+fn apply(operator: Operator, left: &Value, right: &Value) -> Result<Value, JqError> {
+    let result = match (operator, left, right) {
+        (Operator::Eq, left, right) => Value::Boolean(left == right),
+        (Operator::Ne, left, right) => Value::Boolean(left != right),
+        (Operator::Lt, left, right) => Value::Boolean(order(left, right).is_lt()),
+        (Operator::Le, left, right) => Value::Boolean(order(left, right).is_le()),
+        (Operator::Gt, left, right) => Value::Boolean(order(left, right).is_gt()),
+        (Operator::Ge, left, right) => Value::Boolean(order(left, right).is_ge()),
+        (Operator::Add, Value::Null, value) | (Operator::Add, value, Value::Null) => value.clone(),
+        (Operator::Add, Value::Number(a), Value::Number(b)) => Value::Number(a + b),
+        (Operator::Add, Value::String(a), Value::String(b)) => Value::String(format!("{a}{b}")),
+        (Operator::Add, Value::Array(a), Value::Array(b)) => {
+            Value::Array(a.iter().chain(b).cloned().collect())
+        }
+        (Operator::Add, Value::Object(a), Value::Object(b)) => {
+            let mut merged = a.clone();
+            for (key, value) in b.iter() {
+                merged.insert(key.clone(), value.clone());
+            }
+            Value::Object(merged)
+        }
+        (Operator::Subtract, Value::Number(a), Value::Number(b)) => Value::Number(a - b),
+        (Operator::Subtract, Value::Array(a), Value::Array(b)) => Value::Array(
+            a.iter()
+                .filter(|value| !b.contains(value))
+                .cloned()
+                .collect(),
+        ),
+        (Operator::Multiply, Value::Number(a), Value::Number(b)) => Value::Number(a * b),
+        (Operator::Divide | Operator::Remainder, Value::Number(a), Value::Number(b)) => {
+            // `%` works on the operands truncated to integers, so `5 % 0.5`
+            // divides by zero too
+            let divisor = match operator {
+                Operator::Divide => *b,
+                _ => b.trunc(),
+            };
+            if divisor == 0.0 {
+                return runtime(format!("{a} cannot be divided by zero"));
+            }
+            match operator {
+                Operator::Divide => Value::Number(a / b),
+                _ => match (a.trunc() as i64).checked_rem(divisor as i64) {
+                    Some(remainder) => Value::Number(remainder as f64),
+                    None => return runtime(format!("{a} % {b} is out of range")),
+                },
+            }
+        }
+        (Operator::Divide, Value::String(a), Value::String(b)) => split(a, b),
+        (_, left, right) if left.is_rope() || right.is_rope() => {
+            return apply(operator, &joined(left), &joined(right))
+        }
+        (_, left, right) => {
+            let verb = match operator {
+                Operator::Add => "added to",
+                Operator::Subtract => "subtracted from",
+                Operator::Multiply => "multiplied by",
+                _ => "divided by",
+            };
+            return runtime(format!("{} cannot be {verb} {}", kind(right), kind(left)));
+        }
+    };
+    Ok(result)
+}
+
+fn split(s: &str, separator: &str) -> Value {
+    if s.is_empty() {
+        return Value::Array(Vec::new());
+    }
+    s.split(separator).map(Value::from).collect()
+}
+
+/// jq's total order: null, false, true, numbers, strings, arrays, objects.
+fn order(a: &Value, b: &Value) -> Ordering {
+    let rank = |value: &Value| match value {
+        Value::Null => 0,
+        Value::Boolean(false) => 1,
+        Value::Boolean(true) => 2,
+        Value::Number(_) => 3,
+        Value::String(_) => 4,
+        #[cfg(feature = "datetime")]
+        Value::DateTime(_) => 4,
+        Value::Rope(_) => 4,
+        Value::Array(_) => 5,
+        Value::Object(_) => 6,
+    };
+
+    match (a, b) {
+        (Value::Number(a), Value::Number(b)) => a.total_cmp(b),
+        (Value::Array(a), Value::Array(b)) => a
+            .iter()
+            .zip(b)
+            .map(|(a, b)| order(a, b))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or_else(|| a.len().cmp(&b.len())),
+        (Value::Object(a), Value::Object(b)) => {
+            let keys = |map: &Map<String, Value>| {
+                let mut keys: Vec<_> = map.keys().cloned().collect();
+                keys.sort();
+                keys
+            };
+            let (a_keys, b_keys) = (keys(a), keys(b));
+            a_keys.cmp(&b_keys).then_with(|| {
+                a_keys
+                    .iter()
+                    .map(|key| order(&a[key], &b[key]))
+                    .find(|ordering| ordering.is_ne())
+                    .unwrap_or(Ordering::Equal)
+            })
+        }
+        (a, b) => compare_text(a, b).unwrap_or_else(|| rank(a).cmp(&rank(b))),
+    }
+}
+
+fn call(name: &str, args: &[Ast], input: &Value) -> Result<Vec<Value>, JqError> {
+    if let [arg] = args {
+        return call_with_arg(name, arg, input);
+    }
+
+    let expected = |what: &str| runtime(format!("{name} expects {what}, not {}", kind(input)));
+    let result = match (name, input) {
+        ("empty", _) => return Ok(Vec::new()),
+        ("not", value) => Value::Boolean(!is_truthy(value)),
+        ("type", value) => Value::from(kind(value)),
+        ("length", Value::Null) => Value::from(0),
+        ("length", Value::Number(n)) => Value::Number(n.abs()),
+        ("length", Value::String(s)) => Value::from(s.chars().count()),
+        ("length", Value::Rope(rope)) => Value::from(rope.chars().count()),
+        ("length", Value::Array(values)) => Value::from(values.len()),
+        ("length", Value::Object(map)) => Value::from(map.len()),
+        ("keys", Value::Object(map)) => sorted_keys(map)
+            .into_iter()
+            .map(|key| Value::from(key.as_str()))
+            .collect(),
+        ("keys", Value::Array(values)) => (0..values.len()).map(Value::from).collect(),
+        ("add", value) => iterate(value)?
+            .iter()
+            .try_fold(Value::Null, |sum, value| apply(Operator::Add, &sum, value))?,
+        ("tostring", value) => Value::String(to_text(value)),
+        ("tonumber", Value::Number(n)) => Value::Number(*n),
+        ("tonumber", Value::String(s)) => match parse_number(s) {
+            Ok(n) => Value::Number(n),
+            Err(_) => return runtime(format!("cannot parse \"{s}\" as a number")),
+        },
+        ("sort", Value::Array(values)) => {
+            let mut values = values.clone();
+            values.sort_by(order);
+            Value::Array(values)
+        }
+        ("unique", Value::Array(values)) => {
+            let mut values = values.clone();
+            values.sort_by(order);
+            values.dedup();
+            Value::Array(values)
+        }
+        ("min" | "max", Value::Array(values)) => {
+            let pick = |a: &&Value, b: &&Value| order(a, b);
+            let extreme = if name == "min" {
+                values.iter().min_by(pick)
+            } else {
+                values.iter().max_by(pick)
+            };
+            extreme.cloned().unwrap_or(Value::Null)
+        }
+        ("reverse", Value::Null) => Value::Array(Vec::new()),
+        ("reverse", Value::Array(values)) => values.iter().rev().cloned().collect(),
+        ("reverse", Value::String(s)) => Value::String(s.chars().rev().collect()),
+        ("floor", Value::Number(n)) => Value::Number(n.floor()),
+        ("sqrt", Value::Number(n)) => Value::Number(n.sqrt()),
+        ("to_entries", Value::Object(map)) => sorted_keys(map)
+            .into_iter()
+            .map(|key| {
+                let value = map[key].clone();
+                Value::object([("key", Value::from(key.as_str())), ("value", value)])
+            })
+            .collect(),
+        ("from_entries", Value::Array(entries)) => {
+            let mut map = Map::new();
+            for entry in entries {
+                let key = ["key", "k", "name"]
+                    .iter()
+                    .find_map(|field| entry.get(field).filter(|key| !key.is_null()));
+                let key = match key {
+                    Some(Value::String(key)) => key.clone(),
+                    Some(Value::Rope(key)) => key.to_string(),
+                    Some(key @ (Value::Number(_) | Value::Boolean(_))) => key.to_string(),
+                    _ => return runtime(String::from("from_entries expects entries with a key")),
+                };
+                let value = ["value", "v"]
+                    .iter()
+                    .find_map(|field| entry.get(field))
+                    .cloned()
+                    .unwrap_or(Value::Null);
+                map.insert(key, value);
+            }
+            Value::Object(map)
+        }
+        ("ascii_downcase", Value::String(s)) => Value::String(s.to_ascii_lowercase()),
+        ("ascii_upcase", Value::String(s)) => Value::String(s.to_ascii_uppercase()),
+        (_, Value::Rope(_)) => return call(name, args, &joined(input)),
+        ("keys" | "to_entries", _) => return expected("an object"),
+        ("sort" | "unique" | "min" | "max" | "from_entries", _) => return expected("an array"),
+        ("floor" | "sqrt" | "tonumber", _) => return expected("a number"),
+        ("ascii_downcase" | "ascii_upcase", _) => return expected("a string"),
+        _ => return runtime(format!("{} has no {name}", kind(input))),
+    };
+    Ok(vec![result])
+}
+
+fn call_with_arg(name: &str, arg: &Ast, input: &Value) -> Result<Vec<Value>, JqError> {
+    let mut outputs = Vec::new();
+    match name {
+        "map" => {
+            let mut mapped = Vec::new();
+            for value in iterate(input)? {
+                mapped.extend(run(arg, &value)?);
+            }
+            outputs.push(Value::Array(mapped));
+        }
+        "select" => {
+            for value in run(arg, input)? {
+                if is_truthy(&value) {
+                    outputs.push(input.clone());
+                }
+            }
+        }
+        "sort_by" => {
+            let Value::Array(values) = input else {
+                return runtime(format!("{} cannot be sorted", kind(input)));
+            };
+            let mut keyed = values
+                .iter()
+                .map(|value| Ok((Value::Array(run(arg, value)?), value)))
+                .collect::<Result<Vec<_>, JqError>>()?;
+            keyed.sort_by(|(a, _), (b, _)| order(a, b));
+            outputs.push(keyed.into_iter().map(|(_, value)| value.clone()).collect());
+        }
+        _ => {
+            for arg in run(arg, input)? {
+                outputs.push(call_with_value(name, &arg, input)?);
+            }
+        }
+    }
+    Ok(outputs)
+}
+
+/// Builtins whose argument is only used for its values.
+fn call_with_value(name: &str, arg: &Value, input: &Value) -> Result<Value, JqError> {
+    let result = match (name, input, arg) {
+        ("has", Value::Object(map), Value::String(key)) => Value::Boolean(map.contains_key(key)),
+        ("has", Value::Array(values), Value::Number(n)) => {
+            Value::Boolean(*n >= 0.0 && (*n as usize) < values.len())
+        }
+        ("join", Value::Array(values), Value::String(separator)) => {
+            let mut parts = Vec::with_capacity(values.len());
+            for value in values {
+                parts.push(match value {
+                    Value::Null => String::new(),
+                    Value::Array(_) | Value::Object(_) => {
+                        return runtime(format!("cannot join {}", kind(value)))
+                    }
+                    value => to_text(value),
+                });
+            }
+            Value::String(parts.join(separator))
+        }
+        ("split", Value::String(s), Value::String(separator)) => split(s, separator),
+        ("startswith", Value::String(s), Value::String(prefix)) => {
+            Value::Boolean(s.starts_with(prefix.as_str()))
+        }
+        ("endswith", Value::String(s), Value::String(suffix)) => {
+            Value::Boolean(s.ends_with(suffix.as_str()))
+        }
+        _ if input.is_rope() || arg.is_rope() => {
+            return call_with_value(name, &joined(arg), &joined(input))
+        }
+        _ => {
+            return runtime(format!(
+                "{name} cannot be applied to {} with {}",
+                kind(input),
+                kind(arg)
+            ))
+        }
+    };
+    Ok(result)
+}
+
+fn sorted_keys(map: &Map<String, Value>) -> Vec<&String> {
+    let mut keys: Vec<_> = map.keys().collect();
+    keys.sort();
+    keys
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse, QueryLimits, Value};
+
+    use super::{eval, JqError, JqFilter};
+
+    fn store() -> Value {
+        parse(String::from(
+            r#"{
+                "name": "shop",
+                "items": [
+                    {"name": "pen", "price": 2, "tags": ["office"]},
+                    {"name": "lamp", "price": 30, "tags": ["home", "office"]},
+                    {"name": "mug", "price": 8, "tags": []}
+                ]
+            }"#,
+        ))
+        .unwrap()
+    }
+
+    /// Outputs of the filter as one JSON text per line.
+    fn jq(expression: &str) -> String {
+        let outputs = eval(expression, &store()).unwrap();
+        let lines: Vec<_> = outputs.iter().map(Value::to_string).collect();
+        lines.join("\n")
+    }
+
+    #[test]
+    fn accesses_paths() {
+        assert_eq!(jq(".name"), r#""shop""#);
+        assert_eq!(jq(".items[1].name"), r#""lamp""#);
+        assert_eq!(jq(".items[-1].tags"), "[]");
+        assert_eq!(jq(".items[].name"), "\"pen\"\n\"lamp\"\n\"mug\"");
+        assert_eq!(jq(".items[:1] | length"), "1");
+        assert_eq!(jq("[.items[1:][].price]"), "[30,8]");
+        assert_eq!(jq(r#"."name"[1:3]"#), r#""ho""#);
+        assert_eq!(jq(".missing.deeper"), "null");
+        assert_eq!(jq(".name, .items[0].price"), "\"shop\"\n2");
+    }
+
+    #[test]
+    fn maps_and_selects() {
+        assert_eq!(
+            jq(".items | map(select(.price > 5) | .name)"),
+            r#"["lamp","mug"]"#
+        );
+        assert_eq!(
+            jq(r#"[.items[] | select(.tags | length > 0 and .[0] == "office") | .name]"#),
+            r#"["pen"]"#
+        );
+        assert_eq!(
+            jq(".items | sort_by(-.price) | map(.name)"),
+            r#"["lamp","mug","pen"]"#
+        );
+    }
+
+    #[test]
+    fn evaluates_arithmetic() {
+        assert_eq!(jq(".items | map(.price) | add"), "40");
+        assert_eq!(jq(".items[0].price * 3 + 1 - 10 / 4"), "4.5");
+        assert_eq!(jq("7 % 3, (1, 2) + (10, 20)"), "1\n11\n12\n21\n22");
+        assert_eq!(jq(r#""a-b" / "-""#), r#"["a","b"]"#);
+        assert_eq!(jq("[1, 2, 3] - [2]"), "[1,3]");
+        assert_eq!(jq(".missing // .name"), r#""shop""#);
+    }
+
+    #[test]
+    fn interpolates_strings() {
+        assert_eq!(
+            jq(r#".items[] | select(.price < 5) | "\(.name) costs \(.price) (\(.tags))""#),
+            r#""pen costs 2 ([\"office\"])""#
+        );
+        assert_eq!(jq(r#""\(1, 2)!""#), "\"1!\"\n\"2!\"");
+    }
+
+    #[test]
+    fn constructs_values() {
+        let value = Value::object([("n", Value::from("pen")), ("price", Value::from(2))]);
+
+        assert_eq!(
+            eval("{n: .items[0].name, price: .items[0].price}", &store()),
+            Ok(vec![value.clone()])
+        );
+        assert_eq!(
+            eval(".items[0] | {(.name): .price}", &store()).unwrap()[0]["pen"],
+            Value::from(2)
+        );
+        assert_eq!(
+            eval("{name}", &store()).unwrap()[0]["name"],
+            Value::from("shop")
+        );
+        assert_eq!(
+            jq(r#"if .items[0].price > 5 then "dear" elif .name then "cheap" else null end"#),
+            r#""cheap""#
+        );
+        assert_eq!(
+            jq("[.items[].tags[]] | unique | join(\",\")"),
+            r#""home,office""#
+        );
+        assert_eq!(jq("[.items[] | .price] | min, max"), "2\n30");
+    }
+
+    #[test]
+    fn reports_errors() {
+        let cases = [
+            (".items[", JqError::UnexpectedEnd),
+            (".a | | .b", JqError::UnexpectedChar(5, '|')),
+            ("if . then 1", JqError::UnexpectedEnd),
+            ("nope(1)", JqError::UnknownFunction(String::from("nope/1"))),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(JqFilter::parse(input), Err(expected), "{input}");
+        }
+
+        assert_eq!(
+            eval(".name.first", &store()),
+            Err(JqError::Runtime(String::from(
+                "cannot index string with \"first\""
+            )))
+        );
+        assert_eq!(eval(".name.first?", &store()), Ok(Vec::new()));
+        assert_eq!(
+            eval(".name + 1", &store()),
+            Err(JqError::Runtime(String::from(
+                "number cannot be added to string"
+            )))
+        );
+        assert_eq!(
+            eval("5 % 0.5", &Value::Null),
+            Err(JqError::Runtime(String::from(
+                "5 cannot be divided by zero"
+            )))
+        );
+        assert_eq!(
+            eval("-9223372036854775808 % -1", &Value::Null),
+            Err(JqError::Runtime(String::from(
+                "-9223372036854776000 % -1 is out of range"
+            )))
+        );
+    }
+
+    #[test]
+    fn rejects_filters_beyond_limits() {
+        for depth in [700, 5000] {
+            let nested = format!("{}.{}", "(".repeat(depth), ")".repeat(depth));
+            assert_eq!(
+                JqFilter::parse(&nested),
+                Err(JqError::TooDeep { limit: 128 }),
+                "{depth}"
+            );
+        }
+
+        let limits = QueryLimits {
+            max_depth: 2,
+            max_length: 8,
+        };
+        assert_eq!(
+            JqFilter::parse_with_limits(".items[0].name", &limits),
+            Err(JqError::TooLong {
+                length: 14,
+                limit: 8
+            })
+        );
+        assert_eq!(
+            JqFilter::parse_with_limits("((.))", &limits),
+            Err(JqError::TooDeep { limit: 2 })
+        );
+    }
+}
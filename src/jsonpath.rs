@@ -0,0 +1,815 @@
+//! JSONPath queries such as `$..book[?(@.price < 10)].title`, following the
+//! semantics of RFC 9535.
+//!
+//! Supported are member names in dot and bracket notation, wildcards,
+//! recursive descent, indexes (negative ones count from the end), slices,
+//! unions and filters with comparisons, existence tests and `&&`, `||`, `!`.
+//! Filter functions are not supported.
+
+use std::cmp::Ordering;
+
+use crate::{rope::compare_text, JsonPointer, QueryLimits, Value};
+
+/// Parsed JSONPath query, reusable across documents
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonPath {
+    segments: Vec<Segment>,
+}
+
+/// Possible errors when parsing a JSONPath query
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JsonPathError {
+    /// The query ended in the middle of an expression
+    UnexpectedEnd,
+
+    /// The character at this byte offset does not fit the grammar
+    UnexpectedChar(usize, char),
+
+    /// Filters and parentheses are nested deeper than allowed
+    TooDeep { limit: usize },
+
+    /// The query is longer than allowed
+    TooLong { length: usize, limit: usize },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    /// Selects from the children of each node
+    Child(Vec<Selector>),
+
+    /// Selects from the children of each node and all of its descendants
+    Descendant(Vec<Selector>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Selector {
+    Name(String),
+    Wildcard,
+    Index(i64),
+    Slice {
+        start: Option<i64>,
+        end: Option<i64>,
+        step: Option<i64>,
+    },
+    Filter(Expr),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Or(Box<Expr>, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Compare(Operand, Op, Operand),
+    /// True when the query selects at least one node
+    Exists(Query),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Operand {
+    Literal(Value),
+    /// Compared by the single node it selects, absent otherwise
+    Query(Query),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+/// Query embedded in a filter, relative to the current node (`@`) or to the
+/// root (`$`)
+#[derive(Debug, Clone, PartialEq)]
+struct Query {
+    absolute: bool,
+    segments: Vec<Segment>,
+}
+
+impl JsonPath {
+    pub fn parse(query: &str) -> Result<Self, JsonPathError> {
+        Self::parse_with_limits(query, &QueryLimits::nesting_only())
+    }
+
+    /// Parses a query from untrusted input, checking its length up front and
+    /// its nesting while parsing.
+    pub fn parse_with_limits(query: &str, limits: &QueryLimits) -> Result<Self, JsonPathError> {
+        if query.len() > limits.max_length {
+            return Err(JsonPathError::TooLong {
+                length: query.len(),
+                limit: limits.max_length,
+            });
+        }
+
+        let mut parser = Parser {
+            input: query,
+            pos: 0,
+            depth: 0,
+            max_depth: limits.max_depth,
+        };
+        parser.expect('$')?;
+        let segments = parser.segments()?;
+        parser.skip_whitespace();
+        match parser.peek() {
+            None => Ok(Self { segments }),
+            Some(_) => Err(parser.error()),
+        }
+    }
+
+    /// Values selected by the query, in document order where it is defined.
+    pub fn query<'a>(&self, root: &'a Value) -> Vec<&'a Value> {
+        self.query_located(root)
+            .into_iter()
+            .map(|(_, value)| value)
+            .collect()
+    }
+
+    /// Values selected by the query together with their locations.
+    pub fn query_located<'a>(&self, root: &'a Value) -> Vec<(JsonPointer, &'a Value)> {
+        select(&self.segments, vec![(JsonPointer::root(), root)], root)
+    }
+}
+
+impl Value {
+    /// Parses `query` as JSONPath and returns the values it selects.
+    pub fn select(&self, query: &str) -> Result<Vec<&Value>, JsonPathError> {
+        Ok(JsonPath::parse(query)?.query(self))
+    }
+}
+
+type Node<'a> = (JsonPointer, &'a Value);
+
+fn select<'a>(segments: &[Segment], mut nodes: Vec<Node<'a>>, root: &'a Value) -> Vec<Node<'a>> {
+    for segment in segments {
+        let mut selected = Vec::new();
+        for (path, value) in nodes {
+            match segment {
+                Segment::Child(selectors) => {
+                    apply_selectors(selectors, &path, value, root, &mut selected)
+                }
+                Segment::Descendant(selectors) => {
+                    for (path, value) in descendants(path, value) {
+                        apply_selectors(selectors, &path, value, root, &mut selected);
+                    }
+                }
+            }
+        }
+        nodes = selected;
+    }
+    nodes
+}
+
+/// The node followed by all values below it, in pre-order.
+fn descendants(path: JsonPointer, value: &Value) -> Vec<Node<'_>> {
+    let mut nodes = Vec::new();
+    let mut pending = vec![(path, value)];
+    while let Some((path, value)) = pending.pop() {
+        let mut children = children(&path, value);
+        children.reverse();
+        pending.extend(children);
+        nodes.push((path, value));
+    }
+    nodes
+}
+
+fn children<'a>(path: &JsonPointer, value: &'a Value) -> Vec<Node<'a>> {
+    match value {
+        Value::Array(values) => values
+            .iter()
+            .enumerate()
+            .map(|(index, value)| (path.child(index.to_string()), value))
+            .collect(),
+        Value::Object(map) => map
+            .iter()
+            .map(|(key, value)| (path.child(key.clone()), value))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn apply_selectors<'a>(
+    selectors: &[Selector],
+    path: &JsonPointer,
+    value: &'a Value,
+    root: &'a Value,
+    selected: &mut Vec<Node<'a>>,
+) {
+    for selector in selectors {
+        match (selector, value) {
+            (Selector::Name(name), Value::Object(map)) => {
+                if let Some(member) = map.get(name) {
+                    selected.push((path.child(name.clone()), member));
+                }
+            }
+            (Selector::Wildcard, _) => selected.extend(children(path, value)),
+            (Selector::Index(index), Value::Array(values)) => {
+                if let Some(index) = normalize_index(*index, values.len()) {
+                    selected.push((path.child(index.to_string()), &values[index]));
+                }
+            }
+            (Selector::Slice { start, end, step }, Value::Array(values)) => {
+                for index in slice_indexes(*start, *end, *step, values.len()) {
+                    selected.push((path.child(index.to_string()), &values[index]));
+                }
+            }
+            (Selector::Filter(expr), _) => selected.extend(
+                children(path, value)
+                    .into_iter()
+                    .filter(|(_, child)| evaluate(expr, child, root)),
+            ),
+            _ => {}
+        }
+    }
+}
+
+fn normalize_index(index: i64, len: usize) -> Option<usize> {
+    let len = i64::try_from(len).ok()?;
+    let index = if index < 0 { len + index } else { index };
+    (0..len).contains(&index).then_some(index as usize)
+}
+
+/// Indexes selected by a slice, following the algorithm of RFC 9535.
+fn slice_indexes(
+    start: Option<i64>,
+    end: Option<i64>,
+    step: Option<i64>,
+    len: usize,
+) -> Vec<usize> {
+    let len = len as i64;
+    let step = step.unwrap_or(1);
+    let normalize = |index: i64| if index < 0 { len + index } else { index };
+    let mut indexes = Vec::new();
+
+    if step > 0 {
+        let lower = normalize(start.unwrap_or(0)).clamp(0, len);
+        let upper = normalize(end.unwrap_or(len)).clamp(0, len);
+        let mut index = lower;
+        while index < upper {
+            indexes.push(index as usize);
+            index = index.saturating_add(step);
+        }
+    } else if step < 0 {
+        let upper = normalize(start.unwrap_or(len - 1)).clamp(-1, len - 1);
+        let lower = normalize(end.unwrap_or(-len - 1)).clamp(-1, len - 1);
+        let mut index = upper;
+        while lower < index {
+            indexes.push(index as usize);
+            index = index.saturating_add(step);
+        }
+    }
+
+    indexes
+}
+
+fn evaluate(expr: &Expr, current: &Value, root: &Value) -> bool {
+    match expr {
+        Expr::Or(left, right) => evaluate(left, current, root) || evaluate(right, current, root),
+        Expr::And(left, right) => evaluate(left, current, root) && evaluate(right, current, root),
+        Expr::Not(inner) => !evaluate(inner, current, root),
+        Expr::Exists(query) => !run_query(query, current, root).is_empty(),
+        Expr::Compare(left, op, right) => {
+            let left = operand_value(left, current, root);
+            let right = operand_value(right, current, root);
+            compare(left, *op, right)
+        }
+    }
+}
+
+fn run_query<'a>(query: &Query, current: &'a Value, root: &'a Value) -> Vec<Node<'a>> {
+    let start = if query.absolute { root } else { current };
+    select(&query.segments, vec![(JsonPointer::root(), start)], root)
+}
+
+fn operand_value<'a>(
+    operand: &'a Operand,
+    current: &'a Value,
+    root: &'a Value,
+) -> Option<&'a Value> {
+    match operand {
+        Operand::Literal(value) => Some(value),
+        Operand::Query(query) => match run_query(query, current, root).as_slice() {
+            [(_, value)] => Some(value),
+            _ => None,
+        },
+    }
+}
+
+/// Compares two operands, where `None` is an absent value. Absent values
+/// only equal each other, and only numbers and strings are ordered.
+fn compare(left: Option<&Value>, op: Op, right: Option<&Value>) -> bool {
+    let less = |a: Option<&Value>, b: Option<&Value>| match (a, b) {
+        (Some(Value::Number(a)), Some(Value::Number(b))) => a < b,
+        (Some(a), Some(b)) => compare_text(a, b).is_some_and(Ordering::is_lt),
+        _ => false,
+    };
+
+    match op {
+        Op::Eq => left == right,
+        Op::Ne => left != right,
+        Op::Lt => less(left, right),
+        Op::Le => less(left, right) || left == right,
+        Op::Gt => less(right, left),
+        Op::Ge => less(right, left) || left == right,
+    }
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    /// Byte offset of the next char
+    pos: usize,
+    /// Number of filter expressions being parsed around the current one
+    depth: usize,
+    max_depth: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn eat(&mut self, expected: &str) -> bool {
+        let matches = self.input[self.pos..].starts_with(expected);
+        if matches {
+            self.pos += expected.len();
+        }
+        matches
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), JsonPathError> {
+        match self.peek() {
+            Some(c) if c == expected => {
+                self.pos += c.len_utf8();
+                Ok(())
+            }
+            _ => Err(self.error()),
+        }
+    }
+
+    fn error(&self) -> JsonPathError {
+        match self.peek() {
+            Some(c) => JsonPathError::UnexpectedChar(self.pos, c),
+            None => JsonPathError::UnexpectedEnd,
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self
+            .peek()
+            .is_some_and(|c| matches!(c, ' ' | '\t' | '\n' | '\r'))
+        {
+            self.pos += 1;
+        }
+    }
+
+    /// Segments up to the first char that cannot continue the path.
+    fn segments(&mut self) -> Result<Vec<Segment>, JsonPathError> {
+        let mut segments = Vec::new();
+        loop {
+            let before = self.pos;
+            self.skip_whitespace();
+
+            if self.eat("..") {
+                let selectors = match self.peek() {
+                    Some('[') => self.bracket()?,
+                    Some('*') => {
+                        self.pos += 1;
+                        vec![Selector::Wildcard]
+                    }
+                    _ => vec![Selector::Name(self.member_name()?)],
+                };
+                segments.push(Segment::Descendant(selectors));
+            } else if self.eat(".") {
+                let selector = if self.eat("*") {
+                    Selector::Wildcard
+                } else {
+                    Selector::Name(self.member_name()?)
+                };
+                segments.push(Segment::Child(vec![selector]));
+            } else if self.peek() == Some('[') {
+                segments.push(Segment::Child(self.bracket()?));
+            } else {
+                self.pos = before;
+                return Ok(segments);
+            }
+        }
+    }
+
+    /// Name in dot notation: a letter, `_` or non-ASCII char, then also digits.
+    fn member_name(&mut self) -> Result<String, JsonPathError> {
+        let start = self.pos;
+        match self.peek() {
+            Some(c) if c.is_ascii_alphabetic() || c == '_' || !c.is_ascii() => {}
+            _ => return Err(self.error()),
+        }
+        while self
+            .peek()
+            .is_some_and(|c| c.is_ascii_alphanumeric() || c == '_' || !c.is_ascii())
+        {
+            self.bump();
+        }
+        Ok(self.input[start..self.pos].to_string())
+    }
+
+    fn bracket(&mut self) -> Result<Vec<Selector>, JsonPathError> {
+        self.expect('[')?;
+        let mut selectors = Vec::new();
+        loop {
+            self.skip_whitespace();
+            selectors.push(self.selector()?);
+            self.skip_whitespace();
+            if !self.eat(",") {
+                break;
+            }
+        }
+        self.expect(']')?;
+        Ok(selectors)
+    }
+
+    fn selector(&mut self) -> Result<Selector, JsonPathError> {
+        match self.peek() {
+            Some('\'' | '"') => Ok(Selector::Name(self.string()?)),
+            Some('*') => {
+                self.pos += 1;
+                Ok(Selector::Wildcard)
+            }
+            Some('?') => {
+                self.pos += 1;
+                self.skip_whitespace();
+                Ok(Selector::Filter(self.or_expr()?))
+            }
+            _ => self.index_or_slice(),
+        }
+    }
+
+    fn index_or_slice(&mut self) -> Result<Selector, JsonPathError> {
+        let start = self.optional_integer()?;
+        self.skip_whitespace();
+        if !self.eat(":") {
+            return start.map(Selector::Index).ok_or_else(|| self.error());
+        }
+
+        self.skip_whitespace();
+        let end = self.optional_integer()?;
+        self.skip_whitespace();
+        let step = if self.eat(":") {
+            self.skip_whitespace();
+            self.optional_integer()?
+        } else {
+            None
+        };
+
+        Ok(Selector::Slice { start, end, step })
+    }
+
+    fn optional_integer(&mut self) -> Result<Option<i64>, JsonPathError> {
+        let start = self.pos;
+        self.eat("-");
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        match &self.input[start..self.pos] {
+            "" => Ok(None),
+            text => text.parse().map(Some).map_err(|_| {
+                let c = text.chars().next().expect("text is not empty");
+                JsonPathError::UnexpectedChar(start, c)
+            }),
+        }
+    }
+
+    /// String literal in single or double quotes, with JSON escapes and `\'`.
+    fn string(&mut self) -> Result<String, JsonPathError> {
+        let quote = self.bump().ok_or(JsonPathError::UnexpectedEnd)?;
+        let mut output = String::new();
+        loop {
+            let escape_pos = self.pos;
+            match self.bump().ok_or(JsonPathError::UnexpectedEnd)? {
+                c if c == quote => return Ok(output),
+                '\\' => {
+                    let escaped = match self.bump().ok_or(JsonPathError::UnexpectedEnd)? {
+                        'b' => '\u{8}',
+                        'f' => '\u{c}',
+                        'n' => '\n',
+                        'r' => '\r',
+                        't' => '\t',
+                        'u' => self.unicode_escape(escape_pos)?,
+                        c @ ('\'' | '"' | '\\' | '/') => c,
+                        c => return Err(JsonPathError::UnexpectedChar(self.pos - c.len_utf8(), c)),
+                    };
+                    output.push(escaped);
+                }
+                c => output.push(c),
+            }
+        }
+    }
+
+    /// Reads the hex digits of a `\u` escape, combining surrogate pairs.
+    fn unicode_escape(&mut self, escape_pos: usize) -> Result<char, JsonPathError> {
+        let high = self.hex4()?;
+        let code = if (0xd800..0xdc00).contains(&high) && self.eat("\\u") {
+            let low = self.hex4()?;
+            if !(0xdc00..0xe000).contains(&low) {
+                return Err(JsonPathError::UnexpectedChar(escape_pos, '\\'));
+            }
+            0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00)
+        } else {
+            high
+        };
+        char::from_u32(code).ok_or(JsonPathError::UnexpectedChar(escape_pos, '\\'))
+    }
+
+    fn hex4(&mut self) -> Result<u32, JsonPathError> {
+        let mut code = 0;
+        for _ in 0..4 {
+            let digit = self
+                .peek()
+                .and_then(|c| c.to_digit(16))
+                .ok_or_else(|| self.error())?;
+            self.pos += 1;
+            code = code * 16 + digit;
+        }
+        Ok(code)
+    }
+
+    fn or_expr(&mut self) -> Result<Expr, JsonPathError> {
+        let mut expr = self.and_expr()?;
+        loop {
+            self.skip_whitespace();
+            if !self.eat("||") {
+                return Ok(expr);
+            }
+            self.skip_whitespace();
+            expr = Expr::Or(Box::new(expr), Box::new(self.and_expr()?));
+        }
+    }
+
+    fn and_expr(&mut self) -> Result<Expr, JsonPathError> {
+        let mut expr = self.unary_expr()?;
+        loop {
+            self.skip_whitespace();
+            if !self.eat("&&") {
+                return Ok(expr);
+            }
+            self.skip_whitespace();
+            expr = Expr::And(Box::new(expr), Box::new(self.unary_expr()?));
+        }
+    }
+
+    /// Parses a negation, parenthesized expression or comparison, one level
+    /// of nesting deeper than the expression around it.
+    fn unary_expr(&mut self) -> Result<Expr, JsonPathError> {
+        if self.depth == self.max_depth {
+            return Err(JsonPathError::TooDeep {
+                limit: self.max_depth,
+            });
+        }
+        self.depth += 1;
+        let expr = self.nested_expr();
+        self.depth -= 1;
+        expr
+    }
+
+    fn nested_expr(&mut self) -> Result<Expr, JsonPathError> {
+        if self.eat("!") {
+            self.skip_whitespace();
+            return Ok(Expr::Not(Box::new(self.unary_expr()?)));
+        }
+        if self.eat("(") {
+            self.skip_whitespace();
+            let expr = self.or_expr()?;
+            self.skip_whitespace();
+            self.expect(')')?;
+            return Ok(expr);
+        }
+
+        let start = self.pos;
+        let left = self.operand()?;
+        self.skip_whitespace();
+        let Some(op) = self.comparison_op() else {
+            return match left {
+                Operand::Query(query) => Ok(Expr::Exists(query)),
+                Operand::Literal(_) => Err(self.error_at(start)),
+            };
+        };
+        self.skip_whitespace();
+        let right = self.operand()?;
+        Ok(Expr::Compare(left, op, right))
+    }
+
+    fn error_at(&self, pos: usize) -> JsonPathError {
+        match self.input[pos..].chars().next() {
+            Some(c) => JsonPathError::UnexpectedChar(pos, c),
+            None => JsonPathError::UnexpectedEnd,
+        }
+    }
+
+    fn comparison_op(&mut self) -> Option<Op> {
+        let ops = [
+            ("==", Op::Eq),
+            ("!=", Op::Ne),
+            ("<=", Op::Le),
+            (">=", Op::Ge),
+            ("<", Op::Lt),
+            (">", Op::Gt),
+        ];
+        ops.into_iter()
+            .find(|(text, _)| self.eat(text))
+            .map(|(_, op)| op)
+    }
+
+    fn operand(&mut self) -> Result<Operand, JsonPathError> {
+        match self.peek() {
+            Some(c @ ('@' | '$')) => {
+                self.pos += 1;
+                Ok(Operand::Query(Query {
+                    absolute: c == '$',
+                    segments: self.segments()?,
+                }))
+            }
+            Some('\'' | '"') => Ok(Operand::Literal(Value::String(self.string()?))),
+            Some(c) if c == '-' || c.is_ascii_digit() => self.number(),
+            _ if self.eat("true") => Ok(Operand::Literal(Value::Boolean(true))),
+            _ if self.eat("false") => Ok(Operand::Literal(Value::Boolean(false))),
+            _ if self.eat("null") => Ok(Operand::Literal(Value::Null)),
+            _ => Err(self.error()),
+        }
+    }
+
+    fn number(&mut self) -> Result<Operand, JsonPathError> {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E'))
+        {
+            self.pos += 1;
+        }
+        self.input[start..self.pos]
+            .parse()
+            .map(|n| Operand::Literal(Value::Number(n)))
+            .map_err(|_| self.error_at(start))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse, QueryLimits, Value};
+
+    use super::{JsonPath, JsonPathError};
+
+    fn store() -> Value {
+        parse(String::from(
+            r#"{"store": {
+                "book": [
+                    {"category": "reference", "author": "Nigel Rees", "title": "Sayings of the Century", "price": 8.95},
+                    {"category": "fiction", "author": "Evelyn Waugh", "title": "Sword of Honour", "price": 12.99},
+                    {"category": "fiction", "author": "Herman Melville", "title": "Moby Dick", "isbn": "0-553-21311-3", "price": 8.99},
+                    {"category": "fiction", "author": "J. R. R. Tolkien", "title": "The Lord of the Rings", "isbn": "0-395-19395-8", "price": 22.99}
+                ],
+                "bicycle": {"color": "red", "price": 399}
+            }}"#,
+        ))
+        .unwrap()
+    }
+
+    fn titles(query: &str) -> Vec<String> {
+        let root = store();
+        root.select(query)
+            .unwrap()
+            .into_iter()
+            .map(|value| match value {
+                Value::String(s) => s.clone(),
+                other => other.to_string(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn filters_with_comparisons() {
+        assert_eq!(
+            titles("$..book[?(@.price < 10)].title"),
+            ["Sayings of the Century", "Moby Dick"]
+        );
+        assert_eq!(
+            titles("$.store.book[?@.category == 'fiction' && !(@.price >= 20)].title"),
+            ["Sword of Honour", "Moby Dick"]
+        );
+        assert_eq!(
+            titles("$.store.book[?(@.price > $.store.bicycle.price || @.author == \"Nigel Rees\")].title"),
+            ["Sayings of the Century"]
+        );
+    }
+
+    #[test]
+    fn filters_by_existence() {
+        assert_eq!(
+            titles("$.store.book[?(@.isbn)].title"),
+            ["Moby Dick", "The Lord of the Rings"]
+        );
+        assert_eq!(titles("$.store.book[?(!@.isbn)].price"), ["8.95", "12.99"]);
+    }
+
+    #[test]
+    fn selects_indexes_slices_and_unions() {
+        assert_eq!(titles("$.store.book[-1].title"), ["The Lord of the Rings"]);
+        assert_eq!(
+            titles("$.store.book[1:3].title"),
+            ["Sword of Honour", "Moby Dick"]
+        );
+        assert_eq!(
+            titles("$.store.book[::-2].title"),
+            ["The Lord of the Rings", "Sword of Honour"]
+        );
+        assert_eq!(
+            titles("$['store']['book'][0, 2]['title']"),
+            ["Sayings of the Century", "Moby Dick"]
+        );
+        assert_eq!(titles("$.store.book[5]"), Vec::<String>::new());
+        assert_eq!(
+            titles("$.store.book[1::9223372036854775807].price"),
+            ["12.99"]
+        );
+    }
+
+    #[test]
+    fn descends_recursively_with_wildcards() {
+        let root = store();
+
+        let prices = root.select("$..price").unwrap();
+        let authors = root.select("$.store.book[*].author").unwrap();
+        let everything = root.select("$..*").unwrap();
+
+        assert_eq!(prices.len(), 5);
+        assert_eq!(authors.len(), 4);
+        assert_eq!(everything.len(), 27);
+    }
+
+    #[test]
+    fn reports_locations() {
+        let root = store();
+        let path = JsonPath::parse("$..book[?(@.price > 20)].author").unwrap();
+
+        let actual: Vec<String> = path
+            .query_located(&root)
+            .into_iter()
+            .map(|(pointer, _)| pointer.to_string())
+            .collect();
+
+        assert_eq!(actual, ["/store/book/3/author"]);
+    }
+
+    #[test]
+    fn rejects_malformed_queries() {
+        let cases = [
+            ("store", JsonPathError::UnexpectedChar(0, 's')),
+            ("$.", JsonPathError::UnexpectedEnd),
+            ("$[1", JsonPathError::UnexpectedEnd),
+            ("$[?(@.a < )]", JsonPathError::UnexpectedChar(10, ')')),
+            ("$[?(1)]", JsonPathError::UnexpectedChar(4, '1')),
+            ("$.a b", JsonPathError::UnexpectedChar(4, 'b')),
+            ("$['a\\x']", JsonPathError::UnexpectedChar(5, 'x')),
+        ];
+
+        for (input, expected) in cases {
+            assert_eq!(JsonPath::parse(input), Err(expected), "{input}");
+        }
+    }
+
+    #[test]
+    fn rejects_queries_beyond_limits() {
+        let nested = format!("$[?({}@.a{})]", "(".repeat(5000), ")".repeat(5000));
+        assert_eq!(
+            JsonPath::parse(&nested),
+            Err(JsonPathError::TooDeep { limit: 128 })
+        );
+
+        let limits = QueryLimits {
+            max_depth: 2,
+            max_length: 8,
+        };
+        assert_eq!(
+            JsonPath::parse_with_limits("$.store.book", &limits),
+            Err(JsonPathError::TooLong {
+                length: 12,
+                limit: 8
+            })
+        );
+        assert_eq!(
+            JsonPath::parse_with_limits(
+                "$[?(((@)))]",
+                &QueryLimits {
+                    max_length: 64,
+                    ..limits
+                }
+            ),
+            Err(JsonPathError::TooDeep { limit: 2 })
+        );
+    }
+}
@@ -0,0 +1,155 @@
+use crate::{parse, ParseError, Value};
+
+/// Decoded header and payload of a JSON Web Token
+#[derive(Debug, PartialEq)]
+pub struct JwtClaims {
+    pub header: Value,
+    pub payload: Value,
+}
+
+/// Segment of a token an error was found in
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum JwtSegment {
+    Header,
+    Payload,
+}
+
+/// Possible errors when reading the claims of a token
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum JwtError {
+    /// The token does not consist of three dot-separated segments
+    MalformedToken,
+
+    /// The segment is not valid base64url
+    InvalidBase64(JwtSegment),
+
+    /// The decoded segment is not valid UTF-8
+    InvalidUtf8(JwtSegment),
+
+    /// The decoded segment is not valid JSON
+    ParseError(JwtSegment, ParseError),
+}
+
+/// Splits a compact JWT and parses its base64url-encoded header and payload.
+///
+/// The signature is not verified, so the claims must not be trusted for
+/// authorization decisions.
+pub fn parse_jwt_claims(token: &str) -> Result<JwtClaims, JwtError> {
+    let mut segments = token.split('.');
+    let (Some(header), Some(payload), Some(_signature), None) = (
+        segments.next(),
+        segments.next(),
+        segments.next(),
+        segments.next(),
+    ) else {
+        return Err(JwtError::MalformedToken);
+    };
+
+    Ok(JwtClaims {
+        header: parse_segment(header, JwtSegment::Header)?,
+        payload: parse_segment(payload, JwtSegment::Payload)?,
+    })
+}
+
+fn parse_segment(segment: &str, kind: JwtSegment) -> Result<Value, JwtError> {
+    let bytes = decode_base64url(segment).ok_or(JwtError::InvalidBase64(kind))?;
+    let text = String::from_utf8(bytes).map_err(|_| JwtError::InvalidUtf8(kind))?;
+    parse(text).map_err(|err| JwtError::ParseError(kind, err))
+}
+
+/// Decodes base64url, with or without trailing `=` padding.
+fn decode_base64url(text: &str) -> Option<Vec<u8>> {
+    let unpadded = text.trim_end_matches('=');
+    if unpadded.len() % 4 == 1 {
+        return None;
+    }
+
+    let mut bytes = Vec::with_capacity(unpadded.len() * 3 / 4);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for b in unpadded.bytes() {
+        let sextet = match b {
+            b'A'..=b'Z' => b - b'A',
+            b'a'..=b'z' => b - b'a' + 26,
+            b'0'..=b'9' => b - b'0' + 52,
+            b'-' => 62,
+            b'_' => 63,
+            _ => return None,
+        };
+        buffer = (buffer << 6) | u32::from(sextet);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ParseError, TokenParseError, Value};
+
+    use super::{decode_base64url, parse_jwt_claims, JwtClaims, JwtError, JwtSegment};
+
+    #[test]
+    fn parses_header_and_payload() {
+        let input = "eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9\
+            .eyJzdWIiOiIxMjM0NTY3ODkwIiwibmFtZSI6IkpvaG4gRG9lIiwiaWF0IjoxNTE2MjM5MDIyfQ\
+            .SflKxwRJSMeKKF2QT4fwpMeJf36POk6yJV_adQssw5c";
+        let expected = JwtClaims {
+            header: Value::object([
+                ("alg", Value::String(String::from("HS256"))),
+                ("typ", Value::String(String::from("JWT"))),
+            ]),
+            payload: Value::object([
+                ("sub", Value::String(String::from("1234567890"))),
+                ("name", Value::String(String::from("John Doe"))),
+                ("iat", Value::Number(1516239022.0)),
+            ]),
+        };
+
+        let actual = parse_jwt_claims(input);
+
+        assert_eq!(actual, Ok(expected));
+    }
+
+    #[test]
+    fn decodes_url_safe_alphabet() {
+        let input = "eyJrIjoiPz8-In0=";
+        let expected = Some(br#"{"k":"??>"}"#.to_vec());
+
+        let actual = decode_base64url(input);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn reports_failing_segment() {
+        let cases = [
+            ("eyJhIjox.e30", JwtError::MalformedToken),
+            ("a.b.c.d", JwtError::MalformedToken),
+            ("e30.e+0.", JwtError::InvalidBase64(JwtSegment::Payload)),
+            ("_w.e30.", JwtError::InvalidUtf8(JwtSegment::Header)),
+            (
+                "e30.eyx9.",
+                JwtError::ParseError(
+                    JwtSegment::Payload,
+                    ParseError::ParseError(TokenParseError::ExpectedProperty),
+                ),
+            ),
+            (
+                "e30.eyJhIjox.sig",
+                JwtError::ParseError(
+                    JwtSegment::Payload,
+                    ParseError::ParseError(TokenParseError::UnexpectedEof),
+                ),
+            ),
+        ];
+
+        for (input, expected) in cases {
+            assert_eq!(parse_jwt_claims(input), Err(expected), "{input}");
+        }
+    }
+}
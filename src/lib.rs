@@ -1,19 +1,257 @@
-use std::collections::HashMap;
+use intern::Unchanged;
+use parser::{parse_tokens, parse_tokens_in, Context};
+use tokenizer::{tokenize, tokenize_partial};
 
-use parser::{parse_tokens, TokenParseError};
-use tokenizer::{tokenize, TokenizeError};
+pub use access::{AccessTracker, Tracked};
+pub use builder::{ArrayBuilder, ObjectBuilder};
+pub use bytes::{
+    find_invalid_utf8, tokenize_bytes, ByteTokens, BytesTokenizeError, InvalidUtf8Handling,
+};
+pub use canonical::CanonicalError;
+pub use check::{Check, Problem, Violation};
+pub use convert::{ConvertError, IntegerError, KindMismatch};
+pub use cursor::{object_members, ArrayCursor, ObjectCursor, ObjectMembers};
+pub use cycle::CycleDetected;
+#[cfg(feature = "datetime")]
+pub use datetime::{DateTime, DateTimeError};
+#[cfg(feature = "color")]
+pub use diff::diff_to_colored_string;
+pub use diff::{diff_to_string, Change};
+pub use duplicates::{duplicate_report, DuplicateKey, DuplicateReport, RepeatedSubtree};
+pub use edit::{Edit, EditError};
+pub use encoding::{detect_encoding, parse_bytes_any_encoding, Encoding, EncodingError};
+pub use escape::{raw_char_ranges, raw_offset, unescaped_len, unescaped_offset};
+#[cfg(feature = "extended_json")]
+pub use extended::ExtendedJsonError;
+pub use filter::Filter;
+pub use frozen::FrozenValue;
+#[cfg(feature = "geojson")]
+pub use geojson::{
+    BoundingBox, Coordinate, Feature, FeatureCollection, GeoJson, GeoJsonError, Geometry,
+};
+pub use hash::{semantic_hash, semantic_hash_unordered, SubtreeHashes};
+pub use hint::suggest;
+pub use hook::ValueHook;
+pub use intern::Interner;
+pub use jmespath::{JmesPath, JmesPathError};
+pub use jq::{eval, JqError, JqFilter};
+pub use jsonpath::{JsonPath, JsonPathError};
+pub use jwt::{parse_jwt_claims, JwtClaims, JwtError, JwtSegment};
+pub use lookup::FieldState;
+#[cfg(feature = "preserve_order")]
+pub use map::OrderedMap;
+pub use map::{Entry, Map};
+pub use merge::{diff_to_merge_patch, merge_patch, ArrayMerge, MergeStrategy};
+pub use migration::{MigrationError, Migrations};
+pub use ndjson::{parse_ndjson, LineFailure, NdjsonBatch};
+pub use options::{ParseOptions, SizeLimits};
+pub use parser::TokenParseError;
+pub use patch::{diff_to_patch, parse_patch, PatchError, PatchOp};
+pub use pointer::{JsonPointer, PointerError, PointerLimits};
+pub use probe::{count_at, exists_at};
+pub use progress::{Progress, ProgressReader};
+pub use query::{CompiledQuery, QueryError, QueryLimits};
+pub use reference::{bundle_file, RefError};
+pub use relative::{RelativePointer, RelativeValue};
+pub use repair::{explain_leniency, repair, Fix, FixKind};
+pub use rope::Rope;
+pub use serializer::{write_value, ControlEscape, EscapePolicy, KeyOrder, SerializeOptions};
+pub use shape::{
+    analyze, compare_shapes, KindChange, PathSegment, PathStats, ShapeDiff, ShapePath, ShapeReport,
+    DISTINCT_LIMIT,
+};
+pub use shared::SharedValue;
+pub use sniff::sniff;
+pub use span::{Position, Span};
+pub use split::split_documents;
+pub use stream::{parse_reader, StreamError, TokenReader};
+#[cfg(feature = "trace")]
+pub use tokenizer::{tokenize_traced, Scanner, TraceStep};
+pub use tokenizer::{Token, TokenKind, TokenizeError};
+pub use transcode::{minify, reformat, transcode, Style, TranscodeOptions};
+pub use transform::{
+    KeyMapping, Pipeline, RenameKeys, SortKeys, StripNulls, Transform, TransformError,
+};
+pub use visit::{
+    decode, decode_with, Decoder, FromJson, MapAccess, SeqAccess, ValueVisitor, VisitError,
+};
 
+pub mod generator;
+#[cfg(feature = "test-util")]
+pub mod test_util;
+
+mod access;
+mod accessors;
+mod builder;
+mod bytes;
+#[cfg(any(feature = "datetime", feature = "extended_json"))]
+mod calendar;
+mod canonical;
+mod check;
+mod convert;
+mod cursor;
+mod cycle;
+#[cfg(feature = "datetime")]
+mod datetime;
+mod deep;
+mod diff;
+mod duplicates;
+mod edit;
+mod encoding;
+mod escape;
+#[cfg(feature = "extended_json")]
+mod extended;
+mod filter;
+mod frozen;
+#[cfg(feature = "geojson")]
+mod geojson;
+mod hash;
+mod hint;
+mod hook;
+mod intern;
+mod jmespath;
+mod jq;
+mod jsonpath;
+mod jwt;
+mod lookup;
+mod macros;
+mod map;
+mod merge;
+mod migration;
+mod ndjson;
+mod number;
+mod ops;
+mod options;
 mod parser;
+mod patch;
+mod pointer;
+mod probe;
+mod progress;
+mod query;
+mod reference;
+mod relative;
+mod repair;
+mod rope;
+mod scan;
+mod serializer;
+mod shape;
+mod shared;
+mod sniff;
+mod span;
+mod split;
+mod stream;
 mod tokenizer;
+mod transcode;
+mod transform;
+mod visit;
 
 pub fn parse(input: String) -> Result<Value, ParseError> {
+    parse_with_options(input, &ParseOptions::default())
+}
+
+/// Parses the input, applying the given options.
+pub fn parse_with_options(input: String, options: &ParseOptions) -> Result<Value, ParseError> {
+    parse_with_interner(input, options, &mut Unchanged)
+}
+
+/// Parses the input like [`parse_with_options`], storing the string returned
+/// by the interner for every object key (and string value, see
+/// [`ParseOptions::intern_values`]).
+pub fn parse_with_interner(
+    input: String,
+    options: &ParseOptions,
+    interner: &mut dyn Interner,
+) -> Result<Value, ParseError> {
+    let mut context = Context {
+        options,
+        interner,
+        hook: &mut Unchanged,
+        path: JsonPointer::root(),
+        depth: 0,
+    };
+    parse_in(input, &mut context)
+}
+
+/// Parses the input like [`parse_with_options`], storing the value returned
+/// by the hook in place of every value parsed.
+pub fn parse_with_hook(
+    input: String,
+    options: &ParseOptions,
+    hook: &mut dyn ValueHook,
+) -> Result<Value, ParseError> {
+    let mut context = Context {
+        options,
+        interner: &mut Unchanged,
+        hook,
+        path: JsonPointer::root(),
+        depth: 0,
+    };
+    parse_in(input, &mut context)
+}
+
+fn parse_in(input: String, context: &mut Context<'_>) -> Result<Value, ParseError> {
     let tokens = tokenize(input)?;
-    let value = parse_tokens(&tokens, &mut 0)?;
+
+    if context.options.require_container_root
+        && !matches!(tokens.first(), Some(Token::LeftBrace | Token::LeftBracket))
+    {
+        return Err(TokenParseError::ExpectedContainerRoot.into());
+    }
+
+    let mut index = 0;
+    let value = parse_tokens_in(&tokens, &mut index, context)?;
+    if index < tokens.len() {
+        return Err(TokenParseError::TrailingContent.into());
+    }
     Ok(value)
 }
 
+/// Parses the input like [`parse`], but on failure also reports the tokens
+/// read before the error and where in the input the error was detected.
+pub fn parse_with_failure(input: String) -> Result<Value, ParseFailure> {
+    let (spanned, tokenize_error) = tokenize_partial(&input);
+    let (tokens, spans): (Vec<Token>, Vec<Span>) = spanned.into_iter().unzip();
+
+    if let Some((err, position)) = tokenize_error {
+        let error = ParseError::TokenizeError(err);
+        return Err(ParseFailure {
+            hint: suggest(&input, &error, position),
+            error,
+            consumed: tokens,
+            position,
+        });
+    }
+
+    let mut index = 0;
+    let result = parse_tokens(&tokens, &mut index).and_then(|value| match tokens.get(index) {
+        Some(_) => Err(TokenParseError::TrailingContent),
+        None => Ok(value),
+    });
+    result.map_err(|err| {
+        let position = match spans.get(index) {
+            Some(span) => span.start,
+            None => Position::START.advance_str(&input),
+        };
+        let mut consumed = tokens;
+        consumed.truncate(index);
+
+        let error = ParseError::ParseError(err);
+        ParseFailure {
+            hint: suggest(&input, &error, position),
+            error,
+            consumed,
+            position,
+        }
+    })
+}
+
 /// Representation of possible JSON values.
-#[derive(Debug, Clone, PartialEq)]
+///
+/// `Value` implements `Drop` to free deep documents without recursing, so
+/// its contents cannot be moved out by a `match` or `let` pattern; use
+/// [`Value::into_string`], [`Value::into_array`] and [`Value::into_object`],
+/// or `std::mem::take` on a `&mut` binding.
+#[derive(Debug)]
 pub enum Value {
     /// Literal 'null' value
     Null,
@@ -31,25 +269,106 @@ pub enum Value {
     Array(Vec<Value>),
 
     /// JSON value identified by a String key
-    Object(HashMap<String, Value>),
+    Object(Map<String, Value>),
+
+    /// RFC 3339 timestamp, read from and written as a string
+    #[cfg(feature = "datetime")]
+    DateTime(DateTime),
+
+    /// String stored in chunks, for very large strings
+    Rope(Rope),
 }
 
-#[derive(Debug, PartialEq)]
+/// Kinds of JSON values, without their contents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ValueKind {
+    Null,
+    Boolean,
+    String,
+    Number,
+    Array,
+    Object,
+}
+
+impl ValueKind {
+    /// Whether values of this kind contain other values.
+    pub fn is_container(&self) -> bool {
+        matches!(self, ValueKind::Array | ValueKind::Object)
+    }
+
+    pub fn is_scalar(&self) -> bool {
+        !self.is_container()
+    }
+}
+
+impl Value {
+    pub fn kind(&self) -> ValueKind {
+        match self {
+            Value::Null => ValueKind::Null,
+            Value::Boolean(_) => ValueKind::Boolean,
+            Value::String(_) => ValueKind::String,
+            // Timestamps stand in for the strings they are written as
+            #[cfg(feature = "datetime")]
+            Value::DateTime(_) => ValueKind::String,
+            Value::Rope(_) => ValueKind::String,
+            Value::Number(_) => ValueKind::Number,
+            Value::Array(_) => ValueKind::Array,
+            Value::Object(_) => ValueKind::Object,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub enum ParseError {
     TokenizeError(TokenizeError),
     ParseError(TokenParseError),
 }
 
-#[cfg(test)]
+/// Details about a failed parse, separating the valid prefix from the invalid suffix.
+#[derive(Debug, PartialEq)]
+pub struct ParseFailure {
+    /// Reason the parse failed
+    pub error: ParseError,
+
+    /// Tokens read before the one that caused the failure
+    pub consumed: Vec<Token>,
+
+    /// Position in the input where the failure was detected
+    pub position: Position,
+
+    /// How to fix the input when the failure looks like a common mistake,
+    /// see [`suggest`]
+    pub hint: Option<String>,
+}
+
 impl Value {
-    pub(crate) fn object<const N: usize>(pairs: [(&'static str, Self); N]) -> Self {
-        let owned_pairs = pairs.map(|(key, value)| (String::from(key), value));
-        let map = HashMap::from(owned_pairs);
+    /// Creates an object from key-value pairs. Later pairs replace earlier ones with the same key.
+    pub fn object<K, I>(pairs: I) -> Self
+    where
+        K: Into<String>,
+        I: IntoIterator<Item = (K, Value)>,
+    {
+        let map = pairs
+            .into_iter()
+            .map(|(key, value)| (key.into(), value))
+            .collect();
         Self::Object(map)
     }
 
-    pub(crate) fn string(s: &str) -> Self {
-        Self::String(String::from(s))
+    pub fn array<I: IntoIterator<Item = Value>>(values: I) -> Self {
+        Self::Array(values.into_iter().collect())
+    }
+
+    pub fn string(s: impl Into<String>) -> Self {
+        Self::String(s.into())
+    }
+
+    pub fn number(n: f64) -> Self {
+        Self::Number(n)
+    }
+
+    pub fn bool(b: bool) -> Self {
+        Self::Boolean(b)
     }
 }
 
@@ -64,3 +383,469 @@ impl From<TokenizeError> for ParseError {
         Self::TokenizeError(err)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        parse, parse_with_failure, parse_with_hook, parse_with_interner, parse_with_options,
+        JsonPointer, Map, ParseError, ParseOptions, Position, SizeLimits, Token, TokenParseError,
+        TokenizeError, Value,
+    };
+
+    fn container_root_options() -> ParseOptions {
+        ParseOptions {
+            require_container_root: true,
+            ..ParseOptions::default()
+        }
+    }
+
+    #[test]
+    fn accepts_scalar_root_by_default() {
+        let input = String::from("42");
+
+        let actual = parse(input);
+
+        assert_eq!(actual, Ok(Value::Number(42.0)));
+    }
+
+    #[test]
+    fn rejects_scalar_root_when_container_required() {
+        let inputs = ["42", "\"text\"", "null", " true"];
+
+        for input in inputs {
+            let actual = parse_with_options(String::from(input), &container_root_options());
+
+            assert_eq!(
+                actual,
+                Err(ParseError::ParseError(
+                    TokenParseError::ExpectedContainerRoot
+                )),
+                "input: {input}"
+            );
+        }
+    }
+
+    #[test]
+    fn accepts_container_root_when_required() {
+        let input = String::from("[null]");
+
+        let actual = parse_with_options(input, &container_root_options());
+
+        assert_eq!(actual, Ok(Value::Array(vec![Value::Null])));
+    }
+
+    #[test]
+    fn failure_reports_tokenize_error_position() {
+        let input = String::from("[1, @]");
+
+        let actual = parse_with_failure(input).unwrap_err();
+
+        assert_eq!(
+            actual.error,
+            ParseError::TokenizeError(TokenizeError::CharNotRecognized('@'))
+        );
+        assert_eq!(
+            actual.consumed,
+            [Token::LeftBracket, Token::Number(1.0), Token::Comma]
+        );
+        assert_eq!(actual.position, Position::START.advance_str("[1, "));
+    }
+
+    #[test]
+    fn failure_reports_parse_error_position() {
+        let input = String::from("{\"a\": 1\n \"b\": 2}");
+
+        let actual = parse_with_failure(input).unwrap_err();
+
+        assert_eq!(
+            actual.error,
+            ParseError::ParseError(TokenParseError::ExpectedComma)
+        );
+        assert_eq!(
+            actual.consumed,
+            [
+                Token::LeftBrace,
+                Token::string("a"),
+                Token::Colon,
+                Token::Number(1.0)
+            ]
+        );
+        assert_eq!(
+            actual.position,
+            Position {
+                line: 2,
+                column: 2,
+                offset: 9
+            }
+        );
+        assert_eq!(
+            actual.hint.as_deref(),
+            Some("missing `,` between values, or `:` after an object key")
+        );
+    }
+
+    #[test]
+    fn fails_on_truncated_input() {
+        let inputs = [
+            "[",
+            "[1",
+            "[1,",
+            "{",
+            r#"{"a""#,
+            r#"{"a":1"#,
+            r#"{"a":[1,{"b":2"#,
+        ];
+
+        for input in inputs {
+            let actual = parse(String::from(input));
+
+            assert_eq!(
+                actual,
+                Err(ParseError::ParseError(TokenParseError::UnexpectedEof)),
+                "input: {input}"
+            );
+        }
+    }
+
+    #[test]
+    fn parses_numbers_by_json_grammar() {
+        assert_eq!(parse(String::from("1e5")), Ok(Value::Number(1e5)));
+        assert_eq!(
+            parse(String::from("[1E-3, -2.5E+3]")),
+            Ok(Value::array([Value::Number(1e-3), Value::Number(-2500.0)]))
+        );
+        for input in ["012", "-01", "1."] {
+            assert_eq!(
+                parse(String::from(input)),
+                Err(ParseError::TokenizeError(
+                    TokenizeError::InvalidNumberLiteral(String::from(input))
+                )),
+                "input: {input}"
+            );
+        }
+    }
+
+    #[test]
+    fn fails_on_trailing_content() {
+        for input in ["1 2", "[1] [2]", "{} null"] {
+            let actual = parse(String::from(input));
+
+            assert_eq!(
+                actual,
+                Err(ParseError::ParseError(TokenParseError::TrailingContent)),
+                "input: {input}"
+            );
+        }
+    }
+
+    #[test]
+    fn failure_reports_start_of_trailing_content() {
+        let input = String::from("[1] [2]");
+
+        let actual = parse_with_failure(input).unwrap_err();
+
+        assert_eq!(
+            actual.error,
+            ParseError::ParseError(TokenParseError::TrailingContent)
+        );
+        assert_eq!(
+            actual.consumed,
+            [Token::LeftBracket, Token::Number(1.0), Token::RightBracket]
+        );
+        assert_eq!(actual.position, Position::START.advance_str("[1] "));
+    }
+
+    #[test]
+    fn failure_reports_end_of_truncated_input() {
+        let input = String::from("[1");
+
+        let actual = parse_with_failure(input).unwrap_err();
+
+        assert_eq!(
+            actual.error,
+            ParseError::ParseError(TokenParseError::UnexpectedEof)
+        );
+        assert_eq!(actual.consumed, [Token::LeftBracket, Token::Number(1.0)]);
+        assert_eq!(actual.position, Position::START.advance_str("[1"));
+    }
+
+    #[test]
+    fn failure_reports_position_of_missing_member_value() {
+        let input = String::from("{\"id\": 1, \"a\":\n}");
+
+        let actual = parse_with_failure(input).unwrap_err();
+
+        assert_eq!(
+            actual.error,
+            ParseError::ParseError(TokenParseError::ExpectedValueForKey(String::from("a")))
+        );
+        assert_eq!(
+            actual.position,
+            Position {
+                line: 2,
+                column: 1,
+                offset: 15
+            }
+        );
+    }
+
+    #[test]
+    fn failure_reports_position_of_mismatched_bracket() {
+        let input = String::from("[{]}");
+
+        let actual = parse_with_failure(input).unwrap_err();
+
+        assert_eq!(
+            actual.error,
+            ParseError::ParseError(TokenParseError::MismatchedBracket {
+                expected: '}',
+                found: ']'
+            })
+        );
+        assert_eq!(actual.position, Position::START.advance_str("[{"));
+    }
+
+    #[test]
+    fn failure_api_parses_valid_input() {
+        let input = String::from("[true]");
+
+        let actual = parse_with_failure(input);
+
+        assert_eq!(actual, Ok(Value::Array(vec![Value::Boolean(true)])));
+    }
+
+    #[cfg(feature = "preserve_order")]
+    #[test]
+    fn preserves_member_order() {
+        let input = String::from(r#"{"z": 1, "a": 2, "m": {"y": 3, "b": 4}, "z": 5}"#);
+        let expected = ["z", "a", "m"];
+
+        let actual = parse(input).unwrap();
+
+        let Value::Object(map) = &actual else {
+            panic!("expected an object");
+        };
+        assert_eq!(map.keys().collect::<Vec<_>>(), expected);
+        assert_eq!(map.get("z"), Some(&Value::Number(5.0)));
+    }
+
+    #[test]
+    fn builds_values_with_constructors() {
+        let key = String::from("dynamic");
+        let expected = Value::Object(Map::from([
+            (
+                String::from("dynamic"),
+                Value::Array(vec![Value::Number(1.0), Value::Boolean(false)]),
+            ),
+            (String::from("name"), Value::String(String::from("x"))),
+        ]));
+
+        let actual = Value::object([
+            (
+                key.as_str(),
+                Value::array([Value::number(1.0), Value::bool(false)]),
+            ),
+            ("name", Value::string(key.replace("dynamic", "x"))),
+        ]);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn interns_object_keys() {
+        let input = String::from(r#"[{"id": "a"}, {"id": "b", "tag": "id"}]"#);
+        let mut symbols: Vec<String> = Vec::new();
+        let mut interner = |key: String| {
+            if !symbols.contains(&key) {
+                symbols.push(key.clone());
+            }
+            key.to_uppercase()
+        };
+        let expected = Value::array([
+            Value::object([("ID", Value::string("a"))]),
+            Value::object([("ID", Value::string("b")), ("TAG", Value::string("id"))]),
+        ]);
+
+        let actual = parse_with_interner(input, &ParseOptions::default(), &mut interner).unwrap();
+
+        assert_eq!(actual, expected);
+        assert_eq!(symbols, ["id", "tag"]);
+    }
+
+    #[test]
+    fn interns_string_values_when_requested() {
+        let input = String::from(r#"{"Key": ["Value", 1]}"#);
+        let options = ParseOptions {
+            lowercase_keys: true,
+            intern_values: true,
+            ..ParseOptions::default()
+        };
+        let mut seen = Vec::new();
+        let mut interner = |s: String| {
+            seen.push(s.clone());
+            s
+        };
+
+        parse_with_interner(input, &options, &mut interner).unwrap();
+
+        assert_eq!(seen, ["key", "Value"]);
+    }
+
+    fn attachment_limits() -> ParseOptions {
+        ParseOptions {
+            size_limits: SizeLimits {
+                max_string_len: Some(8),
+                max_array_len: Some(2),
+            },
+            scoped_size_limits: vec![(
+                JsonPointer::parse("/attachment/data").unwrap(),
+                SizeLimits {
+                    max_string_len: Some(64),
+                    max_array_len: None,
+                },
+            )],
+            ..ParseOptions::default()
+        }
+    }
+
+    #[test]
+    fn scoped_size_limits_override_global_limits() {
+        let input = String::from(
+            r#"{"name": "a.png", "attachment": {"data": "aGVsbG8gd29ybGQgaGVsbG8gd29ybGQ="}}"#,
+        );
+
+        let actual = parse_with_options(input, &attachment_limits()).unwrap();
+
+        assert_eq!(
+            actual.pointer("/attachment/data").unwrap(),
+            &Value::string("aGVsbG8gd29ybGQgaGVsbG8gd29ybGQ=")
+        );
+    }
+
+    #[test]
+    fn rejects_values_over_size_limits() {
+        let input = String::from(r#"{"attachment": {"name": "holiday.png"}}"#);
+
+        let actual = parse_with_options(input, &attachment_limits()).unwrap_err();
+
+        assert_eq!(
+            actual,
+            ParseError::ParseError(TokenParseError::StringTooLong {
+                pointer: JsonPointer::parse("/attachment/name").unwrap(),
+                limit: 8,
+            })
+        );
+
+        let input = String::from(r#"{"tags": [1, 2, 3]}"#);
+
+        let actual = parse_with_options(input, &attachment_limits()).unwrap_err();
+
+        assert_eq!(
+            actual,
+            ParseError::ParseError(TokenParseError::ArrayTooLong {
+                pointer: JsonPointer::parse("/tags").unwrap(),
+                limit: 2,
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_nesting_beyond_max_depth() {
+        let nested = |depth: usize| format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+        let options = ParseOptions {
+            max_depth: 3,
+            ..ParseOptions::default()
+        };
+        let expected = Err(ParseError::ParseError(TokenParseError::NestingTooDeep {
+            limit: 3,
+        }));
+
+        assert!(parse_with_options(nested(3), &options).is_ok());
+        assert_eq!(parse_with_options(nested(4), &options), expected);
+        assert_eq!(
+            parse_with_options(String::from(r#"[{"a": [{}]}]"#), &options),
+            expected
+        );
+    }
+
+    #[test]
+    fn rejects_hostile_nesting_by_default() {
+        let input = "[".repeat(100_000);
+        let expected = Err(ParseError::ParseError(TokenParseError::NestingTooDeep {
+            limit: 128,
+        }));
+
+        assert_eq!(parse(input), expected);
+        assert!(parse("[".repeat(128) + &"]".repeat(128)).is_ok());
+    }
+
+    #[test]
+    fn deduplicates_matching_arrays() {
+        let input = String::from(
+            r#"{"users": [{"tags": ["a", "b", "a"]}, {"tags": [1, {"x": 1}, 1, {"x": 1}]},
+                           {"tags": [0, -0.0]}],
+                "other": ["a", "a"]}"#,
+        );
+        let options = ParseOptions {
+            dedup_arrays: vec![JsonPointer::parse("/users/*/tags").unwrap()],
+            ..ParseOptions::default()
+        };
+
+        let actual = parse_with_options(input, &options).unwrap();
+
+        assert_eq!(
+            actual.pointer("/users/0/tags"),
+            Some(&Value::array([Value::string("a"), Value::string("b")]))
+        );
+        assert_eq!(
+            actual.pointer("/users/1/tags"),
+            Some(&Value::array([
+                Value::Number(1.0),
+                Value::object([("x", Value::Number(1.0))])
+            ]))
+        );
+        assert_eq!(
+            actual.pointer("/users/2/tags"),
+            Some(&Value::array([Value::Number(0.0)]))
+        );
+        assert_eq!(
+            actual.get("other"),
+            Some(&Value::array([Value::string("a"), Value::string("a")]))
+        );
+    }
+
+    #[test]
+    fn applies_hook_while_building_tree() {
+        let input = String::from(
+            r#"{"order": {"amount": "12.50", "note": "12", "placed": {"$date": "2024-01-02"}}}"#,
+        );
+        let mut hook = |key: Option<&str>, mut value: Value| {
+            match (key, &mut value) {
+                (Some("amount"), Value::String(s)) => {
+                    if let Ok(n) = s.parse() {
+                        return Value::Number(n);
+                    }
+                }
+                (_, Value::Object(map)) if map.len() == 1 => {
+                    if let Some(date) = map.remove("$date") {
+                        return date;
+                    }
+                }
+                _ => {}
+            }
+            value
+        };
+        let expected = Value::object([(
+            "order",
+            Value::object([
+                ("amount", Value::Number(12.5)),
+                ("note", Value::string("12")),
+                ("placed", Value::string("2024-01-02")),
+            ]),
+        )]);
+
+        let actual = parse_with_hook(input, &ParseOptions::default(), &mut hook).unwrap();
+
+        assert_eq!(actual, expected);
+    }
+}
@@ -0,0 +1,409 @@
+use std::{iter::Peekable, str::Chars};
+
+use crate::{
+    convert::mismatch, map::Entry, pointer::parse_index, KindMismatch, PointerError, PointerLimits,
+    Value, ValueKind,
+};
+
+/// Whether an object member is absent, explicitly `null` or holds a value
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FieldState<'a> {
+    /// The key is not in the object
+    Missing,
+
+    /// The member is `null`
+    Null,
+
+    /// The member holds a value other than `null`
+    Present(&'a Value),
+}
+
+impl<'a> FieldState<'a> {
+    pub fn is_missing(&self) -> bool {
+        matches!(self, FieldState::Missing)
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, FieldState::Null)
+    }
+
+    /// The value of the member, `None` when it is missing or `null`.
+    pub fn present(&self) -> Option<&'a Value> {
+        match self {
+            FieldState::Present(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+impl Value {
+    /// Looks up an object member, `None` when the key is absent or the value
+    /// is not an object.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Object(map) => map.get(key),
+            _ => None,
+        }
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut Value> {
+        match self {
+            Value::Object(map) => map.get_mut(key),
+            _ => None,
+        }
+    }
+
+    /// Member `key` of an object for in-place manipulation, like
+    /// `HashMap::entry`. Fails for anything but an object.
+    pub fn entry(&mut self, key: impl Into<String>) -> Result<Entry<'_>, KindMismatch> {
+        match self {
+            Value::Object(map) => Ok(map.entry(key.into())),
+            value => Err(mismatch(ValueKind::Object, value)),
+        }
+    }
+
+    /// Looks up an array element, `None` when the index is out of range or
+    /// the value is not an array.
+    pub fn get_index(&self, index: usize) -> Option<&Value> {
+        match self {
+            Value::Array(values) => values.get(index),
+            _ => None,
+        }
+    }
+
+    pub fn get_index_mut(&mut self, index: usize) -> Option<&mut Value> {
+        match self {
+            Value::Array(values) => values.get_mut(index),
+            _ => None,
+        }
+    }
+
+    /// Looks up a value by a dotted path such as `servers[2].host`: keys are
+    /// separated by `.` and array indexes are given in brackets. `\` escapes
+    /// the next character in a key. `None` when the path is malformed or
+    /// nothing is there; the empty path is the value itself.
+    pub fn get_path(&self, path: &str) -> Option<&Value> {
+        follow_path(&parse_path(path)?, self)
+    }
+
+    /// Looks up a dotted path from untrusted input like [`Value::get_path`],
+    /// checking its length and number of segments against `limits` first.
+    /// `Ok(None)` when nothing is there.
+    pub fn get_path_with_limits(
+        &self,
+        path: &str,
+        limits: &PointerLimits,
+    ) -> Result<Option<&Value>, PointerError> {
+        if path.len() > limits.max_length {
+            return Err(PointerError::TooLong {
+                length: path.len(),
+                limit: limits.max_length,
+            });
+        }
+        let segments =
+            parse_path(path).ok_or_else(|| PointerError::InvalidSyntax(path.to_string()))?;
+        if segments.len() > limits.max_depth {
+            return Err(PointerError::TooDeep {
+                depth: segments.len(),
+                limit: limits.max_depth,
+            });
+        }
+        Ok(follow_path(&segments, self))
+    }
+
+    /// Looks up an object member, telling an absent key apart from an
+    /// explicit `null`. Anything but an object has no members, so every key
+    /// is missing from it.
+    pub fn get_opt(&self, key: &str) -> FieldState<'_> {
+        let Value::Object(map) = self else {
+            return FieldState::Missing;
+        };
+
+        match map.get(key) {
+            None => FieldState::Missing,
+            Some(Value::Null) => FieldState::Null,
+            Some(value) => FieldState::Present(value),
+        }
+    }
+
+    /// Looks up an object member by key ignoring case. An exact match is
+    /// preferred; otherwise the first member matching case-insensitively is
+    /// returned, so the result is unspecified when several keys differ only in case.
+    pub fn get_ignore_case(&self, key: &str) -> Option<&Value> {
+        let Value::Object(map) = self else {
+            return None;
+        };
+
+        map.get(key).or_else(|| {
+            map.iter()
+                .find(|(candidate, _)| eq_ignore_case(candidate, key))
+                .map(|(_, value)| value)
+        })
+    }
+}
+
+/// A step of a dotted path
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+/// Splits a path like `servers[2].host` into keys and indexes. `\` escapes
+/// the next character, so keys can contain `.`, `[` and `\`.
+pub(crate) fn parse_path(path: &str) -> Option<Vec<Segment>> {
+    let mut segments = Vec::new();
+    let mut chars = path.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            '[' => {
+                chars.next();
+                let mut digits = String::new();
+                loop {
+                    match chars.next()? {
+                        ']' => break,
+                        c => digits.push(c),
+                    }
+                }
+                segments.push(Segment::Index(parse_index(&digits)?));
+            }
+            '.' if !segments.is_empty() => {
+                chars.next();
+                segments.push(Segment::Key(read_key(&mut chars)?));
+            }
+            _ if segments.is_empty() => segments.push(Segment::Key(read_key(&mut chars)?)),
+            _ => return None,
+        }
+    }
+
+    Some(segments)
+}
+
+/// Value at the end of a parsed dotted path.
+pub(crate) fn follow_path<'a>(segments: &[Segment], value: &'a Value) -> Option<&'a Value> {
+    segments
+        .iter()
+        .try_fold(value, |value, segment| match segment {
+            Segment::Key(key) => value.get(key),
+            Segment::Index(index) => value.get_index(*index),
+        })
+}
+
+/// Reads an unescaped key up to the next unescaped `.` or `[`. Keys cannot be empty.
+fn read_key(chars: &mut Peekable<Chars<'_>>) -> Option<String> {
+    let mut key = String::new();
+    while let Some(&c) = chars.peek() {
+        match c {
+            '.' | '[' => break,
+            '\\' => {
+                chars.next();
+                key.push(chars.next()?);
+            }
+            c => {
+                chars.next();
+                key.push(c);
+            }
+        }
+    }
+    (!key.is_empty()).then_some(key)
+}
+
+fn eq_ignore_case(a: &str, b: &str) -> bool {
+    a.chars()
+        .flat_map(char::to_lowercase)
+        .eq(b.chars().flat_map(char::to_lowercase))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{KindMismatch, PointerError, PointerLimits, Value, ValueKind};
+
+    use super::FieldState;
+
+    fn headers() -> Value {
+        Value::object([
+            ("Content-Type", Value::string("application/json")),
+            ("ÄRGER", Value::Boolean(true)),
+        ])
+    }
+
+    #[test]
+    fn navigates_members_and_elements() {
+        let value = Value::object([(
+            "user",
+            Value::object([("tags", Value::array([Value::string("admin")]))]),
+        )]);
+
+        let actual = value
+            .get("user")
+            .and_then(|user| user.get("tags"))
+            .and_then(|tags| tags.get_index(0));
+
+        assert_eq!(actual, Some(&Value::string("admin")));
+        assert_eq!(value.get("missing"), None);
+        assert_eq!(value.get_index(0), None);
+        assert_eq!(
+            value
+                .get("user")
+                .and_then(|user| user.get("tags"))
+                .and_then(|tags| tags.get_index(1)),
+            None
+        );
+    }
+
+    #[test]
+    fn modifies_through_mutable_lookups() {
+        let mut value = Value::object([("list", Value::array([Value::Null]))]);
+
+        if let Some(element) = value.get_mut("list").and_then(|list| list.get_index_mut(0)) {
+            *element = Value::Boolean(true);
+        }
+
+        assert_eq!(
+            value,
+            Value::object([("list", Value::array([Value::Boolean(true)]))])
+        );
+        assert_eq!(value.get_mut("other"), None);
+        assert_eq!(Value::Null.get_index_mut(0), None);
+    }
+
+    #[test]
+    fn follows_dotted_path() {
+        let value = Value::object([
+            (
+                "servers",
+                Value::array([Value::Null, Value::object([("host", Value::string("b"))])]),
+            ),
+            ("a.b", Value::object([("[c]", Value::Boolean(true))])),
+            ("list", Value::array([Value::array([Value::Number(1.0)])])),
+        ]);
+
+        assert_eq!(value.get_path("servers[1].host"), Some(&Value::string("b")));
+        assert_eq!(value.get_path(r"a\.b.\[c]"), Some(&Value::Boolean(true)));
+        assert_eq!(value.get_path("list[0][0]"), Some(&Value::Number(1.0)));
+        assert_eq!(value.get_path(""), Some(&value));
+        assert_eq!(value.get_path("servers[2]"), None);
+        assert_eq!(value.get_path("servers.host"), None);
+    }
+
+    #[test]
+    fn rejects_malformed_paths() {
+        let value = Value::object([("a", Value::array([Value::Null]))]);
+
+        for path in ["a[x]", "a[01]", "a..b", ".a", "a.", "a[0]b", "a[0", r"a\"] {
+            assert_eq!(value.get_path(path), None, "{path}");
+        }
+        assert_eq!(
+            Value::array([Value::Null]).get_path("[0]"),
+            Some(&Value::Null)
+        );
+    }
+
+    #[test]
+    fn finds_key_with_different_case() {
+        let actual = headers().get_ignore_case("content-type").cloned();
+
+        assert_eq!(actual, Some(Value::string("application/json")));
+    }
+
+    #[test]
+    fn finds_non_ascii_key() {
+        let actual = headers().get_ignore_case("ärger").cloned();
+
+        assert_eq!(actual, Some(Value::Boolean(true)));
+    }
+
+    #[test]
+    fn prefers_exact_match() {
+        let value = Value::object([("id", Value::Number(1.0)), ("ID", Value::Number(2.0))]);
+
+        let actual = value.get_ignore_case("ID").cloned();
+
+        assert_eq!(actual, Some(Value::Number(2.0)));
+    }
+
+    #[test]
+    fn misses_absent_key_and_non_objects() {
+        assert_eq!(headers().get_ignore_case("accept"), None);
+        assert_eq!(Value::Null.get_ignore_case("a"), None);
+    }
+
+    #[test]
+    fn distinguishes_missing_from_null() {
+        let patch = Value::object([("name", Value::Null), ("age", Value::Number(3.0))]);
+
+        assert_eq!(patch.get_opt("name"), FieldState::Null);
+        assert_eq!(
+            patch.get_opt("age"),
+            FieldState::Present(&Value::Number(3.0))
+        );
+        assert_eq!(patch.get_opt("email"), FieldState::Missing);
+        assert!(Value::array([]).get_opt("name").is_missing());
+    }
+
+    #[test]
+    fn upserts_through_entries() {
+        let mut value = Value::object([("hits", Value::Number(1.0))]);
+
+        for key in ["hits", "misses"] {
+            value
+                .entry(key)
+                .unwrap()
+                .and_modify(|count| *count = Value::Number(count.as_f64().unwrap() + 1.0))
+                .or_insert(Value::Number(1.0));
+        }
+        let tags = value
+            .entry("tags")
+            .unwrap()
+            .or_insert_with(|| Value::array([]));
+        *tags = Value::array([Value::string("new")]);
+
+        assert_eq!(value["hits"], Value::Number(2.0));
+        assert_eq!(value["misses"], Value::Number(1.0));
+        assert_eq!(value["tags"][0], Value::string("new"));
+    }
+
+    #[test]
+    fn entry_requires_object() {
+        let mut value = Value::array([]);
+
+        assert_eq!(
+            value.entry("a").err(),
+            Some(KindMismatch {
+                expected: ValueKind::Object,
+                found: ValueKind::Array
+            })
+        );
+    }
+
+    #[test]
+    fn limits_untrusted_paths() {
+        let value = Value::object([("a", Value::object([("b", Value::Number(1.0))]))]);
+        let limits = PointerLimits {
+            max_depth: 2,
+            max_length: 8,
+        };
+
+        assert_eq!(
+            value.get_path_with_limits("a.b", &limits),
+            Ok(Some(&Value::Number(1.0)))
+        );
+        assert_eq!(value.get_path_with_limits("a.c", &limits), Ok(None));
+        assert_eq!(
+            value.get_path_with_limits("a.b.c", &limits),
+            Err(PointerError::TooDeep { depth: 3, limit: 2 })
+        );
+        assert_eq!(
+            value.get_path_with_limits("abcdefghi", &limits),
+            Err(PointerError::TooLong {
+                length: 9,
+                limit: 8
+            })
+        );
+        assert_eq!(
+            value.get_path_with_limits("a..b", &limits),
+            Err(PointerError::InvalidSyntax(String::from("a..b")))
+        );
+    }
+}
@@ -0,0 +1,135 @@
+/// Builds a [`Value`](crate::Value) from JSON literal syntax.
+///
+/// Any other Rust expression in value position is converted with
+/// `Value::from`, and an object key may be a parenthesized expression
+/// evaluating to something `String::from` accepts.
+///
+/// `json!({ "a": [1, 2, true], "b": null })`
+#[macro_export]
+macro_rules! json {
+    (null) => {
+        $crate::Value::Null
+    };
+    ([]) => {
+        $crate::Value::Array(::std::vec::Vec::new())
+    };
+    ([ $($elements:tt)+ ]) => {
+        $crate::Value::Array($crate::json!(@array [] $($elements)+))
+    };
+    ({}) => {
+        $crate::Value::Object($crate::Map::new())
+    };
+    ({ $($members:tt)+ }) => {{
+        let mut map = $crate::Map::new();
+        $crate::json!(@object map $($members)+);
+        $crate::Value::Object(map)
+    }};
+    ($other:expr) => {
+        $crate::Value::from($other)
+    };
+
+    // Elements are collected one at a time, `null` and containers before
+    // plain expressions since a captured `expr` can no longer be matched
+    // against literal syntax.
+    (@array [$($done:expr,)*]) => {
+        ::std::vec![$($done),*]
+    };
+    (@array [$($done:expr,)*] null $(, $($rest:tt)*)?) => {
+        $crate::json!(@array [$($done,)* $crate::Value::Null,] $($($rest)*)?)
+    };
+    (@array [$($done:expr,)*] [$($inner:tt)*] $(, $($rest:tt)*)?) => {
+        $crate::json!(@array [$($done,)* $crate::json!([$($inner)*]),] $($($rest)*)?)
+    };
+    (@array [$($done:expr,)*] {$($inner:tt)*} $(, $($rest:tt)*)?) => {
+        $crate::json!(@array [$($done,)* $crate::json!({$($inner)*}),] $($($rest)*)?)
+    };
+    (@array [$($done:expr,)*] $next:expr $(, $($rest:tt)*)?) => {
+        $crate::json!(@array [$($done,)* $crate::Value::from($next),] $($($rest)*)?)
+    };
+
+    (@object $map:ident) => {};
+    (@object $map:ident $key:tt : null $(, $($rest:tt)*)?) => {
+        $map.insert(::std::string::String::from($key), $crate::Value::Null);
+        $crate::json!(@object $map $($($rest)*)?);
+    };
+    (@object $map:ident $key:tt : [$($inner:tt)*] $(, $($rest:tt)*)?) => {
+        $map.insert(::std::string::String::from($key), $crate::json!([$($inner)*]));
+        $crate::json!(@object $map $($($rest)*)?);
+    };
+    (@object $map:ident $key:tt : {$($inner:tt)*} $(, $($rest:tt)*)?) => {
+        $map.insert(::std::string::String::from($key), $crate::json!({$($inner)*}));
+        $crate::json!(@object $map $($($rest)*)?);
+    };
+    (@object $map:ident $key:tt : $value:expr $(, $($rest:tt)*)?) => {
+        $map.insert(::std::string::String::from($key), $crate::Value::from($value));
+        $crate::json!(@object $map $($($rest)*)?);
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Value;
+
+    #[test]
+    fn builds_nested_literals() {
+        let expected = Value::object([
+            (
+                "a",
+                Value::Array(vec![
+                    Value::Number(1.0),
+                    Value::Number(2.0),
+                    Value::Boolean(true),
+                ]),
+            ),
+            ("b", Value::Null),
+            ("c", Value::object([("d", Value::Array(vec![]))])),
+        ]);
+
+        let actual = json!({ "a": [1, 2, true], "b": null, "c": { "d": [] } });
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn splices_expressions() {
+        let name = String::from("ada");
+        let scores = vec![Value::from(1.5), json!(null)];
+        let key = "dynamic";
+        let expected = Value::object([
+            ("name", Value::String(String::from("ada"))),
+            ("total", Value::Number(-3.0)),
+            (
+                "scores",
+                Value::Array(vec![Value::Number(1.5), Value::Null]),
+            ),
+            (
+                "dynamic",
+                Value::Array(vec![Value::Null, Value::Number(4.0)]),
+            ),
+        ]);
+
+        let actual = json!({
+            "name": name,
+            "total": -1 - 2,
+            "scores": scores,
+            (key): [None::<f64>, 2 * 2],
+        });
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn builds_scalars() {
+        let cases = [
+            (json!(null), Value::Null),
+            (json!(false), Value::Boolean(false)),
+            (json!("x"), Value::String(String::from("x"))),
+            (json!(7u8), Value::Number(7.0)),
+            (json!({}), Value::Object(crate::Map::new())),
+        ];
+
+        for (actual, expected) in cases {
+            assert_eq!(actual, expected);
+        }
+    }
+}
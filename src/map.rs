@@ -0,0 +1,325 @@
+//! Map type backing [`crate::Value::Object`].
+//!
+//! By default objects are stored in a `HashMap`. With the `preserve_order`
+//! feature they are stored in an [`OrderedMap`] that keeps members in the
+//! order they were first inserted, which is the order they appear in the
+//! parsed input.
+
+#[cfg(not(feature = "preserve_order"))]
+/// Map of object members
+pub type Map<K = String, V = crate::Value> = std::collections::HashMap<K, V>;
+
+#[cfg(feature = "preserve_order")]
+/// Map of object members, in insertion order
+pub type Map<K = String, V = crate::Value> = OrderedMap<K, V>;
+
+#[cfg(not(feature = "preserve_order"))]
+/// Member of a [`Map`] that may or may not be present
+pub type Entry<'a, K = String, V = crate::Value> = std::collections::hash_map::Entry<'a, K, V>;
+
+#[cfg(feature = "preserve_order")]
+pub use ordered::{Entry, OrderedMap};
+
+#[cfg(feature = "preserve_order")]
+mod ordered {
+    use std::{
+        borrow::Borrow,
+        collections::HashMap,
+        fmt::{self, Debug},
+        hash::Hash,
+        iter,
+        ops::Index,
+        slice, vec,
+    };
+
+    /// Hash map that iterates in insertion order.
+    ///
+    /// Mirrors the parts of the `HashMap` API the crate uses, so code written
+    /// against [`super::Map`] compiles with and without `preserve_order`.
+    /// Equality ignores order, like it does for `HashMap`.
+    #[derive(Clone)]
+    pub struct OrderedMap<K, V> {
+        entries: Vec<(K, V)>,
+        indexes: HashMap<K, usize>,
+    }
+
+    impl<K, V> OrderedMap<K, V> {
+        pub fn new() -> Self {
+            Self {
+                entries: Vec::new(),
+                indexes: HashMap::new(),
+            }
+        }
+
+        pub fn with_capacity(capacity: usize) -> Self {
+            Self {
+                entries: Vec::with_capacity(capacity),
+                indexes: HashMap::with_capacity(capacity),
+            }
+        }
+
+        pub fn len(&self) -> usize {
+            self.entries.len()
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.entries.is_empty()
+        }
+
+        pub fn iter(&self) -> Iter<'_, K, V> {
+            self.entries.iter().map(|(key, value)| (key, value))
+        }
+
+        pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+            self.entries.iter_mut().map(|(key, value)| (&*key, value))
+        }
+
+        pub fn keys(&self) -> impl Iterator<Item = &K> {
+            self.entries.iter().map(|(key, _)| key)
+        }
+
+        pub fn values(&self) -> impl Iterator<Item = &V> {
+            self.entries.iter().map(|(_, value)| value)
+        }
+
+        pub fn values_mut(&mut self) -> impl Iterator<Item = &mut V> {
+            self.entries.iter_mut().map(|(_, value)| value)
+        }
+
+        pub fn into_values(self) -> impl Iterator<Item = V> {
+            self.entries.into_iter().map(|(_, value)| value)
+        }
+    }
+
+    impl<K: Hash + Eq + Clone, V> OrderedMap<K, V> {
+        /// Inserts the member, keeping the position of an existing member with the same key.
+        pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+            match self.indexes.get(&key) {
+                Some(&index) => Some(std::mem::replace(&mut self.entries[index].1, value)),
+                None => {
+                    self.indexes.insert(key.clone(), self.entries.len());
+                    self.entries.push((key, value));
+                    None
+                }
+            }
+        }
+
+        pub fn get<Q>(&self, key: &Q) -> Option<&V>
+        where
+            K: Borrow<Q>,
+            Q: Hash + Eq + ?Sized,
+        {
+            let index = *self.indexes.get(key)?;
+            Some(&self.entries[index].1)
+        }
+
+        pub fn get_key_value<Q>(&self, key: &Q) -> Option<(&K, &V)>
+        where
+            K: Borrow<Q>,
+            Q: Hash + Eq + ?Sized,
+        {
+            let index = *self.indexes.get(key)?;
+            let (key, value) = &self.entries[index];
+            Some((key, value))
+        }
+
+        pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+        where
+            K: Borrow<Q>,
+            Q: Hash + Eq + ?Sized,
+        {
+            let index = *self.indexes.get(key)?;
+            Some(&mut self.entries[index].1)
+        }
+
+        pub fn contains_key<Q>(&self, key: &Q) -> bool
+        where
+            K: Borrow<Q>,
+            Q: Hash + Eq + ?Sized,
+        {
+            self.indexes.contains_key(key)
+        }
+
+        /// Removes the member, shifting the following members to keep their order.
+        pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+        where
+            K: Borrow<Q>,
+            Q: Hash + Eq + ?Sized,
+        {
+            let index = self.indexes.remove(key)?;
+            let (_, value) = self.entries.remove(index);
+            for (moved, _) in &self.entries[index..] {
+                *self.indexes.get_mut::<K>(moved).unwrap() -= 1;
+            }
+            Some(value)
+        }
+
+        pub fn entry(&mut self, key: K) -> Entry<'_, K, V> {
+            Entry { map: self, key }
+        }
+    }
+
+    /// A member that may or may not be present, see [`OrderedMap::entry`]
+    pub struct Entry<'a, K = String, V = crate::Value> {
+        map: &'a mut OrderedMap<K, V>,
+        key: K,
+    }
+
+    impl<'a, K: Hash + Eq + Clone, V> Entry<'a, K, V> {
+        pub fn key(&self) -> &K {
+            &self.key
+        }
+
+        pub fn or_insert(self, default: V) -> &'a mut V {
+            self.or_insert_with(|| default)
+        }
+
+        pub fn or_default(self) -> &'a mut V
+        where
+            V: Default,
+        {
+            self.or_insert_with(V::default)
+        }
+
+        /// Calls `f` with the member's value when it is present.
+        pub fn and_modify<F: FnOnce(&mut V)>(self, f: F) -> Self {
+            if let Some(value) = self.map.get_mut(&self.key) {
+                f(value);
+            }
+            self
+        }
+
+        pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V {
+            let index = match self.map.indexes.get(&self.key) {
+                Some(&index) => index,
+                None => {
+                    self.map.insert(self.key, default());
+                    self.map.entries.len() - 1
+                }
+            };
+            &mut self.map.entries[index].1
+        }
+    }
+
+    impl<K, V> Default for OrderedMap<K, V> {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl<K: Debug, V: Debug> Debug for OrderedMap<K, V> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_map().entries(self.iter()).finish()
+        }
+    }
+
+    impl<K: Hash + Eq + Clone, V: PartialEq> PartialEq for OrderedMap<K, V> {
+        fn eq(&self, other: &Self) -> bool {
+            self.len() == other.len()
+                && self
+                    .iter()
+                    .all(|(key, value)| other.get(key) == Some(value))
+        }
+    }
+
+    impl<K: Hash + Eq + Clone, V: Eq> Eq for OrderedMap<K, V> {}
+
+    impl<K, Q, V> Index<&Q> for OrderedMap<K, V>
+    where
+        K: Hash + Eq + Clone + Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        type Output = V;
+
+        fn index(&self, key: &Q) -> &V {
+            self.get(key).expect("key not found in map")
+        }
+    }
+
+    impl<K: Hash + Eq + Clone, V> FromIterator<(K, V)> for OrderedMap<K, V> {
+        fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+            let mut map = Self::new();
+            for (key, value) in iter {
+                map.insert(key, value);
+            }
+            map
+        }
+    }
+
+    impl<K: Hash + Eq + Clone, V, const N: usize> From<[(K, V); N]> for OrderedMap<K, V> {
+        fn from(entries: [(K, V); N]) -> Self {
+            entries.into_iter().collect()
+        }
+    }
+
+    pub type Iter<'a, K, V> = iter::Map<slice::Iter<'a, (K, V)>, fn(&'a (K, V)) -> (&'a K, &'a V)>;
+
+    pub type IterMut<'a, K, V> =
+        iter::Map<slice::IterMut<'a, (K, V)>, fn(&'a mut (K, V)) -> (&'a K, &'a mut V)>;
+
+    impl<K, V> IntoIterator for OrderedMap<K, V> {
+        type Item = (K, V);
+        type IntoIter = vec::IntoIter<(K, V)>;
+
+        fn into_iter(self) -> Self::IntoIter {
+            self.entries.into_iter()
+        }
+    }
+
+    impl<'a, K, V> IntoIterator for &'a OrderedMap<K, V> {
+        type Item = (&'a K, &'a V);
+        type IntoIter = Iter<'a, K, V>;
+
+        fn into_iter(self) -> Self::IntoIter {
+            self.iter()
+        }
+    }
+
+    impl<'a, K, V> IntoIterator for &'a mut OrderedMap<K, V> {
+        type Item = (&'a K, &'a mut V);
+        type IntoIter = IterMut<'a, K, V>;
+
+        fn into_iter(self) -> Self::IntoIter {
+            self.iter_mut()
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::OrderedMap;
+
+        #[test]
+        fn iterates_in_insertion_order() {
+            let mut map = OrderedMap::new();
+            map.insert("b", 1);
+            map.insert("a", 2);
+            map.insert("c", 3);
+            map.insert("b", 4);
+            let expected = [("b", 4), ("a", 2), ("c", 3)];
+
+            let actual: Vec<_> = map.into_iter().collect();
+
+            assert_eq!(actual, expected);
+        }
+
+        #[test]
+        fn remove_keeps_order_of_remaining_members() {
+            let mut map = OrderedMap::from([("a", 1), ("b", 2), ("c", 3)]);
+
+            let removed = map.remove("a");
+            let actual: Vec<_> = map.keys().copied().collect();
+
+            assert_eq!(removed, Some(1));
+            assert_eq!(actual, ["b", "c"]);
+            assert_eq!(map.get("c"), Some(&3));
+        }
+
+        #[test]
+        fn equality_ignores_order() {
+            let left = OrderedMap::from([("a", 1), ("b", 2)]);
+            let right = OrderedMap::from([("b", 2), ("a", 1)]);
+
+            assert_eq!(left, right);
+        }
+    }
+}
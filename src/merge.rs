@@ -0,0 +1,253 @@
+use std::mem;
+
+use crate::{Map, Value};
+
+/// How [`Value::merge`] combines two values. Objects are always merged
+/// member by member; any other pair is combined by replacing the old value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MergeStrategy {
+    /// How an array is combined with an array
+    pub arrays: ArrayMerge,
+
+    /// A `null` member removes the member instead of setting it to `null`
+    pub null_deletes: bool,
+}
+
+/// How two arrays are merged
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ArrayMerge {
+    /// The new array replaces the old one
+    #[default]
+    Replace,
+
+    /// The new elements are appended to the old ones
+    Concat,
+}
+
+impl Value {
+    /// Merges `other` into the value, for layering defaults, user settings
+    /// and overrides: members of nested objects are merged recursively, and
+    /// everything else from `other` wins as `strategy` describes.
+    pub fn merge(&mut self, mut other: Value, strategy: MergeStrategy) {
+        match (&mut *self, &mut other) {
+            (Value::Object(map), Value::Object(more)) => {
+                for (key, value) in mem::take(more) {
+                    if strategy.null_deletes && value.is_null() {
+                        map.remove(&key);
+                        continue;
+                    }
+                    match map.get_mut(&key) {
+                        Some(existing) => existing.merge(value, strategy),
+                        None => {
+                            map.insert(key, value);
+                        }
+                    }
+                }
+            }
+            (Value::Array(values), Value::Array(more)) if strategy.arrays == ArrayMerge::Concat => {
+                values.append(more);
+            }
+            _ => *self = other,
+        }
+    }
+}
+
+/// Applies a JSON Merge Patch (RFC 7386): members of an object patch are
+/// merged into the target recursively, `null` members remove the member, and
+/// any other patch replaces the target.
+pub fn merge_patch(target: &mut Value, patch: &Value) {
+    let Value::Object(members) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+    let Value::Object(map) = target else {
+        unreachable!("the target was just made an object");
+    };
+
+    for (key, value) in members {
+        if value.is_null() {
+            map.remove(key);
+            continue;
+        }
+        match map.get_mut(key) {
+            Some(existing) => merge_patch(existing, value),
+            None => {
+                let mut created = Value::Null;
+                merge_patch(&mut created, value);
+                map.insert(key.clone(), created);
+            }
+        }
+    }
+}
+
+/// The JSON Merge Patch that turns `a` into `b` when applied with
+/// [`merge_patch`]. Merge patches cannot set a member to `null`, so `null`
+/// members of objects in `b` are not reproduced.
+pub fn diff_to_merge_patch(a: &Value, b: &Value) -> Value {
+    let (Value::Object(old), Value::Object(new)) = (a, b) else {
+        return b.clone();
+    };
+
+    let mut patch = Map::new();
+    for key in old.keys() {
+        if !new.contains_key(key) {
+            patch.insert(key.clone(), Value::Null);
+        }
+    }
+    for (key, value) in new {
+        match old.get(key) {
+            Some(previous) if previous == value => {}
+            Some(previous) => {
+                patch.insert(key.clone(), diff_to_merge_patch(previous, value));
+            }
+            None => {
+                patch.insert(key.clone(), value.clone());
+            }
+        }
+    }
+    Value::Object(patch)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse, Value};
+
+    use super::{diff_to_merge_patch, merge_patch, ArrayMerge, MergeStrategy};
+
+    fn defaults() -> Value {
+        Value::object([
+            ("port", Value::Number(80.0)),
+            (
+                "log",
+                Value::object([
+                    ("level", Value::string("info")),
+                    ("file", Value::string("app.log")),
+                ]),
+            ),
+            ("plugins", Value::Array(vec![Value::string("core")])),
+        ])
+    }
+
+    fn user() -> Value {
+        Value::object([
+            (
+                "log",
+                Value::object([("level", Value::string("debug")), ("file", Value::Null)]),
+            ),
+            ("plugins", Value::Array(vec![Value::string("extra")])),
+            ("name", Value::string("dev")),
+        ])
+    }
+
+    #[test]
+    fn merges_objects_and_replaces_arrays() {
+        let mut value = defaults();
+        let expected = Value::object([
+            ("port", Value::Number(80.0)),
+            (
+                "log",
+                Value::object([("level", Value::string("debug")), ("file", Value::Null)]),
+            ),
+            ("plugins", Value::Array(vec![Value::string("extra")])),
+            ("name", Value::string("dev")),
+        ]);
+
+        value.merge(user(), MergeStrategy::default());
+
+        assert_eq!(value, expected);
+    }
+
+    #[test]
+    fn concatenates_arrays_and_deletes_nulls() {
+        let mut value = defaults();
+        let strategy = MergeStrategy {
+            arrays: ArrayMerge::Concat,
+            null_deletes: true,
+        };
+        let expected = Value::object([
+            ("port", Value::Number(80.0)),
+            ("log", Value::object([("level", Value::string("debug"))])),
+            (
+                "plugins",
+                Value::Array(vec![Value::string("core"), Value::string("extra")]),
+            ),
+            ("name", Value::string("dev")),
+        ]);
+
+        value.merge(user(), strategy);
+
+        assert_eq!(value, expected);
+    }
+
+    #[test]
+    fn layers_several_sources() {
+        let mut value = defaults();
+        let overrides = Value::object([("port", Value::Number(8080.0)), ("log", Value::Null)]);
+
+        value.merge(user(), MergeStrategy::default());
+        value.merge(overrides, MergeStrategy::default());
+
+        assert_eq!(value.get("port"), Some(&Value::Number(8080.0)));
+        assert_eq!(value.get("log"), Some(&Value::Null));
+        assert_eq!(value.get("name"), Some(&Value::string("dev")));
+    }
+
+    fn json(text: &str) -> Value {
+        parse(String::from(text)).unwrap()
+    }
+
+    #[test]
+    fn applies_rfc_7386_examples() {
+        let cases = [
+            (r#"{"a":"b"}"#, r#"{"a":"c"}"#, r#"{"a":"c"}"#),
+            (r#"{"a":"b"}"#, r#"{"b":"c"}"#, r#"{"a":"b","b":"c"}"#),
+            (r#"{"a":"b"}"#, r#"{"a":null}"#, r#"{}"#),
+            (r#"{"a":"b","b":"c"}"#, r#"{"a":null}"#, r#"{"b":"c"}"#),
+            (r#"{"a":["b"]}"#, r#"{"a":"c"}"#, r#"{"a":"c"}"#),
+            (r#"{"a":"c"}"#, r#"{"a":["b"]}"#, r#"{"a":["b"]}"#),
+            (
+                r#"{"a":{"b":"c"}}"#,
+                r#"{"a":{"b":"d","c":null}}"#,
+                r#"{"a":{"b":"d"}}"#,
+            ),
+            (r#"{"a":[{"b":"c"}]}"#, r#"{"a":[1]}"#, r#"{"a":[1]}"#),
+            (r#"["a","b"]"#, r#"["c","d"]"#, r#"["c","d"]"#),
+            (r#"{"a":"b"}"#, r#"["c"]"#, r#"["c"]"#),
+            (r#"{"a":"foo"}"#, "null", "null"),
+            (r#"{"a":"foo"}"#, r#""bar""#, r#""bar""#),
+            (r#"{"e":null}"#, r#"{"a":1}"#, r#"{"e":null,"a":1}"#),
+            (r#"[1,2]"#, r#"{"a":"b","c":null}"#, r#"{"a":"b"}"#),
+            (
+                r#"{}"#,
+                r#"{"a":{"bb":{"ccc":null}}}"#,
+                r#"{"a":{"bb":{}}}"#,
+            ),
+        ];
+
+        for (target, patch, expected) in cases {
+            let mut actual = json(target);
+
+            merge_patch(&mut actual, &json(patch));
+
+            assert_eq!(actual, json(expected), "{target} patched with {patch}");
+        }
+    }
+
+    #[test]
+    fn diffs_to_merge_patch() {
+        let a = json(r#"{"title":"Hello","author":{"name":"A","email":"a@x"},"tags":["x"]}"#);
+        let b = json(r#"{"title":"Hello","author":{"name":"A"},"tags":["x","y"],"draft":true}"#);
+        let expected = json(r#"{"author":{"email":null},"tags":["x","y"],"draft":true}"#);
+
+        let patch = diff_to_merge_patch(&a, &b);
+        let mut patched = a;
+        merge_patch(&mut patched, &patch);
+
+        assert_eq!(patch, expected);
+        assert_eq!(patched, b);
+        assert_eq!(diff_to_merge_patch(&b, &b), json("{}"));
+    }
+}
@@ -0,0 +1,155 @@
+use std::collections::BTreeMap;
+
+use crate::{Transform, TransformError, Value};
+
+type Check = Box<dyn Fn(&Value) -> Result<(), String>>;
+
+/// Upgrades stored documents between numbered schema versions, one version
+/// at a time.
+#[derive(Default)]
+pub struct Migrations {
+    steps: BTreeMap<u32, Box<dyn Transform>>,
+    checks: BTreeMap<u32, Check>,
+}
+
+/// Possible errors when migrating a document
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum MigrationError {
+    /// The target version is older than the version of the document
+    Downgrade { from: u32, to: u32 },
+
+    /// No migration from this version is registered
+    MissingStep(u32),
+
+    /// The migration from this version failed
+    StepFailed { from: u32, error: TransformError },
+
+    /// The document produced for this version failed its check
+    Invalid { version: u32, reason: String },
+}
+
+impl Migrations {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the migration from version `from` to `from + 1`, replacing
+    /// an earlier one for the same version.
+    pub fn register(mut self, from: u32, step: impl Transform + 'static) -> Self {
+        self.steps.insert(from, Box::new(step));
+        self
+    }
+
+    /// Registers a check run on every document a migration produces for `version`.
+    pub fn check<F>(mut self, version: u32, check: F) -> Self
+    where
+        F: Fn(&Value) -> Result<(), String> + 'static,
+    {
+        self.checks.insert(version, Box::new(check));
+        self
+    }
+
+    /// Applies the migrations from version `from` up to version `to` in order,
+    /// checking the document after each one. Stops at the first failure.
+    pub fn migrate(&self, value: Value, from: u32, to: u32) -> Result<Value, MigrationError> {
+        if from > to {
+            return Err(MigrationError::Downgrade { from, to });
+        }
+        if let Some(missing) = (from..to).find(|version| !self.steps.contains_key(version)) {
+            return Err(MigrationError::MissingStep(missing));
+        }
+
+        (from..to).try_fold(value, |value, version| {
+            let value =
+                self.steps[&version]
+                    .apply(value)
+                    .map_err(|error| MigrationError::StepFailed {
+                        from: version,
+                        error,
+                    })?;
+            if let Some(check) = self.checks.get(&(version + 1)) {
+                check(&value).map_err(|reason| MigrationError::Invalid {
+                    version: version + 1,
+                    reason,
+                })?;
+            }
+            Ok(value)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{TransformError, Value};
+
+    use super::{MigrationError, Migrations};
+
+    fn migrations() -> Migrations {
+        Migrations::new()
+            .register(1, |mut value: Value| {
+                if let Ok(port) = value.remove_by_pointer("/port") {
+                    value["server"]["port"] = port;
+                }
+                Ok(value)
+            })
+            .register(2, |mut value: Value| {
+                value["version"] = Value::Number(3.0);
+                Ok(value)
+            })
+            .check(2, |value| match value.pointer("/server/port") {
+                Some(Value::Number(_)) => Ok(()),
+                _ => Err(String::from("missing port")),
+            })
+    }
+
+    #[test]
+    fn applies_steps_in_order() {
+        let input = Value::object([("port", Value::Number(80.0))]);
+        let expected = Value::object([
+            ("server", Value::object([("port", Value::Number(80.0))])),
+            ("version", Value::Number(3.0)),
+        ]);
+
+        let actual = migrations().migrate(input, 1, 3);
+
+        assert_eq!(actual, Ok(expected));
+        assert_eq!(migrations().migrate(Value::Null, 2, 2), Ok(Value::Null));
+    }
+
+    #[test]
+    fn stops_at_failing_check() {
+        let input = Value::object([("host", Value::string("a"))]);
+
+        let actual = migrations().migrate(input, 1, 3);
+
+        assert_eq!(
+            actual,
+            Err(MigrationError::Invalid {
+                version: 2,
+                reason: String::from("missing port")
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_missing_steps_and_downgrades() {
+        let failing =
+            Migrations::new().register(1, |_| Err(TransformError::Custom(String::from("no"))));
+
+        assert_eq!(
+            migrations().migrate(Value::Null, 1, 4),
+            Err(MigrationError::MissingStep(3))
+        );
+        assert_eq!(
+            migrations().migrate(Value::Null, 3, 1),
+            Err(MigrationError::Downgrade { from: 3, to: 1 })
+        );
+        assert_eq!(
+            failing.migrate(Value::Null, 1, 2),
+            Err(MigrationError::StepFailed {
+                from: 1,
+                error: TransformError::Custom(String::from("no"))
+            })
+        );
+    }
+}
@@ -0,0 +1,121 @@
+use crate::{parse, ParseError, Value};
+
+/// Outcome of parsing newline-delimited JSON, one document per line
+#[derive(Debug, PartialEq, Default)]
+pub struct NdjsonBatch {
+    /// Documents of the lines that parsed, in input order
+    pub records: Vec<Value>,
+
+    /// Lines that failed to parse, in input order
+    pub failures: Vec<LineFailure>,
+}
+
+/// A line of newline-delimited JSON that is not a valid document
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct LineFailure {
+    /// Line number, starting at 1
+    pub line: usize,
+
+    /// Why the line failed to parse
+    pub error: ParseError,
+
+    /// Text of the line, without the line ending
+    pub raw: String,
+}
+
+impl NdjsonBatch {
+    /// Whether every line parsed.
+    pub fn is_complete(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+/// Parses each line of the input as a separate document. A line that fails
+/// is reported in [`NdjsonBatch::failures`] and does not stop the lines
+/// after it from being parsed. Blank lines are skipped.
+pub fn parse_ndjson(input: &str) -> NdjsonBatch {
+    let mut batch = NdjsonBatch::default();
+
+    for (index, line) in input.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        match parse(line.to_string()) {
+            Ok(value) => batch.records.push(value),
+            Err(error) => batch.failures.push(LineFailure {
+                line: index + 1,
+                error,
+                raw: line.to_string(),
+            }),
+        }
+    }
+
+    batch
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parser::TokenParseError, tokenizer::TokenizeError, ParseError, Value};
+
+    use super::{parse_ndjson, LineFailure};
+
+    #[test]
+    fn collects_records_and_failed_lines() {
+        let input = "{\"id\": 1}\r\n{\"id\": }\n\n[2]\n\"unclosed\n3\n";
+
+        let actual = parse_ndjson(input);
+
+        assert_eq!(
+            actual.records,
+            [
+                Value::object([("id", Value::Number(1.0))]),
+                Value::array([Value::Number(2.0)]),
+                Value::Number(3.0),
+            ]
+        );
+        assert_eq!(
+            actual.failures,
+            [
+                LineFailure {
+                    line: 2,
+                    error: ParseError::ParseError(TokenParseError::ExpectedValueForKey(
+                        String::from("id")
+                    )),
+                    raw: String::from("{\"id\": }"),
+                },
+                LineFailure {
+                    line: 5,
+                    error: ParseError::TokenizeError(TokenizeError::UnclosedQuotes),
+                    raw: String::from("\"unclosed"),
+                },
+            ]
+        );
+        assert!(!actual.is_complete());
+    }
+
+    #[test]
+    fn reports_truncated_lines() {
+        let input = "[1\n{\"a\": 1\n";
+
+        let actual = parse_ndjson(input);
+
+        assert!(actual.records.is_empty());
+        assert_eq!(
+            actual
+                .failures
+                .iter()
+                .map(|failure| &failure.error)
+                .collect::<Vec<_>>(),
+            [&ParseError::ParseError(TokenParseError::UnexpectedEof); 2]
+        );
+    }
+
+    #[test]
+    fn blank_input_is_an_empty_batch() {
+        let actual = parse_ndjson("\n  \n");
+
+        assert!(actual.records.is_empty());
+        assert!(actual.is_complete());
+    }
+}
@@ -0,0 +1,160 @@
+use std::num::ParseFloatError;
+
+/// Parses the text of a number token.
+///
+/// With the `fast_float` feature, numbers whose digits fit exactly in an
+/// `f64` are converted without going through `str::parse`.
+pub(crate) fn parse_number(text: &str) -> Result<f64, ParseFloatError> {
+    #[cfg(feature = "fast_float")]
+    if let Some(number) = fast_path(text) {
+        return Ok(number);
+    }
+
+    text.parse()
+}
+
+/// Whether the text follows the number grammar of RFC 8259: an optional
+/// minus, an integer part without leading zeros, then an optional fraction
+/// and exponent, each with at least one digit.
+pub(crate) fn is_json_number(text: &str) -> bool {
+    let digits =
+        |text: &str| text.len() - text.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+
+    let rest = text.strip_prefix('-').unwrap_or(text);
+    let rest = match digits(rest) {
+        0 => return false,
+        1 => &rest[1..],
+        _ if rest.starts_with('0') => return false,
+        len => &rest[len..],
+    };
+    let rest = match rest.strip_prefix('.') {
+        Some(fraction) if digits(fraction) == 0 => return false,
+        Some(fraction) => &fraction[digits(fraction)..],
+        None => rest,
+    };
+    let rest = match rest.strip_prefix(['e', 'E']) {
+        Some(exponent) => {
+            let exponent = exponent.strip_prefix(['+', '-']).unwrap_or(exponent);
+            match digits(exponent) {
+                0 => return false,
+                len => &exponent[len..],
+            }
+        }
+        None => rest,
+    };
+
+    rest.is_empty()
+}
+
+/// Largest integer an `f64` represents exactly, together with every smaller one
+#[cfg(feature = "fast_float")]
+const MAX_EXACT_MANTISSA: u64 = 1 << 53;
+
+/// Powers of ten an `f64` represents exactly
+#[cfg(feature = "fast_float")]
+const POWERS_OF_TEN: [f64; 23] = [
+    1e0, 1e1, 1e2, 1e3, 1e4, 1e5, 1e6, 1e7, 1e8, 1e9, 1e10, 1e11, 1e12, 1e13, 1e14, 1e15, 1e16,
+    1e17, 1e18, 1e19, 1e20, 1e21, 1e22,
+];
+
+/// Clinger's fast path: when both the digits, read as an integer, and the
+/// power of ten they are divided by are exact in an `f64`, a single
+/// division gives the correctly rounded result. Returns `None` for
+/// anything else, leaving it to `str::parse`.
+#[cfg(feature = "fast_float")]
+fn fast_path(text: &str) -> Option<f64> {
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text),
+    };
+    let (integer, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+    if integer.is_empty() && fraction.is_empty() {
+        return None;
+    }
+
+    let mut mantissa: u64 = 0;
+    for byte in integer.bytes().chain(fraction.bytes()) {
+        if !byte.is_ascii_digit() {
+            return None;
+        }
+        mantissa = mantissa
+            .checked_mul(10)?
+            .checked_add(u64::from(byte - b'0'))?;
+    }
+    if mantissa > MAX_EXACT_MANTISSA {
+        return None;
+    }
+
+    let number = mantissa as f64 / POWERS_OF_TEN.get(fraction.len())?;
+    Some(if negative { -number } else { number })
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "fast_float")]
+    use super::fast_path;
+    use super::is_json_number;
+
+    #[test]
+    fn checks_number_grammar() {
+        let valid = ["0", "-0", "12", "0.5", "-1.50", "1e5", "1E+5", "2.5e-3"];
+        let invalid = [
+            "", "-", "012", "-01", "1.", ".5", "-.5", "1-2", "1.2.3", "1e", "1e+",
+        ];
+
+        for input in valid {
+            assert!(is_json_number(input), "input: {input}");
+        }
+        for input in invalid {
+            assert!(!is_json_number(input), "input: {input}");
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "fast_float")]
+    fn fast_path_matches_std() {
+        let cases = [
+            "0",
+            "-0",
+            "12",
+            "0012",
+            "1.",
+            "-.5",
+            "3.14159",
+            "-2.5",
+            "0.1",
+            "0.3",
+            "9007199254740992",
+            "123456.789012",
+        ];
+
+        for input in cases {
+            let expected: f64 = input.parse().unwrap();
+
+            let actual = fast_path(input).unwrap();
+
+            assert_eq!(actual.to_bits(), expected.to_bits(), "input: {input}");
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "fast_float")]
+    fn leaves_other_numbers_to_std() {
+        let cases = [
+            "-",
+            ".",
+            "1-2",
+            "1.2.3",
+            "9007199254740993",
+            "123456.7890123456789012",
+            "123456789012345678901234567890",
+            "0.00000000000000000000001",
+        ];
+
+        for input in cases {
+            let actual = fast_path(input);
+
+            assert_eq!(actual, None, "input: {input}");
+        }
+    }
+}
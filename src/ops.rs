@@ -0,0 +1,221 @@
+use std::{
+    mem,
+    ops::{Add, Index, IndexMut},
+};
+
+use crate::{convert::mismatch, KindMismatch, Map, Value, ValueKind};
+
+static NULL: Value = Value::Null;
+
+impl Value {
+    /// Appends the elements of `other` to those of `self`. Both must be arrays.
+    pub fn concat(mut self, mut other: Value) -> Result<Value, KindMismatch> {
+        match (&mut self, &mut other) {
+            (Value::Array(values), Value::Array(more)) => {
+                values.append(more);
+                Ok(self)
+            }
+            (Value::Array(_), other) => Err(mismatch(ValueKind::Array, other)),
+            (this, _) => Err(mismatch(ValueKind::Array, this)),
+        }
+    }
+
+    /// Adds the members of `other` to those of `self`, replacing members of
+    /// `self` with the same key. Both must be objects; nested objects are
+    /// replaced, not merged.
+    pub fn union(mut self, mut other: Value) -> Result<Value, KindMismatch> {
+        match (&mut self, &mut other) {
+            (Value::Object(map), Value::Object(more)) => {
+                for (key, value) in mem::take(more) {
+                    map.insert(key, value);
+                }
+                Ok(self)
+            }
+            (Value::Object(_), other) => Err(mismatch(ValueKind::Object, other)),
+            (this, _) => Err(mismatch(ValueKind::Object, this)),
+        }
+    }
+}
+
+/// Concatenates two arrays or takes the union of two objects, see
+/// [`Value::concat`] and [`Value::union`].
+impl Add for Value {
+    type Output = Result<Value, KindMismatch>;
+
+    fn add(self, other: Value) -> Self::Output {
+        match self {
+            Value::Object(_) => self.union(other),
+            _ => self.concat(other),
+        }
+    }
+}
+
+/// Looks up an object member, `null` when the key is absent or the value is
+/// not an object, so lookups can be chained like `value["a"]["b"]`.
+impl Index<&str> for Value {
+    type Output = Value;
+
+    fn index(&self, key: &str) -> &Value {
+        self.get(key).unwrap_or(&NULL)
+    }
+}
+
+/// Looks up an array element, `null` when the index is out of range or the
+/// value is not an array.
+impl Index<usize> for Value {
+    type Output = Value;
+
+    fn index(&self, index: usize) -> &Value {
+        self.get_index(index).unwrap_or(&NULL)
+    }
+}
+
+/// Returns the object member, inserting `null` when the key is absent. A
+/// `null` value is replaced by an empty object first.
+///
+/// # Panics
+///
+/// Panics if the value is neither an object nor `null`.
+impl IndexMut<&str> for Value {
+    fn index_mut(&mut self, key: &str) -> &mut Value {
+        if let Value::Null = self {
+            *self = Value::Object(Map::new());
+        }
+        match self {
+            Value::Object(map) => map.entry(key.to_string()).or_insert_with(|| Value::Null),
+            other => panic!("cannot index into {:?} with a key", other.kind()),
+        }
+    }
+}
+
+/// Returns the array element.
+///
+/// # Panics
+///
+/// Panics if the value is not an array or the index is out of range.
+impl IndexMut<usize> for Value {
+    fn index_mut(&mut self, index: usize) -> &mut Value {
+        match self {
+            Value::Array(values) => {
+                let len = values.len();
+                values.get_mut(index).unwrap_or_else(|| {
+                    panic!("index {index} out of range for array of length {len}")
+                })
+            }
+            other => panic!("cannot index into {:?} with an index", other.kind()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{KindMismatch, Value, ValueKind};
+
+    #[test]
+    fn adds_arrays_by_concatenation() {
+        let left = Value::array([Value::Number(1.0), Value::Null]);
+        let right = Value::array([Value::Boolean(true)]);
+        let expected = Value::array([Value::Number(1.0), Value::Null, Value::Boolean(true)]);
+
+        let actual = left + right;
+
+        assert_eq!(actual, Ok(expected));
+    }
+
+    #[test]
+    fn adds_objects_by_shallow_union() {
+        let left = Value::object([
+            ("a", Value::Number(1.0)),
+            ("nested", Value::object([("x", Value::Null)])),
+        ]);
+        let right = Value::object([
+            ("b", Value::Number(2.0)),
+            ("nested", Value::object([("y", Value::Null)])),
+        ]);
+        let expected = Value::object([
+            ("a", Value::Number(1.0)),
+            ("b", Value::Number(2.0)),
+            ("nested", Value::object([("y", Value::Null)])),
+        ]);
+
+        let actual = left + right;
+
+        assert_eq!(actual, Ok(expected));
+    }
+
+    #[test]
+    fn rejects_mismatched_kinds() {
+        let cases = [
+            (
+                Value::array([]),
+                Value::Number(1.0),
+                ValueKind::Array,
+                ValueKind::Number,
+            ),
+            (
+                Value::object([("a", Value::Null)]),
+                Value::array([]),
+                ValueKind::Object,
+                ValueKind::Array,
+            ),
+            (
+                Value::string("a"),
+                Value::string("b"),
+                ValueKind::Array,
+                ValueKind::String,
+            ),
+        ];
+
+        for (left, right, expected, found) in cases {
+            let actual = left + right;
+
+            assert_eq!(actual, Err(KindMismatch { expected, found }));
+        }
+    }
+
+    #[test]
+    fn indexes_members_and_elements() {
+        let value = Value::object([(
+            "config",
+            Value::object([("ports", Value::array([Value::Number(80.0)]))]),
+        )]);
+
+        assert_eq!(value["config"]["ports"][0], Value::Number(80.0));
+        assert_eq!(value["config"]["ports"][1], Value::Null);
+        assert_eq!(value["missing"]["deeper"], Value::Null);
+        assert_eq!(value[0], Value::Null);
+    }
+
+    #[test]
+    fn creates_members_on_mutable_index() {
+        let mut value = Value::Null;
+
+        value["config"]["port"] = Value::Number(8080.0);
+        value["list"] = Value::array([Value::Null]);
+        value["list"][0] = Value::Boolean(true);
+
+        assert_eq!(
+            value,
+            Value::object([
+                ("config", Value::object([("port", Value::Number(8080.0))])),
+                ("list", Value::array([Value::Boolean(true)])),
+            ])
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "index 1 out of range for array of length 1")]
+    fn panics_on_mutable_index_out_of_range() {
+        let mut value = Value::array([Value::Null]);
+
+        value[1] = Value::Null;
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot index into Number with a key")]
+    fn panics_on_mutable_key_into_scalar() {
+        let mut value = Value::Number(1.0);
+
+        value["a"] = Value::Null;
+    }
+}
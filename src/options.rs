@@ -0,0 +1,105 @@
+use crate::JsonPointer;
+
+/// Bounds on the size of strings and arrays, `None` meaning unbounded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SizeLimits {
+    /// Longest string value in bytes, after unescaping
+    pub max_string_len: Option<usize>,
+
+    /// Most elements in an array
+    pub max_array_len: Option<usize>,
+}
+
+/// Deepest nesting of arrays and objects accepted unless configured otherwise
+pub(crate) const DEFAULT_MAX_DEPTH: usize = 128;
+
+/// Options controlling how the input is parsed.
+#[derive(Debug, Clone)]
+pub struct ParseOptions {
+    /// Reject top-level values which are not objects or arrays, as required by
+    /// RFC 4627 and consumers built against it
+    pub require_container_root: bool,
+
+    /// Convert object keys to lowercase. When keys collide after conversion,
+    /// the last one wins, as with duplicate keys
+    pub lowercase_keys: bool,
+
+    /// Pass string values to the interner given to
+    /// [`crate::parse_with_interner`], not only object keys
+    pub intern_values: bool,
+
+    /// Size limits applied wherever no scoped limits apply
+    pub size_limits: SizeLimits,
+
+    /// Size limits for the values at and below a pointer, such as a large
+    /// string allowed only under `/attachment/data`. When several pointers
+    /// match, the longest one wins
+    pub scoped_size_limits: Vec<(JsonPointer, SizeLimits)>,
+
+    /// Arrays whose location matches one of these pointers keep only the
+    /// first of several equal elements. A `*` token matches any member or
+    /// element, as in `/users/*/tags`
+    pub dedup_arrays: Vec<JsonPointer>,
+
+    /// Deepest nesting of arrays and objects to accept, 128 by default.
+    /// Parsing takes a stack frame per level, so unbounded nesting lets
+    /// hostile input overflow the stack
+    pub max_depth: usize,
+
+    /// Read string values holding RFC 3339 timestamps as [`crate::Value::DateTime`]
+    #[cfg(feature = "datetime")]
+    pub datetimes: bool,
+
+    /// Store strings longer than this many bytes as [`crate::Value::Rope`],
+    /// in chunks of at most this size
+    pub rope_chunk_size: Option<usize>,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            require_container_root: false,
+            lowercase_keys: false,
+            intern_values: false,
+            size_limits: SizeLimits::default(),
+            scoped_size_limits: Vec::new(),
+            dedup_arrays: Vec::new(),
+            max_depth: DEFAULT_MAX_DEPTH,
+            #[cfg(feature = "datetime")]
+            datetimes: false,
+            rope_chunk_size: None,
+        }
+    }
+}
+
+impl ParseOptions {
+    pub(crate) fn has_size_limits(&self) -> bool {
+        self.size_limits != SizeLimits::default() || !self.scoped_size_limits.is_empty()
+    }
+
+    /// Whether the parser has to know the location of each value.
+    pub(crate) fn tracks_path(&self) -> bool {
+        self.has_size_limits() || !self.dedup_arrays.is_empty()
+    }
+
+    /// Whether equal elements of the array at `path` are dropped.
+    pub(crate) fn dedups_array_at(&self, path: &JsonPointer) -> bool {
+        self.dedup_arrays.iter().any(|pattern| {
+            let pattern = pattern.tokens();
+            pattern.len() == path.tokens().len()
+                && pattern
+                    .iter()
+                    .zip(path.tokens())
+                    .all(|(expected, token)| expected == "*" || expected == token)
+        })
+    }
+
+    /// Limits for the value at `path`.
+    pub(crate) fn size_limits_at(&self, path: &JsonPointer) -> SizeLimits {
+        self.scoped_size_limits
+            .iter()
+            .filter(|(scope, _)| path.starts_with(scope))
+            .max_by_key(|(scope, _)| scope.tokens().len())
+            .map_or(self.size_limits, |(_, limits)| *limits)
+    }
+}
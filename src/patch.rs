@@ -0,0 +1,682 @@
+use std::collections::HashMap;
+
+use crate::{
+    edit::split_parent,
+    hash::structural_hash,
+    pointer::{parse_index, parse_pointer},
+    JsonPointer, PointerError, Value,
+};
+
+/// A single JSON Patch (RFC 6902) operation. Locations are JSON pointers.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PatchOp {
+    /// Adds a value, inserting before the addressed array element (`-`
+    /// appends) or setting an object member
+    Add { path: String, value: Value },
+
+    /// Removes the addressed value
+    Remove { path: String },
+
+    /// Replaces the addressed value, which must exist
+    Replace { path: String, value: Value },
+
+    /// Removes the value at `from` and adds it at `path`
+    Move { from: String, path: String },
+
+    /// Adds a copy of the value at `from` at `path`
+    Copy { from: String, path: String },
+
+    /// Checks that the addressed value equals `value`
+    Test { path: String, value: Value },
+}
+
+/// Possible errors when reading or applying a JSON Patch. `index` is the
+/// position of the offending operation in the patch
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatchError {
+    /// The patch is not an array, or the operation is not an object with a
+    /// known `op` and the members that operation requires
+    Malformed { index: usize },
+
+    /// The operation addresses a location that is malformed, does not exist,
+    /// or cannot be added to
+    Pointer { index: usize, error: PointerError },
+
+    /// The `test` operation found a different value
+    TestFailed { index: usize },
+
+    /// The `move` operation moves a value into one of its own children
+    MoveIntoChild { index: usize },
+}
+
+/// Reads the operations of a JSON Patch document, an array of objects such as
+/// `{"op": "add", "path": "/a", "value": 1}`.
+pub fn parse_patch(patch: &Value) -> Result<Vec<PatchOp>, PatchError> {
+    let Value::Array(operations) = patch else {
+        return Err(PatchError::Malformed { index: 0 });
+    };
+
+    operations
+        .iter()
+        .enumerate()
+        .map(|(index, operation)| {
+            PatchOp::from_value(operation).ok_or(PatchError::Malformed { index })
+        })
+        .collect()
+}
+
+impl PatchOp {
+    fn from_value(operation: &Value) -> Option<Self> {
+        let member = |key: &str| operation.get(key).and_then(Value::as_str).map(String::from);
+        let path = member("path")?;
+        let value = || operation.get("value").cloned();
+
+        let op = match operation.get("op")?.as_str()? {
+            "add" => PatchOp::Add {
+                path,
+                value: value()?,
+            },
+            "remove" => PatchOp::Remove { path },
+            "replace" => PatchOp::Replace {
+                path,
+                value: value()?,
+            },
+            "move" => PatchOp::Move {
+                from: member("from")?,
+                path,
+            },
+            "copy" => PatchOp::Copy {
+                from: member("from")?,
+                path,
+            },
+            "test" => PatchOp::Test {
+                path,
+                value: value()?,
+            },
+            _ => return None,
+        };
+        Some(op)
+    }
+
+    /// The operation as a JSON Patch object, as read by [`parse_patch`].
+    pub fn to_value(&self) -> Value {
+        let string = |s: &str| Value::string(s);
+        match self {
+            PatchOp::Add { path, value } => Value::object([
+                ("op", string("add")),
+                ("path", string(path)),
+                ("value", value.clone()),
+            ]),
+            PatchOp::Remove { path } => {
+                Value::object([("op", string("remove")), ("path", string(path))])
+            }
+            PatchOp::Replace { path, value } => Value::object([
+                ("op", string("replace")),
+                ("path", string(path)),
+                ("value", value.clone()),
+            ]),
+            PatchOp::Move { from, path } => Value::object([
+                ("op", string("move")),
+                ("from", string(from)),
+                ("path", string(path)),
+            ]),
+            PatchOp::Copy { from, path } => Value::object([
+                ("op", string("copy")),
+                ("from", string(from)),
+                ("path", string(path)),
+            ]),
+            PatchOp::Test { path, value } => Value::object([
+                ("op", string("test")),
+                ("path", string(path)),
+                ("value", value.clone()),
+            ]),
+        }
+    }
+}
+
+impl Value {
+    /// Applies the operations in order. Either every operation succeeds, or
+    /// the value is left unchanged and the first failing one is reported.
+    pub fn apply_patch(&mut self, patch: &[PatchOp]) -> Result<(), PatchError> {
+        let original = self.clone();
+
+        for (index, op) in patch.iter().enumerate() {
+            if let Err(error) = self.apply_patch_op(op, index) {
+                *self = original;
+                return Err(error);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn apply_patch_op(&mut self, op: &PatchOp, index: usize) -> Result<(), PatchError> {
+        let pointer_error = |error| PatchError::Pointer { index, error };
+
+        match op {
+            PatchOp::Add { path, value } => add(self, path, value.clone()).map_err(pointer_error),
+            PatchOp::Remove { path } => self
+                .remove_by_pointer(path)
+                .map(|_| ())
+                .map_err(pointer_error),
+            PatchOp::Replace { path, value } => self
+                .update_pointer(path, false, |_| value.clone())
+                .map_err(pointer_error),
+            PatchOp::Move { from, path } => {
+                let source = JsonPointer::parse(from).map_err(pointer_error)?;
+                let target = JsonPointer::parse(path).map_err(pointer_error)?;
+                if source == target {
+                    return Ok(());
+                }
+                if target.starts_with(&source) {
+                    return Err(PatchError::MoveIntoChild { index });
+                }
+                let value = self.remove_by_pointer(from).map_err(pointer_error)?;
+                add(self, path, value).map_err(pointer_error)
+            }
+            PatchOp::Copy { from, path } => {
+                let value = lookup(self, from).map_err(pointer_error)?.clone();
+                add(self, path, value).map_err(pointer_error)
+            }
+            PatchOp::Test { path, value } => {
+                if lookup(self, path).map_err(pointer_error)? == value {
+                    Ok(())
+                } else {
+                    Err(PatchError::TestFailed { index })
+                }
+            }
+        }
+    }
+}
+
+fn lookup<'a>(document: &'a Value, pointer: &str) -> Result<&'a Value, PointerError> {
+    if parse_pointer(pointer).is_none() {
+        return Err(PointerError::InvalidSyntax(pointer.to_string()));
+    }
+    document
+        .pointer(pointer)
+        .ok_or_else(|| PointerError::NotFound(pointer.to_string()))
+}
+
+/// Adds `value` at `pointer` as the `add` operation does, replacing the whole
+/// document for the empty pointer.
+fn add(document: &mut Value, pointer: &str, value: Value) -> Result<(), PointerError> {
+    if pointer.is_empty() {
+        *document = value;
+        return Ok(());
+    }
+
+    let (parent, last) = split_parent(document, pointer)?;
+    match parent {
+        Value::Array(values) => {
+            let index = if last == "-" {
+                values.len()
+            } else {
+                parse_index(&last)
+                    .filter(|&index| index <= values.len())
+                    .ok_or_else(|| PointerError::NotFound(pointer.to_string()))?
+            };
+            values.insert(index, value);
+        }
+        Value::Object(map) => {
+            map.insert(last, value);
+        }
+        _ => return Err(PointerError::NotFound(pointer.to_string())),
+    }
+    Ok(())
+}
+
+/// Size of the table beyond which [`diff_to_patch`] stops looking for the
+/// longest common subsequence of two arrays and pairs elements by position
+const MAX_LCS_CELLS: usize = 1 << 22;
+
+/// The JSON Patch that turns `a` into `b` when applied with
+/// [`Value::apply_patch`]. Objects are compared member by member. Array
+/// elements kept in order are left alone, elements that only changed
+/// position become `move` operations, and changed elements are diffed in place.
+pub fn diff_to_patch(a: &Value, b: &Value) -> Vec<PatchOp> {
+    let mut ops = Vec::new();
+    diff_at(a, b, &mut JsonPointer::root(), &mut ops);
+    ops
+}
+
+fn diff_at(a: &Value, b: &Value, path: &mut JsonPointer, ops: &mut Vec<PatchOp>) {
+    match (a, b) {
+        _ if a == b => {}
+        (Value::Object(old), Value::Object(new)) => {
+            let mut removed: Vec<&String> =
+                old.keys().filter(|key| !new.contains_key(*key)).collect();
+            removed.sort();
+            for key in removed {
+                ops.push(PatchOp::Remove {
+                    path: path.child(key.as_str()).to_string(),
+                });
+            }
+
+            let mut keys: Vec<&String> = new.keys().collect();
+            keys.sort();
+            for key in keys {
+                path.push(key.as_str());
+                match old.get(key) {
+                    Some(old) => diff_at(old, &new[key.as_str()], path, ops),
+                    None => ops.push(PatchOp::Add {
+                        path: path.to_string(),
+                        value: new[key.as_str()].clone(),
+                    }),
+                }
+                path.pop();
+            }
+        }
+        (Value::Array(old), Value::Array(new)) => diff_arrays(old, new, path, ops),
+        _ => ops.push(PatchOp::Replace {
+            path: path.to_string(),
+            value: b.clone(),
+        }),
+    }
+}
+
+/// What becomes of an element of the new array
+#[derive(Clone, Copy)]
+enum Target {
+    /// Part of the common subsequence, left in place
+    Kept,
+    /// The old element at this index, diffed in place
+    Changed(usize),
+    /// An equal old element, moved here
+    Moved,
+    /// A new element
+    Added,
+}
+
+fn diff_arrays(old: &[Value], new: &[Value], path: &mut JsonPointer, ops: &mut Vec<PatchOp>) {
+    // Index in `new` of each element of `old` that stays
+    let mut destination: Vec<Option<usize>> = vec![None; old.len()];
+    let mut targets = vec![Target::Added; new.len()];
+
+    let matches = common_subsequence(old, new);
+    for &(i, j) in &matches {
+        destination[i] = Some(j);
+        targets[j] = Target::Kept;
+    }
+
+    // Old elements equal to a new element outside the subsequence are moved
+    let mut unmatched: HashMap<u64, Vec<usize>> = HashMap::new();
+    for i in (0..old.len()).rev().filter(|&i| destination[i].is_none()) {
+        unmatched
+            .entry(structural_hash(&old[i]))
+            .or_default()
+            .push(i);
+    }
+    for j in 0..new.len() {
+        if !matches!(targets[j], Target::Added) {
+            continue;
+        }
+        let Some(candidates) = unmatched.get_mut(&structural_hash(&new[j])) else {
+            continue;
+        };
+        if let Some(at) = candidates.iter().rposition(|&i| old[i] == new[j]) {
+            let i = candidates.remove(at);
+            destination[i] = Some(j);
+            targets[j] = Target::Moved;
+        }
+    }
+
+    // Between two kept elements, the remaining old and new elements are paired
+    // up by position and diffed in place
+    let mut bounds: Vec<(usize, usize)> = vec![(0, 0)];
+    bounds.extend(matches.iter().map(|&(i, j)| (i + 1, j + 1)));
+    let ends = matches.iter().copied().chain([(old.len(), new.len())]);
+    for (&(old_start, new_start), (old_end, new_end)) in bounds.iter().zip(ends) {
+        let removed = (old_start..old_end).filter(|&i| destination[i].is_none());
+        let added = (new_start..new_end).filter(|&j| matches!(targets[j], Target::Added));
+        for (i, j) in removed.zip(added).collect::<Vec<_>>() {
+            destination[i] = Some(j);
+            targets[j] = Target::Changed(i);
+        }
+    }
+
+    for i in (0..old.len()).rev().filter(|&i| destination[i].is_none()) {
+        ops.push(PatchOp::Remove {
+            path: path.child(i.to_string()).to_string(),
+        });
+    }
+
+    // The array being patched, as the destination index of each element.
+    // Elements before the one being placed are always in their final order.
+    let mut current: Vec<usize> = destination.into_iter().flatten().collect();
+    let insertion_point =
+        |current: &[usize], j: usize| current.iter().rposition(|&d| d < j).map_or(0, |at| at + 1);
+    for (j, target) in targets.into_iter().enumerate() {
+        match target {
+            Target::Kept => {}
+            Target::Changed(i) => {
+                let at = current
+                    .iter()
+                    .position(|&d| d == j)
+                    .expect("changed elements stay");
+                path.push(at.to_string());
+                diff_at(&old[i], &new[j], path, ops);
+                path.pop();
+            }
+            Target::Moved => {
+                let from = current
+                    .iter()
+                    .position(|&d| d == j)
+                    .expect("moved elements stay");
+                current.remove(from);
+                let to = insertion_point(&current, j);
+                current.insert(to, j);
+                if from != to {
+                    ops.push(PatchOp::Move {
+                        from: path.child(from.to_string()).to_string(),
+                        path: path.child(to.to_string()).to_string(),
+                    });
+                }
+            }
+            Target::Added => {
+                let to = insertion_point(&current, j);
+                current.insert(to, j);
+                ops.push(PatchOp::Add {
+                    path: path.child(to.to_string()).to_string(),
+                    value: new[j].clone(),
+                });
+            }
+        }
+    }
+}
+
+/// Index pairs of a longest common subsequence of equal elements, in order.
+/// Past [`MAX_LCS_CELLS`] only the common prefix and suffix are matched.
+fn common_subsequence(old: &[Value], new: &[Value]) -> Vec<(usize, usize)> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let (old_middle, new_middle) = (
+        &old[prefix..old.len() - suffix],
+        &new[prefix..new.len() - suffix],
+    );
+
+    let mut matches: Vec<(usize, usize)> = (0..prefix).map(|i| (i, i)).collect();
+    let (n, m) = (old_middle.len(), new_middle.len());
+    if n > 0 && m > 0 && (n + 1) * (m + 1) <= MAX_LCS_CELLS {
+        // lengths[i][j] is the length of a longest common subsequence of
+        // old_middle[i..] and new_middle[j..]
+        let mut lengths = vec![vec![0u32; m + 1]; n + 1];
+        for i in (0..n).rev() {
+            for j in (0..m).rev() {
+                lengths[i][j] = if old_middle[i] == new_middle[j] {
+                    lengths[i + 1][j + 1] + 1
+                } else {
+                    lengths[i + 1][j].max(lengths[i][j + 1])
+                };
+            }
+        }
+
+        let (mut i, mut j) = (0, 0);
+        while i < n && j < m {
+            if old_middle[i] == new_middle[j] {
+                matches.push((prefix + i, prefix + j));
+                i += 1;
+                j += 1;
+            } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+    }
+    matches.extend((0..suffix).map(|k| (old.len() - suffix + k, new.len() - suffix + k)));
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse, PointerError, Value};
+
+    use super::{diff_to_patch, parse_patch, PatchError, PatchOp};
+
+    fn json(text: &str) -> Value {
+        parse(String::from(text)).unwrap()
+    }
+
+    fn apply(document: &str, patch: &str) -> Result<Value, PatchError> {
+        let mut value = json(document);
+        let patch = parse_patch(&json(patch))?;
+        value.apply_patch(&patch).map(|_| value)
+    }
+
+    #[test]
+    fn applies_each_operation() {
+        let cases = [
+            (
+                r#"{"foo":"bar"}"#,
+                r#"[{"op":"add","path":"/baz","value":"qux"}]"#,
+                r#"{"baz":"qux","foo":"bar"}"#,
+            ),
+            (
+                r#"{"foo":["bar","baz"]}"#,
+                r#"[{"op":"add","path":"/foo/1","value":"qux"}]"#,
+                r#"{"foo":["bar","qux","baz"]}"#,
+            ),
+            (
+                r#"{"foo":["bar"]}"#,
+                r#"[{"op":"add","path":"/foo/-","value":["abc"]}]"#,
+                r#"{"foo":["bar",["abc"]]}"#,
+            ),
+            (
+                r#"{"baz":"qux","foo":"bar"}"#,
+                r#"[{"op":"remove","path":"/baz"}]"#,
+                r#"{"foo":"bar"}"#,
+            ),
+            (
+                r#"{"baz":"qux","foo":"bar"}"#,
+                r#"[{"op":"replace","path":"/baz","value":"boo"}]"#,
+                r#"{"baz":"boo","foo":"bar"}"#,
+            ),
+            (
+                r#"{"foo":{"bar":"baz","waldo":"fred"},"qux":{"corge":"grault"}}"#,
+                r#"[{"op":"move","from":"/foo/waldo","path":"/qux/thud"}]"#,
+                r#"{"foo":{"bar":"baz"},"qux":{"corge":"grault","thud":"fred"}}"#,
+            ),
+            (
+                r#"{"foo":["all","grass","cows","eat"]}"#,
+                r#"[{"op":"move","from":"/foo/1","path":"/foo/3"}]"#,
+                r#"{"foo":["all","cows","eat","grass"]}"#,
+            ),
+            (
+                r#"{"a":{"b":1}}"#,
+                r#"[{"op":"copy","from":"/a","path":"/c"}]"#,
+                r#"{"a":{"b":1},"c":{"b":1}}"#,
+            ),
+            (
+                r#"{"baz":"qux","foo":["a",2,"c"]}"#,
+                r#"[{"op":"test","path":"/baz","value":"qux"},{"op":"test","path":"/foo/1","value":2}]"#,
+                r#"{"baz":"qux","foo":["a",2,"c"]}"#,
+            ),
+            (
+                r#"{"a":1}"#,
+                r#"[{"op":"replace","path":"","value":[1]}]"#,
+                r#"[1]"#,
+            ),
+        ];
+
+        for (document, patch, expected) in cases {
+            assert_eq!(apply(document, patch), Ok(json(expected)), "patch: {patch}");
+        }
+    }
+
+    #[test]
+    fn rolls_back_on_failure() {
+        let mut value = json(r#"{"baz":"qux"}"#);
+        let patch = parse_patch(&json(
+            r#"[{"op":"add","path":"/a","value":1},{"op":"test","path":"/baz","value":"bar"}]"#,
+        ))
+        .unwrap();
+
+        let actual = value.apply_patch(&patch);
+
+        assert_eq!(actual, Err(PatchError::TestFailed { index: 1 }));
+        assert_eq!(value, json(r#"{"baz":"qux"}"#));
+    }
+
+    #[test]
+    fn reports_failing_operations() {
+        let cases = [
+            (
+                r#"[{"op":"remove","path":"/missing"}]"#,
+                PatchError::Pointer {
+                    index: 0,
+                    error: PointerError::NotFound(String::from("/missing")),
+                },
+            ),
+            (
+                r#"[{"op":"add","path":"/list/5","value":1}]"#,
+                PatchError::Pointer {
+                    index: 0,
+                    error: PointerError::NotFound(String::from("/list/5")),
+                },
+            ),
+            (
+                r#"[{"op":"move","from":"/obj","path":"/obj/child"}]"#,
+                PatchError::MoveIntoChild { index: 0 },
+            ),
+            (
+                r#"[{"op":"test","path":"/list","value":[1]},{"op":"copy","from":"x","path":"/y"}]"#,
+                PatchError::Pointer {
+                    index: 1,
+                    error: PointerError::InvalidSyntax(String::from("x")),
+                },
+            ),
+        ];
+
+        for (patch, expected) in cases {
+            assert_eq!(
+                apply(r#"{"list":[1],"obj":{}}"#, patch),
+                Err(expected),
+                "patch: {patch}"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_patches() {
+        let cases = [
+            (r#"{"op":"add"}"#, 0),
+            (
+                r#"[{"op":"remove","path":"/a"},{"op":"add","path":"/a"}]"#,
+                1,
+            ),
+            (r#"[{"op":"move","path":"/a"}]"#, 0),
+            (r#"[{"op":"frobnicate","path":"/a"}]"#, 0),
+            (r#"[{"op":"remove"}]"#, 0),
+        ];
+
+        for (patch, index) in cases {
+            assert_eq!(
+                parse_patch(&json(patch)),
+                Err(PatchError::Malformed { index }),
+                "patch: {patch}"
+            );
+        }
+    }
+
+    #[test]
+    fn round_trips_operations_through_values() {
+        let patch = json(
+            r#"[{"op":"move","from":"/a","path":"/b"},{"op":"test","path":"/b","value":null}]"#,
+        );
+
+        let ops = parse_patch(&patch).unwrap();
+        let values: Vec<Value> = ops.iter().map(PatchOp::to_value).collect();
+
+        assert_eq!(Value::Array(values), patch);
+    }
+
+    fn ops(patch: &str) -> Vec<PatchOp> {
+        parse_patch(&json(patch)).unwrap()
+    }
+
+    #[test]
+    fn diffs_to_small_patches() {
+        let cases = [
+            (
+                r#"{"a":1,"b":2}"#,
+                r#"{"a":1,"b":3,"c":4}"#,
+                r#"[{"op":"replace","path":"/b","value":3},{"op":"add","path":"/c","value":4}]"#,
+            ),
+            (
+                r#"{"a/b":1,"c":2}"#,
+                r#"{"c":2}"#,
+                r#"[{"op":"remove","path":"/a~1b"}]"#,
+            ),
+            (r#"[1,2,3]"#, r#"[1,3]"#, r#"[{"op":"remove","path":"/1"}]"#),
+            (
+                r#"[1,3]"#,
+                r#"[0,1,2,3]"#,
+                r#"[{"op":"add","path":"/0","value":0},{"op":"add","path":"/2","value":2}]"#,
+            ),
+            (
+                r#"[1,2,3,4]"#,
+                r#"[2,3,4,1]"#,
+                r#"[{"op":"move","from":"/0","path":"/3"}]"#,
+            ),
+            (
+                r#"[4,1,2,3]"#,
+                r#"[1,2,3,4]"#,
+                r#"[{"op":"move","from":"/0","path":"/3"}]"#,
+            ),
+            (
+                r#"[{"id":1,"n":"a"},{"id":2}]"#,
+                r#"[{"id":1,"n":"b"},{"id":2}]"#,
+                r#"[{"op":"replace","path":"/0/n","value":"b"}]"#,
+            ),
+            (
+                r#"{"a":[1]}"#,
+                r#"{"a":{"0":1}}"#,
+                r#"[{"op":"replace","path":"/a","value":{"0":1}}]"#,
+            ),
+            (r#"[1]"#, r#"[1]"#, r#"[]"#),
+        ];
+
+        for (a, b, expected) in cases {
+            assert_eq!(
+                diff_to_patch(&json(a), &json(b)),
+                ops(expected),
+                "{a} -> {b}"
+            );
+        }
+    }
+
+    #[test]
+    fn generated_patches_reproduce_target() {
+        let cases = [
+            (r#"[1,2,3,4,5]"#, r#"[5,4,3,2,1]"#),
+            (r#"[1,2,1,2,3]"#, r#"[3,2,1,9,1]"#),
+            (
+                r#"["a",{"x":[1,2]},"b","c"]"#,
+                r#"["c",{"x":[2,1,0]},"d","a"]"#,
+            ),
+            (r#"[[1],[2],[3]]"#, r#"[[3],[1,1],[2],[4]]"#),
+            (
+                r#"{"k":[1,2,3],"o":{"p":null}}"#,
+                r#"{"k":[3,1],"o":{"q":[]},"n":true}"#,
+            ),
+            (r#"[]"#, r#"[1,[2],{"3":3}]"#),
+            (r#"[1,[2],{"3":3}]"#, r#"[]"#),
+            (r#"1"#, r#"[1]"#),
+        ];
+
+        for (a, b) in cases {
+            let mut patched = json(a);
+            let patch = diff_to_patch(&patched, &json(b));
+
+            patched.apply_patch(&patch).unwrap();
+
+            assert_eq!(patched, json(b), "{a} -> {b} with {patch:?}");
+        }
+    }
+}
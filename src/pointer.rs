@@ -0,0 +1,643 @@
+use std::{fmt, mem, str::FromStr};
+
+use crate::Value;
+
+/// Location within a document as defined by JSON Pointer (RFC 6901),
+/// stored as a sequence of unescaped reference tokens.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct JsonPointer {
+    tokens: Vec<String>,
+}
+
+impl JsonPointer {
+    /// Pointer to the whole document.
+    pub fn root() -> Self {
+        Self::default()
+    }
+
+    pub fn parse(pointer: &str) -> Result<Self, PointerError> {
+        parse_pointer(pointer)
+            .map(|tokens| Self { tokens })
+            .ok_or_else(|| PointerError::InvalidSyntax(pointer.to_string()))
+    }
+
+    /// Parses a pointer from untrusted input, checking its size before
+    /// splitting it.
+    pub fn parse_with_limits(pointer: &str, limits: &PointerLimits) -> Result<Self, PointerError> {
+        if pointer.len() > limits.max_length {
+            return Err(PointerError::TooLong {
+                length: pointer.len(),
+                limit: limits.max_length,
+            });
+        }
+        if pointer.starts_with('#') {
+            return Err(PointerError::Unsupported(String::from(
+                "URI fragment identifiers",
+            )));
+        }
+        let depth = pointer.bytes().filter(|&b| b == b'/').count();
+        if depth > limits.max_depth {
+            return Err(PointerError::TooDeep {
+                depth,
+                limit: limits.max_depth,
+            });
+        }
+        Self::parse(pointer)
+    }
+
+    pub fn tokens(&self) -> &[String] {
+        &self.tokens
+    }
+
+    pub fn is_root(&self) -> bool {
+        self.tokens.is_empty()
+    }
+
+    /// Appends an unescaped reference token.
+    pub fn push(&mut self, token: impl Into<String>) {
+        self.tokens.push(token.into());
+    }
+
+    /// Removes and returns the last reference token.
+    pub fn pop(&mut self) -> Option<String> {
+        self.tokens.pop()
+    }
+
+    /// Pointer to a member or element of the pointed-to value.
+    pub fn child(&self, token: impl Into<String>) -> Self {
+        let mut child = self.clone();
+        child.push(token);
+        child
+    }
+
+    /// Whether `prefix` points to this location or to one containing it.
+    pub fn starts_with(&self, prefix: &JsonPointer) -> bool {
+        self.tokens.starts_with(&prefix.tokens)
+    }
+
+    /// Pointer to the value containing the pointed-to value, `None` for the root.
+    pub fn parent(&self) -> Option<Self> {
+        let (_, parent) = self.tokens.split_last()?;
+        Some(Self {
+            tokens: parent.to_vec(),
+        })
+    }
+}
+
+impl fmt::Display for JsonPointer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for token in &self.tokens {
+            write!(f, "/{}", escape_token(token))?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for JsonPointer {
+    type Err = PointerError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+/// Bounds checked by [`JsonPointer::parse_with_limits`] and the other
+/// `_with_limits` lookups
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PointerLimits {
+    /// Largest number of reference tokens
+    pub max_depth: usize,
+
+    /// Largest length of the pointer in bytes
+    pub max_length: usize,
+}
+
+impl Default for PointerLimits {
+    fn default() -> Self {
+        Self {
+            max_depth: 128,
+            max_length: 4096,
+        }
+    }
+}
+
+/// Possible errors that can occur when applying a JSON pointer
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum PointerError {
+    /// The pointer is not empty and does not start with '/'
+    InvalidSyntax(String),
+
+    /// The pointer has more reference tokens than allowed
+    TooDeep { depth: usize, limit: usize },
+
+    /// The pointer is longer than allowed
+    TooLong { length: usize, limit: usize },
+
+    /// The pointer uses a form this crate does not resolve
+    Unsupported(String),
+
+    /// No value exists at the pointer and it could not be created
+    NotFound(String),
+
+    /// A value already exists where a new one was meant to be added
+    AlreadyExists(String),
+}
+
+impl Value {
+    /// Looks up the value at a JSON pointer such as `/users/0/name`, where `~1`
+    /// stands for `/` and `~0` for `~` within a token. `None` when the pointer
+    /// is malformed or nothing is there.
+    pub fn pointer(&self, pointer: &str) -> Option<&Value> {
+        resolve_tokens(self, &parse_pointer(pointer)?)
+    }
+
+    /// Looks up the value at a pointer from untrusted input, checking it
+    /// against `limits` first. `Ok(None)` when nothing is there.
+    pub fn pointer_with_limits(
+        &self,
+        pointer: &str,
+        limits: &PointerLimits,
+    ) -> Result<Option<&Value>, PointerError> {
+        let pointer = JsonPointer::parse_with_limits(pointer, limits)?;
+        Ok(resolve_tokens(self, pointer.tokens()))
+    }
+
+    pub fn pointer_mut(&mut self, pointer: &str) -> Option<&mut Value> {
+        resolve_tokens_mut(self, &parse_pointer(pointer)?)
+    }
+
+    /// Replaces the value at the pointer with the result of calling `f` with it.
+    ///
+    /// When `create` is set, missing object members along the path are created,
+    /// as are array elements addressed by `-` or by the index right after the last
+    /// element, and `f` receives `Value::Null` for a missing target. Missing
+    /// intermediate containers are objects, unless the next token is `-`.
+    pub fn update_pointer<F>(
+        &mut self,
+        pointer: &str,
+        create: bool,
+        f: F,
+    ) -> Result<(), PointerError>
+    where
+        F: FnOnce(Value) -> Value,
+    {
+        let tokens = parse_pointer(pointer)
+            .ok_or_else(|| PointerError::InvalidSyntax(pointer.to_string()))?;
+
+        let target = if create {
+            resolve_or_create(self, &tokens)
+        } else {
+            resolve_tokens_mut(self, &tokens)
+        };
+        let target = target.ok_or_else(|| PointerError::NotFound(pointer.to_string()))?;
+
+        let old = mem::replace(target, Value::Null);
+        *target = f(old);
+
+        Ok(())
+    }
+
+    /// Returns pointers to every location in the document holding a value equal
+    /// to `needle`, including the root. Members of an object are visited in
+    /// unspecified order, elements of an array in index order.
+    pub fn paths_of(&self, needle: &Value) -> Vec<JsonPointer> {
+        let mut paths = Vec::new();
+        collect_paths(self, needle, &mut JsonPointer::root(), &mut paths);
+        paths
+    }
+}
+
+fn collect_paths(
+    value: &Value,
+    needle: &Value,
+    current: &mut JsonPointer,
+    paths: &mut Vec<JsonPointer>,
+) {
+    if value == needle {
+        paths.push(current.clone());
+    }
+
+    match value {
+        Value::Array(values) => {
+            for (index, value) in values.iter().enumerate() {
+                current.push(index.to_string());
+                collect_paths(value, needle, current, paths);
+                current.pop();
+            }
+        }
+        Value::Object(map) => {
+            for (key, value) in map {
+                current.push(key.clone());
+                collect_paths(value, needle, current, paths);
+                current.pop();
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Splits a JSON pointer (RFC 6901) into its unescaped reference tokens.
+///
+/// Returns `None` when a non-empty pointer does not start with '/' or a `~`
+/// is not followed by `0` or `1`.
+pub(crate) fn parse_pointer(pointer: &str) -> Option<Vec<String>> {
+    if pointer.is_empty() {
+        return Some(Vec::new());
+    }
+
+    let rest = pointer.strip_prefix('/')?;
+    if has_invalid_escape(rest) {
+        return None;
+    }
+    let tokens = rest.split('/').map(unescape_token).collect();
+    Some(tokens)
+}
+
+/// Follows the reference tokens starting from the given value.
+pub(crate) fn resolve_tokens<'a>(value: &'a Value, tokens: &[String]) -> Option<&'a Value> {
+    let mut current = value;
+    for token in tokens {
+        current = match current {
+            Value::Object(map) => map.get(token)?,
+            Value::Array(values) => values.get(parse_index(token)?)?,
+            _ => return None,
+        };
+    }
+    Some(current)
+}
+
+/// Follows the reference tokens starting from the given value, allowing mutation.
+pub(crate) fn resolve_tokens_mut<'a>(
+    value: &'a mut Value,
+    tokens: &[String],
+) -> Option<&'a mut Value> {
+    let mut current = value;
+    for token in tokens {
+        current = match current {
+            Value::Object(map) => map.get_mut(token)?,
+            Value::Array(values) => values.get_mut(parse_index(token)?)?,
+            _ => return None,
+        };
+    }
+    Some(current)
+}
+
+/// Like [`resolve_tokens_mut`], but creates missing members and appended array
+/// elements along the way. Scalars on the path cannot be descended into.
+pub(crate) fn resolve_or_create<'a>(
+    value: &'a mut Value,
+    tokens: &[String],
+) -> Option<&'a mut Value> {
+    let mut current = value;
+    for (position, token) in tokens.iter().enumerate() {
+        let placeholder = || match tokens.get(position + 1) {
+            None => Value::Null,
+            Some(next) if next == "-" => Value::Array(Vec::new()),
+            Some(_) => Value::Object(Default::default()),
+        };
+
+        current = match current {
+            Value::Object(map) => map.entry(token.clone()).or_insert_with(placeholder),
+            Value::Array(values) => {
+                let index = if token == "-" {
+                    values.len()
+                } else {
+                    parse_index(token)?
+                };
+                if index == values.len() {
+                    values.push(placeholder());
+                }
+                values.get_mut(index)?
+            }
+            _ => return None,
+        };
+    }
+    Some(current)
+}
+
+/// Parses an array index token, rejecting leading zeros as required by RFC 6901.
+pub(crate) fn parse_index(token: &str) -> Option<usize> {
+    let is_digits = !token.is_empty() && token.chars().all(|c| c.is_ascii_digit());
+    if !is_digits || (token.len() > 1 && token.starts_with('0')) {
+        return None;
+    }
+    token.parse().ok()
+}
+
+/// Escapes a reference token so it can be appended to a pointer.
+pub(crate) fn escape_token(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+/// Whether a `~` is not followed by `0` or `1`.
+fn has_invalid_escape(pointer: &str) -> bool {
+    let mut bytes = pointer.bytes();
+    while let Some(byte) = bytes.next() {
+        if byte == b'~' && !matches!(bytes.next(), Some(b'0' | b'1')) {
+            return true;
+        }
+    }
+    false
+}
+
+fn unescape_token(token: &str) -> String {
+    token.replace("~1", "/").replace("~0", "~")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Map, Value};
+
+    use super::{parse_pointer, resolve_tokens, JsonPointer, PointerError, PointerLimits};
+
+    fn config() -> Value {
+        Value::object([(
+            "server",
+            Value::object([
+                ("port", Value::Number(80.0)),
+                ("hosts", Value::Array(vec![Value::string("a")])),
+            ]),
+        )])
+    }
+
+    #[test]
+    fn parses_empty_pointer() {
+        let expected: Vec<String> = vec![];
+
+        let actual = parse_pointer("").unwrap();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn parses_escaped_tokens() {
+        let expected = vec![String::from("a/b"), String::from("m~n")];
+
+        let actual = parse_pointer("/a~1b/m~0n").unwrap();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn rejects_pointer_without_leading_slash() {
+        let actual = parse_pointer("a/b");
+
+        assert_eq!(actual, None);
+    }
+
+    #[test]
+    fn resolves_nested_tokens() {
+        let value = Value::object([(
+            "list",
+            Value::Array(vec![Value::Null, Value::string("found")]),
+        )]);
+        let tokens = parse_pointer("/list/1").unwrap();
+        let expected = Value::string("found");
+
+        let actual = resolve_tokens(&value, &tokens).unwrap();
+
+        assert_eq!(actual, &expected);
+    }
+
+    #[test]
+    fn rejects_index_with_leading_zero() {
+        let value = Value::Array(vec![Value::Null, Value::Null]);
+        let tokens = parse_pointer("/01").unwrap();
+
+        let actual = resolve_tokens(&value, &tokens);
+
+        assert_eq!(actual, None);
+    }
+
+    #[test]
+    fn updates_existing_value() {
+        let mut value = config();
+
+        value
+            .update_pointer("/server/port", false, |port| match port {
+                Value::Number(n) => Value::Number(n + 1.0),
+                other => other,
+            })
+            .unwrap();
+
+        let port = resolve_tokens(&value, &parse_pointer("/server/port").unwrap());
+        assert_eq!(port, Some(&Value::Number(81.0)));
+    }
+
+    #[test]
+    fn update_fails_on_missing_path() {
+        let mut value = config();
+        let expected = Err(PointerError::NotFound(String::from("/server/timeout")));
+
+        let actual = value.update_pointer("/server/timeout", false, |v| v);
+
+        assert_eq!(actual, expected);
+        assert_eq!(value, config());
+    }
+
+    #[test]
+    fn update_fails_on_invalid_pointer() {
+        let mut value = config();
+        let expected = Err(PointerError::InvalidSyntax(String::from("server")));
+
+        let actual = value.update_pointer("server", false, |v| v);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn update_creates_missing_path() {
+        let mut value = Value::Object(Map::new());
+        let expected = Value::object([(
+            "a",
+            Value::object([("list", Value::Array(vec![Value::Boolean(true)]))]),
+        )]);
+
+        value
+            .update_pointer("/a/list/-", true, |old| {
+                assert_eq!(old, Value::Null);
+                Value::Boolean(true)
+            })
+            .unwrap();
+
+        assert_eq!(value, expected);
+    }
+
+    #[test]
+    fn update_appends_at_array_length() {
+        let mut value = config();
+
+        value
+            .update_pointer("/server/hosts/1", true, |_| Value::string("b"))
+            .unwrap();
+
+        let hosts = resolve_tokens(&value, &parse_pointer("/server/hosts").unwrap());
+        assert_eq!(
+            hosts,
+            Some(&Value::Array(vec![Value::string("a"), Value::string("b")]))
+        );
+    }
+
+    #[test]
+    fn update_does_not_descend_into_scalar() {
+        let mut value = config();
+        let expected = Err(PointerError::NotFound(String::from("/server/port/x")));
+
+        let actual = value.update_pointer("/server/port/x", true, |v| v);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn pointer_round_trips_through_display() {
+        let input = "/a~1b/0/m~0n";
+
+        let actual = JsonPointer::parse(input).unwrap().to_string();
+
+        assert_eq!(actual, input);
+    }
+
+    #[test]
+    fn pointer_parent_and_child() {
+        let pointer = JsonPointer::parse("/a/b").unwrap();
+
+        assert_eq!(pointer.parent().unwrap().to_string(), "/a");
+        assert_eq!(pointer.child("c/d").to_string(), "/a/b/c~1d");
+        assert_eq!(JsonPointer::root().parent(), None);
+    }
+
+    #[test]
+    fn finds_all_paths_of_subvalue() {
+        let needle = Value::object([("id", Value::Number(7.0))]);
+        let value = Value::object([
+            ("first", needle.clone()),
+            (
+                "list",
+                Value::Array(vec![Value::Null, needle.clone(), Value::Null]),
+            ),
+            ("other", Value::object([("id", Value::Number(8.0))])),
+        ]);
+        let expected = vec![String::from("/first"), String::from("/list/1")];
+
+        let mut actual: Vec<String> = value
+            .paths_of(&needle)
+            .iter()
+            .map(JsonPointer::to_string)
+            .collect();
+        actual.sort();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn finds_root_path() {
+        let value = Value::Array(vec![]);
+
+        let actual = value.paths_of(&Value::Array(vec![]));
+
+        assert_eq!(actual, vec![JsonPointer::root()]);
+    }
+
+    #[test]
+    fn finds_no_paths_of_missing_value() {
+        let value = config();
+
+        let actual = value.paths_of(&Value::Boolean(false));
+
+        assert!(actual.is_empty());
+    }
+
+    #[test]
+    fn rejects_unknown_escapes() {
+        let value = Value::object([("a~2", Value::Null)]);
+
+        assert_eq!(
+            JsonPointer::parse("/a~2"),
+            Err(PointerError::InvalidSyntax(String::from("/a~2")))
+        );
+        assert_eq!(value.pointer("/a~2"), None);
+    }
+
+    #[test]
+    fn looks_up_pointer_within_limits() {
+        let value = config();
+        let limits = PointerLimits {
+            max_depth: 2,
+            max_length: 16,
+        };
+
+        assert_eq!(
+            value.pointer_with_limits("/server/port", &limits),
+            Ok(Some(&Value::Number(80.0)))
+        );
+        assert_eq!(
+            value.pointer_with_limits("/a/b/c", &limits),
+            Err(PointerError::TooDeep { depth: 3, limit: 2 })
+        );
+    }
+
+    #[test]
+    fn parses_pointer_within_limits() {
+        let limits = PointerLimits {
+            max_depth: 2,
+            max_length: 8,
+        };
+
+        let actual = JsonPointer::parse_with_limits("/a~1/0", &limits);
+
+        assert_eq!(actual.unwrap().tokens(), ["a/", "0"]);
+    }
+
+    #[test]
+    fn rejects_pointer_beyond_limits() {
+        let limits = PointerLimits {
+            max_depth: 2,
+            max_length: 8,
+        };
+        let cases = [
+            ("/a/b/c", PointerError::TooDeep { depth: 3, limit: 2 }),
+            (
+                "/abcdefgh",
+                PointerError::TooLong {
+                    length: 9,
+                    limit: 8,
+                },
+            ),
+            (
+                "#/a",
+                PointerError::Unsupported(String::from("URI fragment identifiers")),
+            ),
+            ("/a~2", PointerError::InvalidSyntax(String::from("/a~2"))),
+            ("/a~", PointerError::InvalidSyntax(String::from("/a~"))),
+            ("a", PointerError::InvalidSyntax(String::from("a"))),
+        ];
+
+        for (input, expected) in cases {
+            assert_eq!(
+                JsonPointer::parse_with_limits(input, &limits),
+                Err(expected),
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn looks_up_value_at_pointer() {
+        let mut value = Value::object([(
+            "users",
+            Value::array([Value::object([("a/b~c", Value::string("x"))])]),
+        )]);
+
+        assert_eq!(value.pointer("/users/0/a~1b~0c"), Some(&Value::string("x")));
+        assert_eq!(
+            value.pointer("").map(Value::kind),
+            Some(crate::ValueKind::Object)
+        );
+        assert_eq!(value.pointer("/users/01"), None);
+        assert_eq!(value.pointer("users"), None);
+
+        *value.pointer_mut("/users/0").unwrap() = Value::Null;
+
+        assert_eq!(value.pointer("/users/0"), Some(&Value::Null));
+    }
+}
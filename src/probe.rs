@@ -0,0 +1,124 @@
+use std::io::Read;
+
+use crate::{
+    stream::{opens_container, Step, StreamError, Walker},
+    tokenizer::TokenizeError,
+    JsonPointer,
+};
+
+/// Whether the document read from `reader` has a value at `pointer`.
+///
+/// Reading stops as soon as the answer is known, so the rest of the input is
+/// not checked for errors.
+pub fn exists_at<R: Read>(reader: R, pointer: &str) -> Result<bool, StreamError> {
+    let target = JsonPointer::parse(pointer)?;
+    let mut walker = Walker::new(reader);
+
+    Ok(walker.seek(target.tokens())?.is_some())
+}
+
+/// Number of elements or members of the container at `pointer`, `None` when
+/// there is no value at `pointer` or it is not a container.
+///
+/// Reading stops once the container is closed, so the rest of the input is
+/// not checked for errors.
+pub fn count_at<R: Read>(reader: R, pointer: &str) -> Result<Option<usize>, StreamError> {
+    let target = JsonPointer::parse(pointer)?;
+    let mut walker = Walker::new(reader);
+
+    match walker.seek(target.tokens())? {
+        Some(token) if opens_container(&token) => {}
+        _ => return Ok(None),
+    }
+
+    let mut count = 0;
+    loop {
+        match walker.next()? {
+            Some(Step::Value { token, .. }) => {
+                count += 1;
+                if opens_container(&token) {
+                    walker.skip_container()?;
+                }
+            }
+            Some(Step::Close(_)) => return Ok(Some(count)),
+            None => return Err(TokenizeError::UnexpectedEof.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{stream::StreamError, PointerError};
+
+    use super::{count_at, exists_at};
+
+    const DOCUMENT: &str = r#"{
+        "items": [{"id": 1}, {"id": 2, "tags": ["a", "b"]}, 3],
+        "meta": {"next": null, "total": 3},
+        "empty": {}
+    }"#;
+
+    #[test]
+    fn counts_array_elements_and_object_members() {
+        let cases = [
+            ("/items", Some(3)),
+            ("/meta", Some(2)),
+            ("/items/1/tags", Some(2)),
+            ("/empty", Some(0)),
+            ("", Some(3)),
+        ];
+
+        for (pointer, expected) in cases {
+            let actual = count_at(DOCUMENT.as_bytes(), pointer).unwrap();
+
+            assert_eq!(actual, expected, "pointer: {pointer}");
+        }
+    }
+
+    #[test]
+    fn counts_nothing_at_scalars_and_missing_paths() {
+        let cases = ["/items/2", "/missing", "/items/3", "/meta/total/x"];
+
+        for pointer in cases {
+            let actual = count_at(DOCUMENT.as_bytes(), pointer).unwrap();
+
+            assert_eq!(actual, None, "pointer: {pointer}");
+        }
+    }
+
+    #[test]
+    fn checks_existence() {
+        let cases = [
+            ("/meta/next", true),
+            ("/items/1/tags/0", true),
+            ("/items/01", false),
+            ("/meta/prev", false),
+            ("/empty/x", false),
+        ];
+
+        for (pointer, expected) in cases {
+            let actual = exists_at(DOCUMENT.as_bytes(), pointer).unwrap();
+
+            assert_eq!(actual, expected, "pointer: {pointer}");
+        }
+    }
+
+    #[test]
+    fn stops_reading_once_found() {
+        let input = r#"{"a": 1, "b": [oops"#;
+
+        let actual = exists_at(input.as_bytes(), "/a");
+
+        assert!(actual.unwrap());
+    }
+
+    #[test]
+    fn rejects_invalid_pointer() {
+        let actual = exists_at(DOCUMENT.as_bytes(), "items");
+
+        assert!(matches!(
+            actual,
+            Err(StreamError::PointerError(PointerError::InvalidSyntax(_)))
+        ));
+    }
+}
@@ -0,0 +1,108 @@
+use std::io::{self, Read};
+
+/// How much of the input has been read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    pub bytes_read: u64,
+
+    /// Size of the whole input, when known
+    pub total: Option<u64>,
+}
+
+impl Progress {
+    /// Share of the input read so far, from 0 to 1, when the total is known.
+    pub fn fraction(&self) -> Option<f64> {
+        match self.total {
+            Some(0) => Some(1.0),
+            Some(total) => Some((self.bytes_read as f64 / total as f64).min(1.0)),
+            None => None,
+        }
+    }
+}
+
+/// Reader reporting its progress to a callback, for rendering progress of
+/// reader-based parsing such as [`crate::parse_reader`] or [`crate::transcode`].
+///
+/// The callback runs after every read that returned data. The streaming
+/// parsers read in chunks of several kilobytes, so it runs once per chunk.
+pub struct ProgressReader<R, F> {
+    reader: R,
+    progress: Progress,
+    on_progress: F,
+}
+
+impl<R: Read, F: FnMut(Progress)> ProgressReader<R, F> {
+    /// Wraps `reader`. `total` is the input size if known, e.g. the file length.
+    pub fn new(reader: R, total: Option<u64>, on_progress: F) -> Self {
+        Self {
+            reader,
+            progress: Progress {
+                bytes_read: 0,
+                total,
+            },
+            on_progress,
+        }
+    }
+
+    pub fn progress(&self) -> Progress {
+        self.progress
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R: Read, F: FnMut(Progress)> Read for ProgressReader<R, F> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.reader.read(buf)?;
+        if read > 0 {
+            self.progress.bytes_read += read as u64;
+            (self.on_progress)(self.progress);
+        }
+        Ok(read)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse, parse_reader};
+
+    use super::{Progress, ProgressReader};
+
+    #[test]
+    fn reports_progress_while_parsing() {
+        let input = format!("[{}]", vec!["\"item\""; 10_000].join(","));
+        let total = input.len() as u64;
+        let mut reports = Vec::new();
+
+        let reader = ProgressReader::new(input.as_bytes(), Some(total), |progress| {
+            reports.push(progress)
+        });
+        let actual = parse_reader(reader).unwrap();
+
+        assert_eq!(actual, parse(input.clone()).unwrap());
+        assert!(reports.len() > 1);
+        assert!(reports
+            .windows(2)
+            .all(|pair| pair[0].bytes_read < pair[1].bytes_read));
+        assert_eq!(
+            reports.last(),
+            Some(&Progress {
+                bytes_read: total,
+                total: Some(total),
+            })
+        );
+        assert_eq!(reports.last().unwrap().fraction(), Some(1.0));
+    }
+
+    #[test]
+    fn fraction_needs_total() {
+        let progress = Progress {
+            bytes_read: 10,
+            total: None,
+        };
+
+        assert_eq!(progress.fraction(), None);
+    }
+}
@@ -0,0 +1,168 @@
+use crate::{
+    lookup::{follow_path, parse_path, Segment},
+    JsonPath, JsonPathError, Value,
+};
+
+/// Dotted path or JSONPath query parsed once, for running the same query
+/// against many values
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompiledQuery {
+    kind: QueryKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum QueryKind {
+    Path(Vec<Segment>),
+    JsonPath(JsonPath),
+}
+
+/// Bounds on query expressions from untrusted input, checked by
+/// [`JsonPath::parse_with_limits`], [`crate::JmesPath::parse_with_limits`]
+/// and [`crate::JqFilter::parse_with_limits`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueryLimits {
+    /// Deepest nesting of parentheses, filters and other sub-expressions
+    pub max_depth: usize,
+
+    /// Largest length of the expression in bytes
+    pub max_length: usize,
+}
+
+impl QueryLimits {
+    /// Limits applied by the plain `parse` constructors: the parsers recurse
+    /// once per level of nesting, so the depth stays bounded even for
+    /// trusted expressions, while their length does not.
+    pub(crate) fn nesting_only() -> Self {
+        Self {
+            max_length: usize::MAX,
+            ..Self::default()
+        }
+    }
+}
+
+impl Default for QueryLimits {
+    fn default() -> Self {
+        Self {
+            max_depth: 128,
+            max_length: 4096,
+        }
+    }
+}
+
+/// Possible errors when compiling a query
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QueryError {
+    /// The dotted path is malformed, see [`Value::get_path`]
+    MalformedPath,
+
+    /// The JSONPath query could not be parsed
+    JsonPath(JsonPathError),
+}
+
+impl CompiledQuery {
+    /// Compiles a JSONPath query when `query` starts with `$`, a dotted path
+    /// otherwise.
+    pub fn parse(query: &str) -> Result<Self, QueryError> {
+        if query.starts_with('$') {
+            Self::jsonpath(query)
+        } else {
+            Self::path(query)
+        }
+    }
+
+    /// Compiles a dotted path like `servers[2].host`.
+    pub fn path(path: &str) -> Result<Self, QueryError> {
+        let segments = parse_path(path).ok_or(QueryError::MalformedPath)?;
+        Ok(Self {
+            kind: QueryKind::Path(segments),
+        })
+    }
+
+    pub fn jsonpath(query: &str) -> Result<Self, QueryError> {
+        let path = JsonPath::parse(query).map_err(QueryError::JsonPath)?;
+        Ok(Self {
+            kind: QueryKind::JsonPath(path),
+        })
+    }
+
+    /// Every value the query selects. A dotted path selects at most one.
+    pub fn run<'a>(&self, value: &'a Value) -> Vec<&'a Value> {
+        match &self.kind {
+            QueryKind::Path(segments) => follow_path(segments, value).into_iter().collect(),
+            QueryKind::JsonPath(path) => path.query(value),
+        }
+    }
+
+    /// The first value the query selects.
+    pub fn first<'a>(&self, value: &'a Value) -> Option<&'a Value> {
+        match &self.kind {
+            QueryKind::Path(segments) => follow_path(segments, value),
+            QueryKind::JsonPath(path) => path.query(value).into_iter().next(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{JsonPathError, Value};
+
+    use super::{CompiledQuery, QueryError};
+
+    fn records() -> Vec<Value> {
+        (0..3)
+            .map(|i| {
+                Value::object([(
+                    "servers",
+                    Value::array([
+                        Value::object([("host", Value::string(format!("a{i}")))]),
+                        Value::object([("host", Value::string(format!("b{i}")))]),
+                    ]),
+                )])
+            })
+            .collect()
+    }
+
+    #[test]
+    fn runs_compiled_path_against_many_values() {
+        let query = CompiledQuery::parse("servers[1].host").unwrap();
+
+        let hosts: Vec<_> = records()
+            .iter()
+            .map(|record| query.first(record).cloned())
+            .collect();
+
+        assert_eq!(
+            hosts,
+            [
+                Some(Value::string("b0")),
+                Some(Value::string("b1")),
+                Some(Value::string("b2"))
+            ]
+        );
+        assert_eq!(query.run(&Value::Null), Vec::<&Value>::new());
+    }
+
+    #[test]
+    fn runs_compiled_jsonpath() {
+        let query = CompiledQuery::parse("$.servers[*].host").unwrap();
+        let records = records();
+
+        assert_eq!(
+            query.run(&records[2]),
+            [&Value::string("a2"), &Value::string("b2")]
+        );
+        assert_eq!(query.first(&records[0]), Some(&Value::string("a0")));
+    }
+
+    #[test]
+    fn reports_invalid_queries() {
+        assert_eq!(
+            CompiledQuery::parse("servers[x]"),
+            Err(QueryError::MalformedPath)
+        );
+        assert_eq!(
+            CompiledQuery::parse("$.servers["),
+            Err(QueryError::JsonPath(JsonPathError::UnexpectedEnd))
+        );
+    }
+}
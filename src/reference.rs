@@ -0,0 +1,529 @@
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use crate::{
+    parse,
+    pointer::{parse_pointer, resolve_tokens},
+    Map, ParseError, Value,
+};
+
+/// Deepest chain of references followed from one value
+const MAX_REF_DEPTH: usize = 32;
+
+/// Most values a resolved document may contain, so that references used
+/// many times over cannot expand it without bound
+const MAX_RESOLVED_VALUES: usize = 1 << 20;
+
+/// Possible errors that can occur when resolving `$ref` references
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum RefError {
+    /// The reference targets a resource that cannot be loaded (remote URL, a
+    /// file outside the base directory, or an external file while external
+    /// references are disabled)
+    UnsupportedReference(String),
+
+    /// The reference points to a location that does not exist
+    UnresolvedReference(String),
+
+    /// The reference directly or transitively points to itself
+    CycleDetected(String),
+
+    /// A referenced file could not be read
+    Io(PathBuf, io::ErrorKind),
+
+    /// A referenced file does not contain valid JSON
+    InvalidDocument(PathBuf, ParseError),
+
+    /// More than this many references are followed one inside another
+    TooDeep { limit: usize },
+
+    /// The resolved document would hold more than this many values
+    TooLarge { limit: usize },
+}
+
+impl Value {
+    /// Returns a copy of the value where every `{"$ref": "#/..."}` object is
+    /// replaced by the subtree it points to. Sibling keys of `$ref` are ignored.
+    pub fn resolve_refs(&self) -> Result<Value, RefError> {
+        let mut resolver = Resolver::new(None);
+        let document = Document::in_memory(self);
+        resolver.resolve(&document, self)
+    }
+
+    /// Like [`Value::resolve_refs`], but also loads references to local files
+    /// (`"common.json#/definitions/id"`) relative to `base_dir`, bundling them
+    /// into a single self-contained document. Files outside `base_dir` are
+    /// never loaded.
+    pub fn bundle_refs(&self, base_dir: impl AsRef<Path>) -> Result<Value, RefError> {
+        let base_dir = base_dir.as_ref();
+        let root = canonicalize(base_dir)?;
+        let mut resolver = Resolver::new(Some((base_dir.to_path_buf(), root)));
+        let document = Document::in_memory(self);
+        resolver.resolve(&document, self)
+    }
+}
+
+/// Reads the JSON file at `path` and bundles all references it contains.
+/// Only files in the directory of `path` and below it are loaded.
+pub fn bundle_file(path: impl AsRef<Path>) -> Result<Value, RefError> {
+    let path = canonicalize(path.as_ref())?;
+    let base_dir = path.parent().unwrap_or(Path::new("")).to_path_buf();
+
+    let mut resolver = Resolver::new(Some((base_dir.clone(), base_dir)));
+    let document = resolver.load(path)?;
+    let root = Rc::clone(&document.root);
+    resolver.resolve(&document, &root)
+}
+
+/// A document references are resolved against.
+struct Document {
+    /// File the document was loaded from, `None` for the value passed in by the caller
+    path: Option<PathBuf>,
+    root: Rc<Value>,
+}
+
+impl Document {
+    fn in_memory(value: &Value) -> Self {
+        Self {
+            path: None,
+            root: Rc::new(value.clone()),
+        }
+    }
+}
+
+struct Resolver {
+    /// Directory of the in-memory document as given and its canonical form,
+    /// which every loaded file must be inside. `None` disables external references
+    base_dir: Option<(PathBuf, PathBuf)>,
+    /// Loaded files by canonical path
+    documents: HashMap<PathBuf, Rc<Value>>,
+    active: Vec<(Option<PathBuf>, String)>,
+    /// Values produced so far
+    resolved: usize,
+}
+
+impl Resolver {
+    fn new(base_dir: Option<(PathBuf, PathBuf)>) -> Self {
+        Self {
+            base_dir,
+            documents: HashMap::new(),
+            active: Vec::new(),
+            resolved: 0,
+        }
+    }
+
+    fn resolve(&mut self, document: &Document, value: &Value) -> Result<Value, RefError> {
+        self.resolved += 1;
+        if self.resolved > MAX_RESOLVED_VALUES {
+            return Err(RefError::TooLarge {
+                limit: MAX_RESOLVED_VALUES,
+            });
+        }
+
+        match value {
+            Value::Object(map) => {
+                if let Some(Value::String(reference)) = map.get("$ref") {
+                    return self.resolve_reference(document, reference);
+                }
+
+                let mut output = Map::with_capacity(map.len());
+                for (key, value) in map {
+                    output.insert(key.clone(), self.resolve(document, value)?);
+                }
+                Ok(Value::Object(output))
+            }
+            Value::Array(values) => {
+                let output = values
+                    .iter()
+                    .map(|value| self.resolve(document, value))
+                    .collect::<Result<_, _>>()?;
+                Ok(Value::Array(output))
+            }
+            _ => Ok(value.clone()),
+        }
+    }
+
+    fn resolve_reference(
+        &mut self,
+        document: &Document,
+        reference: &str,
+    ) -> Result<Value, RefError> {
+        let (location, fragment) = reference.split_once('#').unwrap_or((reference, ""));
+
+        let target_document = if location.is_empty() {
+            Document {
+                path: document.path.clone(),
+                root: Rc::clone(&document.root),
+            }
+        } else {
+            let path = self.external_path(document, location, reference)?;
+            self.load(path)?
+        };
+
+        let key = (target_document.path.clone(), fragment.to_string());
+        if self.active.contains(&key) {
+            return Err(RefError::CycleDetected(reference.to_string()));
+        }
+        if self.active.len() == MAX_REF_DEPTH {
+            return Err(RefError::TooDeep {
+                limit: MAX_REF_DEPTH,
+            });
+        }
+
+        let root = Rc::clone(&target_document.root);
+        let target = lookup(&root, fragment)
+            .ok_or_else(|| RefError::UnresolvedReference(reference.to_string()))?;
+
+        self.active.push(key);
+        let resolved = self.resolve(&target_document, target);
+        self.active.pop();
+
+        resolved
+    }
+
+    /// Canonical path of the file `location` refers to, which must be inside
+    /// the base directory.
+    fn external_path(
+        &self,
+        document: &Document,
+        location: &str,
+        reference: &str,
+    ) -> Result<PathBuf, RefError> {
+        let unsupported = || RefError::UnsupportedReference(reference.to_string());
+
+        if location.contains("://") {
+            return Err(unsupported());
+        }
+
+        let (base_dir, root) = self.base_dir.as_ref().ok_or_else(unsupported)?;
+        let document_dir = match &document.path {
+            Some(path) => path.parent().unwrap_or(Path::new("")),
+            None => base_dir,
+        };
+        let location = percent_decode(location).ok_or_else(unsupported)?;
+
+        let path = canonicalize(&document_dir.join(location))?;
+        if !path.starts_with(root) {
+            return Err(unsupported());
+        }
+        Ok(path)
+    }
+
+    /// Loads the file at the canonical `path`, once.
+    fn load(&mut self, path: PathBuf) -> Result<Document, RefError> {
+        if let Some(root) = self.documents.get(&path) {
+            return Ok(Document {
+                path: Some(path),
+                root: Rc::clone(root),
+            });
+        }
+
+        let input =
+            fs::read_to_string(&path).map_err(|err| RefError::Io(path.clone(), err.kind()))?;
+        let value = parse(input).map_err(|err| RefError::InvalidDocument(path.clone(), err))?;
+
+        let root = Rc::new(value);
+        self.documents.insert(path.clone(), Rc::clone(&root));
+
+        Ok(Document {
+            path: Some(path),
+            root,
+        })
+    }
+}
+
+fn canonicalize(path: &Path) -> Result<PathBuf, RefError> {
+    fs::canonicalize(path).map_err(|err| RefError::Io(path.to_path_buf(), err.kind()))
+}
+
+fn lookup<'a>(root: &'a Value, fragment: &str) -> Option<&'a Value> {
+    let pointer = percent_decode(fragment)?;
+    parse_pointer(&pointer).and_then(|tokens| resolve_tokens(root, &tokens))
+}
+
+/// URI fragments may percent-encode characters of the pointer (RFC 6901, section 6).
+fn percent_decode(input: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(input.len());
+    let mut iter = input.bytes();
+    while let Some(byte) = iter.next() {
+        if byte == b'%' {
+            let high = char::from(iter.next()?).to_digit(16)?;
+            let low = char::from(iter.next()?).to_digit(16)?;
+            bytes.push((high * 16 + low) as u8);
+        } else {
+            bytes.push(byte);
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, io, path::PathBuf};
+
+    use crate::Value;
+
+    use super::{bundle_file, RefError};
+
+    fn reference(target: &str) -> Value {
+        Value::object([("$ref", Value::string(target))])
+    }
+
+    fn write_files(test_name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("json_parser_{test_name}"));
+        fs::create_dir_all(dir.join("nested")).unwrap();
+        for (name, contents) in files {
+            fs::write(dir.join(name), contents).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn resolves_definition_reference() {
+        let input = Value::object([
+            (
+                "definitions",
+                Value::object([("id", Value::string("integer"))]),
+            ),
+            ("property", reference("#/definitions/id")),
+        ]);
+        let expected = Value::object([
+            (
+                "definitions",
+                Value::object([("id", Value::string("integer"))]),
+            ),
+            ("property", Value::string("integer")),
+        ]);
+
+        let actual = input.resolve_refs().unwrap();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn resolves_transitive_references() {
+        let input = Value::object([
+            ("a", reference("#/b")),
+            ("b", reference("#/c")),
+            ("c", Value::Boolean(true)),
+        ]);
+        let expected = Value::object([
+            ("a", Value::Boolean(true)),
+            ("b", Value::Boolean(true)),
+            ("c", Value::Boolean(true)),
+        ]);
+
+        let actual = input.resolve_refs().unwrap();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn resolves_references_inside_arrays() {
+        let input = Value::object([
+            ("x", Value::Null),
+            ("list", Value::Array(vec![reference("#/x")])),
+        ]);
+        let expected = Value::object([
+            ("x", Value::Null),
+            ("list", Value::Array(vec![Value::Null])),
+        ]);
+
+        let actual = input.resolve_refs().unwrap();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn resolves_percent_encoded_fragment() {
+        let input = Value::object([("a b", Value::Number(1.0)), ("ref", reference("#/a%20b"))]);
+        let expected = Value::object([("a b", Value::Number(1.0)), ("ref", Value::Number(1.0))]);
+
+        let actual = input.resolve_refs().unwrap();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn fails_on_self_reference() {
+        let input = Value::object([("a", Value::object([("b", reference("#/a"))]))]);
+        let expected = Err(RefError::CycleDetected(String::from("#/a")));
+
+        let actual = input.resolve_refs();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn fails_on_missing_target() {
+        let input = Value::object([("a", reference("#/missing"))]);
+        let expected = Err(RefError::UnresolvedReference(String::from("#/missing")));
+
+        let actual = input.resolve_refs();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn fails_on_external_reference() {
+        let input = Value::object([("a", reference("other.json#/a"))]);
+        let expected = Err(RefError::UnsupportedReference(String::from(
+            "other.json#/a",
+        )));
+
+        let actual = input.resolve_refs();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn bundles_external_file_reference() {
+        let dir = write_files(
+            "bundles_external_file_reference",
+            &[("common.json", r#"{"definitions": {"id": "integer"}}"#)],
+        );
+        let input = Value::object([("property", reference("common.json#/definitions/id"))]);
+        let expected = Value::object([("property", Value::string("integer"))]);
+
+        let actual = input.bundle_refs(&dir).unwrap();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn bundles_whole_file_and_nested_relative_references() {
+        let dir = write_files(
+            "bundles_whole_file_and_nested_relative_references",
+            &[
+                ("root.json", r#"{"pet": {"$ref": "nested/pet.json"}}"#),
+                (
+                    "nested/pet.json",
+                    r##"{"name": {"$ref": "#/definitions/name"}, "definitions": {"name": "string"}}"##,
+                ),
+            ],
+        );
+        let expected = Value::object([(
+            "pet",
+            Value::object([
+                ("name", Value::string("string")),
+                (
+                    "definitions",
+                    Value::object([("name", Value::string("string"))]),
+                ),
+            ]),
+        )]);
+
+        let actual = bundle_file(dir.join("root.json")).unwrap();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn fails_on_cycle_across_files() {
+        let dir = write_files(
+            "fails_on_cycle_across_files",
+            &[
+                ("a.json", r#"{"next": {"$ref": "b.json#/next"}}"#),
+                ("b.json", r#"{"next": {"$ref": "a.json#/next"}}"#),
+            ],
+        );
+        let expected = Err(RefError::CycleDetected(String::from("b.json#/next")));
+
+        let actual = bundle_file(dir.join("a.json"));
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn fails_on_cycle_through_parent_segments() {
+        let dir = write_files(
+            "fails_on_cycle_through_parent_segments",
+            &[("a.json", r#"{"next": {"$ref": "nested/../a.json#/next"}}"#)],
+        );
+        let expected = Err(RefError::CycleDetected(String::from(
+            "nested/../a.json#/next",
+        )));
+
+        let actual = bundle_file(dir.join("a.json"));
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn fails_on_files_outside_base_dir() {
+        let dir = write_files(
+            "fails_on_files_outside_base_dir",
+            &[
+                ("secret.json", r#"{"token": "x"}"#),
+                ("nested/public.json", "1"),
+            ],
+        );
+        let absolute = dir.join("secret.json");
+        let absolute = absolute.to_str().unwrap();
+
+        for target in ["../secret.json", "./../nested/../secret.json", absolute] {
+            let expected = Err(RefError::UnsupportedReference(target.to_string()));
+
+            let actual = reference(target).bundle_refs(dir.join("nested"));
+
+            assert_eq!(actual, expected, "{target}");
+        }
+        assert_eq!(
+            reference("../nested/public.json").bundle_refs(dir.join("nested")),
+            Ok(Value::Number(1.0))
+        );
+    }
+
+    #[test]
+    fn limits_reference_depth_and_expansion() {
+        let chain = Value::Object(
+            (0..40)
+                .map(|i| (format!("r{i}"), reference(&format!("#/r{}", i + 1))))
+                .chain([(String::from("r40"), Value::Null)])
+                .collect(),
+        );
+        assert_eq!(chain.resolve_refs(), Err(RefError::TooDeep { limit: 32 }));
+
+        // Every level holds ten copies of the one before it
+        let mut levels = vec![(String::from("l0"), Value::Array(vec![Value::Null; 10]))];
+        for i in 1..8 {
+            let copies = vec![reference(&format!("#/l{}", i - 1)); 10];
+            levels.push((format!("l{i}"), Value::Array(copies)));
+        }
+        let laughs = Value::Object(levels.into_iter().collect());
+        assert_eq!(
+            laughs.resolve_refs(),
+            Err(RefError::TooLarge { limit: 1 << 20 })
+        );
+    }
+
+    #[test]
+    fn fails_on_missing_file() {
+        let dir = write_files("fails_on_missing_file", &[]);
+        let input = reference("missing.json");
+        let expected = Err(RefError::Io(
+            dir.join("missing.json"),
+            io::ErrorKind::NotFound,
+        ));
+
+        let actual = input.bundle_refs(&dir);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn fails_on_remote_reference() {
+        let input = reference("https://example.com/schema.json");
+        let expected = Err(RefError::UnsupportedReference(String::from(
+            "https://example.com/schema.json",
+        )));
+
+        let actual = input.bundle_refs(".");
+
+        assert_eq!(actual, expected);
+    }
+}
@@ -0,0 +1,295 @@
+use crate::{
+    pointer::{parse_index, resolve_tokens},
+    JsonPointer, PointerError, PointerLimits, Value,
+};
+
+/// Location relative to another one, as defined by the Relative JSON Pointer
+/// draft: a number of levels to go up, an optional index offset for array
+/// elements, then either `#` or a JSON pointer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RelativePointer {
+    up: usize,
+    /// Steps to move along the array after going up
+    offset: Option<i64>,
+    target: Target,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Target {
+    /// `#`: the key or index of the location reached
+    Key,
+    Pointer(JsonPointer),
+}
+
+/// Result of evaluating a [`RelativePointer`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RelativeValue<'a> {
+    Value(&'a Value),
+
+    /// Key of the object member reached by a pointer ending in `#`
+    Key(&'a str),
+
+    /// Index of the array element reached by a pointer ending in `#`
+    Index(usize),
+}
+
+impl RelativePointer {
+    /// Parses a relative pointer such as `0`, `1/name`, `2#` or `0+1/id`.
+    pub fn parse(pointer: &str) -> Result<Self, PointerError> {
+        parse_relative(pointer).ok_or_else(|| PointerError::InvalidSyntax(pointer.to_string()))
+    }
+
+    /// Parses a relative pointer from untrusted input, checking its length
+    /// and the number of reference tokens it appends against `limits`.
+    pub fn parse_with_limits(pointer: &str, limits: &PointerLimits) -> Result<Self, PointerError> {
+        if pointer.len() > limits.max_length {
+            return Err(PointerError::TooLong {
+                length: pointer.len(),
+                limit: limits.max_length,
+            });
+        }
+        let parsed = Self::parse(pointer)?;
+        if let Target::Pointer(target) = &parsed.target {
+            let depth = target.tokens().len();
+            if depth > limits.max_depth {
+                return Err(PointerError::TooDeep {
+                    depth,
+                    limit: limits.max_depth,
+                });
+            }
+        }
+        Ok(parsed)
+    }
+
+    /// Absolute pointer to the location reached from `current`, `None` for
+    /// pointers ending in `#` and when going up past the root. The index
+    /// offset needs the document, see [`RelativePointer::evaluate`].
+    pub fn resolve_from(&self, current: &JsonPointer) -> Option<JsonPointer> {
+        let Target::Pointer(pointer) = &self.target else {
+            return None;
+        };
+        if self.offset.is_some() {
+            return None;
+        }
+
+        let mut resolved = ancestor(current, self.up)?;
+        for token in pointer.tokens() {
+            resolved.push(token.clone());
+        }
+        Some(resolved)
+    }
+
+    /// Evaluates the pointer against `root`, starting at `current`. `None`
+    /// when a location along the way does not exist.
+    pub fn evaluate<'a>(
+        &self,
+        root: &'a Value,
+        current: &JsonPointer,
+    ) -> Option<RelativeValue<'a>> {
+        resolve_tokens(root, current.tokens())?;
+        let mut base = ancestor(current, self.up)?;
+
+        if let Some(offset) = self.offset {
+            let index = parse_index(&base.pop()?)?;
+            let Value::Array(values) = resolve_tokens(root, base.tokens())? else {
+                return None;
+            };
+            let moved = i64::try_from(index).ok()?.checked_add(offset)?;
+            let moved = usize::try_from(moved).ok().filter(|i| *i < values.len())?;
+            base.push(moved.to_string());
+        }
+
+        match &self.target {
+            Target::Key => {
+                let (last, parent) = base.tokens().split_last()?;
+                match resolve_tokens(root, parent)? {
+                    Value::Array(_) => parse_index(last).map(RelativeValue::Index),
+                    Value::Object(map) => map
+                        .get_key_value(last)
+                        .map(|(key, _)| RelativeValue::Key(key)),
+                    _ => None,
+                }
+            }
+            Target::Pointer(pointer) => {
+                let base = resolve_tokens(root, base.tokens())?;
+                resolve_tokens(base, pointer.tokens()).map(RelativeValue::Value)
+            }
+        }
+    }
+}
+
+fn ancestor(current: &JsonPointer, up: usize) -> Option<JsonPointer> {
+    let tokens = current.tokens();
+    let depth = tokens.len().checked_sub(up)?;
+    let mut ancestor = JsonPointer::root();
+    for token in &tokens[..depth] {
+        ancestor.push(token.clone());
+    }
+    Some(ancestor)
+}
+
+fn parse_relative(pointer: &str) -> Option<RelativePointer> {
+    let (up, rest) = split_digits(pointer);
+    let up = parse_index(up)?;
+
+    let (offset, rest) = if let Some(after_sign) = rest.strip_prefix('+') {
+        let (offset, rest) = parse_offset(after_sign)?;
+        (Some(offset), rest)
+    } else if let Some(after_sign) = rest.strip_prefix('-') {
+        let (offset, rest) = parse_offset(after_sign)?;
+        (Some(-offset), rest)
+    } else {
+        (None, rest)
+    };
+
+    let target = match rest {
+        "#" => Target::Key,
+        rest => Target::Pointer(JsonPointer::parse(rest).ok()?),
+    };
+
+    Some(RelativePointer { up, offset, target })
+}
+
+fn parse_offset(text: &str) -> Option<(i64, &str)> {
+    let (digits, rest) = split_digits(text);
+    let offset = parse_index(digits)?.try_into().ok()?;
+    Some((offset, rest))
+}
+
+/// Splits off the leading ASCII digits.
+fn split_digits(text: &str) -> (&str, &str) {
+    let len = text.len() - text.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    text.split_at(len)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{JsonPointer, PointerError, PointerLimits, Value};
+
+    use super::{RelativePointer, RelativeValue};
+
+    fn document() -> Value {
+        Value::object([
+            (
+                "foo",
+                Value::array([Value::string("bar"), Value::string("baz")]),
+            ),
+            (
+                "highly",
+                Value::object([("nested", Value::object([("objects", Value::Boolean(true))]))]),
+            ),
+        ])
+    }
+
+    fn evaluate<'a>(root: &'a Value, current: &str, pointer: &str) -> Option<RelativeValue<'a>> {
+        let current = JsonPointer::parse(current).unwrap();
+        RelativePointer::parse(pointer)
+            .unwrap()
+            .evaluate(root, &current)
+    }
+
+    #[test]
+    fn evaluates_draft_examples() {
+        let root = document();
+        let baz = Value::string("baz");
+        let bar = Value::string("bar");
+        let truthy = Value::Boolean(true);
+
+        assert_eq!(
+            evaluate(&root, "/foo/1", "0"),
+            Some(RelativeValue::Value(&baz))
+        );
+        assert_eq!(
+            evaluate(&root, "/foo/1", "1/0"),
+            Some(RelativeValue::Value(&bar))
+        );
+        assert_eq!(
+            evaluate(&root, "/foo/1", "0-1"),
+            Some(RelativeValue::Value(&bar))
+        );
+        assert_eq!(
+            evaluate(&root, "/foo/1", "0#"),
+            Some(RelativeValue::Index(1))
+        );
+        assert_eq!(
+            evaluate(&root, "/foo/1", "1#"),
+            Some(RelativeValue::Key("foo"))
+        );
+        assert_eq!(
+            evaluate(&root, "/highly/nested", "0/objects"),
+            Some(RelativeValue::Value(&truthy))
+        );
+        assert_eq!(
+            evaluate(&root, "/highly/nested", "1/nested/objects"),
+            Some(RelativeValue::Value(&truthy))
+        );
+        assert_eq!(
+            evaluate(&root, "/highly/nested", "2/foo/0"),
+            Some(RelativeValue::Value(&bar))
+        );
+    }
+
+    #[test]
+    fn misses_locations_outside_document() {
+        let root = document();
+
+        assert_eq!(evaluate(&root, "/foo/1", "3"), None);
+        assert_eq!(evaluate(&root, "/foo/1", "0+1"), None);
+        assert_eq!(evaluate(&root, "/highly", "0+1"), None);
+        assert_eq!(evaluate(&root, "", "0#"), None);
+        assert_eq!(evaluate(&root, "/missing", "1"), None);
+    }
+
+    #[test]
+    fn resolves_to_absolute_pointer() {
+        let current = JsonPointer::parse("/a/b/c").unwrap();
+
+        let resolve = |pointer: &str| {
+            RelativePointer::parse(pointer)
+                .unwrap()
+                .resolve_from(&current)
+                .map(|pointer| pointer.to_string())
+        };
+
+        assert_eq!(resolve("1/d~1e"), Some(String::from("/a/b/d~1e")));
+        assert_eq!(resolve("3"), Some(String::new()));
+        assert_eq!(resolve("4"), None);
+        assert_eq!(resolve("0#"), None);
+    }
+
+    #[test]
+    fn rejects_malformed_pointers() {
+        for input in ["", "01", "/a", "0+", "1a", "0##", "-1"] {
+            assert_eq!(
+                RelativePointer::parse(input),
+                Err(PointerError::InvalidSyntax(input.to_string())),
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_pointers_beyond_limits() {
+        let limits = PointerLimits {
+            max_depth: 2,
+            max_length: 8,
+        };
+
+        assert!(RelativePointer::parse_with_limits("1/a/b", &limits).is_ok());
+        assert_eq!(
+            RelativePointer::parse_with_limits("1/a/b/c", &limits),
+            Err(PointerError::TooDeep { depth: 3, limit: 2 })
+        );
+        assert_eq!(
+            RelativePointer::parse_with_limits("0/abcdefg", &limits),
+            Err(PointerError::TooLong {
+                length: 9,
+                limit: 8
+            })
+        );
+        assert_eq!(
+            RelativePointer::parse_with_limits("0/a~2", &limits),
+            Err(PointerError::InvalidSyntax(String::from("0/a~2")))
+        );
+    }
+}
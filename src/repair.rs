@@ -0,0 +1,487 @@
+use crate::{
+    parse,
+    span::{Position, Span},
+    ParseError,
+};
+
+/// Kinds of defects [`repair`] knows how to fix
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FixKind {
+    /// A string delimited by single quotes was converted to double quotes
+    SingleQuotedString,
+
+    /// A comma before a closing bracket or brace, or at the end of the input,
+    /// was removed
+    TrailingComma,
+
+    /// An object key without quotes was quoted
+    UnquotedKey,
+
+    /// A string still open at the end of the input was closed
+    UnclosedString,
+
+    /// A bracket or brace still open at the end of the input was closed
+    MissingClosingBracket(char),
+
+    /// A `//` or `/* */` comment was removed
+    Comment,
+
+    /// A backslash not starting a valid escape sequence was escaped itself
+    InvalidEscape,
+}
+
+/// A single change made by [`repair`], located in the original input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fix {
+    pub kind: FixKind,
+    pub span: Span,
+}
+
+/// Makes a best-effort attempt to turn almost-JSON into JSON, returning the
+/// repaired text along with every fix applied. Input that is already valid is
+/// returned unchanged. The output is not guaranteed to be valid for inputs with
+/// defects other than the ones described by [`FixKind`].
+pub fn repair(input: &str) -> (String, Vec<Fix>) {
+    let mut repairer = Repairer {
+        chars: input.chars().collect(),
+        index: 0,
+        position: Position::START,
+        output: String::with_capacity(input.len()),
+        fixes: Vec::new(),
+        open: Vec::new(),
+        expect_key: false,
+    };
+    repairer.run();
+    (repairer.output, repairer.fixes)
+}
+
+/// Parses the input leniently, tolerating the defects [`repair`] fixes, and
+/// returns every place strict parsing would reject, ordered by position.
+pub fn explain_leniency(input: &str) -> Result<Vec<Fix>, ParseError> {
+    let (repaired, mut fixes) = repair(input);
+    parse(repaired)?;
+    fixes.sort_by_key(|fix| fix.span.start);
+    Ok(fixes)
+}
+
+struct Repairer {
+    chars: Vec<char>,
+    index: usize,
+    position: Position,
+    output: String,
+    fixes: Vec<Fix>,
+    /// Closing characters of the containers currently open
+    open: Vec<char>,
+    /// Whether the next significant token is an object key
+    expect_key: bool,
+}
+
+impl Repairer {
+    fn run(&mut self) {
+        while let Some(ch) = self.peek(0) {
+            match ch {
+                '"' => {
+                    self.copy_string();
+                    self.expect_key = false;
+                }
+                '\'' => {
+                    self.convert_single_quoted();
+                    self.expect_key = false;
+                }
+                '{' => {
+                    self.copy();
+                    self.open.push('}');
+                    self.expect_key = true;
+                }
+                '[' => {
+                    self.copy();
+                    self.open.push(']');
+                    self.expect_key = false;
+                }
+                '}' | ']' => {
+                    if self.open.last() == Some(&ch) {
+                        self.open.pop();
+                    }
+                    self.copy();
+                    self.expect_key = false;
+                }
+                ',' => self.comma(),
+                '/' if matches!(self.peek(1), Some('/' | '*')) => self.skip_comment(),
+                c if self.expect_key && is_identifier_char(c) => self.quote_key(),
+                _ => {
+                    self.copy();
+                    if !ch.is_whitespace() {
+                        self.expect_key = false;
+                    }
+                }
+            }
+        }
+
+        while let Some(closing) = self.open.pop() {
+            self.output.push(closing);
+            self.fixes.push(Fix {
+                kind: FixKind::MissingClosingBracket(closing),
+                span: Span::new(self.position, self.position),
+            });
+        }
+    }
+
+    fn peek(&self, ahead: usize) -> Option<char> {
+        self.chars.get(self.index + ahead).copied()
+    }
+
+    fn next_char(&mut self) -> Option<char> {
+        let ch = self.peek(0)?;
+        self.index += 1;
+        self.position = self.position.advance(ch);
+        Some(ch)
+    }
+
+    fn copy(&mut self) {
+        if let Some(ch) = self.next_char() {
+            self.output.push(ch);
+        }
+    }
+
+    fn copy_string(&mut self) {
+        let start = self.position;
+        self.copy();
+
+        loop {
+            match self.next_char() {
+                Some('"') => {
+                    self.output.push('"');
+                    return;
+                }
+                Some('\\') => self.escape(),
+                Some(ch) => self.output.push(ch),
+                None => {
+                    self.output.push('"');
+                    self.fixes.push(Fix {
+                        kind: FixKind::UnclosedString,
+                        span: Span::new(start, self.position),
+                    });
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Copies the escape sequence after a backslash that was just read,
+    /// escaping the backslash when the sequence is not valid JSON.
+    fn escape(&mut self) {
+        // The backslash is one byte and one column wide
+        let start = Position {
+            offset: self.position.offset - 1,
+            column: self.position.column - 1,
+            ..self.position
+        };
+        let valid = match self.peek(0) {
+            Some('"' | '\\' | '/' | 'b' | 'f' | 'n' | 'r' | 't') => true,
+            Some('u') => {
+                (1..=4).all(|ahead| self.peek(ahead).is_some_and(|c| c.is_ascii_hexdigit()))
+            }
+            _ => false,
+        };
+
+        if valid {
+            self.output.push('\\');
+            self.copy();
+        } else {
+            self.output.push_str("\\\\");
+            let end = self
+                .peek(0)
+                .map_or(self.position, |c| self.position.advance(c));
+            self.fixes.push(Fix {
+                kind: FixKind::InvalidEscape,
+                span: Span::new(start, end),
+            });
+        }
+    }
+
+    fn skip_comment(&mut self) {
+        let start = self.position;
+        self.next_char();
+        if self.next_char() == Some('/') {
+            while self.peek(0).is_some_and(|c| c != '\n') {
+                self.next_char();
+            }
+        } else {
+            while let Some(ch) = self.next_char() {
+                if ch == '*' && self.peek(0) == Some('/') {
+                    self.next_char();
+                    break;
+                }
+            }
+        }
+
+        self.fixes.push(Fix {
+            kind: FixKind::Comment,
+            span: Span::new(start, self.position),
+        });
+    }
+
+    fn convert_single_quoted(&mut self) {
+        let start = self.position;
+        self.next_char();
+        self.output.push('"');
+
+        loop {
+            match self.next_char() {
+                Some('\'') | None => break,
+                Some('"') => self.output.push_str("\\\""),
+                Some('\\') if self.peek(0) == Some('\'') => {
+                    self.next_char();
+                    self.output.push('\'');
+                }
+                Some('\\') => self.escape(),
+                Some(ch) => self.output.push(ch),
+            }
+        }
+
+        self.output.push('"');
+        self.fixes.push(Fix {
+            kind: FixKind::SingleQuotedString,
+            span: Span::new(start, self.position),
+        });
+    }
+
+    fn comma(&mut self) {
+        let mut ahead = 1;
+        while self.peek(ahead).is_some_and(char::is_whitespace) {
+            ahead += 1;
+        }
+
+        if matches!(self.peek(ahead), Some('}' | ']') | None) {
+            let start = self.position;
+            self.next_char();
+            self.fixes.push(Fix {
+                kind: FixKind::TrailingComma,
+                span: Span::new(start, self.position),
+            });
+        } else {
+            self.copy();
+            self.expect_key = self.open.last() == Some(&'}');
+        }
+    }
+
+    fn quote_key(&mut self) {
+        let start = self.position;
+        let mut key = String::new();
+        while let Some(ch) = self.peek(0).filter(|c| is_identifier_char(*c)) {
+            key.push(ch);
+            self.next_char();
+        }
+
+        self.output.push('"');
+        self.output.push_str(&key);
+        self.output.push('"');
+        self.fixes.push(Fix {
+            kind: FixKind::UnquotedKey,
+            span: Span::new(start, self.position),
+        });
+        self.expect_key = false;
+    }
+}
+
+fn is_identifier_char(ch: char) -> bool {
+    ch.is_alphanumeric() || ch == '_' || ch == '$' || ch == '-'
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        parse,
+        span::{Position, Span},
+        Value,
+    };
+
+    use super::{explain_leniency, repair, Fix, FixKind};
+
+    fn kinds(fixes: &[Fix]) -> Vec<FixKind> {
+        fixes.iter().map(|fix| fix.kind).collect()
+    }
+
+    #[test]
+    fn leaves_valid_input_unchanged() {
+        let input = r#"{"a": [1, "two", {"b": null}], "c": "it's"}"#;
+
+        let (output, fixes) = repair(input);
+
+        assert_eq!(output, input);
+        assert!(fixes.is_empty());
+    }
+
+    #[test]
+    fn converts_single_quotes() {
+        let input = r#"{'a': 'say "hi"', 'b': 'it\'s'}"#;
+        let expected = r#"{"a": "say \"hi\"", "b": "it's"}"#;
+
+        let (output, fixes) = repair(input);
+
+        assert_eq!(output, expected);
+        assert_eq!(kinds(&fixes), [FixKind::SingleQuotedString; 4]);
+        assert_eq!(
+            fixes[0].span,
+            Span::of_str(Position::START.advance_str("{"), "'a'")
+        );
+    }
+
+    #[test]
+    fn removes_trailing_commas() {
+        let input = "[1, 2, ] {\"a\": 1 ,\n}";
+        let expected = "[1, 2 ] {\"a\": 1 \n}";
+
+        let (output, fixes) = repair(input);
+
+        assert_eq!(output, expected);
+        assert_eq!(kinds(&fixes), [FixKind::TrailingComma; 2]);
+        assert_eq!(
+            fixes[0].span,
+            Span::of_str(Position::START.advance_str("[1, 2"), ",")
+        );
+    }
+
+    #[test]
+    fn quotes_unquoted_keys() {
+        let input = "{name: \"x\", max_size: 2, nested: {$id: true}}";
+        let expected = r#"{"name": "x", "max_size": 2, "nested": {"$id": true}}"#;
+
+        let (output, fixes) = repair(input);
+
+        assert_eq!(output, expected);
+        assert_eq!(kinds(&fixes), [FixKind::UnquotedKey; 4]);
+    }
+
+    #[test]
+    fn does_not_quote_literal_values() {
+        let input = r#"{"a": true, "b": [null, false]}"#;
+
+        let (output, fixes) = repair(input);
+
+        assert_eq!(output, input);
+        assert!(fixes.is_empty());
+    }
+
+    #[test]
+    fn closes_brackets_at_end() {
+        let input = r#"{"list": [1, {"a": "open"#;
+        let expected = r#"{"list": [1, {"a": "open"}]}"#;
+
+        let (output, fixes) = repair(input);
+
+        assert_eq!(output, expected);
+        assert_eq!(
+            kinds(&fixes),
+            [
+                FixKind::UnclosedString,
+                FixKind::MissingClosingBracket('}'),
+                FixKind::MissingClosingBracket(']'),
+                FixKind::MissingClosingBracket('}'),
+            ]
+        );
+    }
+
+    #[test]
+    fn removes_comma_before_synthesized_closers() {
+        let cases = [
+            ("[1,2,", "[1,2]"),
+            ("{a:1, ", r#"{"a":1 }"#),
+            (r#"{"a": [true,"#, r#"{"a": [true]}"#),
+        ];
+
+        for (input, expected) in cases {
+            let (output, fixes) = repair(input);
+
+            assert_eq!(output, expected, "input: {input}");
+            assert!(
+                kinds(&fixes).contains(&FixKind::TrailingComma),
+                "input: {input}"
+            );
+            assert!(parse(output).is_ok(), "input: {input}");
+        }
+        assert_eq!(
+            kinds(&explain_leniency("[1,2,").unwrap()),
+            [FixKind::TrailingComma, FixKind::MissingClosingBracket(']')]
+        );
+    }
+
+    #[test]
+    fn repaired_output_parses() {
+        let input = "{users: [{'name': 'a',}, {name: 'b'}";
+        let expected = Value::object([(
+            "users",
+            Value::Array(vec![
+                Value::object([("name", Value::string("a"))]),
+                Value::object([("name", Value::string("b"))]),
+            ]),
+        )]);
+
+        let (output, _) = repair(input);
+
+        assert_eq!(parse(output), Ok(expected));
+    }
+
+    #[test]
+    fn removes_comments() {
+        let input = "{\"a\": 1, // note\n/* block */ \"b\": 2}";
+        let expected = "{\"a\": 1, \n \"b\": 2}";
+
+        let (output, fixes) = repair(input);
+
+        assert_eq!(output, expected);
+        assert_eq!(kinds(&fixes), [FixKind::Comment; 2]);
+        assert_eq!(
+            fixes[0].span,
+            Span::of_str(Position::START.advance_str("{\"a\": 1, "), "// note")
+        );
+    }
+
+    #[test]
+    fn escapes_invalid_escapes() {
+        let input = r#"["a\q\n", 'b\d\"', "\u12"]"#;
+        let expected = r#"["a\\q\n", "b\\d\"", "\\u12"]"#;
+
+        let (output, fixes) = repair(input);
+
+        assert_eq!(output, expected);
+        assert_eq!(
+            kinds(&fixes),
+            [
+                FixKind::InvalidEscape,
+                FixKind::InvalidEscape,
+                FixKind::SingleQuotedString,
+                FixKind::InvalidEscape
+            ]
+        );
+        assert_eq!(
+            fixes[0].span,
+            Span::of_str(Position::START.advance_str("[\"a"), "\\q")
+        );
+        assert!(parse(output).is_ok());
+    }
+
+    #[test]
+    fn explains_leniency_in_input_order() {
+        let input = "{'a': 1, /* c */ b: [2,],}";
+
+        let fixes = explain_leniency(input).unwrap();
+
+        assert_eq!(
+            kinds(&fixes),
+            [
+                FixKind::SingleQuotedString,
+                FixKind::Comment,
+                FixKind::UnquotedKey,
+                FixKind::TrailingComma,
+                FixKind::TrailingComma,
+            ]
+        );
+        assert_eq!(explain_leniency(r#"{"a": [1]}"#), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn explaining_fails_when_lenient_parse_fails() {
+        assert!(explain_leniency("{\"a\" 1}").is_err());
+    }
+}
@@ -0,0 +1,443 @@
+use std::{
+    borrow::Cow,
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    ops::Range,
+    sync::Arc,
+};
+
+use crate::Value;
+
+/// String stored as a sequence of chunks instead of one contiguous buffer.
+/// Slicing shares the chunks with the original, so taking a substring of a
+/// multi-megabyte string copies nothing.
+#[derive(Clone, Default)]
+pub struct Rope {
+    chunks: Vec<Chunk>,
+    len: usize,
+}
+
+/// Part of a shared buffer
+#[derive(Clone)]
+struct Chunk {
+    text: Arc<str>,
+    range: Range<usize>,
+}
+
+impl Chunk {
+    fn as_str(&self) -> &str {
+        &self.text[self.range.clone()]
+    }
+}
+
+impl Rope {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Length in bytes.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Appends `text` as a new chunk.
+    pub fn push_str(&mut self, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        self.len += text.len();
+        self.chunks.push(Chunk {
+            text: Arc::from(text),
+            range: 0..text.len(),
+        });
+    }
+
+    /// The chunks in order, never empty ones.
+    pub fn chunks(&self) -> impl Iterator<Item = &str> {
+        self.chunks.iter().map(Chunk::as_str)
+    }
+
+    pub fn chars(&self) -> impl Iterator<Item = char> + '_ {
+        self.chunks().flat_map(str::chars)
+    }
+
+    /// The bytes in `range`, sharing chunks with this rope. `None` when the
+    /// range is out of bounds or does not fall on character boundaries.
+    pub fn slice(&self, range: Range<usize>) -> Option<Rope> {
+        if range.start > range.end || range.end > self.len {
+            return None;
+        }
+
+        let mut slice = Rope::new();
+        let mut offset = 0;
+        for chunk in &self.chunks {
+            let len = chunk.range.len();
+            let start = range.start.max(offset) - offset;
+            let end = range.end.min(offset + len).saturating_sub(offset);
+            if start < end {
+                let text = chunk.as_str();
+                if !text.is_char_boundary(start) || !text.is_char_boundary(end) {
+                    return None;
+                }
+                slice.len += end - start;
+                slice.chunks.push(Chunk {
+                    text: Arc::clone(&chunk.text),
+                    range: chunk.range.start + start..chunk.range.start + end,
+                });
+            }
+            offset += len;
+        }
+
+        // An empty range must still start on a character boundary
+        if slice.is_empty() && !self.is_char_boundary(range.start) {
+            return None;
+        }
+        Some(slice)
+    }
+
+    fn is_char_boundary(&self, index: usize) -> bool {
+        let mut offset = 0;
+        for text in self.chunks() {
+            if index < offset + text.len() {
+                return text.is_char_boundary(index - offset);
+            }
+            offset += text.len();
+        }
+        index == offset
+    }
+
+    fn bytes(&self) -> impl Iterator<Item = u8> + '_ {
+        self.chunks().flat_map(str::bytes)
+    }
+}
+
+impl From<&str> for Rope {
+    fn from(text: &str) -> Self {
+        let mut rope = Rope::new();
+        rope.push_str(text);
+        rope
+    }
+}
+
+impl From<String> for Rope {
+    fn from(text: String) -> Self {
+        let len = text.len();
+        if len == 0 {
+            return Rope::new();
+        }
+        Rope {
+            chunks: vec![Chunk {
+                text: Arc::from(text),
+                range: 0..len,
+            }],
+            len,
+        }
+    }
+}
+
+impl From<Rope> for Value {
+    fn from(rope: Rope) -> Self {
+        Value::Rope(rope)
+    }
+}
+
+/// Ropes are equal when their text is, however it is chunked.
+impl PartialEq for Rope {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.bytes().eq(other.bytes())
+    }
+}
+
+impl Eq for Rope {}
+
+impl PartialEq<str> for Rope {
+    fn eq(&self, other: &str) -> bool {
+        self.len == other.len() && self.bytes().eq(other.bytes())
+    }
+}
+
+/// Hashes the same as the equivalent `str`.
+impl Hash for Rope {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for text in self.chunks() {
+            state.write(text.as_bytes());
+        }
+        state.write_u8(0xff);
+    }
+}
+
+/// Orders two strings by their text however each is stored, `None` unless
+/// both are strings.
+pub(crate) fn compare_text(a: &Value, b: &Value) -> Option<Ordering> {
+    let bytes = |value| Value::text_chunks(value).map(|chunks| chunks.flat_map(str::bytes));
+    Some(bytes(a)?.cmp(bytes(b)?))
+}
+
+impl fmt::Display for Rope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.chunks().try_for_each(|text| f.write_str(text))
+    }
+}
+
+impl fmt::Debug for Rope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.chunks()).finish()
+    }
+}
+
+/// Collects unescaped characters into chunks of about `chunk_size` bytes.
+pub(crate) struct RopeBuilder {
+    rope: Rope,
+    current: String,
+    chunk_size: usize,
+}
+
+impl RopeBuilder {
+    pub(crate) fn new(chunk_size: usize) -> Self {
+        Self {
+            rope: Rope::new(),
+            current: String::with_capacity(chunk_size),
+            chunk_size,
+        }
+    }
+
+    pub(crate) fn push(&mut self, ch: char) {
+        if self.current.len() + ch.len_utf8() > self.chunk_size && !self.current.is_empty() {
+            self.flush();
+        }
+        self.current.push(ch);
+    }
+
+    pub(crate) fn finish(mut self) -> Rope {
+        self.flush();
+        self.rope
+    }
+
+    fn flush(&mut self) {
+        let chunk = std::mem::replace(&mut self.current, String::with_capacity(self.chunk_size));
+        self.rope.len += chunk.len();
+        if !chunk.is_empty() {
+            let len = chunk.len();
+            self.rope.chunks.push(Chunk {
+                text: Arc::from(chunk),
+                range: 0..len,
+            });
+        }
+    }
+}
+
+impl Value {
+    /// Whether the value is a string stored as a [`Rope`].
+    pub fn is_rope(&self) -> bool {
+        matches!(self, Value::Rope(_))
+    }
+
+    pub fn as_rope(&self) -> Option<&Rope> {
+        match self {
+            Value::Rope(rope) => Some(rope),
+            _ => None,
+        }
+    }
+
+    /// The text of a string however it is stored, borrowed unless it is a
+    /// rope of several chunks.
+    pub fn as_text(&self) -> Option<Cow<'_, str>> {
+        match self {
+            Value::String(s) => Some(Cow::Borrowed(s)),
+            Value::Rope(rope) => Some(match rope.chunks.as_slice() {
+                [] => Cow::Borrowed(""),
+                [chunk] => Cow::Borrowed(chunk.as_str()),
+                _ => Cow::Owned(rope.to_string()),
+            }),
+            _ => None,
+        }
+    }
+
+    /// The text of a string in the chunks it is stored in, so ropes can be
+    /// compared and measured without joining them.
+    pub(crate) fn text_chunks(&self) -> Option<impl Iterator<Item = &str>> {
+        let (string, rope) = match self {
+            Value::String(s) => (Some(s.as_str()), None),
+            Value::Rope(rope) => (None, Some(rope)),
+            _ => return None,
+        };
+        Some(
+            string
+                .into_iter()
+                .chain(rope.into_iter().flat_map(Rope::chunks)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use crate::{
+        eval, hash::scalar_hash, parse_with_options, JsonPath, ParseOptions, SerializeOptions,
+        Value,
+    };
+
+    use super::{Rope, RopeBuilder};
+
+    fn chunked(chunks: &[&str]) -> Rope {
+        let mut rope = Rope::new();
+        for chunk in chunks {
+            rope.push_str(chunk);
+        }
+        rope
+    }
+
+    #[test]
+    fn compares_text_regardless_of_chunking() {
+        let rope = chunked(&["ab", "", "cdé"]);
+
+        assert_eq!(rope.len(), 6);
+        assert_eq!(rope.chunks().collect::<Vec<_>>(), ["ab", "cdé"]);
+        assert_eq!(rope, Rope::from("abcdé"));
+        assert_eq!(&rope, "abcdé");
+        assert_eq!(rope.to_string(), "abcdé");
+        assert_ne!(rope, Rope::from("abcde"));
+    }
+
+    #[test]
+    fn slices_across_chunks() {
+        let rope = chunked(&["hello ", "big ", "world"]);
+
+        let slice = rope.slice(3..12).unwrap();
+
+        assert_eq!(slice.chunks().collect::<Vec<_>>(), ["lo ", "big ", "wo"]);
+        assert_eq!(slice.slice(3..6).unwrap(), Rope::from("big"));
+        assert_eq!(rope.slice(6..6), Some(Rope::new()));
+        assert_eq!(rope.slice(10..16), None);
+    }
+
+    #[test]
+    fn rejects_slices_inside_characters() {
+        let rope = chunked(&["aé", "b"]);
+
+        assert_eq!(rope.slice(0..2), None);
+        assert_eq!(rope.slice(2..2), None);
+        assert_eq!(rope.slice(3..4), Some(Rope::from("b")));
+    }
+
+    #[test]
+    fn builds_chunks_on_character_boundaries() {
+        let mut builder = RopeBuilder::new(3);
+        for ch in "abéçd".chars() {
+            builder.push(ch);
+        }
+
+        let rope = builder.finish();
+
+        assert_eq!(rope.chunks().collect::<Vec<_>>(), ["ab", "é", "çd"]);
+        assert_eq!(rope.len(), 7);
+    }
+
+    #[test]
+    fn parses_long_strings_as_ropes() {
+        let input = String::from(r#"{"short": "abc", "long": "line\noneé \"quoted\""}"#);
+        let options = ParseOptions {
+            rope_chunk_size: Some(4),
+            ..ParseOptions::default()
+        };
+
+        let actual = parse_with_options(input, &options).unwrap();
+
+        assert_eq!(actual.get("short"), Some(&Value::string("abc")));
+        let long = actual.get("long").and_then(Value::as_rope).unwrap();
+        assert_eq!(long, "line\noneé \"quoted\"");
+        assert!(long.chunks().all(|chunk| chunk.len() <= 4));
+        assert_eq!(
+            Value::Rope(long.clone()).to_string_with(&SerializeOptions::default()),
+            r#""line\noneé \"quoted\"""#
+        );
+    }
+
+    #[test]
+    fn treats_ropes_as_strings() {
+        let rope = Value::Rope(chunked(&["ab", "cé"]));
+        let string = Value::string("abcé");
+
+        assert!(rope.is_string());
+        assert_eq!(rope.as_str(), None);
+        assert_eq!(rope.as_text(), Some(Cow::Owned(String::from("abcé"))));
+        assert_eq!(rope, string);
+        assert_eq!(string, rope);
+        assert_eq!(scalar_hash(&rope), scalar_hash(&string));
+        assert_eq!(String::try_from(&rope), Ok(String::from("abcé")));
+        assert_eq!(String::try_from(rope.clone()), Ok(String::from("abcé")));
+        assert_eq!(
+            Value::Rope(Rope::from("one")).as_text(),
+            Some(Cow::Borrowed("one"))
+        );
+    }
+
+    #[test]
+    fn queries_ropes_as_strings() {
+        let document = Value::object([
+            ("name", Value::Rope(chunked(&["ab", "cé"]))),
+            ("tags", Value::array([Value::Rope(chunked(&["x", "y"]))])),
+        ]);
+
+        assert_eq!(
+            eval(".name | length", &document),
+            Ok(vec![Value::Number(4.0)])
+        );
+        assert_eq!(
+            eval(".tags[0] + \"z\"", &document),
+            Ok(vec![Value::string("xyz")])
+        );
+        assert_eq!(document.search("length(name)"), Ok(Value::Number(4.0)));
+        assert_eq!(
+            document.search("tags[?@ == 'xy']"),
+            Ok(Value::array([Value::string("xy")]))
+        );
+
+        let path = JsonPath::parse("$[?(@.name == 'abcé')].tags").unwrap();
+        assert_eq!(path.query(&Value::array([document.clone()])).len(), 1);
+        let path = JsonPath::parse("$.tags[?(@ < 'xz')]").unwrap();
+        assert_eq!(path.query(&document).len(), 1);
+    }
+
+    #[test]
+    fn keeps_ropes_in_query_results() {
+        let document = Value::object([
+            ("name", Value::Rope(chunked(&["ab", "c"]))),
+            (
+                "tags",
+                Value::array([Value::Rope(chunked(&["y", "z"])), Value::string("ya")]),
+            ),
+        ]);
+
+        let name = eval(".name", &document).unwrap();
+        assert!(name[0].is_rope());
+        let name = document.search("name").unwrap();
+        assert!(name.is_rope());
+
+        assert_eq!(
+            eval(".tags | sort | .[0]", &document),
+            Ok(vec![Value::string("ya")])
+        );
+        assert_eq!(
+            eval(".name | startswith(\"ab\")", &document),
+            Ok(vec![Value::Boolean(true)])
+        );
+        assert_eq!(eval(".name[1:]", &document), Ok(vec![Value::string("bc")]));
+        assert_eq!(
+            eval("{(.name): 1}", &document),
+            Ok(vec![Value::object([("abc", Value::from(1))])])
+        );
+        assert_eq!(document.search("sort(tags)[-1]"), Ok(Value::string("yz")));
+        assert_eq!(
+            document.search("contains(name, 'bc')"),
+            Ok(Value::Boolean(true))
+        );
+        assert_eq!(document.search("reverse(name)"), Ok(Value::string("cba")));
+    }
+}
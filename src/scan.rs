@@ -0,0 +1,138 @@
+const LANES: usize = std::mem::size_of::<u64>();
+const LOW_BITS: u64 = u64::from_ne_bytes([0x01; LANES]);
+const HIGH_BITS: u64 = u64::from_ne_bytes([0x80; LANES]);
+
+/// Index of the first `"` or `\` in `bytes`, checking eight bytes per step.
+pub(crate) fn find_quote_or_backslash(bytes: &[u8]) -> Option<usize> {
+    find(
+        bytes,
+        |word| zero_bytes(word ^ broadcast(b'"')) | zero_bytes(word ^ broadcast(b'\\')),
+        |byte| byte == b'"' || byte == b'\\',
+    )
+}
+
+/// Index of the first byte that must be escaped in a JSON string: `"`, `\`
+/// or a control character.
+pub(crate) fn find_escape_byte(bytes: &[u8]) -> Option<usize> {
+    find(
+        bytes,
+        |word| {
+            zero_bytes(word ^ broadcast(b'"'))
+                | zero_bytes(word ^ broadcast(b'\\'))
+                | bytes_below(word, 0x20)
+        },
+        needs_escape,
+    )
+}
+
+pub(crate) fn needs_escape(byte: u8) -> bool {
+    byte == b'"' || byte == b'\\' || byte < 0x20
+}
+
+/// Scans eight bytes per step with `matches_word`, which flags the lowest
+/// matching byte of a word, and the remainder with `matches_byte`.
+fn find(
+    bytes: &[u8],
+    matches_word: impl Fn(u64) -> u64,
+    matches_byte: impl Fn(u8) -> bool,
+) -> Option<usize> {
+    let mut chunks = bytes.chunks_exact(LANES);
+    let mut offset = 0;
+
+    for chunk in &mut chunks {
+        let word = u64::from_le_bytes(chunk.try_into().unwrap());
+        let found = matches_word(word);
+        if found != 0 {
+            return Some(offset + found.trailing_zeros() as usize / 8);
+        }
+        offset += LANES;
+    }
+
+    chunks
+        .remainder()
+        .iter()
+        .position(|&byte| matches_byte(byte))
+        .map(|index| offset + index)
+}
+
+fn broadcast(byte: u8) -> u64 {
+    LOW_BITS * u64::from(byte)
+}
+
+/// Sets the high bit of the lowest byte of `word` below `limit`, which must be
+/// at most 0x80. Bytes above it may be flagged spuriously, as for [`zero_bytes`].
+fn bytes_below(word: u64, limit: u8) -> u64 {
+    word.wrapping_sub(broadcast(limit)) & !word & HIGH_BITS
+}
+
+/// Sets the high bit of the lowest zero byte of `word`. Bytes above it may be
+/// flagged spuriously, which is fine as only the lowest one is used.
+fn zero_bytes(word: u64) -> u64 {
+    word.wrapping_sub(LOW_BITS) & !word & HIGH_BITS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find_escape_byte, find_quote_or_backslash};
+
+    fn naive(bytes: &[u8]) -> Option<usize> {
+        bytes.iter().position(|&b| b == b'"' || b == b'\\')
+    }
+
+    #[test]
+    fn finds_first_quote_or_backslash() {
+        let cases: [(&[u8], Option<usize>); 7] = [
+            (b"", None),
+            (b"abc", None),
+            (b"abcdefghijklmnop", None),
+            (b"abc\"", Some(3)),
+            (b"abcdefgh\\ij\"", Some(8)),
+            (b"\x01\x00\x22", Some(2)),
+            ("héllo wörld \"".as_bytes(), Some(14)),
+        ];
+
+        for (input, expected) in cases {
+            let actual = find_quote_or_backslash(input);
+
+            assert_eq!(actual, expected, "input: {input:?}");
+        }
+    }
+
+    #[test]
+    fn agrees_with_naive_scan_at_every_position() {
+        for len in 0..24 {
+            for target in 0..len {
+                for special in [b'"', b'\\'] {
+                    let mut input = vec![0x5c ^ 0x80; len];
+                    input[target] = special;
+                    if target + 1 < len {
+                        input[target + 1] = b'"';
+                    }
+
+                    let actual = find_quote_or_backslash(&input);
+
+                    assert_eq!(actual, naive(&input), "len: {len}, target: {target}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn finds_bytes_needing_escape() {
+        for len in 0..24 {
+            for target in 0..len {
+                for special in [b'"', b'\\', b'\n', 0x00, 0x1f] {
+                    let mut input = vec![b' '; len];
+                    input[target] = special;
+
+                    let actual = find_escape_byte(&input);
+
+                    assert_eq!(actual, Some(target), "len: {len}, target: {target}");
+                }
+            }
+        }
+
+        let input = "no escapes ~\x7f héllo wörld";
+        assert_eq!(find_escape_byte(input.as_bytes()), None);
+    }
+}
@@ -0,0 +1,602 @@
+use std::{
+    cmp::Ordering,
+    fmt,
+    io::{self, BufWriter, Write},
+    iter,
+};
+
+use crate::{
+    scan::{find_escape_byte, needs_escape},
+    Style, Value,
+};
+
+/// Settings for writing values as JSON text
+#[derive(Debug, Clone, Default)]
+pub struct SerializeOptions {
+    /// Layout of the output
+    pub style: Style,
+
+    /// Write object members ordered by key instead of in map order, so equal
+    /// values always produce the same text
+    pub sort_keys: bool,
+
+    /// House style for the order of object members, taking precedence over
+    /// `sort_keys`
+    pub key_order: Option<KeyOrder>,
+
+    /// Which characters of strings and keys are escaped
+    pub escape: EscapePolicy,
+}
+
+/// Order in which object members are written
+#[derive(Debug, Clone)]
+pub enum KeyOrder {
+    /// The listed keys first, in list order, then the rest sorted
+    Priority(Vec<String>),
+
+    /// Members sorted by comparing their keys
+    Compare(fn(&str, &str) -> Ordering),
+}
+
+impl KeyOrder {
+    fn compare(&self, a: &str, b: &str) -> Ordering {
+        match self {
+            KeyOrder::Priority(keys) => {
+                let rank = |key: &str| keys.iter().position(|k| k == key).unwrap_or(keys.len());
+                rank(a).cmp(&rank(b)).then_with(|| a.cmp(b))
+            }
+            KeyOrder::Compare(compare) => compare(a, b),
+        }
+    }
+}
+
+/// Which characters are escaped when writing strings. Quotes, backslashes
+/// and control characters are always escaped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct EscapePolicy {
+    /// Escape every non-ASCII character as `\uXXXX`, as a surrogate pair above U+FFFF
+    pub ascii_only: bool,
+
+    /// Escape `/` as `\/`
+    pub escape_slash: bool,
+
+    /// How newlines, carriage returns and tabs are escaped
+    pub control: ControlEscape,
+}
+
+/// Escape used for the control characters that have a short form
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ControlEscape {
+    /// `\n`, `\r` and `\t`
+    #[default]
+    Short,
+
+    /// `\u000a`, `\u000d` and `\u0009`
+    Unicode,
+}
+
+/// Writes the value as JSON text to `writer` without building it in memory
+/// first. The output is the same as [`Value::to_string_with`] returns.
+pub fn write_value<W: io::Write>(
+    value: &Value,
+    writer: W,
+    options: &SerializeOptions,
+) -> io::Result<()> {
+    let mut adapter = IoAdapter {
+        writer: BufWriter::new(writer),
+        error: None,
+    };
+    if format_value(value, options, 0, &mut adapter).is_err() {
+        return Err(adapter
+            .error
+            .unwrap_or_else(|| io::Error::other("formatting failed")));
+    }
+    adapter.writer.flush()
+}
+
+/// Forwards formatted text to an `io::Write`, keeping the error that
+/// `fmt::Error` cannot carry
+struct IoAdapter<W: io::Write> {
+    writer: W,
+    error: Option<io::Error>,
+}
+
+impl<W: io::Write> fmt::Write for IoAdapter<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.writer.write_all(s.as_bytes()).map_err(|err| {
+            self.error = Some(err);
+            fmt::Error
+        })
+    }
+}
+
+/// Writes the value as compact JSON text, so `value.to_string()` can be parsed back.
+///
+/// Non-finite numbers have no JSON representation and are written as `null`.
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        format_value(self, &SerializeOptions::default(), 0, f)
+    }
+}
+
+impl Value {
+    /// Writes the value as JSON text with one member or element per line,
+    /// nested containers indented by `indent` spaces per level.
+    pub fn to_string_pretty(&self, indent: usize) -> String {
+        self.to_string_with(&SerializeOptions {
+            style: Style::Pretty { indent },
+            ..SerializeOptions::default()
+        })
+    }
+
+    /// Writes the value as JSON text laid out according to the options.
+    pub fn to_string_with(&self, options: &SerializeOptions) -> String {
+        let mut output = String::new();
+        format_value(self, options, 0, &mut output).expect("writing to a String cannot fail");
+        output
+    }
+
+    /// Exact byte length of `self.to_string()`, computed without building the text.
+    pub fn estimate_serialized_size(&self) -> usize {
+        match self {
+            Value::Null => 4,
+            Value::Boolean(b) => {
+                if *b {
+                    4
+                } else {
+                    5
+                }
+            }
+            Value::Number(n) => {
+                let mut counter = ByteCounter(0);
+                write_number(*n, &mut counter).expect("counting bytes cannot fail");
+                counter.0
+            }
+            Value::String(s) => string_size(s),
+            #[cfg(feature = "datetime")]
+            Value::DateTime(date_time) => date_time.to_string().len() + 2,
+            Value::Rope(rope) => rope.chunks().map(|s| string_size(s) - 2).sum::<usize>() + 2,
+            Value::Array(values) => {
+                let separators = values.len().saturating_sub(1);
+                2 + separators
+                    + values
+                        .iter()
+                        .map(Value::estimate_serialized_size)
+                        .sum::<usize>()
+            }
+            Value::Object(map) => {
+                let separators = map.len().saturating_sub(1);
+                let members: usize = map
+                    .iter()
+                    .map(|(key, value)| string_size(key) + 1 + value.estimate_serialized_size())
+                    .sum();
+                2 + separators + members
+            }
+        }
+    }
+}
+
+/// Byte length of the string as written by [`write_escaped`] with the default policy.
+fn string_size(s: &str) -> usize {
+    let escaped: usize = s
+        .bytes()
+        .map(|byte| match byte {
+            b'"' | b'\\' | b'\n' | b'\r' | b'\t' => 2,
+            byte if byte < 0x20 => 6,
+            _ => 1,
+        })
+        .sum();
+    2 + escaped
+}
+
+/// Counts the bytes written instead of storing them
+struct ByteCounter(usize);
+
+impl fmt::Write for ByteCounter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0 += s.len();
+        Ok(())
+    }
+}
+
+fn format_value<W: fmt::Write>(
+    value: &Value,
+    options: &SerializeOptions,
+    depth: usize,
+    output: &mut W,
+) -> fmt::Result {
+    let style = options.style;
+    match value {
+        Value::Null => output.write_str("null"),
+        Value::Boolean(b) => output.write_str(if *b { "true" } else { "false" }),
+        Value::Number(n) => write_number(*n, output),
+        Value::String(s) => write_escaped(s, &options.escape, output),
+        #[cfg(feature = "datetime")]
+        Value::DateTime(date_time) => write!(output, "\"{date_time}\""),
+        Value::Rope(rope) => write_escaped_chunks(rope.chunks(), &options.escape, output),
+        Value::Array(values) if values.is_empty() => output.write_str("[]"),
+        Value::Array(values) => {
+            output.write_char('[')?;
+            for (index, value) in values.iter().enumerate() {
+                if index > 0 {
+                    output.write_char(',')?;
+                }
+                newline(style, depth + 1, output)?;
+                format_value(value, options, depth + 1, output)?;
+            }
+            newline(style, depth, output)?;
+            output.write_char(']')
+        }
+        Value::Object(map) if map.is_empty() => output.write_str("{}"),
+        Value::Object(map) if options.key_order.is_some() || options.sort_keys => {
+            let mut members: Vec<_> = map.iter().collect();
+            match &options.key_order {
+                Some(order) => members.sort_unstable_by(|(a, _), (b, _)| order.compare(a, b)),
+                None => members.sort_unstable_by_key(|(key, _)| *key),
+            }
+            format_members(members.into_iter(), options, depth, output)
+        }
+        Value::Object(map) => format_members(map.iter(), options, depth, output),
+    }
+}
+
+fn format_members<'a, W: fmt::Write>(
+    members: impl Iterator<Item = (&'a String, &'a Value)>,
+    options: &SerializeOptions,
+    depth: usize,
+    output: &mut W,
+) -> fmt::Result {
+    let style = options.style;
+    output.write_char('{')?;
+    for (index, (key, value)) in members.enumerate() {
+        if index > 0 {
+            output.write_char(',')?;
+        }
+        newline(style, depth + 1, output)?;
+        write_escaped(key, &options.escape, output)?;
+        output.write_str(match style {
+            Style::Compact => ":",
+            Style::Pretty { .. } => ": ",
+        })?;
+        format_value(value, options, depth + 1, output)?;
+    }
+    newline(style, depth, output)?;
+    output.write_char('}')
+}
+
+/// Starts a new line indented for `depth` in pretty style, writes nothing in compact style.
+fn newline<W: fmt::Write>(style: Style, depth: usize, output: &mut W) -> fmt::Result {
+    let Style::Pretty { indent } = style else {
+        return Ok(());
+    };
+    output.write_char('\n')?;
+    for _ in 0..indent * depth {
+        output.write_char(' ')?;
+    }
+    Ok(())
+}
+
+/// Writes the shortest decimal text that parses back to the same number.
+pub(crate) fn write_number<W: fmt::Write>(n: f64, output: &mut W) -> fmt::Result {
+    if n.is_finite() {
+        write!(output, "{n}")
+    } else {
+        output.write_str("null")
+    }
+}
+
+/// Writes the string between quotes, escaping the characters the policy asks for.
+pub(crate) fn write_escaped<W: fmt::Write>(
+    s: &str,
+    policy: &EscapePolicy,
+    output: &mut W,
+) -> fmt::Result {
+    write_escaped_chunks(iter::once(s), policy, output)
+}
+
+/// Writes the concatenated chunks as one string, see [`write_escaped`].
+fn write_escaped_chunks<'a, W: fmt::Write>(
+    chunks: impl Iterator<Item = &'a str>,
+    policy: &EscapePolicy,
+    output: &mut W,
+) -> fmt::Result {
+    output.write_char('"')?;
+    for chunk in chunks {
+        write_escaped_run(chunk, policy, output)?;
+    }
+    output.write_char('"')
+}
+
+fn write_escaped_run<W: fmt::Write>(s: &str, policy: &EscapePolicy, output: &mut W) -> fmt::Result {
+    // Copy each run of characters that need no escaping in one write
+    let mut rest = s;
+    loop {
+        let found = if policy.ascii_only || policy.escape_slash {
+            rest.bytes().position(|byte| {
+                needs_escape(byte)
+                    || (policy.escape_slash && byte == b'/')
+                    || (policy.ascii_only && !byte.is_ascii())
+            })
+        } else {
+            find_escape_byte(rest.as_bytes())
+        };
+        let Some(index) = found else {
+            break;
+        };
+
+        output.write_str(&rest[..index])?;
+        let ch = rest[index..]
+            .chars()
+            .next()
+            .expect("escapes start at a char");
+        let short = policy.control == ControlEscape::Short;
+        match ch {
+            '"' => output.write_str("\\\"")?,
+            '\\' => output.write_str("\\\\")?,
+            '/' => output.write_str("\\/")?,
+            '\n' if short => output.write_str("\\n")?,
+            '\r' if short => output.write_str("\\r")?,
+            '\t' if short => output.write_str("\\t")?,
+            ch => {
+                for unit in ch.encode_utf16(&mut [0; 2]) {
+                    write!(output, "\\u{unit:04x}")?;
+                }
+            }
+        }
+        rest = &rest[index + ch.len_utf8()..];
+    }
+    output.write_str(rest)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use crate::{parse, Map, Style, Value};
+
+    use super::{write_value, ControlEscape, EscapePolicy, KeyOrder, SerializeOptions};
+
+    #[test]
+    fn serializes_compact_json() {
+        let cases = [
+            (Value::Null, "null"),
+            (Value::Boolean(false), "false"),
+            (Value::Number(1.0), "1"),
+            (Value::Number(-0.25), "-0.25"),
+            (Value::Number(f64::NAN), "null"),
+            (Value::Array(vec![]), "[]"),
+            (Value::Object(Map::new()), "{}"),
+            (
+                Value::array([Value::number(1.0), Value::Null, Value::array([])]),
+                "[1,null,[]]",
+            ),
+            (
+                Value::object([("k", Value::Boolean(true))]),
+                r#"{"k":true}"#,
+            ),
+        ];
+
+        for (input, expected) in cases {
+            let actual = input.to_string();
+
+            assert_eq!(actual, expected, "input: {input:?}");
+        }
+    }
+
+    #[test]
+    fn escapes_strings() {
+        let input = Value::string("\"q\" \\ \n\r\t \u{1} é");
+        let expected = r#""\"q\" \\ \n\r\t \u0001 é""#;
+
+        let actual = input.to_string();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn round_trips_through_parse() {
+        let input = String::from(
+            r#"{"name": "a \"b\"\n", "values": [0.1, -3, 12345678.9, true, null], "nested": {"x": {}}}"#,
+        );
+        let value = parse(input).unwrap();
+
+        let actual = parse(value.to_string()).unwrap();
+
+        assert_eq!(actual, value);
+    }
+
+    #[test]
+    fn ascii_only_output_round_trips_through_parse() {
+        let value = Value::object([("emoji 🌼", Value::string("a😀é\u{10FFFF}"))]);
+        let options = SerializeOptions {
+            escape: EscapePolicy {
+                ascii_only: true,
+                ..EscapePolicy::default()
+            },
+            ..SerializeOptions::default()
+        };
+        let output = value.to_string_with(&options);
+
+        let actual = parse(output.clone()).unwrap();
+
+        assert!(output.is_ascii());
+        assert_eq!(actual, value);
+    }
+
+    #[test]
+    fn floats_round_trip_bit_for_bit() {
+        let inputs = [
+            0.1 + 0.2,
+            1.0 / 3.0,
+            f64::MAX,
+            f64::MIN_POSITIVE,
+            5e-324,
+            -2.225_073_858_507_201e-308,
+            9_007_199_254_740_993.0,
+        ];
+
+        for input in inputs {
+            let actual = parse(Value::Number(input).to_string()).unwrap();
+
+            assert_eq!(actual, Value::Number(input), "input: {input:e}");
+            assert_eq!(actual.as_f64().map(f64::to_bits), Some(input.to_bits()));
+        }
+    }
+
+    #[test]
+    fn estimated_size_matches_serialized_length() {
+        let input = String::from(
+            r#"{"name": "é \"q\"\t\u0001", "values": [0.1, -3, 12345678.9, true, false, null], "nested": {"x": {}, "y": []}}"#,
+        );
+        let value = parse(input).unwrap();
+
+        let actual = value.estimate_serialized_size();
+
+        assert_eq!(actual, value.to_string().len());
+    }
+
+    #[test]
+    fn pretty_prints_with_indentation() {
+        let input = Value::object([(
+            "servers",
+            Value::array([
+                Value::object([("host", Value::string("a"))]),
+                Value::array([]),
+                Value::Number(2.0),
+            ]),
+        )]);
+        let expected =
+            "{\n  \"servers\": [\n    {\n      \"host\": \"a\"\n    },\n    [],\n    2\n  ]\n}";
+
+        let actual = input.to_string_pretty(2);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn writes_to_io_writer() {
+        let input = Value::object([("a", Value::array([Value::Null, Value::string("é")]))]);
+
+        let pretty_options = SerializeOptions {
+            style: Style::Pretty { indent: 4 },
+            ..SerializeOptions::default()
+        };
+
+        let mut compact = Vec::new();
+        write_value(&input, &mut compact, &SerializeOptions::default()).unwrap();
+        let mut pretty = Vec::new();
+        write_value(&input, &mut pretty, &pretty_options).unwrap();
+
+        assert_eq!(String::from_utf8(compact).unwrap(), input.to_string());
+        assert_eq!(
+            String::from_utf8(pretty).unwrap(),
+            input.to_string_pretty(4)
+        );
+    }
+
+    #[test]
+    fn reports_writer_errors() {
+        struct Failing;
+
+        impl io::Write for Failing {
+            fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+                Err(io::Error::new(io::ErrorKind::BrokenPipe, "closed"))
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let input = Value::string("x".repeat(10_000));
+
+        let actual = write_value(&input, Failing, &SerializeOptions::default()).unwrap_err();
+
+        assert_eq!(actual.kind(), io::ErrorKind::BrokenPipe);
+    }
+
+    #[test]
+    fn sorts_keys_when_requested() {
+        let input = parse(String::from(
+            r#"{"b": 1, "a": {"z": null, "y": [], "é": 0}, "A": true, "aa": false}"#,
+        ))
+        .unwrap();
+        let options = SerializeOptions {
+            sort_keys: true,
+            ..SerializeOptions::default()
+        };
+        let expected = r#"{"A":true,"a":{"y":[],"z":null,"é":0},"aa":false,"b":1}"#;
+
+        let actual = input.to_string_with(&options);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn orders_keys_by_priority_list() {
+        let input = parse(String::from(
+            r#"{"version": 2, "name": "app", "deps": {"b": 1, "id": 2, "a": 3}, "id": 7}"#,
+        ))
+        .unwrap();
+        let options = SerializeOptions {
+            key_order: Some(KeyOrder::Priority(vec![
+                String::from("id"),
+                String::from("name"),
+            ])),
+            ..SerializeOptions::default()
+        };
+        let expected = r#"{"id":7,"name":"app","deps":{"id":2,"a":3,"b":1},"version":2}"#;
+
+        let actual = input.to_string_with(&options);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn orders_keys_by_comparator() {
+        let input = parse(String::from(r#"{"bb": 1, "a": 2, "ccc": 3}"#)).unwrap();
+        let options = SerializeOptions {
+            key_order: Some(KeyOrder::Compare(|a, b| b.len().cmp(&a.len()))),
+            ..SerializeOptions::default()
+        };
+        let expected = r#"{"ccc":3,"bb":1,"a":2}"#;
+
+        let actual = input.to_string_with(&options);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn applies_escape_policy() {
+        let input = Value::string("a/é😀\n\t");
+        let cases = [
+            (EscapePolicy::default(), "\"a/é😀\\n\\t\""),
+            (
+                EscapePolicy {
+                    ascii_only: true,
+                    ..EscapePolicy::default()
+                },
+                r#""a/\u00e9\ud83d\ude00\n\t""#,
+            ),
+            (
+                EscapePolicy {
+                    escape_slash: true,
+                    control: ControlEscape::Unicode,
+                    ..EscapePolicy::default()
+                },
+                "\"a\\/é😀\\u000a\\u0009\"",
+            ),
+        ];
+
+        for (escape, expected) in cases {
+            let options = SerializeOptions {
+                escape,
+                ..SerializeOptions::default()
+            };
+
+            let actual = input.to_string_with(&options);
+
+            assert_eq!(actual, expected, "policy: {escape:?}");
+        }
+    }
+}
@@ -0,0 +1,364 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashSet},
+    fmt,
+};
+
+use crate::{hash::scalar_hash, pointer::escape_token, Value, ValueKind};
+
+/// Number of distinct scalar values tracked per path before counting stops
+pub const DISTINCT_LIMIT: usize = 10_000;
+
+/// Location generalized over array elements: a path of object keys and
+/// array steps, where one array step stands for every element of the array,
+/// so that all elements share statistics.
+///
+/// Displayed like a JSON pointer with `*` for array steps (e.g.
+/// `/users/*/name`). That text cannot tell a key named `*` from an array
+/// step, so compare paths rather than their text.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct ShapePath {
+    segments: Vec<PathSegment>,
+}
+
+/// One step of a [`ShapePath`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum PathSegment {
+    /// The object member with this key
+    Key(String),
+
+    /// Any element of an array
+    Element,
+}
+
+impl ShapePath {
+    /// Path of the whole document.
+    pub fn root() -> Self {
+        Self::default()
+    }
+
+    pub fn segments(&self) -> &[PathSegment] {
+        &self.segments
+    }
+
+    /// Path of the member with this key of the values at this path.
+    pub fn key(mut self, key: impl Into<String>) -> Self {
+        self.segments.push(PathSegment::Key(key.into()));
+        self
+    }
+
+    /// Path of the elements of the arrays at this path.
+    pub fn element(mut self) -> Self {
+        self.segments.push(PathSegment::Element);
+        self
+    }
+
+    /// Path of the values containing the values at this path, `None` for the root.
+    pub fn parent(&self) -> Option<Self> {
+        let (_, parent) = self.segments.split_last()?;
+        Some(Self {
+            segments: parent.to_vec(),
+        })
+    }
+}
+
+impl fmt::Display for ShapePath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for segment in &self.segments {
+            match segment {
+                PathSegment::Key(key) => write!(f, "/{}", escape_token(key))?,
+                PathSegment::Element => f.write_str("/*")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Summary of the shape of a document, keyed by generalized path.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ShapeReport {
+    pub paths: BTreeMap<ShapePath, PathStats>,
+}
+
+/// Statistics about the values found at one generalized path
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PathStats {
+    /// Number of values found at the path
+    pub occurrences: usize,
+
+    /// How many of the values were of each kind
+    pub kinds: BTreeMap<ValueKind, usize>,
+
+    /// Number of distinct scalar values, counted up to [`DISTINCT_LIMIT`]
+    pub distinct_values: usize,
+
+    /// Whether more distinct values exist than were counted
+    pub distinct_saturated: bool,
+
+    distinct_hashes: HashSet<u64>,
+}
+
+impl ShapeReport {
+    /// Fraction of the objects at the parent path which contain the key at `path`,
+    /// `None` if the path was not seen or its parent is not an object path.
+    pub fn presence(&self, path: &ShapePath) -> Option<f64> {
+        let stats = self.paths.get(path)?;
+        let parent_stats = self.paths.get(&path.parent()?)?;
+        let objects = *parent_stats.kinds.get(&ValueKind::Object)?;
+        Some(stats.occurrences as f64 / objects as f64)
+    }
+}
+
+/// Differences between the shapes of two documents, by generalized path
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ShapeDiff {
+    /// Paths found only in the left document. Paths below one of these are
+    /// not listed separately.
+    pub only_in_left: Vec<ShapePath>,
+
+    /// Paths found only in the right document. Paths below one of these are
+    /// not listed separately.
+    pub only_in_right: Vec<ShapePath>,
+
+    /// Paths found in both documents whose values are of different kinds
+    pub kind_changes: Vec<KindChange>,
+}
+
+/// Kinds of the values found at one path in each document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KindChange {
+    pub path: ShapePath,
+    pub left: BTreeSet<ValueKind>,
+    pub right: BTreeSet<ValueKind>,
+}
+
+impl ShapeDiff {
+    /// Whether both documents have the same shape.
+    pub fn is_empty(&self) -> bool {
+        self.only_in_left.is_empty()
+            && self.only_in_right.is_empty()
+            && self.kind_changes.is_empty()
+    }
+}
+
+/// Compares the keys and kinds found at every generalized path of two
+/// documents, as collected by [`analyze`]. Values themselves are ignored, as
+/// are array lengths, since all elements of an array share one path.
+pub fn compare_shapes(left: &Value, right: &Value) -> ShapeDiff {
+    let left = analyze(left);
+    let right = analyze(right);
+
+    let mut diff = ShapeDiff {
+        only_in_left: missing_paths(&left, &right),
+        only_in_right: missing_paths(&right, &left),
+        ..ShapeDiff::default()
+    };
+
+    for (path, left_stats) in &left.paths {
+        let Some(right_stats) = right.paths.get(path) else {
+            continue;
+        };
+        let left_kinds: BTreeSet<ValueKind> = left_stats.kinds.keys().copied().collect();
+        let right_kinds: BTreeSet<ValueKind> = right_stats.kinds.keys().copied().collect();
+        if left_kinds != right_kinds {
+            diff.kind_changes.push(KindChange {
+                path: path.clone(),
+                left: left_kinds,
+                right: right_kinds,
+            });
+        }
+    }
+
+    diff
+}
+
+/// Paths of `report` missing from `other` whose parent is present in both.
+fn missing_paths(report: &ShapeReport, other: &ShapeReport) -> Vec<ShapePath> {
+    report
+        .paths
+        .keys()
+        .filter(|path| !other.paths.contains_key(*path))
+        .filter(|path| match path.parent() {
+            Some(parent) => other.paths.contains_key(&parent),
+            None => true,
+        })
+        .cloned()
+        .collect()
+}
+
+/// Collects key frequency, kinds and value cardinality for every path in the value.
+pub fn analyze(value: &Value) -> ShapeReport {
+    let mut report = ShapeReport::default();
+    visit(value, &mut ShapePath::root(), &mut report);
+    report
+}
+
+fn visit(value: &Value, path: &mut ShapePath, report: &mut ShapeReport) {
+    let stats = report.paths.entry(path.clone()).or_default();
+    stats.occurrences += 1;
+    *stats.kinds.entry(value.kind()).or_default() += 1;
+
+    if let Some(hash) = scalar_hash(value) {
+        if stats.distinct_hashes.len() < DISTINCT_LIMIT {
+            if stats.distinct_hashes.insert(hash) {
+                stats.distinct_values += 1;
+            }
+        } else if !stats.distinct_hashes.contains(&hash) {
+            stats.distinct_saturated = true;
+        }
+    }
+
+    match value {
+        Value::Array(values) => {
+            path.segments.push(PathSegment::Element);
+            for value in values {
+                visit(value, path, report);
+            }
+            path.segments.pop();
+        }
+        Value::Object(map) => {
+            for (key, value) in map {
+                path.segments.push(PathSegment::Key(key.clone()));
+                visit(value, path, report);
+                path.segments.pop();
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use crate::{parse, Value, ValueKind};
+
+    use super::{analyze, compare_shapes, KindChange, PathSegment, ShapePath};
+
+    fn users_path() -> ShapePath {
+        ShapePath::root().key("users").element()
+    }
+
+    fn users() -> Value {
+        parse(String::from(
+            r#"{"users": [
+                {"name": "a", "age": 30, "role": "admin"},
+                {"name": "b", "age": null, "role": "user"},
+                {"name": "c", "role": "user"},
+                {"name": "d", "age": 41, "role": "user"}
+            ]}"#,
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn counts_occurrences_per_generalized_path() {
+        let report = analyze(&users());
+
+        let paths: Vec<String> = report.paths.keys().map(ToString::to_string).collect();
+        assert_eq!(
+            paths,
+            [
+                "",
+                "/users",
+                "/users/*",
+                "/users/*/age",
+                "/users/*/name",
+                "/users/*/role"
+            ]
+        );
+        assert_eq!(report.paths[&users_path()].occurrences, 4);
+        assert_eq!(report.paths[&users_path().key("age")].occurrences, 3);
+    }
+
+    #[test]
+    fn records_kinds_per_path() {
+        let report = analyze(&users());
+
+        let kinds = &report.paths[&users_path().key("age")].kinds;
+        assert_eq!(kinds.get(&ValueKind::Number), Some(&2));
+        assert_eq!(kinds.get(&ValueKind::Null), Some(&1));
+    }
+
+    #[test]
+    fn estimates_cardinality() {
+        let report = analyze(&users());
+
+        assert_eq!(report.paths[&users_path().key("role")].distinct_values, 2);
+        assert_eq!(report.paths[&users_path().key("name")].distinct_values, 4);
+        assert!(!report.paths[&users_path().key("name")].distinct_saturated);
+    }
+
+    #[test]
+    fn computes_key_presence() {
+        let report = analyze(&users());
+
+        assert_eq!(report.presence(&users_path().key("age")), Some(0.75));
+        assert_eq!(report.presence(&users_path().key("name")), Some(1.0));
+        assert_eq!(report.presence(&users_path()), None);
+    }
+
+    #[test]
+    fn tells_star_key_from_array_elements() {
+        let input = parse(String::from(r#"{"a": {"*": 1}, "b": [1]}"#)).unwrap();
+
+        let report = analyze(&input);
+
+        let star_key = ShapePath::root().key("a").key("*");
+        assert_eq!(
+            star_key.segments(),
+            [
+                PathSegment::Key(String::from("a")),
+                PathSegment::Key(String::from("*"))
+            ]
+        );
+        assert!(report.paths.contains_key(&star_key));
+        assert!(!report
+            .paths
+            .contains_key(&ShapePath::root().key("a").element()));
+        assert!(report
+            .paths
+            .contains_key(&ShapePath::root().key("b").element()));
+        assert!(!report
+            .paths
+            .contains_key(&ShapePath::root().key("b").key("*")));
+    }
+
+    #[test]
+    fn reports_drift_between_documents() {
+        let left = users();
+        let right = parse(String::from(
+            r#"{"users": [
+                {"name": "a", "age": "30", "contact": {"email": "a@x"}}
+            ], "total": 1}"#,
+        ))
+        .unwrap();
+
+        let diff = compare_shapes(&left, &right);
+
+        assert_eq!(diff.only_in_left, [users_path().key("role")]);
+        assert_eq!(
+            diff.only_in_right,
+            [ShapePath::root().key("total"), users_path().key("contact")]
+        );
+        assert_eq!(
+            diff.kind_changes,
+            [KindChange {
+                path: users_path().key("age"),
+                left: BTreeSet::from([ValueKind::Null, ValueKind::Number]),
+                right: BTreeSet::from([ValueKind::String]),
+            }]
+        );
+    }
+
+    #[test]
+    fn same_shape_has_no_drift() {
+        let right = parse(String::from(
+            r#"{"users": [{"name": "z", "age": 1, "role": "x"}, {"name": "y", "age": null, "role": "x"}]}"#,
+        ))
+        .unwrap();
+
+        let diff = compare_shapes(&users(), &right);
+
+        assert!(diff.is_empty(), "{diff:?}");
+    }
+}
@@ -0,0 +1,223 @@
+use std::{collections::HashMap, sync::Arc};
+
+use crate::{
+    hash::{array_hash, object_hash, scalar_hash},
+    Map, Value,
+};
+
+/// Representation of JSON values where identical subtrees are stored once
+/// and shared between all the places they occur in. Shared subtrees are
+/// immutable, so a value can never contain itself.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SharedValue {
+    /// Literal 'null' value
+    Null,
+
+    /// Literal 'true' or 'false'
+    Boolean(bool),
+
+    /// Value within double quotes "..."
+    String(Arc<str>),
+
+    /// Numbers stored as 64-bit floating point
+    Number(f64),
+
+    /// Zero or more shared JSON values
+    Array(Arc<Vec<SharedValue>>),
+
+    /// Shared JSON value identified by a String key
+    Object(Arc<Map<String, SharedValue>>),
+
+    /// RFC 3339 timestamp
+    #[cfg(feature = "datetime")]
+    DateTime(crate::DateTime),
+
+    /// String stored in chunks
+    Rope(crate::Rope),
+}
+
+impl Value {
+    /// Converts the value into a [`SharedValue`] in which equal strings,
+    /// arrays and objects are allocated once and shared via `Arc`.
+    pub fn dedup_subtrees(&self) -> SharedValue {
+        let mut interner = Interner::default();
+        interner.intern(self).0
+    }
+}
+
+impl SharedValue {
+    /// Converts back into an owned [`Value`], copying shared subtrees.
+    pub fn to_value(&self) -> Value {
+        match self {
+            SharedValue::Null => Value::Null,
+            SharedValue::Boolean(b) => Value::Boolean(*b),
+            SharedValue::String(s) => Value::String(s.to_string()),
+            SharedValue::Number(n) => Value::Number(*n),
+            #[cfg(feature = "datetime")]
+            SharedValue::DateTime(date_time) => Value::DateTime(*date_time),
+            SharedValue::Rope(rope) => Value::Rope(rope.clone()),
+            SharedValue::Array(values) => {
+                Value::Array(values.iter().map(SharedValue::to_value).collect())
+            }
+            SharedValue::Object(map) => Value::Object(
+                map.iter()
+                    .map(|(key, value)| (key.clone(), value.to_value()))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Returns true when both values refer to the same allocation.
+    /// Scalars without an allocation are never considered shared.
+    pub fn ptr_eq(&self, other: &SharedValue) -> bool {
+        match (self, other) {
+            (SharedValue::String(a), SharedValue::String(b)) => Arc::ptr_eq(a, b),
+            (SharedValue::Array(a), SharedValue::Array(b)) => Arc::ptr_eq(a, b),
+            (SharedValue::Object(a), SharedValue::Object(b)) => Arc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+}
+
+/// Keeps one canonical instance per distinct subtree, bucketed by structural hash.
+#[derive(Default)]
+struct Interner {
+    buckets: HashMap<u64, Vec<SharedValue>>,
+}
+
+impl Interner {
+    /// Interns the value bottom-up and returns it together with its structural hash.
+    fn intern(&mut self, value: &Value) -> (SharedValue, u64) {
+        let leaf_hash = || scalar_hash(value).expect("only called for scalars");
+        let (shared, hash) = match value {
+            Value::Null => return (SharedValue::Null, leaf_hash()),
+            Value::Boolean(b) => return (SharedValue::Boolean(*b), leaf_hash()),
+            Value::Number(n) => return (SharedValue::Number(*n), leaf_hash()),
+            #[cfg(feature = "datetime")]
+            Value::DateTime(date_time) => return (SharedValue::DateTime(*date_time), leaf_hash()),
+            Value::Rope(rope) => return (SharedValue::Rope(rope.clone()), leaf_hash()),
+            Value::String(s) => (SharedValue::String(Arc::from(s.as_str())), leaf_hash()),
+            Value::Array(values) => {
+                let (output, hashes): (Vec<_>, Vec<_>) =
+                    values.iter().map(|value| self.intern(value)).unzip();
+                (SharedValue::Array(Arc::new(output)), array_hash(hashes))
+            }
+            Value::Object(map) => {
+                let mut members = Vec::with_capacity(map.len());
+                let mut output = Map::with_capacity(map.len());
+                for (key, value) in map {
+                    let (shared, hash) = self.intern(value);
+                    members.push((key.as_str(), hash));
+                    output.insert(key.clone(), shared);
+                }
+                (SharedValue::Object(Arc::new(output)), object_hash(members))
+            }
+        };
+
+        let bucket = self.buckets.entry(hash).or_default();
+        if let Some(existing) = bucket.iter().find(|existing| shallow_eq(existing, &shared)) {
+            return (existing.clone(), hash);
+        }
+
+        bucket.push(shared.clone());
+        (shared, hash)
+    }
+}
+
+/// Children are already interned, so equal containers hold pointer-equal children.
+fn shallow_eq(a: &SharedValue, b: &SharedValue) -> bool {
+    match (a, b) {
+        (SharedValue::String(a), SharedValue::String(b)) => a == b,
+        (SharedValue::Array(a), SharedValue::Array(b)) => {
+            a.len() == b.len() && a.iter().zip(b.iter()).all(|(a, b)| same_instance(a, b))
+        }
+        (SharedValue::Object(a), SharedValue::Object(b)) => {
+            a.len() == b.len()
+                && a.iter()
+                    .all(|(key, a)| b.get(key).is_some_and(|b| same_instance(a, b)))
+        }
+        _ => false,
+    }
+}
+
+fn same_instance(a: &SharedValue, b: &SharedValue) -> bool {
+    match (a, b) {
+        (SharedValue::Null, SharedValue::Null) => true,
+        (SharedValue::Boolean(a), SharedValue::Boolean(b)) => a == b,
+        (SharedValue::Number(a), SharedValue::Number(b)) => a.to_bits() == b.to_bits(),
+        _ => a.ptr_eq(b),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Value;
+
+    use super::SharedValue;
+
+    fn row(id: f64) -> Value {
+        Value::object([
+            ("id", Value::Number(id)),
+            (
+                "meta",
+                Value::object([
+                    ("source", Value::string("export")),
+                    ("tags", Value::Array(vec![Value::string("a"), Value::Null])),
+                ]),
+            ),
+        ])
+    }
+
+    fn field<'a>(value: &'a SharedValue, key: &str) -> &'a SharedValue {
+        match value {
+            SharedValue::Object(map) => &map[key],
+            _ => panic!("expected an object"),
+        }
+    }
+
+    #[test]
+    fn shares_repeated_subtrees() {
+        let input = Value::Array(vec![row(1.0), row(2.0)]);
+
+        let actual = input.dedup_subtrees();
+
+        let SharedValue::Array(rows) = &actual else {
+            panic!("expected an array");
+        };
+        assert!(field(&rows[0], "meta").ptr_eq(field(&rows[1], "meta")));
+        assert!(!rows[0].ptr_eq(&rows[1]));
+    }
+
+    #[test]
+    fn shares_identical_rows() {
+        let input = Value::Array(vec![row(1.0), row(1.0)]);
+
+        let actual = input.dedup_subtrees();
+
+        let SharedValue::Array(rows) = &actual else {
+            panic!("expected an array");
+        };
+        assert!(rows[0].ptr_eq(&rows[1]));
+    }
+
+    #[test]
+    fn does_not_share_different_strings() {
+        let input = Value::Array(vec![Value::string("a"), Value::string("b")]);
+
+        let actual = input.dedup_subtrees();
+
+        let SharedValue::Array(values) = &actual else {
+            panic!("expected an array");
+        };
+        assert!(!values[0].ptr_eq(&values[1]));
+    }
+
+    #[test]
+    fn converts_back_to_equal_value() {
+        let input = Value::Array(vec![row(1.0), row(2.0), row(1.0)]);
+
+        let actual = input.dedup_subtrees().to_value();
+
+        assert_eq!(actual, input);
+    }
+}
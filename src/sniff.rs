@@ -0,0 +1,77 @@
+use crate::ValueKind;
+
+/// Guesses the kind of the top-level value from its first significant character,
+/// skipping a leading byte order mark and whitespace. The rest of the input is not
+/// validated, so a successful sniff does not guarantee that parsing succeeds.
+pub fn sniff(input: &str) -> Option<ValueKind> {
+    let input = input.strip_prefix('\u{feff}').unwrap_or(input);
+    let first = input
+        .trim_start_matches(|c: char| c.is_ascii_whitespace())
+        .chars()
+        .next()?;
+
+    let kind = match first {
+        '{' => ValueKind::Object,
+        '[' => ValueKind::Array,
+        '"' => ValueKind::String,
+        't' | 'f' => ValueKind::Boolean,
+        'n' => ValueKind::Null,
+        c if c.is_ascii_digit() || c == '-' => ValueKind::Number,
+        _ => return None,
+    };
+
+    Some(kind)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ValueKind;
+
+    use super::sniff;
+
+    #[test]
+    fn sniffs_object() {
+        let actual = sniff(r#"  {"a": 1}"#);
+
+        assert_eq!(actual, Some(ValueKind::Object));
+    }
+
+    #[test]
+    fn sniffs_array_after_bom_and_newlines() {
+        let actual = sniff("\u{feff}\n\r\n[1, 2]");
+
+        assert_eq!(actual, Some(ValueKind::Array));
+    }
+
+    #[test]
+    fn sniffs_scalars() {
+        let inputs = ["\"s\"", "-1", "7", "true", "false", "null"];
+        let expected = [
+            ValueKind::String,
+            ValueKind::Number,
+            ValueKind::Number,
+            ValueKind::Boolean,
+            ValueKind::Boolean,
+            ValueKind::Null,
+        ];
+
+        let actual = inputs.map(|input| sniff(input).unwrap());
+
+        assert_eq!(actual, expected);
+        assert!(actual.iter().all(ValueKind::is_scalar));
+    }
+
+    #[test]
+    fn sniffs_nothing_in_blank_input() {
+        let actual = sniff("  \n ");
+
+        assert_eq!(actual, None);
+    }
+
+    #[test]
+    fn sniffs_nothing_for_invalid_start() {
+        let actual = sniff("<xml/>");
+
+        assert_eq!(actual, None);
+    }
+}
@@ -0,0 +1,185 @@
+use std::cmp::Ordering;
+
+/// Location of a character within the input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Position {
+    /// Line number, starting at 1
+    pub line: usize,
+
+    /// Column within the line counted in characters, starting at 1
+    pub column: usize,
+
+    /// Byte offset from the start of the input
+    pub offset: usize,
+}
+
+impl Position {
+    /// Position of the first character of the input.
+    pub const START: Position = Position {
+        line: 1,
+        column: 1,
+        offset: 0,
+    };
+
+    /// Returns the position right after the given character.
+    pub fn advance(self, ch: char) -> Position {
+        if ch == '\n' {
+            Position {
+                line: self.line + 1,
+                column: 1,
+                offset: self.offset + 1,
+            }
+        } else {
+            Position {
+                line: self.line,
+                column: self.column + 1,
+                offset: self.offset + ch.len_utf8(),
+            }
+        }
+    }
+
+    /// Returns the position right after the given text.
+    pub fn advance_str(self, text: &str) -> Position {
+        text.chars().fold(self, Position::advance)
+    }
+}
+
+impl Default for Position {
+    fn default() -> Self {
+        Self::START
+    }
+}
+
+impl PartialOrd for Position {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Position {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.offset.cmp(&other.offset)
+    }
+}
+
+/// Range of the input between two positions, end exclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Span {
+    pub start: Position,
+    pub end: Position,
+}
+
+impl Span {
+    pub fn new(start: Position, end: Position) -> Self {
+        Self { start, end }
+    }
+
+    /// Span of the given text when it starts at `start`.
+    pub fn of_str(start: Position, text: &str) -> Self {
+        Self::new(start, start.advance_str(text))
+    }
+
+    /// Smallest span covering both spans.
+    pub fn merge(self, other: Span) -> Span {
+        Span {
+            start: self.start.min(other.start),
+            end: self.end.max(other.end),
+        }
+    }
+
+    /// Whether the position is within the span.
+    pub fn contains(&self, position: Position) -> bool {
+        self.start <= position && position < self.end
+    }
+
+    /// Whether the other span lies entirely within this one.
+    pub fn contains_span(&self, other: Span) -> bool {
+        self.start <= other.start && other.end <= self.end
+    }
+
+    /// Length of the span in bytes.
+    pub fn len(&self) -> usize {
+        self.end.offset - self.start.offset
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Text of the input covered by the span.
+    ///
+    /// Panics if the span does not lie on character boundaries of `input`.
+    pub fn slice<'a>(&self, input: &'a str) -> &'a str {
+        &input[self.start.offset..self.end.offset]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Position, Span};
+
+    fn position(line: usize, column: usize, offset: usize) -> Position {
+        Position {
+            line,
+            column,
+            offset,
+        }
+    }
+
+    #[test]
+    fn advances_over_newline() {
+        let expected = position(2, 2, 3);
+
+        let actual = Position::START.advance_str("a\nb");
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn advances_multibyte_offset() {
+        let expected = position(1, 3, 4);
+
+        let actual = Position::START.advance_str("こa");
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn merges_spans() {
+        let first = Span::of_str(Position::START, "ab");
+        let second = Span::of_str(position(1, 5, 4), "cd");
+        let expected = Span::new(Position::START, position(1, 7, 6));
+
+        let actual = first.merge(second);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn contains_is_end_exclusive() {
+        let span = Span::of_str(Position::START, "ab");
+
+        assert!(span.contains(Position::START));
+        assert!(span.contains(position(1, 2, 1)));
+        assert!(!span.contains(position(1, 3, 2)));
+    }
+
+    #[test]
+    fn contains_nested_span() {
+        let outer = Span::of_str(Position::START, "[true]");
+        let inner = Span::of_str(position(1, 2, 1), "true");
+
+        assert!(outer.contains_span(inner));
+        assert!(!inner.contains_span(outer));
+    }
+
+    #[test]
+    fn slices_input() {
+        let input = "[true]";
+        let span = Span::of_str(position(1, 2, 1), "true");
+
+        let actual = span.slice(input);
+
+        assert_eq!(actual, "true");
+    }
+}
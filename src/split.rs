@@ -0,0 +1,186 @@
+use crate::span::{Position, Span};
+
+/// Splits text holding several top-level JSON documents, concatenated or one
+/// per line, into the slice of each document.
+///
+/// Only brackets and strings are tracked to find where a document ends, the
+/// documents are not validated. Malformed input still yields slices that
+/// cover it, so parsing each slice reports the error for that document; an
+/// unclosed container or string runs to the end of the input.
+pub fn split_documents(input: &str) -> Vec<(Span, &str)> {
+    let mut documents = Vec::new();
+    let mut position = Position::START;
+    let mut rest = input;
+
+    loop {
+        let trimmed = rest.trim_start_matches(|c: char| c.is_ascii_whitespace());
+        position = position.advance_str(&rest[..rest.len() - trimmed.len()]);
+        rest = trimmed;
+
+        let Some(first) = rest.chars().next() else {
+            break;
+        };
+        let len = match first {
+            '{' | '[' => container_len(rest),
+            '"' => string_len(rest),
+            c if is_delimiter(c) => c.len_utf8(),
+            _ => rest
+                .find(|c: char| c.is_ascii_whitespace() || is_delimiter(c) || c == '"')
+                .unwrap_or(rest.len()),
+        };
+
+        let (document, remaining) = rest.split_at(len);
+        let span = Span::of_str(position, document);
+        documents.push((span, document));
+
+        position = span.end;
+        rest = remaining;
+    }
+
+    documents
+}
+
+fn is_delimiter(c: char) -> bool {
+    matches!(c, '{' | '}' | '[' | ']' | ',' | ':')
+}
+
+/// Byte length of the container at the start of `input`, up to its closing bracket.
+fn container_len(input: &str) -> usize {
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut in_escape = false;
+
+    for (index, ch) in input.char_indices() {
+        if in_string {
+            match ch {
+                _ if in_escape => in_escape = false,
+                '\\' => in_escape = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+
+        match ch {
+            '"' => in_string = true,
+            '{' | '[' => depth += 1,
+            '}' | ']' => {
+                depth -= 1;
+                if depth == 0 {
+                    return index + 1;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    input.len()
+}
+
+/// Byte length of the string at the start of `input`, including both quotes.
+fn string_len(input: &str) -> usize {
+    let mut in_escape = false;
+
+    for (index, ch) in input.char_indices().skip(1) {
+        match ch {
+            _ if in_escape => in_escape = false,
+            '\\' => in_escape = true,
+            '"' => return index + 1,
+            _ => {}
+        }
+    }
+
+    input.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::span::{Position, Span};
+
+    use super::split_documents;
+
+    fn slices(input: &str) -> Vec<&str> {
+        split_documents(input)
+            .into_iter()
+            .map(|(_, document)| document)
+            .collect()
+    }
+
+    #[test]
+    fn splits_newline_delimited_documents() {
+        let input = "{\"a\": 1}\n[2, 3]\n\n\"four\"\n";
+        let expected = ["{\"a\": 1}", "[2, 3]", "\"four\""];
+
+        let actual = slices(input);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn splits_concatenated_documents() {
+        let input = r#"{"a":"}"}[{"b":[]}]"x\"y"12 true null"#;
+        let expected = [
+            r#"{"a":"}"}"#,
+            r#"[{"b":[]}]"#,
+            r#""x\"y""#,
+            "12",
+            "true",
+            "null",
+        ];
+
+        let actual = slices(input);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn reports_document_spans() {
+        let input = "[1]\n  {\"é\": 2}";
+        let expected = [
+            Span::new(
+                Position::START,
+                Position {
+                    line: 1,
+                    column: 4,
+                    offset: 3,
+                },
+            ),
+            Span::new(
+                Position {
+                    line: 2,
+                    column: 3,
+                    offset: 6,
+                },
+                Position {
+                    line: 2,
+                    column: 11,
+                    offset: 15,
+                },
+            ),
+        ];
+
+        let actual: Vec<Span> = split_documents(input)
+            .into_iter()
+            .map(|(span, _)| span)
+            .collect();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn keeps_malformed_input_in_slices() {
+        let input = "[1] ] {\"a\": [2}";
+        let expected = ["[1]", "]", "{\"a\": [2}"];
+
+        let actual = slices(input);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn splits_blank_input_into_nothing() {
+        let actual = slices(" \n\t");
+
+        assert!(actual.is_empty());
+    }
+}
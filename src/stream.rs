@@ -0,0 +1,888 @@
+use std::io::{self, Read};
+
+use crate::{
+    convert::KindMismatch,
+    number::{is_json_number, parse_number},
+    options::DEFAULT_MAX_DEPTH,
+    parser::{unescape_string, TokenParseError},
+    pointer::PointerError,
+    scan::find_quote_or_backslash,
+    tokenizer::{ends_number, is_number_char, Token, TokenizeError},
+    Map, ParseError, Value,
+};
+
+const BUFFER_SIZE: usize = 8 * 1024;
+
+/// Possible errors that can occur when processing JSON from a reader
+#[derive(Debug)]
+pub enum StreamError {
+    /// Reading the input or writing the output failed
+    Io(io::Error),
+
+    /// The input contains invalid UTF-8 at the given byte offset
+    InvalidUtf8(u64),
+
+    TokenizeError(TokenizeError),
+
+    ParseError(TokenParseError),
+
+    /// Further tokens were found after the end of the top-level value
+    TrailingContent,
+
+    PointerError(PointerError),
+
+    /// The value at the requested location is not of the expected kind
+    KindMismatch(KindMismatch),
+}
+
+impl StreamError {
+    /// Converts an error from reading an in-memory `str`, which cannot fail
+    /// to read or contain invalid UTF-8.
+    pub(crate) fn into_parse_error(self) -> ParseError {
+        match self {
+            StreamError::TokenizeError(err) => ParseError::TokenizeError(err),
+            StreamError::ParseError(err) => ParseError::ParseError(err),
+            StreamError::TrailingContent => {
+                ParseError::ParseError(TokenParseError::TrailingContent)
+            }
+            err => unreachable!("reading a str failed: {err:?}"),
+        }
+    }
+}
+
+impl From<io::Error> for StreamError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<TokenizeError> for StreamError {
+    fn from(err: TokenizeError) -> Self {
+        Self::TokenizeError(err)
+    }
+}
+
+impl From<PointerError> for StreamError {
+    fn from(err: PointerError) -> Self {
+        Self::PointerError(err)
+    }
+}
+
+impl From<TokenParseError> for StreamError {
+    fn from(err: TokenParseError) -> Self {
+        Self::ParseError(err)
+    }
+}
+
+/// Parses the JSON read from `reader`, reading it in small chunks instead of
+/// loading the whole input first.
+pub fn parse_reader<R: Read>(reader: R) -> Result<Value, StreamError> {
+    let mut walker = Walker::new(reader);
+    let Some(Step::Value { token, .. }) = walker.next()? else {
+        return Err(TokenizeError::UnexpectedEof.into());
+    };
+    let value = walker.read_value(token)?;
+    if walker.next()?.is_some() {
+        return Err(StreamError::TrailingContent);
+    }
+    Ok(value)
+}
+
+/// Reads tokens one at a time from a byte reader, holding only a small buffer
+/// and the current token in memory. Produces the same tokens as `tokenize`.
+pub struct TokenReader<R> {
+    reader: R,
+    buffer: Box<[u8]>,
+    start: usize,
+    end: usize,
+    bytes_read: u64,
+    /// Text of the last number token read
+    number_text: String,
+}
+
+impl<R: Read> TokenReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            buffer: vec![0; BUFFER_SIZE].into_boxed_slice(),
+            start: 0,
+            end: 0,
+            bytes_read: 0,
+            number_text: String::new(),
+        }
+    }
+
+    /// Number of input bytes consumed so far.
+    pub fn bytes_read(&self) -> u64 {
+        self.bytes_read
+    }
+
+    /// Text of the last number token read, as written in the input.
+    pub(crate) fn number_text(&self) -> &str {
+        &self.number_text
+    }
+
+    /// Reads the next token, `None` once the input is exhausted.
+    pub fn next_token(&mut self) -> Result<Option<Token>, StreamError> {
+        while let Some(byte) = self.peek_byte()? {
+            if !byte.is_ascii_whitespace() {
+                break;
+            }
+            self.next_byte()?;
+        }
+
+        let Some(byte) = self.next_byte()? else {
+            return Ok(None);
+        };
+
+        let token = match byte {
+            b'{' => Token::LeftBrace,
+            b'}' => Token::RightBrace,
+            b'[' => Token::LeftBracket,
+            b']' => Token::RightBracket,
+            b',' => Token::Comma,
+            b':' => Token::Colon,
+            b'n' => self.literal(b"ull", Token::Null)?,
+            b't' => self.literal(b"rue", Token::True)?,
+            b'f' => self.literal(b"alse", Token::False)?,
+            b'"' => self.string()?,
+            b if b.is_ascii_digit() || b == b'-' => self.number(b)?,
+            b => {
+                let ch = self.decode_char(b)?;
+                return Err(TokenizeError::CharNotRecognized(ch).into());
+            }
+        };
+
+        Ok(Some(token))
+    }
+
+    fn peek_byte(&mut self) -> io::Result<Option<u8>> {
+        while self.start == self.end {
+            match self.reader.read(&mut self.buffer) {
+                Ok(0) => return Ok(None),
+                Ok(read) => {
+                    self.start = 0;
+                    self.end = read;
+                }
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(Some(self.buffer[self.start]))
+    }
+
+    fn next_byte(&mut self) -> io::Result<Option<u8>> {
+        let byte = self.peek_byte()?;
+        if byte.is_some() {
+            self.consume(1);
+        }
+        Ok(byte)
+    }
+
+    fn literal(&mut self, rest: &[u8], token: Token) -> Result<Token, StreamError> {
+        for expected in rest {
+            match self.next_byte()? {
+                None => return Err(TokenizeError::UnexpectedEof.into()),
+                Some(byte) if byte != *expected => {
+                    return Err(TokenizeError::UnfinishedLiteralValue.into())
+                }
+                Some(_) => {}
+            }
+        }
+        Ok(token)
+    }
+
+    fn number(&mut self, first: u8) -> Result<Token, StreamError> {
+        let start = self.bytes_read - 1;
+        let mut unparsed_num = String::from(char::from(first));
+
+        while let Some(byte) = self.peek_byte()? {
+            if !is_number_char(char::from(byte)) {
+                break;
+            }
+            unparsed_num.push(char::from(byte));
+            self.next_byte()?;
+        }
+
+        if self
+            .peek_byte()?
+            .is_some_and(|byte| !ends_number(char::from(byte)))
+        {
+            let mut run = unparsed_num.into_bytes();
+            while let Some(byte) = self.peek_byte()? {
+                // Bytes of multi-byte chars are never delimiters
+                if byte.is_ascii() && ends_number(char::from(byte)) {
+                    break;
+                }
+                run.push(byte);
+                self.next_byte()?;
+            }
+            return match String::from_utf8(run) {
+                Ok(run) => Err(TokenizeError::InvalidNumberLiteral(run).into()),
+                Err(err) => Err(StreamError::InvalidUtf8(
+                    start + err.utf8_error().valid_up_to() as u64,
+                )),
+            };
+        }
+
+        if !is_json_number(&unparsed_num) {
+            return Err(TokenizeError::InvalidNumberLiteral(unparsed_num).into());
+        }
+        let number = match parse_number(&unparsed_num) {
+            Ok(f) => Token::Number(f),
+            Err(err) => return Err(TokenizeError::ParseNumberError(err).into()),
+        };
+        self.number_text = unparsed_num;
+        Ok(number)
+    }
+
+    fn string(&mut self) -> Result<Token, StreamError> {
+        let start = self.bytes_read;
+        let mut bytes = Vec::new();
+
+        loop {
+            if self.peek_byte()?.is_none() {
+                return Err(TokenizeError::UnclosedQuotes.into());
+            }
+
+            let available = &self.buffer[self.start..self.end];
+            let Some(index) = find_quote_or_backslash(available) else {
+                bytes.extend_from_slice(available);
+                self.consume(available.len());
+                continue;
+            };
+
+            let special = available[index];
+            bytes.extend_from_slice(&available[..index]);
+            self.consume(index + 1);
+            if special == b'"' {
+                break;
+            }
+
+            // Keep the escape as is, the escaped byte cannot end the string
+            let escaped = self.next_byte()?.ok_or(TokenizeError::UnclosedQuotes)?;
+            bytes.extend_from_slice(&[b'\\', escaped]);
+        }
+
+        match String::from_utf8(bytes) {
+            Ok(string) => Ok(Token::String(string)),
+            Err(err) => Err(StreamError::InvalidUtf8(
+                start + err.utf8_error().valid_up_to() as u64,
+            )),
+        }
+    }
+
+    fn consume(&mut self, len: usize) {
+        self.start += len;
+        self.bytes_read += len as u64;
+    }
+
+    /// Decodes the character starting with `first` for error reporting.
+    fn decode_char(&mut self, first: u8) -> Result<char, StreamError> {
+        let offset = self.bytes_read - 1;
+        let len = match first {
+            0x00..=0x7f => 1,
+            0xc0..=0xdf => 2,
+            0xe0..=0xef => 3,
+            0xf0..=0xf7 => 4,
+            _ => return Err(StreamError::InvalidUtf8(offset)),
+        };
+
+        let mut bytes = vec![first];
+        while bytes.len() < len {
+            match self.peek_byte()? {
+                Some(byte) if byte & 0xc0 == 0x80 => {
+                    bytes.push(byte);
+                    self.next_byte()?;
+                }
+                _ => break,
+            }
+        }
+
+        std::str::from_utf8(&bytes)
+            .ok()
+            .and_then(|s| s.chars().next())
+            .ok_or(StreamError::InvalidUtf8(offset))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Container {
+    Array,
+    Object,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Expect {
+    /// No token read yet
+    Root,
+    /// Right after '['
+    ValueOrEnd,
+    /// Right after ':' or ',' in an array
+    Value,
+    /// Right after '{'
+    KeyOrEnd,
+    /// Right after ',' in an object
+    Key,
+    Colon,
+    CommaOrEnd,
+    /// The top-level value is complete
+    Done,
+}
+
+/// Checks that a sequence of tokens forms exactly one JSON value.
+#[derive(Debug, Clone)]
+pub(crate) struct Validator {
+    stack: Vec<Container>,
+    expect: Expect,
+}
+
+impl Validator {
+    pub(crate) fn new() -> Self {
+        Self {
+            stack: Vec::new(),
+            expect: Expect::Root,
+        }
+    }
+
+    /// Whether the next string token is an object key.
+    pub(crate) fn expects_key(&self) -> bool {
+        matches!(self.expect, Expect::KeyOrEnd | Expect::Key)
+    }
+
+    /// Checks the token is allowed at this point and advances the state.
+    pub(crate) fn accept(&mut self, token: &Token) -> Result<(), StreamError> {
+        match (self.expect, token) {
+            (Expect::Done, _) => return Err(StreamError::TrailingContent),
+
+            (Expect::KeyOrEnd, Token::RightBrace) | (Expect::ValueOrEnd, Token::RightBracket) => {
+                self.close()
+            }
+            (Expect::Key, Token::RightBrace) | (Expect::Value, Token::RightBracket) => {
+                return Err(TokenParseError::TrailingComma.into())
+            }
+            (Expect::KeyOrEnd | Expect::ValueOrEnd, Token::RightBrace | Token::RightBracket) => {
+                return Err(self.mismatched_bracket(token))
+            }
+            (Expect::KeyOrEnd | Expect::Key, Token::String(_)) => self.expect = Expect::Colon,
+            (Expect::KeyOrEnd | Expect::Key, _) => {
+                return Err(TokenParseError::ExpectedProperty.into())
+            }
+
+            (Expect::Colon, Token::Colon) => self.expect = Expect::Value,
+            (Expect::Colon, _) => return Err(TokenParseError::ExpectedColon.into()),
+
+            (Expect::CommaOrEnd, Token::Comma) => {
+                self.expect = match self.stack.last() {
+                    Some(Container::Object) => Expect::Key,
+                    _ => Expect::Value,
+                }
+            }
+            (Expect::CommaOrEnd, Token::RightBrace)
+                if self.stack.last() == Some(&Container::Object) =>
+            {
+                self.close()
+            }
+            (Expect::CommaOrEnd, Token::RightBracket)
+                if self.stack.last() == Some(&Container::Array) =>
+            {
+                self.close()
+            }
+            (Expect::CommaOrEnd, Token::RightBrace | Token::RightBracket) => {
+                return Err(self.mismatched_bracket(token))
+            }
+            (Expect::CommaOrEnd, _) => return Err(TokenParseError::ExpectedComma.into()),
+
+            (_, Token::LeftBrace) => {
+                self.stack.push(Container::Object);
+                self.expect = Expect::KeyOrEnd;
+            }
+            (_, Token::LeftBracket) => {
+                self.stack.push(Container::Array);
+                self.expect = Expect::ValueOrEnd;
+            }
+            (_, Token::Null | Token::True | Token::False | Token::Number(_) | Token::String(_)) => {
+                self.value_done()
+            }
+            (_, _) => return Err(TokenParseError::ExpectedValue.into()),
+        }
+
+        Ok(())
+    }
+
+    /// Fails if the input ended before the top-level value was complete.
+    pub(crate) fn finish(&self) -> Result<(), StreamError> {
+        match self.expect {
+            Expect::Done => Ok(()),
+            _ => Err(TokenizeError::UnexpectedEof.into()),
+        }
+    }
+
+    /// Error for a closing bracket that does not match the innermost container.
+    fn mismatched_bracket(&self, found: &Token) -> StreamError {
+        let expected = match self.stack.last() {
+            Some(Container::Array) => ']',
+            Some(Container::Object) => '}',
+            None => return TokenParseError::ExpectedValue.into(),
+        };
+        let found = match found {
+            Token::RightBrace => '}',
+            _ => ']',
+        };
+        TokenParseError::MismatchedBracket { expected, found }.into()
+    }
+
+    fn close(&mut self) {
+        self.stack.pop();
+        self.value_done();
+    }
+
+    fn value_done(&mut self) {
+        self.expect = if self.stack.is_empty() {
+            Expect::Done
+        } else {
+            Expect::CommaOrEnd
+        };
+    }
+}
+
+/// A structural step of the document read by [`Walker`]
+#[derive(Debug, PartialEq)]
+pub(crate) enum Step {
+    /// A value starts with `token`. `key` is the raw key of the value when it
+    /// is an object member.
+    Value { token: Token, key: Option<String> },
+
+    /// The innermost open container ends with `token`
+    Close(Token),
+}
+
+/// Reads a validated document value by value, keeping track of the path to
+/// the current value. Commas, colons and keys are consumed internally.
+pub(crate) struct Walker<R> {
+    tokens: TokenReader<R>,
+    validator: Validator,
+    /// Unescaped path segments down to the current value
+    path: Vec<String>,
+    /// Open containers, with the index of the next element for arrays
+    frames: Vec<Option<usize>>,
+    /// The last step completed a nested value whose segment is still on `path`
+    pop_pending: bool,
+}
+
+impl<R: Read> Walker<R> {
+    pub(crate) fn new(reader: R) -> Self {
+        Self {
+            tokens: TokenReader::new(reader),
+            validator: Validator::new(),
+            path: Vec::new(),
+            frames: Vec::new(),
+            pop_pending: false,
+        }
+    }
+
+    /// Path of the value started or the container closed by the last step.
+    pub(crate) fn path(&self) -> &[String] {
+        &self.path
+    }
+
+    /// Text of the number read by the last step, when it read a number.
+    pub(crate) fn number_text(&self) -> &str {
+        self.tokens.number_text()
+    }
+
+    pub(crate) fn next(&mut self) -> Result<Option<Step>, StreamError> {
+        if self.pop_pending {
+            self.path.pop();
+            self.pop_pending = false;
+        }
+
+        let mut key = None;
+        loop {
+            let Some(token) = self.tokens.next_token()? else {
+                self.validator.finish()?;
+                return Ok(None);
+            };
+            let is_key = self.validator.expects_key();
+            self.validator.accept(&token)?;
+
+            match token {
+                Token::Comma | Token::Colon => {}
+                Token::String(raw) if is_key => {
+                    self.path.push(unescape_string(&raw)?);
+                    key = Some(raw);
+                }
+                Token::RightBrace | Token::RightBracket => {
+                    self.frames.pop();
+                    self.pop_pending = !self.frames.is_empty();
+                    return Ok(Some(Step::Close(token)));
+                }
+                token => {
+                    if let Some(Some(index)) = self.frames.last_mut() {
+                        self.path.push(index.to_string());
+                        *index += 1;
+                    }
+                    match token {
+                        Token::LeftBrace => self.frames.push(None),
+                        Token::LeftBracket => self.frames.push(Some(0)),
+                        _ => self.pop_pending = !self.frames.is_empty(),
+                    }
+                    return Ok(Some(Step::Value { token, key }));
+                }
+            }
+        }
+    }
+
+    /// Advances to the value at `target` and returns its first token, skipping
+    /// every container that cannot contain it. `None` when there is no such value.
+    pub(crate) fn seek(&mut self, target: &[String]) -> Result<Option<Token>, StreamError> {
+        while let Some(step) = self.next()? {
+            match step {
+                Step::Value { token, .. } => {
+                    if self.path() == target {
+                        return Ok(Some(token));
+                    }
+                    if opens_container(&token) && !target.starts_with(self.path()) {
+                        self.skip_container()?;
+                    }
+                }
+                // Only containers on the way to the target are entered, so the
+                // target is not in the document once one of them closes.
+                Step::Close(_) => return Ok(None),
+            }
+        }
+        Ok(None)
+    }
+
+    /// Builds the value started by the last step from the rest of its tokens.
+    /// Containers nested deeper than [`DEFAULT_MAX_DEPTH`] in the document
+    /// fail with [`TokenParseError::NestingTooDeep`], as this recurses once
+    /// per level.
+    pub(crate) fn read_value(&mut self, first: Token) -> Result<Value, StreamError> {
+        let mut values = Vec::new();
+        let mut map = Map::new();
+
+        match first {
+            Token::Null => return Ok(Value::Null),
+            Token::True => return Ok(Value::Boolean(true)),
+            Token::False => return Ok(Value::Boolean(false)),
+            Token::Number(number) => return Ok(Value::Number(number)),
+            Token::String(raw) => return Ok(Value::String(unescape_string(&raw)?)),
+            _ => {}
+        }
+        if self.frames.len() > DEFAULT_MAX_DEPTH {
+            return Err(TokenParseError::NestingTooDeep {
+                limit: DEFAULT_MAX_DEPTH,
+            }
+            .into());
+        }
+
+        loop {
+            match self.next()? {
+                Some(Step::Value { token, key }) => {
+                    let unescaped_key = key.and_then(|_| self.path().last().cloned());
+                    let value = self.read_value(token)?;
+                    match unescaped_key {
+                        Some(key) => {
+                            map.insert(key, value);
+                        }
+                        None => values.push(value),
+                    }
+                }
+                Some(Step::Close(Token::RightBrace)) => return Ok(Value::Object(map)),
+                Some(Step::Close(_)) => return Ok(Value::Array(values)),
+                None => return Err(TokenizeError::UnexpectedEof.into()),
+            }
+        }
+    }
+
+    /// Consumes the rest of the container opened by the last step.
+    pub(crate) fn skip_container(&mut self) -> Result<(), StreamError> {
+        let mut depth = 1;
+        while depth > 0 {
+            match self.next()? {
+                Some(Step::Value { token, .. }) if opens_container(&token) => depth += 1,
+                Some(Step::Close(_)) => depth -= 1,
+                Some(_) => {}
+                None => return Err(TokenizeError::UnexpectedEof.into()),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Whether the token starts an array or object.
+pub(crate) fn opens_container(token: &Token) -> bool {
+    token.kind().opens_container()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, Read};
+
+    use crate::{
+        parser::TokenParseError,
+        tokenizer::{tokenize, Token, TokenizeError},
+        Value,
+    };
+
+    use super::{parse_reader, Step, StreamError, TokenReader, Validator, Walker};
+
+    /// Returns one byte per read call to exercise buffer refills.
+    struct OneByteReader<'a>(&'a [u8]);
+
+    impl Read for OneByteReader<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            match self.0.split_first() {
+                Some((first, rest)) if !buf.is_empty() => {
+                    buf[0] = *first;
+                    self.0 = rest;
+                    Ok(1)
+                }
+                _ => Ok(0),
+            }
+        }
+    }
+
+    fn read_all<R: Read>(reader: R) -> Result<Vec<Token>, StreamError> {
+        let mut reader = TokenReader::new(reader);
+        let mut tokens = Vec::new();
+        while let Some(token) = reader.next_token()? {
+            tokens.push(token);
+        }
+        Ok(tokens)
+    }
+
+    fn validate(input: &str) -> Result<(), StreamError> {
+        let mut validator = Validator::new();
+        for token in tokenize(String::from(input))? {
+            validator.accept(&token)?;
+        }
+        validator.finish()
+    }
+
+    #[test]
+    fn reads_same_tokens_as_tokenizer() {
+        let input = r#" {"key": [1.5, -2, true, false, null, "a \" é"]} "#;
+        let expected = tokenize(String::from(input)).unwrap();
+
+        let actual = read_all(OneByteReader(input.as_bytes())).unwrap();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn counts_bytes_read() {
+        let input = "[\"é\"]  ";
+        let mut reader = TokenReader::new(input.as_bytes());
+
+        while reader.next_token().unwrap().is_some() {}
+
+        assert_eq!(reader.bytes_read(), input.len() as u64);
+    }
+
+    #[test]
+    fn reports_unrecognized_non_ascii_char() {
+        let input = "[é]";
+
+        let actual = read_all(input.as_bytes());
+
+        assert!(matches!(
+            actual,
+            Err(StreamError::TokenizeError(
+                TokenizeError::CharNotRecognized('é')
+            ))
+        ));
+    }
+
+    #[test]
+    fn reports_invalid_utf8_in_string() {
+        let input = b"[\"ab\xff\"]";
+
+        let actual = read_all(&input[..]);
+
+        assert!(matches!(actual, Err(StreamError::InvalidUtf8(4))));
+    }
+
+    #[test]
+    fn reports_number_followed_by_invalid_chars() {
+        let actual = read_all(OneByteReader("[12é3x ]".as_bytes()));
+
+        assert!(matches!(
+            actual,
+            Err(StreamError::TokenizeError(TokenizeError::InvalidNumberLiteral(run))) if run == "12é3x"
+        ));
+    }
+
+    #[test]
+    fn reports_literal_cut_off() {
+        let actual = read_all("[tru".as_bytes());
+
+        assert!(matches!(
+            actual,
+            Err(StreamError::TokenizeError(TokenizeError::UnexpectedEof))
+        ));
+    }
+
+    #[test]
+    fn validates_well_formed_values() {
+        let inputs = [
+            "1",
+            "[]",
+            "{}",
+            r#"{"a": [1, {"b": null}], "c": {}}"#,
+            "[[], [[]]]",
+        ];
+
+        for input in inputs {
+            assert!(validate(input).is_ok(), "input: {input}");
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_values() {
+        let cases = [
+            ("[1,]", TokenParseError::TrailingComma),
+            (r#"{"a": 1,}"#, TokenParseError::TrailingComma),
+            ("[1 2]", TokenParseError::ExpectedComma),
+            (r#"{"a" 1}"#, TokenParseError::ExpectedColon),
+            ("{1: 2}", TokenParseError::ExpectedProperty),
+            ("[,1]", TokenParseError::ExpectedValue),
+            (
+                "[1}",
+                TokenParseError::MismatchedBracket {
+                    expected: ']',
+                    found: '}',
+                },
+            ),
+            (
+                "[{]}",
+                TokenParseError::MismatchedBracket {
+                    expected: '}',
+                    found: ']',
+                },
+            ),
+        ];
+
+        for (input, expected) in cases {
+            let actual = validate(input);
+
+            assert!(
+                matches!(&actual, Err(StreamError::ParseError(err)) if *err == expected),
+                "input: {input}, got {actual:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_unfinished_and_trailing_values() {
+        assert!(matches!(
+            validate("[1, [2]"),
+            Err(StreamError::TokenizeError(TokenizeError::UnexpectedEof))
+        ));
+        assert!(matches!(validate("1 2"), Err(StreamError::TrailingContent)));
+    }
+
+    #[test]
+    fn walker_tracks_value_paths() {
+        let input = r#"{"a": [1, {"b~/": true}], "c": null}"#;
+        let expected = [
+            "",
+            "/a",
+            "/a/0",
+            "/a/1",
+            "/a/1/b~0~1",
+            "/a/1",
+            "/a",
+            "/c",
+            "",
+        ];
+
+        let mut walker = Walker::new(input.as_bytes());
+        let mut actual = Vec::new();
+        while walker.next().unwrap().is_some() {
+            let pointer: String = walker
+                .path()
+                .iter()
+                .map(|segment| format!("/{}", crate::pointer::escape_token(segment)))
+                .collect();
+            actual.push(pointer);
+        }
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn walker_reports_member_keys_and_skips_containers() {
+        let input = r#"{"skip": {"x": [1, 2]}, "k\n": 3}"#;
+        let mut walker = Walker::new(input.as_bytes());
+
+        walker.next().unwrap();
+        walker.next().unwrap();
+        walker.skip_container().unwrap();
+        let actual = walker.next().unwrap();
+
+        assert_eq!(
+            actual,
+            Some(Step::Value {
+                token: Token::Number(3.0),
+                key: Some(String::from("k\\n"))
+            })
+        );
+        assert_eq!(walker.path(), ["k\n"]);
+    }
+
+    #[test]
+    fn reads_numbers_by_json_grammar() {
+        let actual = parse_reader(b"[1e5, -2.5E+3]".as_slice()).unwrap();
+
+        assert_eq!(
+            actual,
+            Value::array([Value::Number(1e5), Value::Number(-2500.0)])
+        );
+        for input in ["[012]", "[-01]", "[1.]", "[1e]"] {
+            let run = &input[1..input.len() - 1];
+            assert!(
+                matches!(
+                    parse_reader(input.as_bytes()),
+                    Err(StreamError::TokenizeError(TokenizeError::InvalidNumberLiteral(text)))
+                        if text == run
+                ),
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_deeply_nested_reader_input() {
+        let input = format!("{}{}", "[".repeat(5000), "]".repeat(5000));
+        let at_limit = format!("{}{}", "[".repeat(128), "]".repeat(128));
+
+        assert!(matches!(
+            parse_reader(input.as_bytes()),
+            Err(StreamError::ParseError(TokenParseError::NestingTooDeep {
+                limit: 128
+            }))
+        ));
+        assert!(parse_reader(at_limit.as_bytes()).is_ok());
+    }
+
+    #[test]
+    fn parses_value_from_reader() {
+        let input = br#" {"a": [1, "\u00e9"]} "#;
+        let expected =
+            Value::object([("a", Value::array([Value::Number(1.0), Value::string("é")]))]);
+
+        let actual = parse_reader(OneByteReader(input)).unwrap();
+
+        assert_eq!(actual, expected);
+        assert!(matches!(
+            parse_reader("[1] 2".as_bytes()),
+            Err(StreamError::TrailingContent)
+        ));
+        assert!(matches!(
+            parse_reader(" ".as_bytes()),
+            Err(StreamError::TokenizeError(TokenizeError::UnexpectedEof))
+        ));
+    }
+}
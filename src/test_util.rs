@@ -0,0 +1,105 @@
+//! Golden-file assertions for downstream test suites.
+
+use std::{env, fs, io, path::Path};
+
+use crate::{SerializeOptions, Style, Value};
+
+/// Environment variable which, when set to `1`, rewrites snapshots instead of comparing.
+pub const UPDATE_ENV_VAR: &str = "UPDATE_SNAPSHOTS";
+
+/// Asserts that the canonical form of `value` (sorted keys, two-space indentation)
+/// equals the contents of the snapshot file at `path`.
+///
+/// With `UPDATE_SNAPSHOTS=1` the file is (re)written instead, creating missing
+/// parent directories.
+pub fn assert_matches_snapshot(value: &Value, path: impl AsRef<Path>) {
+    let path = path.as_ref();
+    let actual = to_snapshot_string(value);
+
+    if env::var(UPDATE_ENV_VAR).is_ok_and(|v| v == "1") {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).expect("failed to create snapshot directory");
+        }
+        fs::write(path, &actual).expect("failed to write snapshot");
+        return;
+    }
+
+    let expected = match fs::read_to_string(path) {
+        Ok(expected) => expected,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => panic!(
+            "snapshot {} does not exist, run with {UPDATE_ENV_VAR}=1 to create it",
+            path.display()
+        ),
+        Err(err) => panic!("failed to read snapshot {}: {err}", path.display()),
+    };
+
+    assert!(
+        expected == actual,
+        "value does not match snapshot {} (run with {UPDATE_ENV_VAR}=1 to update)\n\
+         --- expected\n{expected}\n+++ actual\n{actual}",
+        path.display()
+    );
+}
+
+/// Canonical text stored in snapshot files: keys sorted, one member per line.
+pub fn to_snapshot_string(value: &Value) -> String {
+    let mut output = value.to_string_with(&SerializeOptions {
+        style: Style::Pretty { indent: 2 },
+        sort_keys: true,
+        ..SerializeOptions::default()
+    });
+    output.push('\n');
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::{Map, Value};
+
+    use super::{assert_matches_snapshot, to_snapshot_string};
+
+    fn document() -> Value {
+        Value::object([
+            ("b", Value::Array(vec![Value::Number(1.5), Value::Null])),
+            ("a", Value::string("line\n\"quoted\"")),
+            ("c", Value::Object(Map::new())),
+        ])
+    }
+
+    #[test]
+    fn snapshot_string_is_sorted_and_indented() {
+        let expected = "{\n  \"a\": \"line\\n\\\"quoted\\\"\",\n  \"b\": [\n    1.5,\n    null\n  ],\n  \"c\": {}\n}\n";
+
+        let actual = to_snapshot_string(&document());
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn matches_existing_snapshot() {
+        let path = std::env::temp_dir().join("json_parser_matches_existing_snapshot.json");
+        fs::write(&path, to_snapshot_string(&document())).unwrap();
+
+        assert_matches_snapshot(&document(), &path);
+    }
+
+    #[test]
+    #[should_panic(expected = "does not match snapshot")]
+    fn fails_on_different_snapshot() {
+        let path = std::env::temp_dir().join("json_parser_fails_on_different_snapshot.json");
+        fs::write(&path, "{}\n").unwrap();
+
+        assert_matches_snapshot(&document(), &path);
+    }
+
+    #[test]
+    #[should_panic(expected = "does not exist")]
+    fn fails_on_missing_snapshot() {
+        let path = std::env::temp_dir().join("json_parser_fails_on_missing_snapshot.json");
+        let _ = fs::remove_file(&path);
+
+        assert_matches_snapshot(&document(), &path);
+    }
+}
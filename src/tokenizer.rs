@@ -1,5 +1,11 @@
 use std::num::ParseFloatError;
 
+use crate::{
+    number::{is_json_number, parse_number},
+    scan::find_quote_or_backslash,
+    span::{Position, Span},
+};
+
 /// Represents possible lexical tokens.
 #[derive(Debug, PartialEq)]
 pub enum Token {
@@ -37,6 +43,69 @@ pub enum Token {
     String(String),
 }
 
+/// Kind of a [`Token`], without the string or number it carries
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TokenKind {
+    LeftBrace,
+    RightBrace,
+    LeftBracket,
+    RightBracket,
+    Comma,
+    Colon,
+    Null,
+    False,
+    True,
+    Number,
+    String,
+}
+
+impl Token {
+    pub fn kind(&self) -> TokenKind {
+        match self {
+            Token::LeftBrace => TokenKind::LeftBrace,
+            Token::RightBrace => TokenKind::RightBrace,
+            Token::LeftBracket => TokenKind::LeftBracket,
+            Token::RightBracket => TokenKind::RightBracket,
+            Token::Comma => TokenKind::Comma,
+            Token::Colon => TokenKind::Colon,
+            Token::Null => TokenKind::Null,
+            Token::False => TokenKind::False,
+            Token::True => TokenKind::True,
+            Token::Number(_) => TokenKind::Number,
+            Token::String(_) => TokenKind::String,
+        }
+    }
+}
+
+impl TokenKind {
+    /// Whether the token is a complete value on its own.
+    pub fn is_scalar(&self) -> bool {
+        matches!(
+            self,
+            TokenKind::Null
+                | TokenKind::False
+                | TokenKind::True
+                | TokenKind::Number
+                | TokenKind::String
+        )
+    }
+
+    /// Whether the token starts an array or object.
+    pub fn opens_container(&self) -> bool {
+        matches!(self, TokenKind::LeftBrace | TokenKind::LeftBracket)
+    }
+
+    /// Whether the token ends an array or object.
+    pub fn closes_container(&self) -> bool {
+        matches!(self, TokenKind::RightBrace | TokenKind::RightBracket)
+    }
+
+    /// Whether a value can start with the token.
+    pub fn starts_value(&self) -> bool {
+        self.is_scalar() || self.opens_container()
+    }
+}
+
 /// Possible errors that can occur when tokenizing the input
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum TokenizeError {
@@ -46,6 +115,11 @@ pub enum TokenizeError {
     /// Unable to parse the float number
     ParseNumberError(ParseFloatError),
 
+    /// A number is outside the JSON grammar, such as `012` or `1.`, or is
+    /// directly followed by characters other than whitespace or a structural
+    /// character. Holds the whole run, such as `123abc`
+    InvalidNumberLiteral(String),
+
     /// Matching closing quotes are not found
     UnclosedQuotes,
 
@@ -58,31 +132,192 @@ pub enum TokenizeError {
 
 /// Creates a vector of tokens from a given String input.
 pub fn tokenize(input: String) -> Result<Vec<Token>, TokenizeError> {
+    let tokens = tokenize_spanned(input)?;
+    Ok(tokens.into_iter().map(|(token, _)| token).collect())
+}
+
+/// Token together with the span it covers in the input
+pub type SpannedToken = (Token, Span);
+
+/// Creates a vector of tokens together with the span each token covers in the input.
+pub fn tokenize_spanned(input: String) -> Result<Vec<SpannedToken>, TokenizeError> {
+    match tokenize_partial(&input) {
+        (tokens, None) => Ok(tokens),
+        (_, Some((err, _))) => Err(err),
+    }
+}
+
+/// Tokenizes as much of the input as possible. On failure, the tokens read so far
+/// are returned together with the error and the position of the offending token.
+pub fn tokenize_partial(input: &str) -> (Vec<SpannedToken>, Option<(TokenizeError, Position)>) {
+    scan(input, |_| {})
+}
+
+/// Tokenizes like [`tokenize_partial`], reporting each run of skipped whitespace.
+fn scan(
+    input: &str,
+    mut on_trivia: impl FnMut(Span),
+) -> (Vec<SpannedToken>, Option<(TokenizeError, Position)>) {
     let chars: Vec<char> = input.chars().collect();
     let mut index = 0;
+    let mut tracker = PositionTracker::default();
 
     let mut tokens = Vec::new();
     while index < chars.len() {
-        let token = create_token(&chars, &mut index)?;
-        tokens.push(token);
+        let trivia_start = index;
+        skip_whitespace(&chars, &mut index);
+        if index > trivia_start {
+            let start = tracker.position_at(&chars, trivia_start);
+            on_trivia(Span::new(start, tracker.position_at(&chars, index)));
+        }
+        if index >= chars.len() {
+            break;
+        }
+
+        let start = tracker.position_at(&chars, index);
+        let token = match create_token(input, &chars, start.offset, &mut index) {
+            Ok(token) => token,
+            Err(err) => return (tokens, Some((err, start))),
+        };
         index += 1;
+
+        let end = tracker.position_at(&chars, index);
+        tokens.push((token, Span::new(start, end)));
+    }
+
+    if tokens.is_empty() {
+        let position = tracker.position_at(&chars, chars.len());
+        return (tokens, Some((TokenizeError::UnexpectedEof, position)));
     }
 
-    Ok(tokens)
+    (tokens, None)
 }
 
-fn create_token(chars: &[char], index: &mut usize) -> Result<Token, TokenizeError> {
-    let mut ch = chars[*index];
+/// Scanner the tokenizer picks from the first char of a token
+#[cfg(feature = "trace")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scanner {
+    /// One of `{}[],:`
+    Structural,
 
-    while ch.is_ascii_whitespace() {
-        *index += 1;
+    /// `null`, `true` or `false`
+    Literal,
 
-        if *index >= chars.len() {
-            return Err(TokenizeError::UnexpectedEof);
+    String,
+
+    Number,
+}
+
+/// One decision of the tokenizer, as recorded by [`tokenize_traced`]
+#[cfg(feature = "trace")]
+#[derive(Debug, PartialEq)]
+pub enum TraceStep {
+    /// Whitespace skipped between tokens
+    Trivia(Span),
+
+    /// Token read by the scanner chosen for its first char
+    Token {
+        token: Token,
+        span: Span,
+        scanner: Scanner,
+    },
+
+    /// Tokenizing stopped at the start of the offending token
+    Error {
+        error: TokenizeError,
+        position: Position,
+    },
+}
+
+#[cfg(feature = "trace")]
+impl std::fmt::Display for TraceStep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let at = |position: Position| format!("{}:{}", position.line, position.column);
+        match self {
+            TraceStep::Trivia(span) => write!(f, "{}-{} trivia", at(span.start), at(span.end)),
+            TraceStep::Token {
+                token,
+                span,
+                scanner,
+            } => write!(
+                f,
+                "{}-{} {:?} via {:?}",
+                at(span.start),
+                at(span.end),
+                token,
+                scanner
+            ),
+            TraceStep::Error { error, position } => {
+                write!(f, "{} error {:?}", at(*position), error)
+            }
         }
+    }
+}
 
-        ch = chars[*index];
+/// Tokenizes the input, recording every token with its span and scanner, the
+/// whitespace skipped in between and the error that stopped tokenizing, if any.
+#[cfg(feature = "trace")]
+pub fn tokenize_traced(input: &str) -> Vec<TraceStep> {
+    let mut trivia = Vec::new();
+    let (tokens, error) = scan(input, |span| trivia.push(span));
+
+    let mut steps = Vec::with_capacity(tokens.len() + trivia.len() + 1);
+    let mut trivia = trivia.into_iter().peekable();
+    for (token, span) in tokens {
+        while let Some(skipped) = trivia.next_if(|skipped| skipped.start < span.start) {
+            steps.push(TraceStep::Trivia(skipped));
+        }
+        let scanner = match token {
+            Token::Null | Token::True | Token::False => Scanner::Literal,
+            Token::String(_) => Scanner::String,
+            Token::Number(_) => Scanner::Number,
+            _ => Scanner::Structural,
+        };
+        steps.push(TraceStep::Token {
+            token,
+            span,
+            scanner,
+        });
     }
+    steps.extend(trivia.map(TraceStep::Trivia));
+    if let Some((error, position)) = error {
+        steps.push(TraceStep::Error { error, position });
+    }
+
+    steps
+}
+
+/// Converts char indexes into positions, scanning forward from the last requested index.
+#[derive(Default)]
+struct PositionTracker {
+    index: usize,
+    position: Position,
+}
+
+impl PositionTracker {
+    fn position_at(&mut self, chars: &[char], index: usize) -> Position {
+        while self.index < index {
+            self.position = self.position.advance(chars[self.index]);
+            self.index += 1;
+        }
+        self.position
+    }
+}
+
+fn skip_whitespace(chars: &[char], index: &mut usize) {
+    while *index < chars.len() && chars[*index].is_ascii_whitespace() {
+        *index += 1;
+    }
+}
+
+/// Creates the token starting at char `index`, which is at byte `byte` of `input`.
+fn create_token(
+    input: &str,
+    chars: &[char],
+    byte: usize,
+    index: &mut usize,
+) -> Result<Token, TokenizeError> {
+    let ch = chars[*index];
 
     let token = match ch {
         '{' => Token::LeftBrace,
@@ -94,7 +329,7 @@ fn create_token(chars: &[char], index: &mut usize) -> Result<Token, TokenizeErro
         'n' => tokenize_literal(chars, index, "null", Token::Null)?,
         't' => tokenize_literal(chars, index, "true", Token::True)?,
         'f' => tokenize_literal(chars, index, "false", Token::False)?,
-        '"' => tokenize_string(chars, index)?,
+        '"' => tokenize_string(input, byte, index)?,
         c if c.is_ascii_digit() || c == '-' => tokenize_float(chars, index)?,
 
         ch => return Err(TokenizeError::CharNotRecognized(ch)),
@@ -110,7 +345,10 @@ fn tokenize_literal(
     token_value: Token,
 ) -> Result<Token, TokenizeError> {
     for expected_char in literal_value.chars() {
-        if expected_char != chars[*index] {
+        let Some(&ch) = chars.get(*index) else {
+            return Err(TokenizeError::UnexpectedEof);
+        };
+        if expected_char != ch {
             return Err(TokenizeError::UnfinishedLiteralValue);
         }
         *index += 1;
@@ -120,52 +358,84 @@ fn tokenize_literal(
     Ok(token_value)
 }
 
+/// Longest number text parsed without allocating
+const NUMBER_BUFFER_SIZE: usize = 64;
+
 fn tokenize_float(chars: &[char], index: &mut usize) -> Result<Token, TokenizeError> {
-    let mut unparsed_num = String::new();
-    let mut is_decimal = false;
-
-    while *index < chars.len() {
-        let ch = chars[*index];
-        match ch {
-            c if c.is_ascii_digit() || c == '-' => unparsed_num.push(c),
-            c if c == '.' && !is_decimal => {
-                unparsed_num.push('.');
-                is_decimal = true;
-            }
-            _ => break,
-        }
+    let start = *index;
+    while chars.get(*index).is_some_and(|&c| is_number_char(c)) {
         *index += 1;
     }
 
+    if chars.get(*index).is_some_and(|&c| !ends_number(c)) {
+        let end = chars[*index..]
+            .iter()
+            .position(|&c| ends_number(c))
+            .map_or(chars.len(), |offset| *index + offset);
+        let run = chars[start..end].iter().collect();
+        return Err(TokenizeError::InvalidNumberLiteral(run));
+    }
+
+    let digits = &chars[start..*index];
     *index -= 1;
 
-    match unparsed_num.parse() {
+    // Number chars are ASCII, so they are copied byte by byte into a stack
+    // buffer instead of allocating a string; only unusually long numbers allocate
+    let mut buffer = [0; NUMBER_BUFFER_SIZE];
+    let long;
+    let text = if digits.len() <= buffer.len() {
+        for (byte, &ch) in buffer.iter_mut().zip(digits) {
+            *byte = ch as u8;
+        }
+        std::str::from_utf8(&buffer[..digits.len()]).expect("number chars are ASCII")
+    } else {
+        long = digits.iter().collect::<String>();
+        &long
+    };
+
+    if !is_json_number(text) {
+        return Err(TokenizeError::InvalidNumberLiteral(text.to_string()));
+    }
+    match parse_number(text) {
         Ok(f) => Ok(Token::Number(f)),
         Err(err) => Err(TokenizeError::ParseNumberError(err)),
     }
 }
 
-fn tokenize_string(chars: &[char], index: &mut usize) -> Result<Token, TokenizeError> {
-    let mut string = String::new();
-    let mut in_escape_mode = false;
+/// Whether the char may be part of a number: digits, signs, the decimal
+/// point and the exponent marker.
+pub(crate) fn is_number_char(c: char) -> bool {
+    c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E')
+}
+
+/// Whether a number may be directly followed by the char.
+pub(crate) fn ends_number(c: char) -> bool {
+    c.is_ascii_whitespace() || matches!(c, '{' | '}' | '[' | ']' | ',' | ':')
+}
+
+fn tokenize_string(input: &str, byte: usize, index: &mut usize) -> Result<Token, TokenizeError> {
+    let bytes = input.as_bytes();
+    let start = byte + 1;
+    let mut end = start;
 
+    // Jump from one quote or backslash to the next, eight bytes at a time
     loop {
-        *index += 1;
-        if *index >= chars.len() {
+        let Some(offset) = bytes.get(end..).and_then(find_quote_or_backslash) else {
             return Err(TokenizeError::UnclosedQuotes);
-        }
+        };
 
-        let ch = chars[*index];
-        match ch {
-            '"' if !in_escape_mode => break,
-            '\\' => in_escape_mode = !in_escape_mode,
-            _ => in_escape_mode = false,
+        end += offset;
+        if bytes[end] == b'"' {
+            break;
         }
-
-        string.push(ch);
+        // Skip the backslash and the first byte it escapes; UTF-8 continuation
+        // bytes are never a quote or backslash
+        end += 2;
     }
 
-    Ok(Token::String(string))
+    let text = &input[start..end];
+    *index += text.chars().count() + 1;
+    Ok(Token::String(String::from(text)))
 }
 
 #[cfg(test)]
@@ -177,9 +447,12 @@ impl Token {
 
 #[cfg(test)]
 mod tests {
-    use crate::tokenizer::TokenizeError;
+    use crate::{
+        span::{Position, Span},
+        tokenizer::TokenizeError,
+    };
 
-    use super::{tokenize, Token};
+    use super::{tokenize, tokenize_spanned, Token, TokenKind};
 
     #[test]
     fn just_comma() {
@@ -301,6 +574,33 @@ mod tests {
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn string_ending_in_escaped_backslash() {
+        let input = String::from(r#"["a\\", "\\\""]"#);
+        let expected = [
+            Token::LeftBracket,
+            Token::string(r"a\\"),
+            Token::Comma,
+            Token::string(r#"\\\""#),
+            Token::RightBracket,
+        ];
+
+        let actual = tokenize(input).unwrap();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn unclosed_string_after_escape() {
+        let inputs = [r#""abc\""#, r#""abc\"#];
+
+        for input in inputs {
+            let actual = tokenize(String::from(input));
+
+            assert_eq!(actual, Err(TokenizeError::UnclosedQuotes), "input: {input}");
+        }
+    }
+
     #[test]
     fn escaped_quote() {
         let input = String::from(r#""the \" is OK""#);
@@ -419,4 +719,310 @@ mod tests {
 
         assert_eq!(actual, expected);
     }
+
+    #[test]
+    fn spans_of_tokens() {
+        let input = String::from("[1,\n \"é\"]");
+        let expected = [
+            (Token::LeftBracket, Span::of_str(Position::START, "[")),
+            (
+                Token::Number(1.0),
+                Span::of_str(Position::START.advance_str("["), "1"),
+            ),
+            (
+                Token::Comma,
+                Span::of_str(Position::START.advance_str("[1"), ","),
+            ),
+            (
+                Token::string("é"),
+                Span::of_str(Position::START.advance_str("[1,\n "), "\"é\""),
+            ),
+            (
+                Token::RightBracket,
+                Span::of_str(Position::START.advance_str("[1,\n \"é\""), "]"),
+            ),
+        ];
+
+        let actual = tokenize_spanned(input).unwrap();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn spans_after_multibyte_strings() {
+        let input = String::from(r#"["Zoë \ü \" naïve façade", "こ"]"#);
+        let first = r#""Zoë \ü \" naïve façade""#;
+        let expected = [
+            (Token::LeftBracket, Span::of_str(Position::START, "[")),
+            (
+                Token::string(r#"Zoë \ü \" naïve façade"#),
+                Span::of_str(Position::START.advance_str("["), first),
+            ),
+            (
+                Token::Comma,
+                Span::of_str(Position::START.advance_str("[").advance_str(first), ","),
+            ),
+            (
+                Token::string("こ"),
+                Span::of_str(
+                    Position::START
+                        .advance_str("[")
+                        .advance_str(first)
+                        .advance_str(", "),
+                    "\"こ\"",
+                ),
+            ),
+            (
+                Token::RightBracket,
+                Span::of_str(
+                    Position::START
+                        .advance_str("[")
+                        .advance_str(first)
+                        .advance_str(", \"こ\""),
+                    "]",
+                ),
+            ),
+        ];
+
+        let actual = tokenize_spanned(input).unwrap();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn span_of_string_token_starts_on_second_line() {
+        let input = String::from("\n  \"a\"");
+        let expected = Position {
+            line: 2,
+            column: 3,
+            offset: 3,
+        };
+
+        let actual = tokenize_spanned(input).unwrap();
+
+        assert_eq!(actual[0].1.start, expected);
+    }
+
+    #[test]
+    fn literals_cut_off_at_end() {
+        let inputs = ["tru", "nul", "f", "[fals"];
+
+        for input in inputs {
+            let actual = tokenize(String::from(input));
+
+            assert_eq!(actual, Err(TokenizeError::UnexpectedEof), "input: {input}");
+        }
+    }
+
+    #[test]
+    fn literal_with_wrong_char() {
+        let input = String::from("trUe");
+        let expected = Err(TokenizeError::UnfinishedLiteralValue);
+
+        let actual = tokenize(input);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn number_at_end() {
+        let input = String::from("[1, 23");
+        let expected = [
+            Token::LeftBracket,
+            Token::Number(1.0),
+            Token::Comma,
+            Token::Number(23.0),
+        ];
+
+        let actual = tokenize(input).unwrap();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn number_longer_than_stack_buffer() {
+        let digits = format!("1.{}", "0".repeat(100));
+        let input = format!("[{digits}]");
+        let expected = [Token::LeftBracket, Token::Number(1.0), Token::RightBracket];
+
+        let actual = tokenize(input).unwrap();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn numbers_with_exponent() {
+        let input = String::from("[1e5, -2.5E+3, 4E-2, 0e0]");
+        let expected = [
+            Token::LeftBracket,
+            Token::Number(1e5),
+            Token::Comma,
+            Token::Number(-2.5e3),
+            Token::Comma,
+            Token::Number(4e-2),
+            Token::Comma,
+            Token::Number(0.0),
+            Token::RightBracket,
+        ];
+
+        let actual = tokenize(input).unwrap();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn numbers_outside_grammar() {
+        for input in [
+            "012", "-01", "1.", ".5", "1e", "1e+", "1.2.3", "--1", "1-2", "+1",
+        ] {
+            let actual = tokenize(format!("[{input}]"));
+
+            let expected = match input {
+                "+1" => TokenizeError::CharNotRecognized('+'),
+                ".5" => TokenizeError::CharNotRecognized('.'),
+                _ => TokenizeError::InvalidNumberLiteral(String::from(input)),
+            };
+            assert_eq!(actual, Err(expected), "{input}");
+        }
+    }
+
+    #[test]
+    fn number_followed_by_invalid_chars() {
+        let cases = [
+            ("123abc", "123abc"),
+            ("[1.5x, 2]", "1.5x"),
+            ("{\"a\": -1\"b\"}", "-1\"b\""),
+            ("[1é]", "1é"),
+        ];
+
+        for (input, run) in cases {
+            let actual = tokenize(String::from(input));
+
+            assert_eq!(
+                actual,
+                Err(TokenizeError::InvalidNumberLiteral(String::from(run))),
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn lone_minus_at_end() {
+        let input = String::from("-");
+
+        let actual = tokenize(input);
+
+        assert_eq!(
+            actual,
+            Err(TokenizeError::InvalidNumberLiteral(String::from("-")))
+        );
+    }
+
+    #[test]
+    fn string_cut_off_after_escape() {
+        let input = String::from(r#""abc\"#);
+        let expected = Err(TokenizeError::UnclosedQuotes);
+
+        let actual = tokenize(input);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn trailing_whitespace() {
+        let input = String::from("true \n");
+        let expected = [Token::True];
+
+        let actual = tokenize(input).unwrap();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn blank_input() {
+        let inputs = ["", " \n\t"];
+
+        for input in inputs {
+            let actual = tokenize(String::from(input));
+
+            assert_eq!(
+                actual,
+                Err(TokenizeError::UnexpectedEof),
+                "input: {input:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn reports_token_kinds() {
+        let input = String::from(r#"{"a": [1, null]}"#);
+        let expected = [
+            TokenKind::LeftBrace,
+            TokenKind::String,
+            TokenKind::Colon,
+            TokenKind::LeftBracket,
+            TokenKind::Number,
+            TokenKind::Comma,
+            TokenKind::Null,
+            TokenKind::RightBracket,
+            TokenKind::RightBrace,
+        ];
+
+        let actual: Vec<TokenKind> = tokenize(input).unwrap().iter().map(Token::kind).collect();
+
+        assert_eq!(actual, expected);
+        assert_eq!(actual.iter().filter(|kind| kind.starts_value()).count(), 5);
+    }
+
+    #[cfg(feature = "trace")]
+    #[test]
+    fn traces_tokens_trivia_and_error() {
+        use super::{tokenize_traced, Scanner, TraceStep};
+
+        let steps = tokenize_traced("[ 1,\n x]");
+        let after = |text: &str| Position::START.advance_str(text);
+        let expected = [
+            TraceStep::Token {
+                token: Token::LeftBracket,
+                span: Span::of_str(Position::START, "["),
+                scanner: Scanner::Structural,
+            },
+            TraceStep::Trivia(Span::of_str(after("["), " ")),
+            TraceStep::Token {
+                token: Token::Number(1.0),
+                span: Span::of_str(after("[ "), "1"),
+                scanner: Scanner::Number,
+            },
+            TraceStep::Token {
+                token: Token::Comma,
+                span: Span::of_str(after("[ 1"), ","),
+                scanner: Scanner::Structural,
+            },
+            TraceStep::Trivia(Span::of_str(after("[ 1,"), "\n ")),
+            TraceStep::Error {
+                error: TokenizeError::CharNotRecognized('x'),
+                position: after("[ 1,\n "),
+            },
+        ];
+
+        assert_eq!(steps, expected);
+        assert_eq!(steps[2].to_string(), "1:3-1:4 Number(1.0) via Number");
+        assert_eq!(steps[5].to_string(), "2:2 error CharNotRecognized('x')");
+    }
+
+    #[cfg(feature = "trace")]
+    #[test]
+    fn traces_trailing_trivia() {
+        use super::{tokenize_traced, TraceStep};
+
+        let steps = tokenize_traced("null  ");
+
+        assert_eq!(
+            steps.last(),
+            Some(&TraceStep::Trivia(Span::of_str(
+                Position::START.advance_str("null"),
+                "  "
+            )))
+        );
+    }
 }
@@ -0,0 +1,456 @@
+use std::io::{self, BufWriter, Read, Write};
+
+use crate::{
+    filter::Filter,
+    parser::validate_escapes,
+    stream::{opens_container, Step, StreamError, Walker},
+    tokenizer::Token,
+    ParseError,
+};
+
+/// Layout of the re-encoded output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Style {
+    /// No whitespace between tokens
+    #[default]
+    Compact,
+
+    /// One member or element per line, indented by the given number of spaces per level
+    Pretty { indent: usize },
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TranscodeOptions {
+    /// Layout of the output
+    pub style: Style,
+
+    /// Parts of the document left out of the output
+    pub filter: Filter,
+}
+
+/// Re-encodes the JSON read from `reader` into `writer` token by token,
+/// without building a [`crate::Value`]. Strings and numbers are copied
+/// verbatim, escapes and digits included, once their escape sequences and
+/// number grammar are checked. Output written before an error is detected
+/// is not rolled back.
+///
+/// Values rejected by the [`Filter`] are skipped while reading, so memory
+/// use does not depend on the size of the document.
+pub fn transcode<R: Read, W: Write>(
+    reader: R,
+    writer: W,
+    options: &TranscodeOptions,
+) -> Result<(), StreamError> {
+    let mut walker = Walker::new(reader);
+    let mut emitter = Emitter::new(BufWriter::new(writer), options.style);
+    // Whether each open container in the output has a member written yet
+    let mut written = Vec::new();
+
+    while let Some(step) = walker.next()? {
+        match step {
+            Step::Value { token, key } => {
+                let is_container = opens_container(&token);
+                let unescaped_key = key.as_ref().and(walker.path().last());
+                if !options.filter.keeps(
+                    walker.path(),
+                    unescaped_key.map(String::as_str),
+                    is_container,
+                ) {
+                    if is_container {
+                        walker.skip_container()?;
+                    }
+                    continue;
+                }
+
+                if let Some(raw) = &key {
+                    validate_escapes(raw)?;
+                }
+                if let Token::String(raw) = &token {
+                    validate_escapes(raw)?;
+                }
+
+                if let Some(has_members) = written.last_mut() {
+                    if *has_members {
+                        emitter.token(&Token::Comma)?;
+                    }
+                    *has_members = true;
+                }
+                if let Some(key) = key {
+                    emitter.token(&Token::String(key))?;
+                    emitter.token(&Token::Colon)?;
+                }
+                match token {
+                    Token::Number(_) => emitter.number(walker.number_text())?,
+                    token => emitter.token(&token)?,
+                }
+                if is_container {
+                    written.push(false);
+                }
+            }
+            Step::Close(token) => {
+                written.pop();
+                emitter.token(&token)?;
+            }
+        }
+    }
+
+    emitter.finish()?;
+    Ok(())
+}
+
+/// Re-indents or compacts the JSON read from `reader` into `writer`, piping
+/// tokens straight from the reader to the output so memory use stays bounded
+/// whatever the size of the document. Same as [`transcode`] without a filter.
+pub fn reformat<R: Read, W: Write>(reader: R, writer: W, style: Style) -> Result<(), StreamError> {
+    let options = TranscodeOptions {
+        style,
+        ..TranscodeOptions::default()
+    };
+    transcode(reader, writer, &options)
+}
+
+/// Strips the whitespace between the tokens of the input while validating
+/// it, without building a [`crate::Value`]. Strings and numbers are copied
+/// verbatim.
+pub fn minify(input: &str) -> Result<String, ParseError> {
+    let mut output = Vec::with_capacity(input.len());
+    transcode(input.as_bytes(), &mut output, &TranscodeOptions::default())
+        .map_err(StreamError::into_parse_error)?;
+    Ok(String::from_utf8(output).expect("strings are copied from valid UTF-8"))
+}
+
+/// Writes a valid token sequence with the whitespace of a [`Style`].
+pub(crate) struct Emitter<W> {
+    writer: W,
+    style: Style,
+    depth: usize,
+    /// A container was opened and its first element has not been written yet
+    after_open: bool,
+}
+
+impl<W: Write> Emitter<W> {
+    pub(crate) fn new(writer: W, style: Style) -> Self {
+        Self {
+            writer,
+            style,
+            depth: 0,
+            after_open: false,
+        }
+    }
+
+    pub(crate) fn token(&mut self, token: &Token) -> io::Result<()> {
+        let closing = token.kind().closes_container();
+        self.separate(closing)?;
+
+        match token {
+            Token::LeftBrace => self.open(b'{')?,
+            Token::LeftBracket => self.open(b'[')?,
+            Token::RightBrace => self.writer.write_all(b"}")?,
+            Token::RightBracket => self.writer.write_all(b"]")?,
+            Token::Comma => {
+                self.writer.write_all(b",")?;
+                self.newline()?;
+            }
+            Token::Colon => match self.style {
+                Style::Compact => self.writer.write_all(b":")?,
+                Style::Pretty { .. } => self.writer.write_all(b": ")?,
+            },
+            Token::String(raw) => write!(self.writer, "\"{raw}\"")?,
+            Token::Number(number) => write!(self.writer, "{number}")?,
+            Token::True => self.writer.write_all(b"true")?,
+            Token::False => self.writer.write_all(b"false")?,
+            Token::Null => self.writer.write_all(b"null")?,
+        }
+
+        Ok(())
+    }
+
+    /// Writes a number token as the given text.
+    pub(crate) fn number(&mut self, text: &str) -> io::Result<()> {
+        self.separate(false)?;
+        self.writer.write_all(text.as_bytes())
+    }
+
+    /// Starts a new line where the style asks for one before the next token.
+    fn separate(&mut self, closing: bool) -> io::Result<()> {
+        if closing {
+            self.depth -= 1;
+        }
+        if self.after_open {
+            self.after_open = false;
+            if !closing {
+                self.newline()?;
+            }
+        } else if closing {
+            self.newline()?;
+        }
+        Ok(())
+    }
+
+    /// Flushes the output and returns the underlying writer.
+    pub(crate) fn finish(mut self) -> io::Result<W> {
+        if let Style::Pretty { .. } = self.style {
+            self.writer.write_all(b"\n")?;
+        }
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+
+    fn open(&mut self, bracket: u8) -> io::Result<()> {
+        self.writer.write_all(&[bracket])?;
+        self.depth += 1;
+        self.after_open = true;
+        Ok(())
+    }
+
+    fn newline(&mut self) -> io::Result<()> {
+        if let Style::Pretty { indent } = self.style {
+            writeln!(self.writer)?;
+            write!(self.writer, "{:width$}", "", width = indent * self.depth)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        filter::Filter, parser::TokenParseError, stream::StreamError, tokenizer::TokenizeError,
+        JsonPointer, ParseError,
+    };
+
+    use super::{minify, reformat, transcode, Style, TranscodeOptions};
+
+    fn transcode_str(input: &str, style: Style) -> Result<String, StreamError> {
+        let options = TranscodeOptions {
+            style,
+            ..TranscodeOptions::default()
+        };
+        transcode_with(input, &options)
+    }
+
+    fn transcode_with(input: &str, options: &TranscodeOptions) -> Result<String, StreamError> {
+        let mut output = Vec::new();
+        transcode(input.as_bytes(), &mut output, options)?;
+        Ok(String::from_utf8(output).unwrap())
+    }
+
+    fn filtered(drop_keys: &[&str], allow: &[&str]) -> TranscodeOptions {
+        TranscodeOptions {
+            filter: Filter {
+                drop_keys: drop_keys.iter().map(|key| key.to_string()).collect(),
+                allow: allow
+                    .iter()
+                    .map(|pointer| JsonPointer::parse(pointer).unwrap())
+                    .collect(),
+            },
+            ..TranscodeOptions::default()
+        }
+    }
+
+    #[test]
+    fn minifies_input() {
+        let input = " {\n  \"a\" : [ 1 , 2.5, true ],\n  \"b\\\"\" : { \"c\": null } } ";
+        let expected = r#"{"a":[1,2.5,true],"b\"":{"c":null}}"#;
+
+        let actual = transcode_str(input, Style::Compact).unwrap();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn pretty_prints_input() {
+        let input = r#"{"a":[1,{}],"b":{"c":[]}}"#;
+        let expected = "{\n  \"a\": [\n    1,\n    {}\n  ],\n  \"b\": {\n    \"c\": []\n  }\n}\n";
+
+        let actual = transcode_str(input, Style::Pretty { indent: 2 }).unwrap();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn transcodes_scalar_root() {
+        let input = "  \"text\"  ";
+        let expected = "\"text\"";
+
+        let actual = transcode_str(input, Style::Compact).unwrap();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn fails_on_invalid_structure() {
+        let actual = transcode_str("[1, 2,]", Style::Compact);
+
+        assert!(matches!(
+            actual,
+            Err(StreamError::ParseError(TokenParseError::TrailingComma))
+        ));
+    }
+
+    #[test]
+    fn fails_on_truncated_input() {
+        let actual = transcode_str(r#"{"a": [1"#, Style::Compact);
+
+        assert!(matches!(
+            actual,
+            Err(StreamError::TokenizeError(TokenizeError::UnexpectedEof))
+        ));
+    }
+
+    #[test]
+    fn fails_on_invalid_escapes() {
+        let cases = [
+            (
+                r#"["\x"]"#,
+                TokenParseError::InvalidEscape(String::from(r"\x")),
+            ),
+            (
+                r#"{"a\u12": 1}"#,
+                TokenParseError::UnfinishedEscape(String::from(r"\u12")),
+            ),
+            (
+                r#"{"a": "\uZZZZ"}"#,
+                TokenParseError::InvalidHexValue(String::from(r"\uZZZZ")),
+            ),
+        ];
+
+        for (input, expected) in cases {
+            let actual = transcode_str(input, Style::Compact);
+
+            assert!(
+                matches!(&actual, Err(StreamError::ParseError(err)) if *err == expected),
+                "input: {input}, actual: {actual:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn drops_matching_keys_at_any_depth() {
+        let input = r#"{"email": "a@b.c", "user": {"name": "n", "email": "x", "api_token": "t"}, "list": [{"email": 1}, 2]}"#;
+        let expected = r#"{"user":{"name":"n"},"list":[{},2]}"#;
+
+        let actual = transcode_with(input, &filtered(&["email", "*_token"], &[])).unwrap();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn keeps_only_allowlisted_pointers() {
+        let input = r#"{"id": 1, "meta": {"next": "p2", "secret": {"k": 1}}, "items": [{"id": 1, "pii": 2}, {"id": 2}]}"#;
+        let expected = r#"{"id":1,"meta":{"next":"p2"},"items":[{"id":1,"pii":2}]}"#;
+
+        let actual =
+            transcode_with(input, &filtered(&[], &["/id", "/meta/next", "/items/0"])).unwrap();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn drop_patterns_apply_inside_allowlisted_values() {
+        let input = r#"{"items": [{"id": 1, "pii": 2}], "other": 3}"#;
+        let expected = r#"{"items":[{"id":1}]}"#;
+
+        let actual = transcode_with(input, &filtered(&["pii"], &["/items"])).unwrap();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn matches_keys_after_unescaping() {
+        let input = r#"{"em\u0061il": 1, "b": 2}"#;
+        let expected = r#"{"b":2}"#;
+
+        let actual = transcode_with(input, &filtered(&["email"], &[])).unwrap();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn validates_skipped_values() {
+        let input = r#"{"email": [1,], "b": 2}"#;
+
+        let actual = transcode_with(input, &filtered(&["email"], &[]));
+
+        assert!(matches!(
+            actual,
+            Err(StreamError::ParseError(TokenParseError::TrailingComma))
+        ));
+    }
+
+    #[test]
+    fn minify_keeps_strings_and_numbers_verbatim() {
+        let input = "[ \"a \\u00e9 b\" ,\n\t1.50, 12345678901234567890, -0.0, 1e5, -2.5E+3 ]";
+        let expected = r#"["a \u00e9 b",1.50,12345678901234567890,-0.0,1e5,-2.5E+3]"#;
+
+        let actual = minify(input);
+
+        assert_eq!(actual, Ok(String::from(expected)));
+    }
+
+    #[test]
+    fn minify_reports_invalid_input() {
+        let cases = [
+            (
+                "[1,]",
+                ParseError::ParseError(TokenParseError::TrailingComma),
+            ),
+            (
+                "[1] 2",
+                ParseError::ParseError(TokenParseError::TrailingContent),
+            ),
+            (
+                "{\"a\": 1",
+                ParseError::TokenizeError(TokenizeError::UnexpectedEof),
+            ),
+            (
+                "[@]",
+                ParseError::TokenizeError(TokenizeError::CharNotRecognized('@')),
+            ),
+            (
+                "[012]",
+                ParseError::TokenizeError(TokenizeError::InvalidNumberLiteral(String::from("012"))),
+            ),
+            (
+                "[1.]",
+                ParseError::TokenizeError(TokenizeError::InvalidNumberLiteral(String::from("1."))),
+            ),
+            (
+                r#"["\x","\u12"]"#,
+                ParseError::ParseError(TokenParseError::InvalidEscape(String::from(r"\x"))),
+            ),
+        ];
+
+        for (input, expected) in cases {
+            let actual = minify(input);
+
+            assert_eq!(actual, Err(expected), "input: {input}");
+        }
+    }
+
+    #[test]
+    fn reformat_keeps_numbers_verbatim() {
+        let input = r#"{"big":12345678901234567890,"price":1.50}"#;
+        let expected = "{\n  \"big\": 12345678901234567890,\n  \"price\": 1.50\n}\n";
+
+        let mut output = Vec::new();
+        reformat(input.as_bytes(), &mut output, Style::Pretty { indent: 2 }).unwrap();
+
+        assert_eq!(String::from_utf8(output).unwrap(), expected);
+    }
+
+    #[test]
+    fn reformats_between_styles() {
+        let input = r#"{"a":[1,{"b":null}],"c":"d"}"#;
+
+        let mut pretty = Vec::new();
+        reformat(input.as_bytes(), &mut pretty, Style::Pretty { indent: 4 }).unwrap();
+        let mut compact = Vec::new();
+        reformat(pretty.as_slice(), &mut compact, Style::Compact).unwrap();
+
+        assert!(String::from_utf8(pretty)
+            .unwrap()
+            .contains("\n    \"a\": [\n        1,"));
+        assert_eq!(String::from_utf8(compact).unwrap(), input);
+    }
+}
@@ -0,0 +1,351 @@
+use std::{collections::HashMap, mem};
+
+use crate::{JsonPointer, Map, Value};
+
+/// A rewrite of a whole document that may fail, composable with others in
+/// a [`Pipeline`]. Closures from `Value` to `Result` implement it.
+pub trait Transform {
+    fn apply(&self, value: Value) -> Result<Value, TransformError>;
+}
+
+/// Possible errors when applying a transform
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum TransformError {
+    /// Renaming a member would replace another member with this key
+    KeyCollision(String),
+
+    /// Renaming a member would replace the member at this location
+    KeyCollisionAt(JsonPointer),
+
+    /// Error reported by a caller-defined transform
+    Custom(String),
+}
+
+impl<F: Fn(Value) -> Result<Value, TransformError>> Transform for F {
+    fn apply(&self, value: Value) -> Result<Value, TransformError> {
+        self(value)
+    }
+}
+
+/// Transforms applied one after another, stopping at the first error.
+#[derive(Default)]
+pub struct Pipeline {
+    steps: Vec<Box<dyn Transform>>,
+}
+
+impl Pipeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a transform to run after the ones already added.
+    pub fn then(mut self, transform: impl Transform + 'static) -> Self {
+        self.steps.push(Box::new(transform));
+        self
+    }
+}
+
+impl Transform for Pipeline {
+    fn apply(&self, value: Value) -> Result<Value, TransformError> {
+        self.steps
+            .iter()
+            .try_fold(value, |value, step| step.apply(value))
+    }
+}
+
+/// Removes object members whose value is `null`, at any depth. Array
+/// elements are kept so indexes do not shift.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StripNulls;
+
+impl Transform for StripNulls {
+    fn apply(&self, value: Value) -> Result<Value, TransformError> {
+        map_objects(value, &|map| {
+            Ok(map
+                .into_iter()
+                .filter(|(_, value)| !matches!(value, Value::Null))
+                .collect())
+        })
+    }
+}
+
+/// Orders object members by key, at any depth. Only observable with the
+/// `preserve_order` feature, since hash maps have no order to keep.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SortKeys;
+
+impl Transform for SortKeys {
+    fn apply(&self, value: Value) -> Result<Value, TransformError> {
+        map_objects(value, &|map| {
+            let mut members: Vec<_> = map.into_iter().collect();
+            members.sort_by(|(a, _), (b, _)| a.cmp(b));
+            Ok(members.into_iter().collect())
+        })
+    }
+}
+
+/// Renames object members from the keys to the values of the map, at any
+/// depth. Fails when a renamed member would replace another one.
+#[derive(Debug, Clone, Default)]
+pub struct RenameKeys(pub HashMap<String, String>);
+
+impl Transform for RenameKeys {
+    fn apply(&self, value: Value) -> Result<Value, TransformError> {
+        map_objects(value, &|map| {
+            let mut renamed = Map::with_capacity(map.len());
+            for (key, value) in map {
+                let key = self.0.get(&key).cloned().unwrap_or(key);
+                if renamed.contains_key(&key) {
+                    return Err(TransformError::KeyCollision(key));
+                }
+                renamed.insert(key, value);
+            }
+            Ok(renamed)
+        })
+    }
+}
+
+/// Renames object members with renames scoped to parts of the document,
+/// for migrating documents between schema versions.
+///
+/// Scopes are pointers into the input document, so they use the keys from
+/// before renaming. When several scopes rename the same key, the innermost
+/// one applies. Members are renamed at the same time, so keys can be swapped.
+#[derive(Debug, Clone, Default)]
+pub struct KeyMapping {
+    scopes: Vec<(JsonPointer, HashMap<String, String>)>,
+}
+
+impl KeyMapping {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Renames `from` to `to` in every object.
+    pub fn rename(self, from: impl Into<String>, to: impl Into<String>) -> Self {
+        self.rename_within(JsonPointer::root(), from, to)
+    }
+
+    /// Renames `from` to `to` in the objects at or below `scope`.
+    pub fn rename_within(
+        mut self,
+        scope: JsonPointer,
+        from: impl Into<String>,
+        to: impl Into<String>,
+    ) -> Self {
+        let renames = match self
+            .scopes
+            .iter_mut()
+            .find(|(existing, _)| *existing == scope)
+        {
+            Some((_, renames)) => renames,
+            None => {
+                self.scopes.push((scope, HashMap::new()));
+                &mut self.scopes.last_mut().expect("scope was just pushed").1
+            }
+        };
+        renames.insert(from.into(), to.into());
+        self
+    }
+
+    /// New key for a member of the object at `path`, `None` to keep it.
+    fn renamed(&self, path: &JsonPointer, key: &str) -> Option<&String> {
+        self.scopes
+            .iter()
+            .filter(|(scope, _)| path.tokens().starts_with(scope.tokens()))
+            .filter_map(|(scope, renames)| Some((scope.tokens().len(), renames.get(key)?)))
+            .max_by_key(|(depth, _)| *depth)
+            .map(|(_, to)| to)
+    }
+
+    fn apply_at(&self, mut value: Value, path: &mut JsonPointer) -> Result<Value, TransformError> {
+        match &mut value {
+            Value::Array(values) => {
+                for (index, element) in values.iter_mut().enumerate() {
+                    path.push(index.to_string());
+                    *element = self.apply_at(mem::replace(element, Value::Null), path)?;
+                    path.pop();
+                }
+            }
+            Value::Object(map) => {
+                let mut renamed = Map::with_capacity(map.len());
+                for (key, value) in mem::take(map) {
+                    path.push(key);
+                    let value = self.apply_at(value, path)?;
+                    let key = path.pop().expect("key was just pushed");
+
+                    let key = self.renamed(path, &key).cloned().unwrap_or(key);
+                    if renamed.contains_key(&key) {
+                        return Err(TransformError::KeyCollisionAt(path.child(key)));
+                    }
+                    renamed.insert(key, value);
+                }
+                return Ok(Value::Object(renamed));
+            }
+            _ => {}
+        }
+        Ok(value)
+    }
+}
+
+impl Transform for KeyMapping {
+    fn apply(&self, value: Value) -> Result<Value, TransformError> {
+        self.apply_at(value, &mut JsonPointer::root())
+    }
+}
+
+/// Rebuilds every object with `f`, innermost objects first.
+fn map_objects<F>(mut value: Value, f: &F) -> Result<Value, TransformError>
+where
+    F: Fn(Map<String, Value>) -> Result<Map<String, Value>, TransformError>,
+{
+    match &mut value {
+        Value::Array(values) => {
+            for element in values.iter_mut() {
+                *element = map_objects(mem::replace(element, Value::Null), f)?;
+            }
+        }
+        Value::Object(map) => {
+            let map = mem::take(map)
+                .into_iter()
+                .map(|(key, value)| Ok((key, map_objects(value, f)?)))
+                .collect::<Result<_, _>>()?;
+            return f(map).map(Value::Object);
+        }
+        _ => {}
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{JsonPointer, Value};
+
+    use super::{KeyMapping, Pipeline, RenameKeys, StripNulls, Transform, TransformError};
+
+    fn renames() -> RenameKeys {
+        RenameKeys(HashMap::from([(String::from("id"), String::from("key"))]))
+    }
+
+    #[test]
+    fn strips_null_members_at_any_depth() {
+        let input = Value::object([
+            ("a", Value::Null),
+            (
+                "b",
+                Value::array([Value::Null, Value::object([("c", Value::Null)])]),
+            ),
+        ]);
+        let expected = Value::object([(
+            "b",
+            Value::array([Value::Null, Value::object::<&str, _>([])]),
+        )]);
+
+        let actual = StripNulls.apply(input);
+
+        assert_eq!(actual, Ok(expected));
+    }
+
+    #[cfg(feature = "preserve_order")]
+    #[test]
+    fn sorts_keys() {
+        let input = Value::object([
+            ("b", Value::object([("y", Value::Null), ("x", Value::Null)])),
+            ("a", Value::Null),
+        ]);
+
+        let actual = super::SortKeys.apply(input).unwrap();
+
+        assert_eq!(actual.to_canonical_string(), actual.to_string());
+    }
+
+    #[test]
+    fn renames_keys_and_reports_collisions() {
+        let input = Value::array([Value::object([("id", Value::Number(1.0))])]);
+        let expected = Value::array([Value::object([("key", Value::Number(1.0))])]);
+        let colliding = Value::object([("id", Value::Null), ("key", Value::Null)]);
+
+        assert_eq!(renames().apply(input), Ok(expected));
+        assert_eq!(
+            renames().apply(colliding),
+            Err(TransformError::KeyCollision(String::from("key")))
+        );
+    }
+
+    #[test]
+    fn runs_steps_in_order() {
+        let pipeline = Pipeline::new()
+            .then(StripNulls)
+            .then(renames())
+            .then(|value: Value| {
+                if value.as_object().is_some_and(|map| map.is_empty()) {
+                    Err(TransformError::Custom(String::from("empty")))
+                } else {
+                    Ok(value)
+                }
+            });
+        let input = Value::object([("id", Value::Number(2.0)), ("key", Value::Null)]);
+        let expected = Value::object([("key", Value::Number(2.0))]);
+
+        assert_eq!(pipeline.apply(input), Ok(expected));
+        assert_eq!(
+            pipeline.apply(Value::object([("x", Value::Null)])),
+            Err(TransformError::Custom(String::from("empty")))
+        );
+    }
+
+    #[test]
+    fn renames_keys_within_scopes() {
+        let mapping = KeyMapping::new()
+            .rename("name", "title")
+            .rename_within(JsonPointer::parse("/users").unwrap(), "name", "login")
+            .rename_within(JsonPointer::parse("/users/0").unwrap(), "a", "b")
+            .rename_within(JsonPointer::parse("/users/0").unwrap(), "b", "a");
+        let input = Value::object([
+            ("name", Value::string("app")),
+            (
+                "users",
+                Value::array([Value::object([
+                    ("name", Value::string("root")),
+                    ("a", Value::Number(1.0)),
+                    ("b", Value::Number(2.0)),
+                ])]),
+            ),
+        ]);
+        let expected = Value::object([
+            ("title", Value::string("app")),
+            (
+                "users",
+                Value::array([Value::object([
+                    ("login", Value::string("root")),
+                    ("b", Value::Number(1.0)),
+                    ("a", Value::Number(2.0)),
+                ])]),
+            ),
+        ]);
+
+        let actual = mapping.apply(input);
+
+        assert_eq!(actual, Ok(expected));
+    }
+
+    #[test]
+    fn reports_location_of_collision() {
+        let mapping = KeyMapping::new().rename("old", "new");
+        let input = Value::object([(
+            "nested",
+            Value::object([("old", Value::Null), ("new", Value::Null)]),
+        )]);
+
+        let actual = mapping.apply(input);
+
+        assert_eq!(
+            actual,
+            Err(TransformError::KeyCollisionAt(
+                JsonPointer::parse("/nested/new").unwrap()
+            ))
+        );
+    }
+}
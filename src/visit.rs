@@ -0,0 +1,689 @@
+use std::{collections::HashMap, marker::PhantomData};
+
+use crate::{
+    options::DEFAULT_MAX_DEPTH,
+    parser::{unescape_string, TokenParseError},
+    tokenizer::{tokenize, Token, TokenizeError},
+    Map, Value, ValueKind,
+};
+
+/// Possible errors that can occur when decoding with a [`ValueVisitor`]
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum VisitError {
+    TokenizeError(TokenizeError),
+
+    ParseError(TokenParseError),
+
+    /// The visitor does not accept values of this kind
+    InvalidType(ValueKind),
+
+    /// Error reported by a visitor
+    Custom(String),
+}
+
+impl From<TokenizeError> for VisitError {
+    fn from(err: TokenizeError) -> Self {
+        Self::TokenizeError(err)
+    }
+}
+
+impl From<TokenParseError> for VisitError {
+    fn from(err: TokenParseError) -> Self {
+        Self::ParseError(err)
+    }
+}
+
+/// Receives the values read by the parser, without an intermediate [`Value`].
+///
+/// Every method rejects its kind of value by default, so a visitor only
+/// implements the kinds it accepts.
+pub trait ValueVisitor: Sized {
+    type Value;
+
+    fn visit_null(self) -> Result<Self::Value, VisitError> {
+        Err(VisitError::InvalidType(ValueKind::Null))
+    }
+
+    fn visit_bool(self, _value: bool) -> Result<Self::Value, VisitError> {
+        Err(VisitError::InvalidType(ValueKind::Boolean))
+    }
+
+    fn visit_f64(self, _value: f64) -> Result<Self::Value, VisitError> {
+        Err(VisitError::InvalidType(ValueKind::Number))
+    }
+
+    fn visit_str(self, _value: &str) -> Result<Self::Value, VisitError> {
+        Err(VisitError::InvalidType(ValueKind::String))
+    }
+
+    /// Elements the visitor does not read are skipped after it returns.
+    fn visit_seq(self, _seq: &mut SeqAccess<'_>) -> Result<Self::Value, VisitError> {
+        Err(VisitError::InvalidType(ValueKind::Array))
+    }
+
+    /// Members the visitor does not read are skipped after it returns.
+    fn visit_map(self, _map: &mut MapAccess<'_>) -> Result<Self::Value, VisitError> {
+        Err(VisitError::InvalidType(ValueKind::Object))
+    }
+}
+
+/// Types that can be decoded straight from the parser.
+pub trait FromJson: Sized {
+    fn from_json(decoder: Decoder<'_>) -> Result<Self, VisitError>;
+}
+
+/// Decodes a `T` from the input without building a [`Value`].
+pub fn decode<T: FromJson>(input: &str) -> Result<T, VisitError> {
+    let tokens = tokenize(String::from(input))?;
+    let mut index = 0;
+    let value = T::from_json(Decoder::new(&tokens, &mut index, 0))?;
+    expect_end(&tokens, index)?;
+    Ok(value)
+}
+
+/// Runs the visitor over the value in the input.
+pub fn decode_with<V: ValueVisitor>(input: &str, visitor: V) -> Result<V::Value, VisitError> {
+    let tokens = tokenize(String::from(input))?;
+    let mut index = 0;
+    let value = Decoder::new(&tokens, &mut index, 0).decode_any(visitor)?;
+    expect_end(&tokens, index)?;
+    Ok(value)
+}
+
+/// Fails when tokens are left after the top-level value, like [`crate::parse`].
+fn expect_end(tokens: &[Token], index: usize) -> Result<(), TokenParseError> {
+    if index < tokens.len() {
+        return Err(TokenParseError::TrailingContent);
+    }
+    Ok(())
+}
+
+/// Source of exactly one value for [`FromJson::from_json`]
+pub struct Decoder<'a> {
+    tokens: &'a [Token],
+    index: &'a mut usize,
+    /// Number of arrays and objects around the value
+    depth: usize,
+}
+
+impl<'a> Decoder<'a> {
+    fn new(tokens: &'a [Token], index: &'a mut usize, depth: usize) -> Self {
+        Self {
+            tokens,
+            index,
+            depth,
+        }
+    }
+
+    /// Whether the value is `null`, without consuming it.
+    pub fn is_null(&self) -> bool {
+        self.tokens.get(*self.index) == Some(&Token::Null)
+    }
+
+    /// Reads the value and passes it to the visitor method for its kind.
+    pub fn decode_any<V: ValueVisitor>(self, visitor: V) -> Result<V::Value, VisitError> {
+        let token = self
+            .tokens
+            .get(*self.index)
+            .ok_or(TokenizeError::UnexpectedEof)?;
+        *self.index += 1;
+
+        match token {
+            Token::Null => visitor.visit_null(),
+            Token::True => visitor.visit_bool(true),
+            Token::False => visitor.visit_bool(false),
+            Token::Number(number) => visitor.visit_f64(*number),
+            Token::String(raw) => visitor.visit_str(&unescape_string(raw)?),
+            Token::LeftBracket | Token::LeftBrace if self.depth == DEFAULT_MAX_DEPTH => {
+                Err(TokenParseError::NestingTooDeep {
+                    limit: DEFAULT_MAX_DEPTH,
+                }
+                .into())
+            }
+            Token::LeftBracket => {
+                let mut seq = SeqAccess {
+                    tokens: self.tokens,
+                    index: self.index,
+                    depth: self.depth + 1,
+                    started: false,
+                    finished: false,
+                };
+                let value = visitor.visit_seq(&mut seq)?;
+                while seq.next_element::<IgnoredAny>()?.is_some() {}
+                Ok(value)
+            }
+            Token::LeftBrace => {
+                let mut map = MapAccess {
+                    tokens: self.tokens,
+                    index: self.index,
+                    depth: self.depth + 1,
+                    started: false,
+                    finished: false,
+                    pending_value: false,
+                };
+                let value = visitor.visit_map(&mut map)?;
+                if map.pending_value {
+                    map.next_value::<IgnoredAny>()?;
+                }
+                while map.next_key()?.is_some() {
+                    map.next_value::<IgnoredAny>()?;
+                }
+                Ok(value)
+            }
+            _ => Err(TokenParseError::ExpectedValue.into()),
+        }
+    }
+}
+
+/// Elements of an array, handed to [`ValueVisitor::visit_seq`]
+pub struct SeqAccess<'a> {
+    tokens: &'a [Token],
+    index: &'a mut usize,
+    /// Depth of the elements
+    depth: usize,
+    started: bool,
+    finished: bool,
+}
+
+impl SeqAccess<'_> {
+    /// Decodes the next element, `None` once the array has ended.
+    pub fn next_element<T: FromJson>(&mut self) -> Result<Option<T>, VisitError> {
+        if !self.advance()? {
+            return Ok(None);
+        }
+        T::from_json(Decoder::new(self.tokens, self.index, self.depth)).map(Some)
+    }
+
+    /// Runs the visitor over the next element, `None` once the array has ended.
+    pub fn next_element_with<V: ValueVisitor>(
+        &mut self,
+        visitor: V,
+    ) -> Result<Option<V::Value>, VisitError> {
+        if !self.advance()? {
+            return Ok(None);
+        }
+        Decoder::new(self.tokens, self.index, self.depth)
+            .decode_any(visitor)
+            .map(Some)
+    }
+
+    /// Moves to the next element, consuming the comma or closing bracket before it.
+    fn advance(&mut self) -> Result<bool, VisitError> {
+        if self.finished {
+            return Ok(false);
+        }
+
+        let token = self
+            .tokens
+            .get(*self.index)
+            .ok_or(TokenizeError::UnexpectedEof)?;
+        match token {
+            Token::RightBracket => {
+                *self.index += 1;
+                self.finished = true;
+                return Ok(false);
+            }
+            Token::RightBrace => return Err(mismatched_bracket(']', '}')),
+            Token::Comma if self.started => {
+                *self.index += 1;
+                if self.tokens.get(*self.index) == Some(&Token::RightBracket) {
+                    return Err(TokenParseError::TrailingComma.into());
+                }
+            }
+            _ if self.started => return Err(TokenParseError::ExpectedComma.into()),
+            _ => {}
+        }
+
+        self.started = true;
+        Ok(true)
+    }
+}
+
+/// Members of an object, handed to [`ValueVisitor::visit_map`]
+pub struct MapAccess<'a> {
+    tokens: &'a [Token],
+    index: &'a mut usize,
+    /// Depth of the member values
+    depth: usize,
+    started: bool,
+    finished: bool,
+    /// A key was read and its value was not yet
+    pending_value: bool,
+}
+
+impl MapAccess<'_> {
+    /// Reads the unescaped key of the next member, `None` once the object has ended.
+    pub fn next_key(&mut self) -> Result<Option<String>, VisitError> {
+        if self.pending_value {
+            self.next_value::<IgnoredAny>()?;
+        }
+        if self.finished {
+            return Ok(None);
+        }
+
+        let mut token = self.token()?;
+        match token {
+            Token::RightBrace => {
+                *self.index += 1;
+                self.finished = true;
+                return Ok(None);
+            }
+            Token::RightBracket => return Err(mismatched_bracket('}', ']')),
+            Token::Comma if self.started => {
+                *self.index += 1;
+                token = self.token()?;
+                if token == &Token::RightBrace {
+                    return Err(TokenParseError::TrailingComma.into());
+                }
+            }
+            _ if self.started => return Err(TokenParseError::ExpectedComma.into()),
+            _ => {}
+        }
+
+        let Token::String(raw) = token else {
+            return Err(TokenParseError::ExpectedProperty.into());
+        };
+        let key = unescape_string(raw)?;
+        *self.index += 1;
+
+        if self.token()? != &Token::Colon {
+            return Err(TokenParseError::ExpectedColon.into());
+        }
+        *self.index += 1;
+        if matches!(
+            self.tokens.get(*self.index),
+            None | Some(Token::RightBrace | Token::RightBracket | Token::Comma | Token::Colon)
+        ) {
+            return Err(TokenParseError::ExpectedValueForKey(key).into());
+        }
+
+        self.started = true;
+        self.pending_value = true;
+        Ok(Some(key))
+    }
+
+    /// Decodes the value of the member whose key was read last.
+    pub fn next_value<T: FromJson>(&mut self) -> Result<T, VisitError> {
+        self.pending_value = false;
+        T::from_json(Decoder::new(self.tokens, self.index, self.depth))
+    }
+
+    /// Runs the visitor over the value of the member whose key was read last.
+    pub fn next_value_with<V: ValueVisitor>(&mut self, visitor: V) -> Result<V::Value, VisitError> {
+        self.pending_value = false;
+        Decoder::new(self.tokens, self.index, self.depth).decode_any(visitor)
+    }
+
+    fn token(&self) -> Result<&Token, VisitError> {
+        Ok(self
+            .tokens
+            .get(*self.index)
+            .ok_or(TokenizeError::UnexpectedEof)?)
+    }
+}
+
+fn mismatched_bracket(expected: char, found: char) -> VisitError {
+    TokenParseError::MismatchedBracket { expected, found }.into()
+}
+
+/// Accepts and discards any value.
+struct IgnoredAny;
+
+impl FromJson for IgnoredAny {
+    fn from_json(decoder: Decoder<'_>) -> Result<Self, VisitError> {
+        decoder.decode_any(IgnoredAny)
+    }
+}
+
+impl ValueVisitor for IgnoredAny {
+    type Value = IgnoredAny;
+
+    fn visit_null(self) -> Result<Self::Value, VisitError> {
+        Ok(self)
+    }
+
+    fn visit_bool(self, _value: bool) -> Result<Self::Value, VisitError> {
+        Ok(self)
+    }
+
+    fn visit_f64(self, _value: f64) -> Result<Self::Value, VisitError> {
+        Ok(self)
+    }
+
+    fn visit_str(self, _value: &str) -> Result<Self::Value, VisitError> {
+        Ok(self)
+    }
+
+    fn visit_seq(self, _seq: &mut SeqAccess<'_>) -> Result<Self::Value, VisitError> {
+        Ok(self)
+    }
+
+    fn visit_map(self, _map: &mut MapAccess<'_>) -> Result<Self::Value, VisitError> {
+        Ok(self)
+    }
+}
+
+/// Visitor for a single kind of scalar, see the `FromJson` impls below.
+struct ScalarVisitor<T>(PhantomData<T>);
+
+impl ValueVisitor for ScalarVisitor<bool> {
+    type Value = bool;
+
+    fn visit_bool(self, value: bool) -> Result<Self::Value, VisitError> {
+        Ok(value)
+    }
+}
+
+impl ValueVisitor for ScalarVisitor<f64> {
+    type Value = f64;
+
+    fn visit_f64(self, value: f64) -> Result<Self::Value, VisitError> {
+        Ok(value)
+    }
+}
+
+impl ValueVisitor for ScalarVisitor<String> {
+    type Value = String;
+
+    fn visit_str(self, value: &str) -> Result<Self::Value, VisitError> {
+        Ok(String::from(value))
+    }
+}
+
+impl FromJson for bool {
+    fn from_json(decoder: Decoder<'_>) -> Result<Self, VisitError> {
+        decoder.decode_any(ScalarVisitor::<bool>(PhantomData))
+    }
+}
+
+impl FromJson for f64 {
+    fn from_json(decoder: Decoder<'_>) -> Result<Self, VisitError> {
+        decoder.decode_any(ScalarVisitor::<f64>(PhantomData))
+    }
+}
+
+impl FromJson for String {
+    fn from_json(decoder: Decoder<'_>) -> Result<Self, VisitError> {
+        decoder.decode_any(ScalarVisitor::<String>(PhantomData))
+    }
+}
+
+impl<T: FromJson> FromJson for Option<T> {
+    fn from_json(decoder: Decoder<'_>) -> Result<Self, VisitError> {
+        if decoder.is_null() {
+            *decoder.index += 1;
+            return Ok(None);
+        }
+        T::from_json(decoder).map(Some)
+    }
+}
+
+struct VecVisitor<T>(PhantomData<T>);
+
+impl<T: FromJson> ValueVisitor for VecVisitor<T> {
+    type Value = Vec<T>;
+
+    fn visit_seq(self, seq: &mut SeqAccess<'_>) -> Result<Self::Value, VisitError> {
+        let mut values = Vec::new();
+        while let Some(value) = seq.next_element()? {
+            values.push(value);
+        }
+        Ok(values)
+    }
+}
+
+impl<T: FromJson> FromJson for Vec<T> {
+    fn from_json(decoder: Decoder<'_>) -> Result<Self, VisitError> {
+        decoder.decode_any(VecVisitor(PhantomData))
+    }
+}
+
+struct HashMapVisitor<T>(PhantomData<T>);
+
+impl<T: FromJson> ValueVisitor for HashMapVisitor<T> {
+    type Value = HashMap<String, T>;
+
+    fn visit_map(self, map: &mut MapAccess<'_>) -> Result<Self::Value, VisitError> {
+        let mut output = HashMap::new();
+        while let Some(key) = map.next_key()? {
+            output.insert(key, map.next_value()?);
+        }
+        Ok(output)
+    }
+}
+
+impl<T: FromJson> FromJson for HashMap<String, T> {
+    fn from_json(decoder: Decoder<'_>) -> Result<Self, VisitError> {
+        decoder.decode_any(HashMapVisitor(PhantomData))
+    }
+}
+
+struct ValueBuilder;
+
+impl ValueVisitor for ValueBuilder {
+    type Value = Value;
+
+    fn visit_null(self) -> Result<Self::Value, VisitError> {
+        Ok(Value::Null)
+    }
+
+    fn visit_bool(self, value: bool) -> Result<Self::Value, VisitError> {
+        Ok(Value::Boolean(value))
+    }
+
+    fn visit_f64(self, value: f64) -> Result<Self::Value, VisitError> {
+        Ok(Value::Number(value))
+    }
+
+    fn visit_str(self, value: &str) -> Result<Self::Value, VisitError> {
+        Ok(Value::string(value))
+    }
+
+    fn visit_seq(self, seq: &mut SeqAccess<'_>) -> Result<Self::Value, VisitError> {
+        let mut values = Vec::new();
+        while let Some(value) = seq.next_element()? {
+            values.push(value);
+        }
+        Ok(Value::Array(values))
+    }
+
+    fn visit_map(self, map: &mut MapAccess<'_>) -> Result<Self::Value, VisitError> {
+        let mut output = Map::new();
+        while let Some(key) = map.next_key()? {
+            output.insert(key, map.next_value()?);
+        }
+        Ok(Value::Object(output))
+    }
+}
+
+impl FromJson for Value {
+    fn from_json(decoder: Decoder<'_>) -> Result<Self, VisitError> {
+        decoder.decode_any(ValueBuilder)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{parse, parser::TokenParseError, Value, ValueKind};
+
+    use super::{
+        decode, decode_with, Decoder, FromJson, MapAccess, SeqAccess, ValueVisitor, VisitError,
+    };
+
+    #[derive(Debug, PartialEq)]
+    struct User {
+        name: String,
+        age: f64,
+        tags: Vec<String>,
+    }
+
+    struct UserVisitor;
+
+    impl ValueVisitor for UserVisitor {
+        type Value = User;
+
+        fn visit_map(self, map: &mut MapAccess<'_>) -> Result<Self::Value, VisitError> {
+            let mut name = None;
+            let mut age = None;
+            let mut tags = Vec::new();
+            while let Some(key) = map.next_key()? {
+                match key.as_str() {
+                    "name" => name = Some(map.next_value()?),
+                    "age" => age = Some(map.next_value()?),
+                    "tags" => tags = map.next_value()?,
+                    _ => {}
+                }
+            }
+
+            Ok(User {
+                name: name.ok_or_else(|| VisitError::Custom(String::from("missing name")))?,
+                age: age.ok_or_else(|| VisitError::Custom(String::from("missing age")))?,
+                tags,
+            })
+        }
+    }
+
+    impl FromJson for User {
+        fn from_json(decoder: Decoder<'_>) -> Result<Self, VisitError> {
+            decoder.decode_any(UserVisitor)
+        }
+    }
+
+    /// Sums all numbers in a document without storing any of them.
+    struct SumVisitor;
+
+    impl ValueVisitor for SumVisitor {
+        type Value = f64;
+
+        fn visit_f64(self, value: f64) -> Result<Self::Value, VisitError> {
+            Ok(value)
+        }
+
+        fn visit_seq(self, seq: &mut SeqAccess<'_>) -> Result<Self::Value, VisitError> {
+            let mut sum = 0.0;
+            while let Some(value) = seq.next_element_with(SumVisitor)? {
+                sum += value;
+            }
+            Ok(sum)
+        }
+    }
+
+    #[test]
+    fn decodes_user_type() {
+        let input = r#"[{"name": "Ann", "extra": {"x": [1, 2]}, "age": 30, "tags": ["a", "b"]}, {"age": 4, "name": "Bo"}]"#;
+        let expected = vec![
+            User {
+                name: String::from("Ann"),
+                age: 30.0,
+                tags: vec![String::from("a"), String::from("b")],
+            },
+            User {
+                name: String::from("Bo"),
+                age: 4.0,
+                tags: vec![],
+            },
+        ];
+
+        let actual = decode::<Vec<User>>(input);
+
+        assert_eq!(actual, Ok(expected));
+    }
+
+    #[test]
+    fn runs_visitor_over_nested_arrays() {
+        let input = "[1, [2, 3], [], [[4]]]";
+
+        let actual = decode_with(input, SumVisitor);
+
+        assert_eq!(actual, Ok(10.0));
+    }
+
+    #[test]
+    fn decodes_std_types() {
+        let input = r#"{"a": [1, null], "b": []}"#;
+        let expected = HashMap::from([
+            (String::from("a"), vec![Some(1.0), None]),
+            (String::from("b"), vec![]),
+        ]);
+
+        let actual = decode::<HashMap<String, Vec<Option<f64>>>>(input);
+
+        assert_eq!(actual, Ok(expected));
+    }
+
+    #[test]
+    fn decodes_same_value_as_parse() {
+        let input = r#"{"a": [true, {"b": "c\n"}], "d": null}"#;
+
+        let actual = decode::<Value>(input);
+
+        assert_eq!(actual, Ok(parse(String::from(input)).unwrap()));
+    }
+
+    #[test]
+    fn rejects_trailing_content() {
+        let trailing = VisitError::ParseError(TokenParseError::TrailingContent);
+
+        assert_eq!(decode::<f64>("1 2"), Err(trailing.clone()));
+        assert_eq!(decode::<Value>("{\"a\":1} {"), Err(trailing.clone()));
+        assert_eq!(decode_with("[1, 2] 3", SumVisitor), Err(trailing));
+        assert_eq!(decode::<f64>(" 1 "), Ok(1.0));
+    }
+
+    #[test]
+    fn rejects_deeply_nested_input() {
+        let input = "[".repeat(100_000);
+
+        assert_eq!(
+            decode::<Value>(&input),
+            Err(VisitError::ParseError(TokenParseError::NestingTooDeep {
+                limit: 128
+            }))
+        );
+        // Skipped values count as well
+        assert!(decode_with(&input, SumVisitor).is_err());
+    }
+
+    #[test]
+    fn reports_visitor_errors() {
+        assert_eq!(
+            decode::<User>(r#"{"name": "x"}"#),
+            Err(VisitError::Custom(String::from("missing age")))
+        );
+        assert_eq!(
+            decode::<Vec<f64>>("[1, \"2\"]"),
+            Err(VisitError::InvalidType(ValueKind::String))
+        );
+    }
+
+    #[test]
+    fn reports_syntax_errors_in_skipped_values() {
+        let cases = [
+            (
+                r#"{"name": "a", "age": 1, "x": [1,]}"#,
+                TokenParseError::TrailingComma,
+            ),
+            (r#"{"name": "a" "age": 1}"#, TokenParseError::ExpectedComma),
+            (r#"{"name" "a"}"#, TokenParseError::ExpectedColon),
+            (
+                r#"{"name": "a", "age": 1]"#,
+                TokenParseError::MismatchedBracket {
+                    expected: '}',
+                    found: ']',
+                },
+            ),
+        ];
+
+        for (input, expected) in cases {
+            let actual = decode::<User>(input);
+
+            assert_eq!(
+                actual,
+                Err(VisitError::ParseError(expected)),
+                "input: {input}"
+            );
+        }
+    }
+}